@@ -0,0 +1,80 @@
+use client::caldav::{calendar_multiget, calendar_query, delete, get, propfind, put, sync};
+
+#[tokio::main]
+async fn main() {
+    // parse args
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        println!("Usage: caldav_client <command>");
+        return;
+    }
+
+    // command: sync, propfind, query, multiget, get, put, delete
+    let command = &args[1];
+    match command.as_str() {
+        "help" => {
+            println!("Usage: caldav_client <command>");
+            println!("Commands:");
+            println!("  sync");
+            println!("  propfind [path] [depth]");
+            println!("  query");
+            println!("  multiget <id>...");
+            println!("  get <id>");
+            println!("  put <name> <file> [etag]");
+            println!("  delete <id> [etag]");
+        }
+        "sync" => {
+            if let Err(e) = sync().await {
+                println!("CalDAV sync failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        "propfind" => {
+            let path = args.get(2).cloned().unwrap_or("/caldav/".to_string());
+            let depth = args.get(3).cloned().unwrap_or("1".to_string());
+            propfind(path, depth).await.unwrap();
+        }
+        "query" => {
+            calendar_query().await.unwrap();
+        }
+        "multiget" => {
+            if args.len() < 3 {
+                println!("Usage: caldav_client multiget <id>...");
+                return;
+            }
+            let ids = args[2..]
+                .iter()
+                .map(|id| id.parse::<i64>().unwrap())
+                .collect();
+            calendar_multiget(ids).await.unwrap();
+        }
+        "get" => {
+            if args.len() < 3 {
+                println!("Usage: caldav_client get <id>");
+                return;
+            }
+            let id = args[2].parse::<i64>().unwrap();
+            get(id).await.unwrap();
+        }
+        "put" => {
+            if args.len() < 4 {
+                println!("Usage: caldav_client put <name> <file> [etag]");
+                return;
+            }
+            put(args[2].clone(), args[3].clone(), args.get(4).cloned())
+                .await
+                .unwrap();
+        }
+        "delete" => {
+            if args.len() < 3 {
+                println!("Usage: caldav_client delete <id> [etag]");
+                return;
+            }
+            let id = args[2].parse::<i64>().unwrap();
+            delete(id, args.get(3).cloned()).await.unwrap();
+        }
+        _ => {
+            println!("Usage: caldav_client <command>");
+        }
+    }
+}
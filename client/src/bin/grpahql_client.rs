@@ -1,5 +1,5 @@
 use client::graphql::{
-    mutation::{create_todo, delete_todo, update_todo},
+    mutation::{complete_todo, create_todo, delete_todo, update_todo},
     query::{find_todo, get_todos},
 };
 
@@ -12,7 +12,7 @@ async fn main() {
         return;
     }
 
-    // command: get_todos, find_todo, create_todo, update_todo, delete_todo
+    // command: get_todos, find_todo, create_todo, update_todo, delete_todo, complete_todo
     let command = &args[1];
     match command.as_str() {
         "help" => {
@@ -21,8 +21,9 @@ async fn main() {
             println!("  get_todos");
             println!("  find_todo <id>");
             println!("  create_todo <title>");
-            println!("  update_todo <id> <title>");
+            println!("  update_todo <id> <title> <expected_version>");
             println!("  delete_todo <id>");
+            println!("  complete_todo <id>");
         }
         "get_todos" => {
             get_todos().await.unwrap();
@@ -44,13 +45,14 @@ async fn main() {
             create_todo(title).await.unwrap();
         }
         "update_todo" => {
-            if args.len() < 4 {
-                println!("Usage: graphql_client update_todo <id> <title>");
+            if args.len() < 5 {
+                println!("Usage: graphql_client update_todo <id> <title> <expected_version>");
                 return;
             }
             let id = args[2].parse::<i64>().unwrap();
             let title = args[3].clone();
-            update_todo(id, title).await.unwrap();
+            let expected_version = args[4].parse::<i64>().unwrap();
+            update_todo(id, title, expected_version).await.unwrap();
         }
         "delete_todo" => {
             if args.len() < 3 {
@@ -60,6 +62,14 @@ async fn main() {
             let id = args[2].parse::<i64>().unwrap();
             delete_todo(id).await.unwrap();
         }
+        "complete_todo" => {
+            if args.len() < 3 {
+                println!("Usage: graphql_client complete_todo <id>");
+                return;
+            }
+            let id = args[2].parse::<i64>().unwrap();
+            complete_todo(id).await.unwrap();
+        }
         _ => {
             println!("Usage: graphql_client <command>");
         }
@@ -1,4 +1,8 @@
-use client::grpc::{create_todo, delete_todo, find_todo, get_todos, update_todo};
+use client::grpc::{
+    batch_create_todos, complete_todo, create_todo, delete_todo, find_todo, get_audit_log,
+    get_todos, list_revisions, list_todos_v2, list_trash, rename_todo_v2, restore_revision,
+    restore_todo, update_todo,
+};
 
 #[tokio::main]
 async fn main() {
@@ -9,7 +13,9 @@ async fn main() {
         return;
     }
 
-    // command: get_todos, find_todo, create_todo, update_todo, delete_todo
+    // command: get_todos, find_todo, create_todo, batch_create_todos, update_todo, delete_todo, complete_todo,
+    // get_audit_log, list_revisions, restore_revision, list_trash, restore_todo, list_todos_v2,
+    // rename_todo_v2
     let command = &args[1];
     match command.as_str() {
         "help" => {
@@ -17,9 +23,18 @@ async fn main() {
             println!("Commands:");
             println!("  get_todos");
             println!("  find_todo <id>");
-            println!("  create_todo <title>");
-            println!("  update_todo <id> <title>");
+            println!("  create_todo <title> [idempotency_key]");
+            println!("  batch_create_todos [--per-item] <title>...");
+            println!("  update_todo <id> <title> [expected_version]");
             println!("  delete_todo <id>");
+            println!("  complete_todo <id>");
+            println!("  get_audit_log [todo_id]");
+            println!("  list_revisions <todo_id>");
+            println!("  restore_revision <todo_id> <revision>");
+            println!("  list_trash");
+            println!("  restore_todo <id>");
+            println!("  list_todos_v2");
+            println!("  rename_todo_v2 <id> <title> [expected_version]");
         }
         "get_todos" => {
             get_todos().await.unwrap();
@@ -34,20 +49,31 @@ async fn main() {
         }
         "create_todo" => {
             if args.len() < 3 {
-                println!("Usage: grpc_client create_todo <title>");
+                println!("Usage: grpc_client create_todo <title> [idempotency_key]");
                 return;
             }
             let title = args[2].clone();
-            create_todo(title).await.unwrap();
+            let idempotency_key = args.get(3).cloned();
+            create_todo(title, idempotency_key).await.unwrap();
+        }
+        "batch_create_todos" => {
+            let per_item = args.get(2).is_some_and(|arg| arg == "--per-item");
+            let titles: Vec<String> = args[if per_item { 3 } else { 2 }..].to_vec();
+            if titles.is_empty() {
+                println!("Usage: grpc_client batch_create_todos [--per-item] <title>...");
+                return;
+            }
+            batch_create_todos(titles, per_item).await.unwrap();
         }
         "update_todo" => {
             if args.len() < 4 {
-                println!("Usage: grpc_client update_todo <id> <title>");
+                println!("Usage: grpc_client update_todo <id> <title> [expected_version]");
                 return;
             }
             let id = args[2].parse::<i64>().unwrap();
             let title = args[3].clone();
-            update_todo(id, title).await.unwrap();
+            let expected_version = args.get(4).map(|version| version.parse::<i64>().unwrap());
+            update_todo(id, title, expected_version).await.unwrap();
         }
         "delete_todo" => {
             if args.len() < 3 {
@@ -57,6 +83,59 @@ async fn main() {
             let id = args[2].parse::<i64>().unwrap();
             delete_todo(id).await.unwrap();
         }
+        "complete_todo" => {
+            if args.len() < 3 {
+                println!("Usage: grpc_client complete_todo <id>");
+                return;
+            }
+            let id = args[2].parse::<i64>().unwrap();
+            complete_todo(id).await.unwrap();
+        }
+        "get_audit_log" => {
+            let todo_id = args.get(2).map(|id| id.parse::<i64>().unwrap());
+            get_audit_log(todo_id).await.unwrap();
+        }
+        "list_revisions" => {
+            if args.len() < 3 {
+                println!("Usage: grpc_client list_revisions <todo_id>");
+                return;
+            }
+            let todo_id = args[2].parse::<i64>().unwrap();
+            list_revisions(todo_id).await.unwrap();
+        }
+        "restore_revision" => {
+            if args.len() < 4 {
+                println!("Usage: grpc_client restore_revision <todo_id> <revision>");
+                return;
+            }
+            let todo_id = args[2].parse::<i64>().unwrap();
+            let revision = args[3].parse::<i64>().unwrap();
+            restore_revision(todo_id, revision).await.unwrap();
+        }
+        "list_trash" => {
+            list_trash().await.unwrap();
+        }
+        "restore_todo" => {
+            if args.len() < 3 {
+                println!("Usage: grpc_client restore_todo <id>");
+                return;
+            }
+            let id = args[2].parse::<i64>().unwrap();
+            restore_todo(id).await.unwrap();
+        }
+        "list_todos_v2" => {
+            list_todos_v2().await.unwrap();
+        }
+        "rename_todo_v2" => {
+            if args.len() < 4 {
+                println!("Usage: grpc_client rename_todo_v2 <id> <title> [expected_version]");
+                return;
+            }
+            let id = args[2].parse::<i64>().unwrap();
+            let title = args[3].clone();
+            let expected_version = args.get(4).map(|version| version.parse::<i64>().unwrap());
+            rename_todo_v2(id, title, expected_version).await.unwrap();
+        }
         _ => {
             println!("Usage: grpc_client <command>");
         }
@@ -1,4 +1,4 @@
-use client::rest::{create_todo, delete_todo, find_todo, get_todos, update_todo};
+use client::rest::{complete_todo, create_todo, delete_todo, find_todo, get_todos, update_todo};
 
 #[tokio::main]
 async fn main() {
@@ -9,7 +9,7 @@ async fn main() {
         return;
     }
 
-    // command: get_todos, find_todo, create_todo, update_todo, delete_todo
+    // command: get_todos, find_todo, create_todo, update_todo, delete_todo, complete_todo
     let command = &args[1];
     match command.as_str() {
         "help" => {
@@ -17,9 +17,10 @@ async fn main() {
             println!("Commands:");
             println!("  get_todos");
             println!("  find_todo <id>");
-            println!("  create_todo <title>");
+            println!("  create_todo <title> [idempotency_key]");
             println!("  update_todo <id> <title>");
             println!("  delete_todo <id>");
+            println!("  complete_todo <id>");
         }
         "get_todos" => {
             get_todos().await.unwrap();
@@ -34,11 +35,12 @@ async fn main() {
         }
         "create_todo" => {
             if args.len() < 3 {
-                println!("Usage: rest_client create_todo <title>");
+                println!("Usage: rest_client create_todo <title> [idempotency_key]");
                 return;
             }
             let title = args[2].clone();
-            create_todo(title).await.unwrap();
+            let idempotency_key = args.get(3).cloned();
+            create_todo(title, idempotency_key).await.unwrap();
         }
         "update_todo" => {
             if args.len() < 4 {
@@ -57,6 +59,14 @@ async fn main() {
             let id = args[2].parse::<i64>().unwrap();
             delete_todo(id).await.unwrap();
         }
+        "complete_todo" => {
+            if args.len() < 3 {
+                println!("Usage: rest_client complete_todo <id>");
+                return;
+            }
+            let id = args[2].parse::<i64>().unwrap();
+            complete_todo(id).await.unwrap();
+        }
         _ => {
             println!("Usage: rest_client <command>");
         }
@@ -0,0 +1,222 @@
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+
+const BASE_URL: &str = "http://localhost:8080";
+const CALENDAR: &str = "/caldav/todos/";
+
+const CALENDAR_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop><D:getetag/><C:calendar-data/></D:prop>
+  <C:filter><C:comp-filter name="VCALENDAR"><C:comp-filter name="VTODO"/></C:comp-filter></C:filter>
+</C:calendar-query>"#;
+
+type Error = Box<dyn std::error::Error>;
+
+fn dav_method(name: &str) -> Method {
+    Method::from_bytes(name.as_bytes()).unwrap()
+}
+
+fn resource_url(name: &str) -> String {
+    format!("{}{}{}", BASE_URL, CALENDAR, name)
+}
+
+/// A calendar holding a single VTODO.
+fn vtodo(summary: &str, extra: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//caldav_client//EN\r\nBEGIN:VTODO\r\n\
+         UID:caldav-client\r\nDTSTAMP:20240101T000000Z\r\nSUMMARY:{}\r\n{}END:VTODO\r\nEND:VCALENDAR\r\n",
+        summary, extra
+    )
+}
+
+async fn print(res: Response) -> Result<(), Error> {
+    println!("Status: {}", res.status());
+    if let Some(etag) = res.headers().get("etag") {
+        println!("ETag: {}", etag.to_str()?);
+    }
+    if let Some(location) = res.headers().get("location") {
+        println!("Location: {}", location.to_str()?);
+    }
+    let body = res.text().await?;
+    println!("Body: {}", body);
+    Ok(())
+}
+
+fn with_etag(request: RequestBuilder, etag: Option<String>) -> RequestBuilder {
+    match etag {
+        Some(etag) => request.header("If-Match", format!("\"{}\"", etag.trim_matches('"'))),
+        None => request,
+    }
+}
+
+pub async fn propfind(path: String, depth: String) -> Result<(), Error> {
+    let client = Client::new();
+    let res = client
+        .request(dav_method("PROPFIND"), format!("{}{}", BASE_URL, path))
+        .header("Depth", depth)
+        .send()
+        .await?;
+    print(res).await
+}
+
+pub async fn calendar_query() -> Result<(), Error> {
+    let client = Client::new();
+    let res = client
+        .request(dav_method("REPORT"), format!("{}{}", BASE_URL, CALENDAR))
+        .header("Depth", "1")
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(CALENDAR_QUERY)
+        .send()
+        .await?;
+    print(res).await
+}
+
+pub async fn calendar_multiget(ids: Vec<i64>) -> Result<(), Error> {
+    let hrefs: String = ids
+        .iter()
+        .map(|id| format!("<D:href>{}{}.ics</D:href>", CALENDAR, id))
+        .collect();
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <C:calendar-multiget xmlns:D=\"DAV:\" xmlns:C=\"urn:ietf:params:xml:ns:caldav\">\
+         <D:prop><D:getetag/><C:calendar-data/></D:prop>{}</C:calendar-multiget>",
+        hrefs
+    );
+    let client = Client::new();
+    let res = client
+        .request(dav_method("REPORT"), format!("{}{}", BASE_URL, CALENDAR))
+        .header("Depth", "1")
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(body)
+        .send()
+        .await?;
+    print(res).await
+}
+
+pub async fn get(id: i64) -> Result<(), Error> {
+    let client = Client::new();
+    let res = client
+        .get(resource_url(&format!("{}.ics", id)))
+        .send()
+        .await?;
+    print(res).await
+}
+
+pub async fn put(name: String, path: String, etag: Option<String>) -> Result<(), Error> {
+    let body = tokio::fs::read_to_string(path).await?;
+    let client = Client::new();
+    let request = client
+        .put(resource_url(&name))
+        .header("Content-Type", "text/calendar; charset=utf-8")
+        .body(body);
+    let res = with_etag(request, etag).send().await?;
+    print(res).await
+}
+
+pub async fn delete(id: i64, etag: Option<String>) -> Result<(), Error> {
+    let client = Client::new();
+    let request = client.delete(resource_url(&format!("{}.ics", id)));
+    let res = with_etag(request, etag).send().await?;
+    print(res).await
+}
+
+fn expect(step: &str, res: &Response, expected: StatusCode) -> Result<(), Error> {
+    println!("{}: {}", step, res.status());
+    if res.status() != expected {
+        return Err(format!("{}: expected {}, got {}", step, expected, res.status()).into());
+    }
+    Ok(())
+}
+
+fn header(res: &Response, name: &str) -> Result<String, Error> {
+    match res.headers().get(name) {
+        Some(value) => Ok(value.to_str()?.to_string()),
+        None => Err(format!("missing {} header", name).into()),
+    }
+}
+
+/// Walks through what a syncing client does: discovers the calendar, creates a VTODO,
+/// finds it again, updates it under its ETag, is refused with a stale one and deletes it.
+pub async fn sync() -> Result<(), Error> {
+    let client = Client::new();
+
+    let res = client
+        .request(
+            dav_method("PROPFIND"),
+            format!("{}/.well-known/caldav", BASE_URL),
+        )
+        .header("Depth", "0")
+        .send()
+        .await?;
+    expect("discover", &res, StatusCode::MULTI_STATUS)?;
+
+    let res = client
+        .put(resource_url("caldav-client.ics"))
+        .header("If-None-Match", "*")
+        .body(vtodo(
+            "Try CalDAV",
+            "PRIORITY:1\r\nDUE:20240201T100000Z\r\n",
+        ))
+        .send()
+        .await?;
+    expect("create", &res, StatusCode::CREATED)?;
+    let location = header(&res, "location")?;
+    let etag = header(&res, "etag")?;
+    let url = format!("{}{}", BASE_URL, location);
+
+    let res = client
+        .request(dav_method("PROPFIND"), format!("{}{}", BASE_URL, CALENDAR))
+        .header("Depth", "1")
+        .send()
+        .await?;
+    expect("list", &res, StatusCode::MULTI_STATUS)?;
+    if !res.text().await?.contains(&location) {
+        return Err(format!("{} is not listed", location).into());
+    }
+
+    let res = client
+        .request(dav_method("REPORT"), format!("{}{}", BASE_URL, CALENDAR))
+        .header("Depth", "1")
+        .body(CALENDAR_QUERY)
+        .send()
+        .await?;
+    expect("query", &res, StatusCode::MULTI_STATUS)?;
+    if !res.text().await?.contains("SUMMARY:Try CalDAV") {
+        return Err("the created VTODO is not returned".into());
+    }
+
+    let res = client
+        .put(&url)
+        .header("If-Match", &etag)
+        .body(vtodo("Try CalDAV sync", "STATUS:COMPLETED\r\n"))
+        .send()
+        .await?;
+    expect("update", &res, StatusCode::NO_CONTENT)?;
+    let current = header(&res, "etag")?;
+
+    let res = client
+        .put(&url)
+        .header("If-Match", &etag)
+        .body(vtodo("Lost update", ""))
+        .send()
+        .await?;
+    expect("stale update", &res, StatusCode::PRECONDITION_FAILED)?;
+
+    let res = client.get(&url).send().await?;
+    expect("get", &res, StatusCode::OK)?;
+    let body = res.text().await?;
+    if !body.contains("SUMMARY:Try CalDAV sync") || !body.contains("STATUS:COMPLETED") {
+        return Err("the update was not applied".into());
+    }
+
+    let res = client
+        .delete(&url)
+        .header("If-Match", &current)
+        .send()
+        .await?;
+    expect("delete", &res, StatusCode::NO_CONTENT)?;
+    let res = client.get(&url).send().await?;
+    expect("get deleted", &res, StatusCode::NOT_FOUND)?;
+
+    println!("CalDAV sync works");
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use graphql_client::GraphQLQuery;
 
+// RFC 3339 string, as serialized by the server
+type DateTime = String;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "src/graphql/schema.graphql",
@@ -27,6 +30,15 @@ pub struct UpdateTodo;
 )]
 pub struct DeleteTodo;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/graphql/schema.graphql",
+    query_path = "src/graphql/queries.graphql",
+    response_derives = "Debug",
+    normalization = "rust"
+)]
+pub struct CompleteTodo;
+
 pub async fn create_todo(title: String) -> Result<(), Box<dyn std::error::Error>> {
     let request_body = CreateTodo::build_query(create_todo::Variables { title });
     let client = reqwest::Client::new();
@@ -40,8 +52,16 @@ pub async fn create_todo(title: String) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
-pub async fn update_todo(id: i64, title: String) -> Result<(), Box<dyn std::error::Error>> {
-    let request_body = UpdateTodo::build_query(update_todo::Variables { id, title });
+pub async fn update_todo(
+    id: i64,
+    title: String,
+    expected_version: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let request_body = UpdateTodo::build_query(update_todo::Variables {
+        id,
+        title,
+        expected_version,
+    });
     let client = reqwest::Client::new();
     let res = client
         .post("http://localhost:8080/graphql")
@@ -65,3 +85,16 @@ pub async fn delete_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", body);
     Ok(())
 }
+
+pub async fn complete_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let request_body = CompleteTodo::build_query(complete_todo::Variables { id });
+    let client = reqwest::Client::new();
+    let res = client
+        .post("http://localhost:8080/graphql")
+        .json(&request_body)
+        .send()
+        .await?;
+    let body = res.text().await?;
+    println!("{}", body);
+    Ok(())
+}
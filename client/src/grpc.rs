@@ -1,6 +1,13 @@
 use presentation::grpc::proto_impl::{
-    CreateTodoRequest, DeleteTodoRequest, FindTodoByIdRequest, GetTodosRequest, TodoServiceClient,
-    UpdateTodoRequest,
+    todo::batch_todos_request::Operation, AuditServiceClient, BatchMode, BatchTodosRequest,
+    CompleteTodoRequest, CreateTodoRequest, DeleteTodoRequest, FindTodoByIdRequest,
+    GetAuditLogRequest, GetTodosRequest, ListRevisionsRequest, ListTrashRequest,
+    RestoreRevisionRequest, RestoreTodoRequest, RevisionServiceClient, TodoServiceClient,
+    TrashServiceClient, UpdateTodoRequest,
+};
+use presentation::grpc::v2::{
+    todo_v2::{self, ListTodosRequest},
+    TodoServiceClient as TodoServiceV2Client,
 };
 use tonic::Request;
 
@@ -28,10 +35,21 @@ pub async fn find_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-pub async fn create_todo(title: String) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn create_todo(
+    title: String,
+    idempotency_key: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = TodoServiceClient::connect("http://localhost:8081").await?;
 
-    let request = Request::new(CreateTodoRequest { title });
+    let mut request = Request::new(CreateTodoRequest {
+        title,
+        ..Default::default()
+    });
+    if let Some(idempotency_key) = idempotency_key {
+        request
+            .metadata_mut()
+            .insert("idempotency-key", idempotency_key.parse()?);
+    }
 
     let response = client.create_todo(request).await?;
 
@@ -40,10 +58,51 @@ pub async fn create_todo(title: String) -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
-pub async fn update_todo(id: i64, title: String) -> Result<(), Box<dyn std::error::Error>> {
+/// Streams one create per title; with `per_item`, rejected titles do not stop the others.
+pub async fn batch_create_todos(
+    titles: Vec<String>,
+    per_item: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TodoServiceClient::connect("http://localhost:8081").await?;
+
+    let mode = if per_item {
+        BatchMode::PerItem
+    } else {
+        BatchMode::Atomic
+    };
+    let requests: Vec<BatchTodosRequest> = titles
+        .into_iter()
+        .map(|title| BatchTodosRequest {
+            operation: Some(Operation::Create(CreateTodoRequest {
+                title,
+                ..Default::default()
+            })),
+            mode: mode.into(),
+        })
+        .collect();
+
+    let response = client
+        .batch_todos(Request::new(tokio_stream::iter(requests)))
+        .await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+pub async fn update_todo(
+    id: i64,
+    title: String,
+    expected_version: Option<i64>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = TodoServiceClient::connect("http://localhost:8081").await?;
 
-    let request = Request::new(UpdateTodoRequest { id, title });
+    let request = Request::new(UpdateTodoRequest {
+        id,
+        title,
+        expected_version,
+        ..Default::default()
+    });
 
     let response = client.update_todo(request).await?;
 
@@ -63,3 +122,118 @@ pub async fn delete_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+pub async fn complete_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TodoServiceClient::connect("http://localhost:8081").await?;
+
+    let request = Request::new(CompleteTodoRequest { id });
+
+    let response = client.complete_todo(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+pub async fn get_audit_log(todo_id: Option<i64>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = AuditServiceClient::connect("http://localhost:8081").await?;
+
+    let request = Request::new(GetAuditLogRequest {
+        todo_id,
+        ..Default::default()
+    });
+
+    let response = client.get_audit_log(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+pub async fn list_revisions(todo_id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = RevisionServiceClient::connect("http://localhost:8081").await?;
+
+    let request = Request::new(ListRevisionsRequest { todo_id });
+
+    let response = client.list_revisions(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+pub async fn restore_revision(
+    todo_id: i64,
+    revision: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = RevisionServiceClient::connect("http://localhost:8081").await?;
+
+    let request = Request::new(RestoreRevisionRequest { todo_id, revision });
+
+    let response = client.restore_revision(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+pub async fn list_trash() -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TrashServiceClient::connect("http://localhost:8081").await?;
+
+    let request = Request::new(ListTrashRequest {});
+
+    let response = client.list_trash(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+pub async fn restore_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TrashServiceClient::connect("http://localhost:8081").await?;
+
+    let request = Request::new(RestoreTodoRequest { id });
+
+    let response = client.restore_todo(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+pub async fn list_todos_v2() -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TodoServiceV2Client::connect("http://localhost:8081").await?;
+
+    let request = Request::new(ListTodosRequest::default());
+
+    let response = client.list_todos(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
+
+/// Renames the todo through todo.v2, leaving its other fields as they are.
+pub async fn rename_todo_v2(
+    id: i64,
+    title: String,
+    expected_version: Option<i64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = TodoServiceV2Client::connect("http://localhost:8081").await?;
+
+    let request = Request::new(todo_v2::UpdateTodoRequest {
+        id,
+        todo: Some(todo_v2::TodoFields {
+            title,
+            ..Default::default()
+        }),
+        update_mask: vec!["title".to_string()],
+        expected_version,
+    });
+
+    let response = client.update_todo(request).await?;
+
+    println!("RESPONSE={:?}", response);
+
+    Ok(())
+}
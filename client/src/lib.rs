@@ -1,3 +1,4 @@
+pub mod caldav;
 pub mod graphql;
 pub mod grpc;
 pub mod rest;
@@ -21,15 +21,20 @@ pub async fn find_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-pub async fn create_todo(title: String) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn create_todo(
+    title: String,
+    idempotency_key: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let client = Client::new();
-    let res = client
+    let mut request = client
         .post("http://localhost:8080/todos")
         .json(&serde_json::json!({
             "title": title,
-        }))
-        .send()
-        .await?;
+        }));
+    if let Some(idempotency_key) = idempotency_key {
+        request = request.header("Idempotency-Key", idempotency_key);
+    }
+    let res = request.send().await?;
     println!("Status: {}", res.status());
     let body = res.text().await?;
     println!("Body: {}", body);
@@ -66,3 +71,15 @@ pub async fn delete_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
     println!("Body: {}", body);
     Ok(())
 }
+
+pub async fn complete_todo(id: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let client = Client::new();
+    let res = client
+        .post(format!("http://localhost:8080/todos/{}/complete", id))
+        .send()
+        .await?;
+    println!("Status: {}", res.status());
+    let body = res.text().await?;
+    println!("Body: {}", body);
+    Ok(())
+}
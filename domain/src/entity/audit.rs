@@ -0,0 +1,257 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::{
+    entity::todo::{Priority, Todo},
+    error::DomainError,
+};
+
+/// The kind of change an audit entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Complete,
+    Delete,
+    Restore,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Complete => "complete",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+        }
+    }
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(AuditAction::Create),
+            "update" => Ok(AuditAction::Update),
+            "complete" => Ok(AuditAction::Complete),
+            "delete" => Ok(AuditAction::Delete),
+            "restore" => Ok(AuditAction::Restore),
+            _ => Err(DomainError::Validation(format!(
+                "unknown audit action: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// The API a change came in through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    Rest,
+    GraphQL,
+    Grpc,
+    CalDav,
+    JsonRpc,
+    /// Changes made by the server itself rather than on behalf of a request.
+    #[default]
+    System,
+}
+
+impl Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Rest => "rest",
+            Protocol::GraphQL => "graphql",
+            Protocol::Grpc => "grpc",
+            Protocol::CalDav => "caldav",
+            Protocol::JsonRpc => "jsonrpc",
+            Protocol::System => "system",
+        }
+    }
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rest" => Ok(Protocol::Rest),
+            "graphql" => Ok(Protocol::GraphQL),
+            "grpc" => Ok(Protocol::Grpc),
+            "caldav" => Ok(Protocol::CalDav),
+            "jsonrpc" => Ok(Protocol::JsonRpc),
+            "system" => Ok(Protocol::System),
+            _ => Err(DomainError::Validation(format!("unknown protocol: {}", s))),
+        }
+    }
+}
+
+/// Who changed which todo, how and when. `before` is `None` for a creation and `after`
+/// is `None` for a deletion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub todo_id: i64,
+    pub action: AuditAction,
+    pub actor: String,
+    pub protocol: Protocol,
+    pub request_id: String,
+    pub before: Option<Todo>,
+    pub after: Option<Todo>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// One field that differs between the two sides of an audit entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl AuditEntry {
+    /// The fields whose values differ between `before` and `after`.
+    pub fn changes(&self) -> Vec<FieldChange> {
+        diff(self.before.as_ref(), self.after.as_ref())
+    }
+}
+
+/// The fields whose values differ between two states of a todo; every set field when one
+/// side is missing.
+pub fn diff(before: Option<&Todo>, after: Option<&Todo>) -> Vec<FieldChange> {
+    fields(before)
+        .into_iter()
+        .zip(fields(after))
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((field, before), (_, after))| FieldChange {
+            field,
+            before,
+            after,
+        })
+        .collect()
+}
+
+fn fields(todo: Option<&Todo>) -> [(&'static str, Option<String>); 5] {
+    let timestamp =
+        |at: Option<DateTime<Utc>>| at.map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true));
+    [
+        ("title", todo.and_then(|todo| todo.title.clone())),
+        ("due_at", timestamp(todo.and_then(|todo| todo.due_at))),
+        (
+            "priority",
+            todo.map(|todo| {
+                match todo.priority {
+                    Priority::Low => "low",
+                    Priority::Medium => "medium",
+                    Priority::High => "high",
+                }
+                .to_string()
+            }),
+        ),
+        (
+            "recurrence",
+            todo.and_then(|todo| todo.recurrence.as_ref())
+                .map(|recurrence| recurrence.to_string()),
+        ),
+        (
+            "completed_at",
+            timestamp(todo.and_then(|todo| todo.completed_at)),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(before: Option<Todo>, after: Option<Todo>) -> AuditEntry {
+        AuditEntry {
+            id: 1,
+            todo_id: 1,
+            action: AuditAction::Update,
+            actor: "alice".to_string(),
+            protocol: Protocol::Rest,
+            request_id: "req-1".to_string(),
+            before,
+            after,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_changes() {
+        let before = Todo {
+            id: 1,
+            title: Some("draft".to_string()),
+            ..Default::default()
+        };
+        let after = Todo {
+            title: Some("final".to_string()),
+            priority: Priority::High,
+            due_at: DateTime::from_timestamp(0, 0),
+            ..before.clone()
+        };
+        assert_eq!(
+            entry(Some(before.clone()), Some(after)).changes(),
+            vec![
+                FieldChange {
+                    field: "title",
+                    before: Some("draft".to_string()),
+                    after: Some("final".to_string()),
+                },
+                FieldChange {
+                    field: "due_at",
+                    before: None,
+                    after: Some("1970-01-01T00:00:00Z".to_string()),
+                },
+                FieldChange {
+                    field: "priority",
+                    before: Some("medium".to_string()),
+                    after: Some("high".to_string()),
+                },
+            ]
+        );
+        let deleted = entry(Some(before), None).changes();
+        assert_eq!(
+            deleted
+                .iter()
+                .map(|change| change.field)
+                .collect::<Vec<_>>(),
+            vec!["title", "priority"]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for protocol in [
+            Protocol::Rest,
+            Protocol::GraphQL,
+            Protocol::Grpc,
+            Protocol::CalDav,
+            Protocol::JsonRpc,
+            Protocol::System,
+        ] {
+            assert_eq!(protocol.as_str().parse::<Protocol>().ok(), Some(protocol));
+        }
+        assert!("soap".parse::<Protocol>().is_err());
+        assert_eq!(
+            "complete".parse::<AuditAction>().ok(),
+            Some(AuditAction::Complete)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::error::DomainError;
+
+/// The name a CalDAV client gave the resource of a todo it created, e.g. `<uid>.ics`, which
+/// the todo keeps being served under. Other todos are served under their default name,
+/// `<id>.ics`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalDavResource {
+    pub name: String,
+    pub todo_id: i64,
+}
+
+impl CalDavResource {
+    pub const MAX_NAME_LENGTH: usize = 255;
+
+    pub fn new(name: String, todo_id: i64) -> Result<Self, DomainError> {
+        Self::validate_name(&name)?;
+        Ok(Self { name, todo_id })
+    }
+
+    /// Fails unless a client may create a resource called `name`: an `.ics` name that is
+    /// not the default name of some todo.
+    pub fn validate_name(name: &str) -> Result<(), DomainError> {
+        if name.len() > Self::MAX_NAME_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "resource names are at most {} bytes long",
+                Self::MAX_NAME_LENGTH
+            )));
+        }
+        if !name.ends_with(".ics") || name.contains('/') {
+            return Err(DomainError::Validation(format!(
+                "`{}` is not the name of a calendar resource",
+                name
+            )));
+        }
+        if Self::default_name_id(name).is_some() {
+            return Err(DomainError::Validation(format!(
+                "`{}` is reserved for the todo of that id",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn default_name(todo_id: i64) -> String {
+        format!("{}.ics", todo_id)
+    }
+
+    /// The todo id a default name such as `42.ics` stands for.
+    pub fn default_name_id(name: &str) -> Option<i64> {
+        let id = name.strip_suffix(".ics")?;
+        if id.is_empty() || !id.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        id.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(CalDavResource::default_name(42), "42.ics");
+        assert_eq!(CalDavResource::default_name_id("42.ics"), Some(42));
+        assert_eq!(CalDavResource::default_name_id("+42.ics"), None);
+        assert_eq!(CalDavResource::default_name_id("a1b2.ics"), None);
+
+        assert!(CalDavResource::new("0f8fad5b-d9cb.ics".to_string(), 1).is_ok());
+        for name in [
+            "42.ics",
+            "note.txt",
+            "a/b.ics",
+            &format!("{}.ics", "x".repeat(252)),
+        ] {
+            assert!(
+                CalDavResource::validate_name(name).is_err(),
+                "{} should be rejected",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+
+use crate::error::DomainError;
+
+/// A private iCalendar subscription URL issued to one user. Anyone holding the token can
+/// read the calendar, so it is only ever listed to the user it was issued to, who can
+/// revoke it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarFeed {
+    pub id: i64,
+    /// The unguessable path segment of the feed URL.
+    pub token: String,
+    pub actor: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CalendarFeed {
+    /// Shortest token accepted, so feed URLs cannot be enumerated.
+    pub const MIN_TOKEN_LENGTH: usize = 32;
+
+    pub fn new(token: String, actor: String, now: DateTime<Utc>) -> Result<Self, DomainError> {
+        if token.len() < Self::MIN_TOKEN_LENGTH {
+            return Err(DomainError::Validation(format!(
+                "calendar feed token must be at least {} characters",
+                Self::MIN_TOKEN_LENGTH
+            )));
+        }
+        Ok(Self {
+            id: 0,
+            token,
+            actor,
+            created_at: now,
+        })
+    }
+
+    pub fn is_owned_by(&self, actor: &str) -> bool {
+        self.actor == actor
+    }
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+
+/// A committed todo event as kept in the event log. Ids grow with every event and are never
+/// reused, so they double as the position of a reader in the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggedEvent {
+    pub id: i64,
+    /// The name of the event, e.g. `TodoCreated`.
+    pub event: String,
+    pub todo_id: i64,
+    /// The event as JSON, with the todo it is about.
+    pub payload: String,
+    pub occurred_at: DateTime<Utc>,
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::entity::todo::Todo;
+
+/// The todo a create request produced, stored under the client's idempotency key so a
+/// retried request gets it back instead of creating a duplicate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    /// Identifies the request body; replaying the key with a different body is rejected.
+    pub fingerprint: String,
+    pub todo: Todo,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    /// Whether the key may be used again for an unrelated request.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    /// The fingerprint of a request body in its canonical serialization.
+    pub fn fingerprint_of(canonical_body: &str) -> String {
+        format!("{:x}", Sha256::digest(canonical_body.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_is_expired() {
+        let created_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let record = IdempotencyRecord {
+            key: "retry-1".to_string(),
+            fingerprint: "buy milk".to_string(),
+            todo: Todo {
+                id: 1,
+                title: Some("buy milk".to_string()),
+                ..Default::default()
+            },
+            created_at,
+            expires_at: created_at + Duration::hours(24),
+        };
+        assert!(!record.is_expired(created_at + Duration::hours(23)));
+        assert!(record.is_expired(created_at + Duration::hours(24)));
+    }
+
+    #[test]
+    fn test_fingerprint_of() {
+        assert_eq!(
+            IdempotencyRecord::fingerprint_of(r#"["buy milk",null,2,null]"#),
+            IdempotencyRecord::fingerprint_of(r#"["buy milk",null,2,null]"#)
+        );
+        assert_ne!(
+            IdempotencyRecord::fingerprint_of(r#"["buy milk",null,2,null]"#),
+            IdempotencyRecord::fingerprint_of(r#"["buy milk",null,3,null]"#)
+        );
+        assert_eq!(IdempotencyRecord::fingerprint_of("").len(), 64);
+    }
+}
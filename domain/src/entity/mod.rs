@@ -1 +1,12 @@
+pub mod audit;
+pub mod caldav_resource;
+pub mod calendar_feed;
+pub mod event_log;
+pub mod idempotency;
+pub mod persisted_query;
+pub mod recurrence;
+pub mod reminder;
+pub mod revision;
 pub mod todo;
+pub mod trash;
+pub mod webhook;
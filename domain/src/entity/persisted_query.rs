@@ -0,0 +1,100 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::error::DomainError;
+
+/// How a persisted query came to be stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySource {
+    /// Registered by an administrator from the manifest of a client.
+    Manifest,
+    /// Sent by a client along with its hash, and kept only as long as room allows.
+    Automatic,
+}
+
+impl QuerySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuerySource::Manifest => "manifest",
+            QuerySource::Automatic => "automatic",
+        }
+    }
+}
+
+impl Display for QuerySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for QuerySource {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manifest" => Ok(QuerySource::Manifest),
+            "automatic" => Ok(QuerySource::Automatic),
+            _ => Err(DomainError::Validation(format!(
+                "unknown query source: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// A GraphQL document clients may run by its hash instead of sending it whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistedQuery {
+    /// The SHA-256 of `document`, in lowercase hex.
+    pub hash: String,
+    pub document: String,
+    /// The named operations of the document, in the order they appear.
+    pub operation_names: Vec<String>,
+    pub source: QuerySource,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PersistedQuery {
+    pub fn new(
+        document: String,
+        operation_names: Vec<String>,
+        source: QuerySource,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            hash: Self::hash_of(&document),
+            document,
+            operation_names,
+            source,
+            created_at: now,
+        }
+    }
+
+    /// The hash a document is persisted under, as clients compute it.
+    pub fn hash_of(document: &str) -> String {
+        format!("{:x}", Sha256::digest(document.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_source() {
+        assert_eq!(
+            PersistedQuery::hash_of("{ todos { id } }"),
+            PersistedQuery::hash_of("{ todos { id } }")
+        );
+        assert_eq!(
+            PersistedQuery::hash_of(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        for source in [QuerySource::Manifest, QuerySource::Automatic] {
+            assert_eq!(source.as_str().parse::<QuerySource>().ok(), Some(source));
+        }
+        assert!("other".parse::<QuerySource>().is_err());
+    }
+}
@@ -0,0 +1,521 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+
+use crate::error::DomainError;
+
+/// A recurrence rule covering the subset of RFC 5545 RRULE we support:
+/// `FREQ` (DAILY, WEEKLY, MONTHLY), `INTERVAL`, `BYDAY`, `COUNT` and `UNTIL`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    /// Number of occurrences left, including the current one.
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A `BYDAY` entry. `ordinal` (e.g. the `-1` in `-1FR`) is only meaningful for monthly rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub ordinal: Option<i8>,
+    pub weekday: Weekday,
+}
+
+// Upper bound on periods scanned when looking for the next matching day, so a rule
+// that can never match (e.g. BYDAY=5MO on a monthly rule in a short month) terminates.
+const MAX_PERIODS: u32 = 1000;
+
+/// Largest `INTERVAL` a rule may have; a thousand periods is already centuries for
+/// monthly rules.
+pub const MAX_INTERVAL: u32 = 1000;
+
+impl Recurrence {
+    /// Returns the first occurrence strictly after `current`, evaluated on the local calendar
+    /// of `tz` so that BYDAY and month boundaries follow the user's wall clock. The local time
+    /// of day of `current` is kept. Returns `None` when COUNT or UNTIL is exhausted, and fails
+    /// when the next occurrence lies beyond the dates we can represent.
+    pub fn next_after<Tz: TimeZone>(
+        &self,
+        current: DateTime<Utc>,
+        tz: &Tz,
+    ) -> Result<Option<DateTime<Utc>>, DomainError> {
+        if self.count.is_some_and(|count| count <= 1) {
+            return Ok(None);
+        }
+        let local = current.with_timezone(tz).naive_local();
+        let next_date = match self.frequency {
+            Frequency::Daily => self.next_daily(local.date())?,
+            Frequency::Weekly => self.next_weekly(local.date())?,
+            Frequency::Monthly => self.next_monthly(local.date())?,
+        };
+        let next = match next_date.and_then(|date| resolve_local(tz, date.and_time(local.time()))) {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+        match self.until {
+            Some(until) if next > until => Ok(None),
+            _ => Ok(Some(next)),
+        }
+    }
+
+    /// The rule that applies to the occurrence following this one.
+    pub fn advance(&self) -> Self {
+        Self {
+            count: self.count.map(|count| count.saturating_sub(1)),
+            ..self.clone()
+        }
+    }
+
+    fn matches_weekday(&self, date: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.iter().any(|d| d.weekday == date.weekday())
+    }
+
+    /// `periods` intervals of the rule, in its own unit.
+    fn span(&self, periods: u32) -> Result<u32, DomainError> {
+        periods
+            .checked_mul(self.interval)
+            .ok_or_else(|| self.out_of_range())
+    }
+
+    fn add_days(&self, date: NaiveDate, days: u64) -> Result<NaiveDate, DomainError> {
+        date.checked_add_days(Days::new(days))
+            .ok_or_else(|| self.out_of_range())
+    }
+
+    fn out_of_range(&self) -> DomainError {
+        DomainError::Validation(format!("the next occurrence of `{}` is out of range", self))
+    }
+
+    fn next_daily(&self, date: NaiveDate) -> Result<Option<NaiveDate>, DomainError> {
+        for step in 1..=MAX_PERIODS {
+            let candidate = self.add_days(date, self.span(step)? as u64)?;
+            if self.matches_weekday(candidate) {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_weekly(&self, date: NaiveDate) -> Result<Option<NaiveDate>, DomainError> {
+        if self.by_day.is_empty() {
+            return self.add_days(date, 7 * self.interval as u64).map(Some);
+        }
+        let week_start = date
+            .checked_sub_days(Days::new(date.weekday().num_days_from_monday() as u64))
+            .ok_or_else(|| self.out_of_range())?;
+        let mut weekdays: Vec<u32> = self
+            .by_day
+            .iter()
+            .map(|d| d.weekday.num_days_from_monday())
+            .collect();
+        weekdays.sort_unstable();
+        for period in 0..MAX_PERIODS {
+            let start = self.add_days(week_start, 7 * self.span(period)? as u64)?;
+            for offset in &weekdays {
+                let candidate = self.add_days(start, *offset as u64)?;
+                if candidate > date {
+                    return Ok(Some(candidate));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_monthly(&self, date: NaiveDate) -> Result<Option<NaiveDate>, DomainError> {
+        let month_start = match date.with_day(1) {
+            Some(month_start) => month_start,
+            None => return Ok(None),
+        };
+        for period in 0..MAX_PERIODS {
+            let start = month_start
+                .checked_add_months(Months::new(self.span(period)?))
+                .ok_or_else(|| self.out_of_range())?;
+            let mut candidates = if self.by_day.is_empty() {
+                start.with_day(date.day()).into_iter().collect::<Vec<_>>()
+            } else {
+                self.by_day
+                    .iter()
+                    .flat_map(|by_day| days_in_month_matching(start, *by_day))
+                    .collect()
+            };
+            candidates.sort_unstable();
+            if let Some(candidate) = candidates.into_iter().find(|candidate| *candidate > date) {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn days_in_month_matching(month_start: NaiveDate, by_day: ByDay) -> Vec<NaiveDate> {
+    let days: Vec<NaiveDate> = month_start
+        .iter_days()
+        .take_while(|day| day.month() == month_start.month())
+        .filter(|day| day.weekday() == by_day.weekday)
+        .collect();
+    match by_day.ordinal {
+        None => days,
+        Some(ordinal) if ordinal > 0 => days
+            .get(ordinal as usize - 1)
+            .copied()
+            .into_iter()
+            .collect(),
+        Some(ordinal) => days
+            .len()
+            .checked_sub(ordinal.unsigned_abs() as usize)
+            .and_then(|index| days.get(index).copied())
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn resolve_local<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        // The wall-clock time was skipped by a DST transition; use the next valid hour.
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|date_time| date_time.with_timezone(&Utc))
+}
+
+impl FromStr for Recurrence {
+    type Err = DomainError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |message: String| DomainError::Validation(format!("invalid RRULE: {}", message));
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut count = None;
+        let mut until = None;
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("malformed part `{}`", part)))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(invalid(format!("unsupported FREQ `{}`", other))),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|interval| (1..=MAX_INTERVAL).contains(interval))
+                        .ok_or_else(|| invalid(format!("bad INTERVAL `{}`", value)))?
+                }
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(|day| {
+                            parse_by_day(day).ok_or_else(|| invalid(format!("bad BYDAY `{}`", day)))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or_else(|| invalid(format!("bad COUNT `{}`", value)))?,
+                    )
+                }
+                "UNTIL" => {
+                    until = Some(
+                        parse_until(value)
+                            .ok_or_else(|| invalid(format!("bad UNTIL `{}`", value)))?,
+                    )
+                }
+                other => return Err(invalid(format!("unsupported part `{}`", other))),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| invalid("FREQ is required".to_string()))?;
+        if count.is_some() && until.is_some() {
+            return Err(invalid(
+                "COUNT and UNTIL are mutually exclusive".to_string(),
+            ));
+        }
+        if frequency != Frequency::Monthly && by_day.iter().any(|d: &ByDay| d.ordinal.is_some()) {
+            return Err(invalid("BYDAY ordinals require FREQ=MONTHLY".to_string()));
+        }
+        Ok(Self {
+            frequency,
+            interval,
+            by_day,
+            count,
+            until,
+        })
+    }
+}
+
+fn parse_by_day(value: &str) -> Option<ByDay> {
+    let value = value.trim().to_ascii_uppercase();
+    // the weekday is split off by byte offset, which is only a char boundary for ASCII
+    if !value.is_ascii() {
+        return None;
+    }
+    let split = value.len().checked_sub(2)?;
+    let (ordinal, weekday) = value.split_at(split);
+    let weekday = match weekday {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i8>()
+                .ok()
+                .filter(|o| *o != 0 && (-5..=5).contains(o))?,
+        ),
+    };
+    Some(ByDay { ordinal, weekday })
+}
+
+fn parse_until(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Some(date_time.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(|date| {
+        date.and_time(NaiveTime::MIN).and_utc() + Duration::days(1) - Duration::seconds(1)
+    })
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self.by_day.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ByDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{}", ordinal)?;
+        }
+        let weekday = match self.weekday {
+            Weekday::Mon => "MO",
+            Weekday::Tue => "TU",
+            Weekday::Wed => "WE",
+            Weekday::Thu => "TH",
+            Weekday::Fri => "FR",
+            Weekday::Sat => "SA",
+            Weekday::Sun => "SU",
+        };
+        write!(f, "{}", weekday)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn occurrence_after<Tz: TimeZone>(
+        rule: &Recurrence,
+        current: DateTime<Utc>,
+        tz: &Tz,
+    ) -> Option<DateTime<Utc>> {
+        match rule.next_after(current, tz) {
+            Ok(next) => next,
+            Err(_) => panic!("failed to find the occurrence after {}", current),
+        }
+    }
+
+    fn rule(s: &str) -> Recurrence {
+        match s.parse() {
+            Ok(rule) => rule,
+            Err(_) => panic!("failed to parse {}", s),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for s in [
+            "FREQ=DAILY",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=5",
+            "FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20241231T000000Z",
+        ] {
+            assert_eq!(rule(s).to_string(), s);
+        }
+        assert_eq!(
+            rule("RRULE:freq=daily;interval=1").to_string(),
+            "FREQ=DAILY"
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_rules() {
+        for s in [
+            "",
+            "FREQ=YEARLY",
+            "FREQ=DAILY;BYMONTH=1",
+            "FREQ=DAILY;COUNT=2;UNTIL=20240101",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;INTERVAL=1001",
+            "FREQ=DAILY;INTERVAL=4294967295",
+        ] {
+            assert!(s.parse::<Recurrence>().is_err(), "{} should be rejected", s);
+        }
+    }
+
+    #[test]
+    fn test_rejects_malformed_by_day() {
+        for s in [
+            "FREQ=WEEKLY;BYDAY=éa",
+            "FREQ=WEEKLY;BYDAY=Mé",
+            "FREQ=MONTHLY;BYDAY=-128MO",
+            "FREQ=MONTHLY;BYDAY=127MO",
+            "FREQ=MONTHLY;BYDAY=0MO",
+        ] {
+            match s.parse::<Recurrence>() {
+                Err(DomainError::Validation(_)) => {}
+                _ => panic!("{} should be rejected as invalid", s),
+            }
+        }
+    }
+
+    #[test]
+    fn test_daily() {
+        let next = occurrence_after(
+            &rule("FREQ=DAILY;INTERVAL=3"),
+            utc("2023-10-30T09:00:00Z"),
+            &Utc,
+        );
+        assert_eq!(next, Some(utc("2023-11-02T09:00:00Z")));
+    }
+
+    #[test]
+    fn test_weekly_by_day() {
+        let weekly = rule("FREQ=WEEKLY;BYDAY=MO,FR");
+        // Monday -> Friday of the same week -> Monday of the next week.
+        let friday = occurrence_after(&weekly, utc("2023-10-02T09:00:00Z"), &Utc);
+        assert_eq!(friday, Some(utc("2023-10-06T09:00:00Z")));
+        let monday = occurrence_after(&weekly, friday.unwrap(), &Utc);
+        assert_eq!(monday, Some(utc("2023-10-09T09:00:00Z")));
+
+        let biweekly = rule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO");
+        let next = occurrence_after(&biweekly, utc("2023-10-02T09:00:00Z"), &Utc);
+        assert_eq!(next, Some(utc("2023-10-16T09:00:00Z")));
+    }
+
+    #[test]
+    fn test_weekly_uses_local_weekday() {
+        // Monday 08:00 in Tokyo is still Sunday in UTC.
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let next = occurrence_after(
+            &rule("FREQ=WEEKLY;BYDAY=MO"),
+            utc("2023-10-01T23:00:00Z"),
+            &tokyo,
+        );
+        assert_eq!(next, Some(utc("2023-10-08T23:00:00Z")));
+    }
+
+    #[test]
+    fn test_monthly() {
+        // The 31st skips months that are too short.
+        let next = occurrence_after(&rule("FREQ=MONTHLY"), utc("2024-01-31T00:00:00Z"), &Utc);
+        assert_eq!(next, Some(utc("2024-03-31T00:00:00Z")));
+
+        let last_friday = rule("FREQ=MONTHLY;BYDAY=-1FR");
+        let next = occurrence_after(&last_friday, utc("2023-10-27T12:00:00Z"), &Utc);
+        assert_eq!(next, Some(utc("2023-11-24T12:00:00Z")));
+    }
+
+    #[test]
+    fn test_count_and_until() {
+        let last = rule("FREQ=DAILY;COUNT=1");
+        assert_eq!(
+            occurrence_after(&last, utc("2023-10-01T00:00:00Z"), &Utc),
+            None
+        );
+        assert_eq!(rule("FREQ=DAILY;COUNT=3").advance().count, Some(2));
+
+        let until = rule("FREQ=DAILY;UNTIL=20231001");
+        assert!(occurrence_after(&until, utc("2023-09-30T10:00:00Z"), &Utc).is_some());
+        assert_eq!(
+            occurrence_after(&until, utc("2023-10-01T10:00:00Z"), &Utc),
+            None
+        );
+    }
+
+    #[test]
+    fn test_huge_interval_fails_instead_of_overflowing() {
+        // the last Saturday of its month, so every rule has to step a whole interval ahead
+        let current = utc("2023-09-30T00:00:00Z");
+        for frequency in [Frequency::Daily, Frequency::Weekly, Frequency::Monthly] {
+            for by_day in [
+                vec![],
+                vec![ByDay {
+                    ordinal: None,
+                    weekday: Weekday::Sat,
+                }],
+            ] {
+                let huge = Recurrence {
+                    frequency,
+                    interval: u32::MAX,
+                    by_day,
+                    count: None,
+                    until: None,
+                };
+                assert!(huge.next_after(current, &Utc).is_err());
+            }
+        }
+
+        let widest = rule("FREQ=MONTHLY;INTERVAL=1000;BYDAY=5MO");
+        assert!(widest.next_after(current, &Utc).is_ok());
+    }
+}
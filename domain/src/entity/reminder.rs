@@ -0,0 +1,122 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{entity::todo::Todo, error::DomainError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub id: i64,
+    pub todo_id: i64,
+    pub trigger: ReminderTrigger,
+    pub fired_at: Option<DateTime<Utc>>,
+    /// Set when delivery was abandoned after [`Reminder::MAX_ATTEMPTS`] failures.
+    pub failed_at: Option<DateTime<Utc>>,
+    /// Failed delivery attempts so far.
+    pub attempts: i64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderTrigger {
+    /// Fire at an absolute instant.
+    At(DateTime<Utc>),
+    /// Fire this long before the todo's `due_at`; follows the due date when it changes.
+    BeforeDue(Duration),
+}
+
+impl Reminder {
+    pub const MAX_ATTEMPTS: i64 = 10;
+
+    pub fn new(todo: &Todo, trigger: ReminderTrigger) -> Result<Self, DomainError> {
+        if let ReminderTrigger::BeforeDue(offset) = trigger {
+            if offset < Duration::zero() {
+                return Err(DomainError::Validation(
+                    "reminder offset must not be negative".to_string(),
+                ));
+            }
+            if todo.due_at.is_none() {
+                return Err(DomainError::Validation(format!(
+                    "todo {} has no due date to remind before",
+                    todo.id
+                )));
+            }
+        }
+        Ok(Self {
+            id: 0,
+            todo_id: todo.id,
+            trigger,
+            fired_at: None,
+            failed_at: None,
+            attempts: 0,
+            last_error: None,
+        })
+    }
+
+    /// When the reminder is due for `todo`, or `None` if it is relative to a missing due date.
+    pub fn fire_at(&self, todo: &Todo) -> Option<DateTime<Utc>> {
+        match self.trigger {
+            ReminderTrigger::At(at) => Some(at),
+            ReminderTrigger::BeforeDue(offset) => todo.due_at.map(|due_at| due_at - offset),
+        }
+    }
+
+    /// Delay before retrying after `attempts` failed deliveries: 30s doubling up to an hour.
+    pub fn retry_backoff(attempts: i64) -> Duration {
+        let exponent = attempts.clamp(0, 7) as u32;
+        std::cmp::min(
+            Duration::seconds(30 * 2_i64.pow(exponent)),
+            Duration::hours(1),
+        )
+    }
+
+    /// When to try again after the `attempts`-th failed delivery, or `None` to give up.
+    pub fn next_retry(attempts: i64, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if attempts >= Self::MAX_ATTEMPTS {
+            return None;
+        }
+        Some(now + Self::retry_backoff(attempts - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fire_at() {
+        let due_at = DateTime::from_timestamp(10_000, 0);
+        let todo = Todo {
+            id: 1,
+            due_at,
+            ..Default::default()
+        };
+        let reminder = Reminder::new(&todo, ReminderTrigger::BeforeDue(Duration::minutes(15)))
+            .ok()
+            .unwrap();
+        assert_eq!(reminder.fire_at(&todo), DateTime::from_timestamp(9_100, 0));
+
+        let undated = Todo::default();
+        assert!(Reminder::new(&undated, ReminderTrigger::BeforeDue(Duration::zero())).is_err());
+        assert!(Reminder::new(&undated, ReminderTrigger::At(Utc::now())).is_ok());
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        assert_eq!(Reminder::retry_backoff(0), Duration::seconds(30));
+        assert_eq!(Reminder::retry_backoff(2), Duration::seconds(120));
+        assert_eq!(Reminder::retry_backoff(50), Duration::hours(1));
+    }
+
+    #[test]
+    fn test_next_retry() {
+        let now = Utc::now();
+        assert_eq!(
+            Reminder::next_retry(1, now),
+            Some(now + Duration::seconds(30))
+        );
+        assert_eq!(
+            Reminder::next_retry(Reminder::MAX_ATTEMPTS - 1, now),
+            Some(now + Duration::hours(1))
+        );
+        assert_eq!(Reminder::next_retry(Reminder::MAX_ATTEMPTS, now), None);
+    }
+}
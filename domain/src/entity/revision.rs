@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+
+use crate::entity::{
+    audit::{diff, FieldChange},
+    todo::Todo,
+};
+
+/// The state of a todo as of one of its revisions. A todo's revisions are numbered from 1
+/// (its creation) and every later change adds the next one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoRevision {
+    pub todo_id: i64,
+    pub revision: i64,
+    pub todo: Todo,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TodoRevision {
+    /// The fields that changed going from this revision to `other`.
+    pub fn diff(&self, other: &TodoRevision) -> Vec<FieldChange> {
+        diff(Some(&self.todo), Some(&other.todo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let first = TodoRevision {
+            todo_id: 1,
+            revision: 1,
+            todo: Todo {
+                id: 1,
+                title: Some("buy milk".to_string()),
+                ..Default::default()
+            },
+            created_at: Utc::now(),
+        };
+        let second = TodoRevision {
+            revision: 2,
+            todo: Todo {
+                title: Some("oops".to_string()),
+                ..first.todo.clone()
+            },
+            ..first.clone()
+        };
+        let changes = first.diff(&second);
+        assert_eq!(
+            changes,
+            vec![FieldChange {
+                field: "title",
+                before: Some("buy milk".to_string()),
+                after: Some("oops".to_string()),
+            }]
+        );
+        assert!(second.diff(&second).is_empty());
+    }
+}
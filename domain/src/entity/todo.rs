@@ -1,5 +1,142 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{entity::recurrence::Recurrence, error::DomainError};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Todo {
     pub id: i64,
     pub title: Option<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+    pub recurrence: Option<Recurrence>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Starts at [`Todo::FIRST_VERSION`] and goes up by one with every update; updates
+    /// only apply to the version they were read at.
+    pub version: i64,
+}
+
+impl Default for Todo {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            title: None,
+            due_at: None,
+            priority: Priority::default(),
+            recurrence: None,
+            completed_at: None,
+            version: Self::FIRST_VERSION,
+        }
+    }
+}
+
+impl Todo {
+    pub const FIRST_VERSION: i64 = 1;
+
+    pub fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    /// Marks this todo as completed at `now`. For a recurring todo, returns the next
+    /// occurrence (with `id` 0, not yet persisted); recurrence is evaluated in `tz`.
+    pub fn complete<Tz: TimeZone>(
+        &mut self,
+        now: DateTime<Utc>,
+        tz: &Tz,
+    ) -> Result<Option<Todo>, DomainError> {
+        if self.is_completed() {
+            return Err(DomainError::Validation(format!(
+                "todo {} is already completed",
+                self.id
+            )));
+        }
+        self.completed_at = Some(now);
+
+        let recurrence = match &self.recurrence {
+            Some(recurrence) => recurrence,
+            None => return Ok(None),
+        };
+        // Without a due date the series is anchored on the completion time.
+        let anchor = self.due_at.unwrap_or(now);
+        let next = recurrence.next_after(anchor, tz)?.map(|due_at| Todo {
+            id: 0,
+            title: self.title.clone(),
+            due_at: Some(due_at),
+            priority: self.priority,
+            recurrence: Some(recurrence.advance()),
+            completed_at: None,
+            version: Self::FIRST_VERSION,
+        });
+        Ok(next)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Priority {
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            Priority::Low => 1,
+            Priority::Medium => 2,
+            Priority::High => 3,
+        }
+    }
+}
+
+impl TryFrom<i64> for Priority {
+    type Error = DomainError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Priority::Low),
+            2 => Ok(Priority::Medium),
+            3 => Ok(Priority::High),
+            _ => Err(DomainError::Validation(format!(
+                "invalid priority: {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete_spawns_next_occurrence() {
+        let due_at = DateTime::parse_from_rfc3339("2023-10-02T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut todo = Todo {
+            id: 1,
+            title: Some("take out the trash".to_string()),
+            due_at: Some(due_at),
+            recurrence: "FREQ=WEEKLY;COUNT=2".parse().ok(),
+            ..Default::default()
+        };
+
+        let next = todo.complete(due_at, &Utc).ok().flatten().unwrap();
+        assert!(todo.is_completed());
+        assert_eq!(next.id, 0);
+        assert_eq!(next.title, todo.title);
+        assert_eq!(next.due_at, Some(due_at + chrono::Duration::weeks(1)));
+        assert!(!next.is_completed());
+
+        // COUNT is exhausted after the second occurrence.
+        let mut next = next;
+        assert!(matches!(next.complete(due_at, &Utc), Ok(None)));
+        assert!(todo.complete(due_at, &Utc).is_err());
+    }
+
+    #[test]
+    fn test_complete_without_recurrence() {
+        let mut todo = Todo::default();
+        assert!(matches!(todo.complete(Utc::now(), &Utc), Ok(None)));
+    }
 }
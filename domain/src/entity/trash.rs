@@ -0,0 +1,39 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::entity::todo::Todo;
+
+/// A deleted todo kept in the trash until it is restored or purged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedTodo {
+    pub todo: Todo,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl TrashedTodo {
+    /// When the todo becomes eligible for purging if trash is kept for `retention`.
+    pub fn purge_at(&self, retention: Duration) -> DateTime<Utc> {
+        self.deleted_at + retention
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_at() {
+        let deleted_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let trashed = TrashedTodo {
+            todo: Todo {
+                id: 1,
+                title: Some("buy milk".to_string()),
+                ..Default::default()
+            },
+            deleted_at,
+        };
+        assert_eq!(
+            trashed.purge_at(Duration::days(30)),
+            deleted_at + Duration::days(30)
+        );
+    }
+}
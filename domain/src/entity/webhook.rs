@@ -0,0 +1,189 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::error::DomainError;
+
+/// Todo changes integrators can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WebhookEvent {
+    TodoCreated,
+    TodoUpdated,
+    TodoCompleted,
+    TodoDeleted,
+    TodoRestored,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::TodoCreated => "todo.created",
+            WebhookEvent::TodoUpdated => "todo.updated",
+            WebhookEvent::TodoCompleted => "todo.completed",
+            WebhookEvent::TodoDeleted => "todo.deleted",
+            WebhookEvent::TodoRestored => "todo.restored",
+        }
+    }
+}
+
+impl Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "todo.created" => Ok(WebhookEvent::TodoCreated),
+            "todo.updated" => Ok(WebhookEvent::TodoUpdated),
+            "todo.completed" => Ok(WebhookEvent::TodoCompleted),
+            "todo.deleted" => Ok(WebhookEvent::TodoDeleted),
+            "todo.restored" => Ok(WebhookEvent::TodoRestored),
+            _ => Err(DomainError::Validation(format!(
+                "unknown webhook event: {}",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    /// Shared secret used to sign every delivery with HMAC-SHA256.
+    pub secret: String,
+    /// Events to deliver; empty means every event.
+    pub events: Vec<WebhookEvent>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookSubscription {
+    pub fn new(
+        url: String,
+        secret: String,
+        events: Vec<WebhookEvent>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, DomainError> {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(DomainError::Validation(format!(
+                "webhook url must be http(s): {}",
+                url
+            )));
+        }
+        if secret.is_empty() {
+            return Err(DomainError::Validation(
+                "webhook secret must not be empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            id: 0,
+            url,
+            secret,
+            events,
+            created_at: now,
+        })
+    }
+
+    pub fn accepts(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// One attempt-tracked delivery of an outbox event to a subscription.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event: WebhookEvent,
+    /// JSON request body, signed and sent verbatim.
+    pub payload: String,
+    pub attempts: i64,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    /// Set when the delivery was abandoned after [`WebhookDelivery::MAX_ATTEMPTS`].
+    pub failed_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookDelivery {
+    pub const MAX_ATTEMPTS: i64 = 10;
+
+    /// When to try again after the `attempts`-th failure, or `None` to give up.
+    /// Backoff starts at 10s and doubles, capped at 6 hours.
+    pub fn next_retry(attempts: i64, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if attempts >= Self::MAX_ATTEMPTS {
+            return None;
+        }
+        let exponent = (attempts - 1).clamp(0, 16) as u32;
+        let delay = std::cmp::min(
+            Duration::seconds(10 * 2_i64.pow(exponent)),
+            Duration::hours(6),
+        );
+        Some(now + delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_round_trip() {
+        for event in [
+            WebhookEvent::TodoCreated,
+            WebhookEvent::TodoUpdated,
+            WebhookEvent::TodoCompleted,
+            WebhookEvent::TodoDeleted,
+            WebhookEvent::TodoRestored,
+        ] {
+            assert_eq!(event.as_str().parse::<WebhookEvent>().ok(), Some(event));
+        }
+        assert!("todo.renamed".parse::<WebhookEvent>().is_err());
+    }
+
+    #[test]
+    fn test_subscription() {
+        let now = Utc::now();
+        assert!(
+            WebhookSubscription::new("ftp://x".to_string(), "s".to_string(), vec![], now).is_err()
+        );
+        assert!(
+            WebhookSubscription::new("https://x".to_string(), "".to_string(), vec![], now).is_err()
+        );
+
+        let all = WebhookSubscription::new("https://x".to_string(), "s".to_string(), vec![], now)
+            .ok()
+            .unwrap();
+        assert!(all.accepts(WebhookEvent::TodoDeleted));
+        let only_created = WebhookSubscription {
+            events: vec![WebhookEvent::TodoCreated],
+            ..all
+        };
+        assert!(only_created.accepts(WebhookEvent::TodoCreated));
+        assert!(!only_created.accepts(WebhookEvent::TodoDeleted));
+    }
+
+    #[test]
+    fn test_next_retry() {
+        let now = Utc::now();
+        assert_eq!(
+            WebhookDelivery::next_retry(1, now),
+            Some(now + Duration::seconds(10))
+        );
+        assert_eq!(
+            WebhookDelivery::next_retry(3, now),
+            Some(now + Duration::seconds(40))
+        );
+        assert_eq!(
+            WebhookDelivery::next_retry(9, now),
+            Some(now + Duration::seconds(2560))
+        );
+        assert_eq!(WebhookDelivery::next_retry(10, now), None);
+    }
+}
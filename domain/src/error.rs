@@ -1,6 +1,34 @@
+use std::fmt::Display;
+
 pub enum DomainError {
     Validation(String),
-    NotFound { entity_type: String, entity_id: i64 },
+    NotFound {
+        entity_type: String,
+        entity_id: i64,
+    },
+    /// The entity changed since the version the caller read.
+    Conflict {
+        entity_type: String,
+        entity_id: i64,
+    },
     Infrastructure(anyhow::Error),
     Unexpected(String),
 }
+
+impl Display for DomainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DomainError::Validation(message) => write!(f, "validation error: {}", message),
+            DomainError::NotFound {
+                entity_type,
+                entity_id,
+            } => write!(f, "{} {} not found", entity_type, entity_id),
+            DomainError::Conflict {
+                entity_type,
+                entity_id,
+            } => write!(f, "{} {} was modified concurrently", entity_type, entity_id),
+            DomainError::Infrastructure(error) => write!(f, "infrastructure error: {}", error),
+            DomainError::Unexpected(message) => write!(f, "unexpected error: {}", message),
+        }
+    }
+}
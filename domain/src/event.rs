@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{entity::todo::Todo, error::DomainError};
+
+/// Something that happened to a todo. Raised by the use case that made the change and
+/// persisted in the same transaction as it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TodoEvent {
+    TodoCreated {
+        todo: Todo,
+    },
+    TodoUpdated {
+        before: Todo,
+        after: Todo,
+    },
+    /// `next` is the follow-up occurrence created for a recurring todo.
+    TodoCompleted {
+        todo: Todo,
+        next: Option<Todo>,
+    },
+    TodoDeleted {
+        todo: Todo,
+    },
+    /// The todo was taken back out of the trash.
+    TodoRestored {
+        todo: Todo,
+    },
+}
+
+impl TodoEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TodoEvent::TodoCreated { .. } => "TodoCreated",
+            TodoEvent::TodoUpdated { .. } => "TodoUpdated",
+            TodoEvent::TodoCompleted { .. } => "TodoCompleted",
+            TodoEvent::TodoDeleted { .. } => "TodoDeleted",
+            TodoEvent::TodoRestored { .. } => "TodoRestored",
+        }
+    }
+
+    /// The todo the event is about, in its state after the change
+    /// (or its last state, for a deletion).
+    pub fn todo(&self) -> &Todo {
+        match self {
+            TodoEvent::TodoCreated { todo } => todo,
+            TodoEvent::TodoUpdated { after, .. } => after,
+            TodoEvent::TodoCompleted { todo, .. } => todo,
+            TodoEvent::TodoDeleted { todo } => todo,
+            TodoEvent::TodoRestored { todo } => todo,
+        }
+    }
+}
+
+/// Reacts to committed todo events in-process.
+#[async_trait]
+pub trait EventSubscriber: Send + Sync + 'static {
+    async fn handle(&self, event: &TodoEvent) -> Result<(), DomainError>;
+}
+
+#[async_trait]
+impl<S: EventSubscriber + ?Sized> EventSubscriber for Arc<S> {
+    async fn handle(&self, event: &TodoEvent) -> Result<(), DomainError> {
+        (**self).handle(event).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_todo() {
+        let before = Todo {
+            id: 1,
+            title: Some("before".to_string()),
+            ..Default::default()
+        };
+        let after = Todo {
+            title: Some("after".to_string()),
+            ..before.clone()
+        };
+        let event = TodoEvent::TodoUpdated {
+            before,
+            after: after.clone(),
+        };
+        assert_eq!(event.name(), "TodoUpdated");
+        assert_eq!(event.todo(), &after);
+    }
+}
@@ -1,6 +1,9 @@
 pub mod entity;
 pub mod error;
+pub mod event;
+pub mod notifier;
 pub mod repository;
+pub mod unit_of_work;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
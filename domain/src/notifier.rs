@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    entity::{
+        reminder::Reminder,
+        todo::Todo,
+        webhook::{WebhookDelivery, WebhookSubscription},
+    },
+    error::DomainError,
+};
+
+/// Delivers fired reminders to the outside world.
+#[async_trait]
+pub trait Notifier: Send + Sync + 'static {
+    async fn notify(&self, reminder: &Reminder, todo: &Todo) -> Result<(), DomainError>;
+}
+
+#[async_trait]
+impl<N: Notifier + ?Sized> Notifier for Arc<N> {
+    async fn notify(&self, reminder: &Reminder, todo: &Todo) -> Result<(), DomainError> {
+        (**self).notify(reminder, todo).await
+    }
+}
+
+/// Sends a webhook delivery to its subscription and returns the HTTP status received.
+/// Transport failures (DNS, timeouts, refused connections) are reported as errors.
+#[async_trait]
+pub trait WebhookSender: Send + Sync + 'static {
+    async fn send(
+        &self,
+        subscription: &WebhookSubscription,
+        delivery: &WebhookDelivery,
+    ) -> Result<u16, DomainError>;
+}
@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::{entity::audit::AuditEntry, error::DomainError};
+
+/// Read side of the audit log; entries are appended through a unit of work, together
+/// with the change they describe.
+#[async_trait]
+pub trait AuditRepository: Send + Sync + 'static {
+    /// Up to `limit` entries, newest first, optionally narrowed to one todo and/or actor.
+    async fn find(
+        &self,
+        todo_id: Option<i64>,
+        actor: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<AuditEntry>, DomainError>;
+}
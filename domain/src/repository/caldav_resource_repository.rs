@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+
+use crate::{entity::caldav_resource::CalDavResource, error::DomainError};
+
+#[async_trait]
+pub trait CalDavResourceRepository: Send + Sync + 'static {
+    async fn find_by_name(&self, name: &str) -> Result<Option<CalDavResource>, DomainError>;
+    async fn find_all(&self) -> Result<Vec<CalDavResource>, DomainError>;
+}
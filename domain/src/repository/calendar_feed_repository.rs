@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::{entity::calendar_feed::CalendarFeed, error::DomainError};
+
+#[async_trait]
+pub trait CalendarFeedRepository: Send + Sync + 'static {
+    async fn create_feed(&self, feed: &CalendarFeed) -> Result<i64, DomainError>;
+    /// Feeds issued to `actor`, oldest first.
+    async fn find_feeds_by_actor(&self, actor: &str) -> Result<Vec<CalendarFeed>, DomainError>;
+    async fn find_feed_by_id(&self, id: i64) -> Result<Option<CalendarFeed>, DomainError>;
+    async fn find_feed_by_token(&self, token: &str) -> Result<Option<CalendarFeed>, DomainError>;
+    async fn delete_feed(&self, id: i64) -> Result<(), DomainError>;
+}
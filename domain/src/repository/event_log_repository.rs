@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::{entity::event_log::LoggedEvent, error::DomainError};
+
+/// The log of committed todo events. Events are appended through a unit of work together
+/// with the change they record; only the newest ones are kept.
+#[async_trait]
+pub trait EventLogRepository: Send + Sync + 'static {
+    /// Up to `limit` events logged after the event `after_id`, oldest first.
+    async fn find_after(&self, after_id: i64, limit: i64) -> Result<Vec<LoggedEvent>, DomainError>;
+    /// The ids of the oldest and the newest event kept, or `None` if the log is empty.
+    async fn find_bounds(&self) -> Result<Option<(i64, i64)>, DomainError>;
+    /// Removes all but the newest `keep` events and returns how many were removed.
+    async fn prune(&self, keep: i64) -> Result<u64, DomainError>;
+}
@@ -1 +1,10 @@
+pub mod audit_repository;
+pub mod caldav_resource_repository;
+pub mod calendar_feed_repository;
+pub mod event_log_repository;
+pub mod persisted_query_repository;
+pub mod reminder_repository;
+pub mod revision_repository;
 pub mod todo_repository;
+pub mod trash_repository;
+pub mod webhook_repository;
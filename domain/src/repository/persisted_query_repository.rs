@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::{entity::persisted_query::PersistedQuery, error::DomainError};
+
+#[async_trait]
+pub trait PersistedQueryRepository: Send + Sync + 'static {
+    async fn find_by_hash(&self, hash: &str) -> Result<Option<PersistedQuery>, DomainError>;
+    /// Every persisted query, registered ones first, oldest first within each source.
+    async fn find_all(&self) -> Result<Vec<PersistedQuery>, DomainError>;
+    /// Stores `query` unless its hash is already there. A manifest query replaces an
+    /// automatic one with the same hash, so that it is kept for good.
+    async fn save(&self, query: &PersistedQuery) -> Result<(), DomainError>;
+    /// Removes all but the newest `keep` automatic queries and returns how many were
+    /// removed. Manifest queries are never removed.
+    async fn prune_automatic(&self, keep: i64) -> Result<u64, DomainError>;
+}
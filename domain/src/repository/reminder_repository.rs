@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{entity::reminder::Reminder, error::DomainError};
+
+#[async_trait]
+pub trait ReminderRepository: Send + Sync + 'static {
+    async fn create(&self, reminder: &Reminder) -> Result<i64, DomainError>;
+    async fn find_by_id(&self, id: i64) -> Result<Option<Reminder>, DomainError>;
+    async fn find_by_todo_id(&self, todo_id: i64) -> Result<Vec<Reminder>, DomainError>;
+    async fn delete(&self, id: i64) -> Result<(), DomainError>;
+    /// Atomically leases up to `limit` unfired, unabandoned reminders of open todos that are
+    /// due at `now` and not leased by someone else, until `lease_until`. A reminder is only
+    /// returned to one caller per lease, so concurrent schedulers never deliver it at once.
+    async fn claim_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Reminder>, DomainError>;
+    async fn mark_fired(&self, id: i64, fired_at: DateTime<Utc>) -> Result<(), DomainError>;
+    /// Records a failed delivery; the reminder stays leased until `retry_at`, or is abandoned
+    /// at `now` when `retry_at` is `None`.
+    async fn mark_failed(
+        &self,
+        id: i64,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<(), DomainError>;
+}
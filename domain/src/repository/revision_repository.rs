@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::{entity::revision::TodoRevision, error::DomainError};
+
+/// Read side of todo revisions; revisions are appended through a unit of work, together
+/// with the change that produced them.
+#[async_trait]
+pub trait RevisionRepository: Send + Sync + 'static {
+    /// Every revision of the todo, oldest first.
+    async fn find_by_todo_id(&self, todo_id: i64) -> Result<Vec<TodoRevision>, DomainError>;
+    async fn find(&self, todo_id: i64, revision: i64) -> Result<Option<TodoRevision>, DomainError>;
+}
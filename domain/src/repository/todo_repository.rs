@@ -1,12 +1,93 @@
+use std::{pin::Pin, sync::Arc};
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_core::Stream;
+
+use crate::{
+    entity::todo::{Priority, Todo},
+    error::DomainError,
+};
 
-use crate::{entity::todo::Todo, error::DomainError};
+/// Todos read lazily, one row at a time.
+pub type TodoStream = Pin<Box<dyn Stream<Item = Result<Todo, DomainError>> + Send>>;
 
 #[async_trait]
 pub trait TodoRepository: Send + Sync + 'static {
-    async fn create(&self, todo: &Todo) -> Result<(), DomainError>;
+    /// Inserts `todo` (its `id` is ignored) and returns the id assigned by the store.
+    async fn create(&self, todo: &Todo) -> Result<i64, DomainError>;
     async fn find_all(&self) -> Result<Vec<Todo>, DomainError>;
+    /// The same todos as [`TodoRepository::find_all`], without loading them all at once.
+    fn stream_all(&self) -> TodoStream;
     async fn find_by_id(&self, id: i64) -> Result<Option<Todo>, DomainError>;
+    /// The todo as it was at `at`, or `None` if it did not exist then. Fails with
+    /// `Validation` where the store keeps no history of its todos.
+    async fn find_by_id_at(&self, id: i64, at: DateTime<Utc>) -> Result<Option<Todo>, DomainError>;
+    /// The todos among `ids` in one read, ordered by id; missing and trashed ones are left
+    /// out.
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Todo>, DomainError>;
     async fn update(&self, todo: &Todo) -> Result<(), DomainError>;
+    /// Moves the todo to the trash; see [`crate::repository::trash_repository`].
     async fn delete(&self, todo_id: i64) -> Result<(), DomainError>;
+    /// Open todos whose `due_at` is strictly before `before`, ordered by `due_at`.
+    async fn find_due_before(&self, before: DateTime<Utc>) -> Result<Vec<Todo>, DomainError>;
+    /// Open todos whose `due_at` falls in `[from, to)`, ordered by `due_at`.
+    async fn find_due_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Todo>, DomainError>;
+    /// Open todos with at least the given priority, highest first.
+    async fn find_by_min_priority(&self, priority: Priority) -> Result<Vec<Todo>, DomainError>;
+}
+
+#[async_trait]
+impl<R: TodoRepository + ?Sized> TodoRepository for Arc<R> {
+    async fn create(&self, todo: &Todo) -> Result<i64, DomainError> {
+        (**self).create(todo).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<Todo>, DomainError> {
+        (**self).find_all().await
+    }
+
+    fn stream_all(&self) -> TodoStream {
+        (**self).stream_all()
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Todo>, DomainError> {
+        (**self).find_by_id(id).await
+    }
+
+    async fn find_by_id_at(&self, id: i64, at: DateTime<Utc>) -> Result<Option<Todo>, DomainError> {
+        (**self).find_by_id_at(id, at).await
+    }
+
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Todo>, DomainError> {
+        (**self).find_by_ids(ids).await
+    }
+
+    async fn update(&self, todo: &Todo) -> Result<(), DomainError> {
+        (**self).update(todo).await
+    }
+
+    async fn delete(&self, todo_id: i64) -> Result<(), DomainError> {
+        (**self).delete(todo_id).await
+    }
+
+    async fn find_due_before(&self, before: DateTime<Utc>) -> Result<Vec<Todo>, DomainError> {
+        (**self).find_due_before(before).await
+    }
+
+    async fn find_due_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Todo>, DomainError> {
+        (**self).find_due_between(from, to).await
+    }
+
+    async fn find_by_min_priority(&self, priority: Priority) -> Result<Vec<Todo>, DomainError> {
+        (**self).find_by_min_priority(priority).await
+    }
 }
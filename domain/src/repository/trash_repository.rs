@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{entity::trash::TrashedTodo, error::DomainError};
+
+/// Deleted todos. Todos are moved to the trash by `delete` and taken back out through a
+/// unit of work, together with the events of the restore.
+#[async_trait]
+pub trait TrashRepository: Send + Sync + 'static {
+    /// Every trashed todo, most recently deleted first.
+    async fn find_all(&self) -> Result<Vec<TrashedTodo>, DomainError>;
+    /// Permanently removes the todos deleted before `before` and returns how many there were.
+    async fn purge_deleted_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError>;
+}
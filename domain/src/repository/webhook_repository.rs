@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    entity::webhook::{WebhookDelivery, WebhookSubscription},
+    error::DomainError,
+};
+
+/// Subscriptions and the delivery queue. Outbox events themselves are written by the todo
+/// repository in the same transaction as the change they describe.
+#[async_trait]
+pub trait WebhookRepository: Send + Sync + 'static {
+    async fn create_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+    ) -> Result<i64, DomainError>;
+    async fn find_subscriptions(&self) -> Result<Vec<WebhookSubscription>, DomainError>;
+    async fn find_subscription_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<WebhookSubscription>, DomainError>;
+    async fn delete_subscription(&self, id: i64) -> Result<(), DomainError>;
+    /// Most recent deliveries for a subscription, newest first.
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, DomainError>;
+    /// Turns unprocessed outbox events into one pending delivery per matching subscription
+    /// and returns how many deliveries were queued.
+    async fn fan_out(&self, now: DateTime<Utc>) -> Result<usize, DomainError>;
+    /// Atomically leases up to `limit` pending deliveries due at `now` until `lease_until`,
+    /// so concurrent workers never send the same delivery twice.
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, DomainError>;
+    async fn mark_delivered(
+        &self,
+        id: i64,
+        status: i64,
+        delivered_at: DateTime<Utc>,
+    ) -> Result<(), DomainError>;
+    /// Records a failed attempt; the delivery is retried at `retry_at`, or abandoned at
+    /// `now` when `retry_at` is `None`.
+    async fn mark_failed(
+        &self,
+        id: i64,
+        status: Option<i64>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<(), DomainError>;
+}
@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    entity::{
+        audit::AuditEntry, caldav_resource::CalDavResource, idempotency::IdempotencyRecord,
+        reminder::Reminder, todo::Todo,
+    },
+    error::DomainError,
+    event::TodoEvent,
+};
+
+/// Starts transactions that group operations across repositories into one atomic change.
+#[async_trait]
+pub trait UnitOfWork: Send + Sync + 'static {
+    /// Dropping the returned transaction without committing discards its changes.
+    async fn begin(&self) -> Result<Box<dyn Transaction>, DomainError>;
+}
+
+/// Repository operations sharing one transaction. Changes made through it and the events
+/// recorded on it are persisted together by [`Transaction::commit`], or not at all.
+#[async_trait]
+pub trait Transaction: Send {
+    /// Inserts `todo` (its `id` is ignored) and returns the id assigned by the store.
+    async fn create_todo(&mut self, todo: &Todo) -> Result<i64, DomainError>;
+    async fn find_todo_by_id(&mut self, id: i64) -> Result<Option<Todo>, DomainError>;
+    async fn update_todo(&mut self, todo: &Todo) -> Result<(), DomainError>;
+    /// Moves the todo to the trash; it is no longer found until restored. Fails with
+    /// `Conflict` unless the todo is still at `expected_version`; `None` skips the check.
+    async fn delete_todo(
+        &mut self,
+        todo_id: i64,
+        expected_version: Option<i64>,
+    ) -> Result<(), DomainError>;
+    /// Takes the todo back out of the trash and returns it, or `None` if it is not there.
+    async fn restore_todo(&mut self, todo_id: i64) -> Result<Option<Todo>, DomainError>;
+    async fn create_reminder(&mut self, reminder: &Reminder) -> Result<i64, DomainError>;
+    async fn find_reminders_by_todo_id(
+        &mut self,
+        todo_id: i64,
+    ) -> Result<Vec<Reminder>, DomainError>;
+    /// Stores `todo` as its next revision and returns the revision number.
+    async fn append_revision(&mut self, todo: &Todo) -> Result<i64, DomainError>;
+    /// Appends `entry` to the audit log and returns its id.
+    async fn append_audit(&mut self, entry: &AuditEntry) -> Result<i64, DomainError>;
+    /// Drops the records expired by `now`, then finds the one stored under `key`. Called
+    /// before any other operation, it makes concurrent transactions creating under one key
+    /// run one after the other.
+    async fn find_idempotency_record(
+        &mut self,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<IdempotencyRecord>, DomainError>;
+    /// Stores `record` and returns `true`, or returns `false`, storing nothing, when another
+    /// transaction already stored a record under the key.
+    async fn save_idempotency_record(
+        &mut self,
+        record: &IdempotencyRecord,
+    ) -> Result<bool, DomainError>;
+    /// Serves the todo under `resource.name` and returns `true`, or returns `false`, storing
+    /// nothing, when the name is already taken.
+    async fn create_caldav_resource(
+        &mut self,
+        resource: &CalDavResource,
+    ) -> Result<bool, DomainError>;
+    /// Marks a point [`Transaction::rollback_to_savepoint`] can return to, undoing what
+    /// came after it while keeping what came before. Savepoints nest.
+    async fn savepoint(&mut self) -> Result<(), DomainError>;
+    /// Keeps the changes and events since the innermost savepoint and forgets it.
+    async fn release_savepoint(&mut self) -> Result<(), DomainError>;
+    /// Undoes the changes and events since the innermost savepoint and forgets it.
+    async fn rollback_to_savepoint(&mut self) -> Result<(), DomainError>;
+    fn record(&mut self, event: TodoEvent);
+    /// Stores the recorded events, commits, and hands the events back for dispatch.
+    async fn commit(self: Box<Self>) -> Result<Vec<TodoEvent>, DomainError>;
+}
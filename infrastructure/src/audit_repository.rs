@@ -0,0 +1,284 @@
+use async_trait::async_trait;
+use chrono::DateTime;
+use domain::{
+    entity::{audit::AuditEntry, todo::Todo},
+    error::DomainError,
+    repository::audit_repository::AuditRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::event_store::TodoState;
+
+#[derive(Debug, Clone)]
+pub struct SqliteAuditRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteAuditRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqliteAuditRepository {
+    async fn find(
+        &self,
+        todo_id: Option<i64>,
+        actor: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<AuditEntry>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteAuditRepository::find(todo_id, actor.as_deref(), limit, &mut conn).await
+    }
+}
+
+/// Row shape of the `audit_log` table; `before` and `after` hold the todo as JSON.
+struct AuditRow {
+    id: i64,
+    todo_id: i64,
+    action: String,
+    actor: String,
+    protocol: String,
+    request_id: String,
+    before: Option<String>,
+    after: Option<String>,
+    occurred_at: i64,
+}
+
+impl TryFrom<AuditRow> for AuditEntry {
+    type Error = DomainError;
+
+    fn try_from(row: AuditRow) -> Result<Self, Self::Error> {
+        let occurred_at = match DateTime::from_timestamp(row.occurred_at, 0) {
+            Some(occurred_at) => occurred_at,
+            None => {
+                return Err(DomainError::Unexpected(format!(
+                    "invalid timestamp: {}",
+                    row.occurred_at
+                )))
+            }
+        };
+        Ok(AuditEntry {
+            id: row.id,
+            todo_id: row.todo_id,
+            action: row.action.parse()?,
+            actor: row.actor,
+            protocol: row.protocol.parse()?,
+            request_id: row.request_id,
+            before: decode(row.todo_id, row.before)?,
+            after: decode(row.todo_id, row.after)?,
+            occurred_at,
+        })
+    }
+}
+
+fn encode(todo: Option<&Todo>) -> Result<Option<String>, DomainError> {
+    match todo {
+        Some(todo) => match serde_json::to_string(&TodoState::from(todo)) {
+            Ok(state) => Ok(Some(state)),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        },
+        None => Ok(None),
+    }
+}
+
+fn decode(todo_id: i64, state: Option<String>) -> Result<Option<Todo>, DomainError> {
+    match state {
+        Some(state) => match serde_json::from_str::<TodoState>(&state) {
+            Ok(state) => Ok(Some(state.into_todo(todo_id)?)),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        },
+        None => Ok(None),
+    }
+}
+
+pub struct InternalSqliteAuditRepository {}
+
+impl InternalSqliteAuditRepository {
+    pub async fn append(
+        entry: &AuditEntry,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, DomainError> {
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO audit_log (todo_id, action, actor, protocol, request_id, before, after, occurred_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(entry.todo_id)
+        .bind(entry.action.as_str())
+        .bind(&entry.actor)
+        .bind(entry.protocol.as_str())
+        .bind(&entry.request_id)
+        .bind(encode(entry.before.as_ref())?)
+        .bind(encode(entry.after.as_ref())?)
+        .bind(entry.occurred_at.timestamp())
+        .fetch_one(&mut *conn)
+        .await;
+        match id {
+            Ok(id) => Ok(id),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find(
+        todo_id: Option<i64>,
+        actor: Option<&str>,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<AuditEntry>, DomainError> {
+        let entries = sqlx::query_as!(
+            AuditRow,
+            r#"
+            SELECT id AS "id!", todo_id, action, actor, protocol, request_id, before, after, occurred_at
+            FROM audit_log
+            WHERE ($1 IS NULL OR todo_id = $1) AND ($2 IS NULL OR actor = $2)
+            ORDER BY id DESC
+            LIMIT $3
+            "#,
+            todo_id,
+            actor,
+            limit
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match entries {
+            Ok(entries) => entries.into_iter().map(AuditEntry::try_from).collect(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use chrono::Utc;
+    use domain::entity::{
+        audit::{AuditAction, Protocol},
+        todo::Priority,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    pub(crate) async fn prepare_audit_table(conn: &mut SqliteConnection) {
+        sqlx::query(
+            r#"
+            CREATE TABLE audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                todo_id INTEGER NOT NULL,
+                action TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                protocol TEXT NOT NULL,
+                request_id TEXT NOT NULL,
+                before TEXT,
+                after TEXT,
+                occurred_at INTEGER NOT NULL
+            );
+            CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+            END;
+            CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+            END;
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    }
+
+    fn entry(todo_id: i64, actor: &str, before: Option<Todo>, after: Option<Todo>) -> AuditEntry {
+        AuditEntry {
+            id: 0,
+            todo_id,
+            action: AuditAction::Update,
+            actor: actor.to_string(),
+            protocol: Protocol::Grpc,
+            request_id: format!("req-{}", todo_id),
+            before,
+            after,
+            occurred_at: DateTime::from_timestamp(1_000, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_and_find() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_audit_table(&mut conn).await;
+
+        let before = Todo {
+            id: 1,
+            title: Some("before".to_string()),
+            ..Default::default()
+        };
+        let after = Todo {
+            title: Some("after".to_string()),
+            priority: Priority::High,
+            completed_at: Some(Utc::now()),
+            ..before.clone()
+        };
+        let mut first = entry(1, "alice", Some(before), Some(after));
+        first.id = InternalSqliteAuditRepository::append(&first, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let mut second = entry(2, "bob", None, None);
+        second.id = InternalSqliteAuditRepository::append(&second, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+
+        let repository = SqliteAuditRepository::new(pool.clone());
+        match repository.find(None, None, 10).await {
+            Ok(entries) => {
+                assert_eq!(
+                    entries.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+                    vec![second.id, first.id]
+                );
+                // timestamps are kept to the second
+                assert_eq!(
+                    entries[1].after.as_ref().unwrap().title,
+                    first.after.as_ref().unwrap().title
+                );
+                assert_eq!(entries[1].changes().len(), 3);
+            }
+            Err(_) => panic!("failed to fetch audit log"),
+        }
+        match repository.find(Some(1), None, 10).await {
+            Ok(entries) => assert_eq!(entries.len(), 1),
+            Err(_) => panic!("failed to fetch audit log"),
+        }
+        match repository.find(None, Some("bob".to_string()), 10).await {
+            Ok(entries) => assert_eq!(entries, vec![second]),
+            Err(_) => panic!("failed to fetch audit log"),
+        }
+        match repository.find(Some(1), Some("bob".to_string()), 10).await {
+            Ok(entries) => assert!(entries.is_empty()),
+            Err(_) => panic!("failed to fetch audit log"),
+        }
+        match repository.find(None, None, 1).await {
+            Ok(entries) => assert_eq!(entries.len(), 1),
+            Err(_) => panic!("failed to fetch audit log"),
+        }
+
+        let result = sqlx::query("DELETE FROM audit_log")
+            .execute(&mut *conn)
+            .await;
+        assert!(result.is_err());
+        let result = sqlx::query("UPDATE audit_log SET actor = 'mallory'")
+            .execute(&mut *conn)
+            .await;
+        assert!(result.is_err());
+    }
+}
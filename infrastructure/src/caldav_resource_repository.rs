@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use domain::{
+    entity::caldav_resource::CalDavResource, error::DomainError,
+    repository::caldav_resource_repository::CalDavResourceRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+#[derive(Debug, Clone)]
+pub struct SqliteCalDavResourceRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteCalDavResourceRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CalDavResourceRepository for SqliteCalDavResourceRepository {
+    async fn find_by_name(&self, name: &str) -> Result<Option<CalDavResource>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteCalDavResourceRepository::find_by_name(name, &mut conn).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<CalDavResource>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteCalDavResourceRepository::find_all(&mut conn).await
+    }
+}
+
+/// Row shape of the `caldav_resources` table.
+#[derive(sqlx::FromRow)]
+struct CalDavResourceRow {
+    name: String,
+    todo_id: i64,
+}
+
+impl From<CalDavResourceRow> for CalDavResource {
+    fn from(row: CalDavResourceRow) -> Self {
+        CalDavResource {
+            name: row.name,
+            todo_id: row.todo_id,
+        }
+    }
+}
+
+pub struct InternalSqliteCalDavResourceRepository {}
+
+impl InternalSqliteCalDavResourceRepository {
+    pub async fn find_by_name(
+        name: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<CalDavResource>, DomainError> {
+        let resource = sqlx::query_as::<_, CalDavResourceRow>(
+            r#"
+            SELECT name, todo_id
+            FROM caldav_resources
+            WHERE name = $1
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await;
+        match resource {
+            Ok(resource) => Ok(resource.map(CalDavResource::from)),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<CalDavResource>, DomainError> {
+        let resources = sqlx::query_as::<_, CalDavResourceRow>(
+            r#"
+            SELECT name, todo_id
+            FROM caldav_resources
+            ORDER BY todo_id
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match resources {
+            Ok(resources) => Ok(resources.into_iter().map(CalDavResource::from).collect()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Returns `false`, storing nothing, when the name is already taken.
+    pub async fn create(
+        resource: &CalDavResource,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, DomainError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO caldav_resources (name, todo_id)
+            VALUES ($1, $2)
+            ON CONFLICT (name) DO NOTHING
+            "#,
+        )
+        .bind(&resource.name)
+        .bind(resource.todo_id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// Mirrors the `caldav_resources` table from `migrations/`.
+    pub(crate) async fn prepare_caldav_resource_table(conn: &mut SqliteConnection) {
+        sqlx::query(
+            r#"
+            CREATE TABLE caldav_resources (
+                name TEXT PRIMARY KEY,
+                todo_id INTEGER NOT NULL UNIQUE
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    }
+
+    fn resource(name: &str, todo_id: i64) -> CalDavResource {
+        CalDavResource::new(name.to_string(), todo_id).ok().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_caldav_resource_table(&mut conn).await;
+
+        for resource in [resource("b.ics", 2), resource("a.ics", 1)] {
+            match InternalSqliteCalDavResourceRepository::create(&resource, &mut conn).await {
+                Ok(created) => assert!(created),
+                Err(_) => panic!("failed to create resource"),
+            }
+        }
+        match InternalSqliteCalDavResourceRepository::find_by_name("b.ics", &mut conn).await {
+            Ok(Some(found)) => assert_eq!(found, resource("b.ics", 2)),
+            _ => panic!("failed to find resource"),
+        }
+
+        // a name stays with the todo that took it first
+        match InternalSqliteCalDavResourceRepository::create(&resource("b.ics", 3), &mut conn).await
+        {
+            Ok(created) => assert!(!created),
+            Err(_) => panic!("failed to create resource"),
+        }
+        match InternalSqliteCalDavResourceRepository::find_all(&mut conn).await {
+            Ok(resources) => {
+                assert_eq!(resources, vec![resource("a.ics", 1), resource("b.ics", 2)])
+            }
+            Err(_) => panic!("failed to find resources"),
+        }
+        match InternalSqliteCalDavResourceRepository::find_by_name("c.ics", &mut conn).await {
+            Ok(None) => {}
+            _ => panic!("found a resource that was never saved"),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use async_trait::async_trait;
+use domain::{
+    entity::calendar_feed::CalendarFeed, error::DomainError,
+    repository::calendar_feed_repository::CalendarFeedRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::webhook_repository::required_timestamp;
+
+#[derive(Debug, Clone)]
+pub struct SqliteCalendarFeedRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteCalendarFeedRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CalendarFeedRepository for SqliteCalendarFeedRepository {
+    async fn create_feed(&self, feed: &CalendarFeed) -> Result<i64, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteCalendarFeedRepository::create_feed(feed, &mut conn).await
+    }
+
+    async fn find_feeds_by_actor(&self, actor: &str) -> Result<Vec<CalendarFeed>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteCalendarFeedRepository::find_feeds_by_actor(actor, &mut conn).await
+    }
+
+    async fn find_feed_by_id(&self, id: i64) -> Result<Option<CalendarFeed>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteCalendarFeedRepository::find_feed_by_id(id, &mut conn).await
+    }
+
+    async fn find_feed_by_token(&self, token: &str) -> Result<Option<CalendarFeed>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteCalendarFeedRepository::find_feed_by_token(token, &mut conn).await
+    }
+
+    async fn delete_feed(&self, id: i64) -> Result<(), DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteCalendarFeedRepository::delete_feed(id, &mut conn).await
+    }
+}
+
+/// Row shape of the `calendar_feeds` table.
+struct CalendarFeedRow {
+    id: i64,
+    token: String,
+    actor: String,
+    created_at: i64,
+}
+
+impl TryFrom<CalendarFeedRow> for CalendarFeed {
+    type Error = DomainError;
+
+    fn try_from(row: CalendarFeedRow) -> Result<Self, Self::Error> {
+        Ok(CalendarFeed {
+            id: row.id,
+            token: row.token,
+            actor: row.actor,
+            created_at: required_timestamp(row.created_at)?,
+        })
+    }
+}
+
+pub struct InternalSqliteCalendarFeedRepository {}
+
+impl InternalSqliteCalendarFeedRepository {
+    pub async fn create_feed(
+        feed: &CalendarFeed,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, DomainError> {
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO calendar_feeds (token, actor, created_at)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(&feed.token)
+        .bind(&feed.actor)
+        .bind(feed.created_at.timestamp())
+        .fetch_one(&mut *conn)
+        .await;
+        match id {
+            Ok(id) => Ok(id),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_feeds_by_actor(
+        actor: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CalendarFeed>, DomainError> {
+        let feeds = sqlx::query_as!(
+            CalendarFeedRow,
+            r#"
+            SELECT id AS "id!", token, actor, created_at
+            FROM calendar_feeds
+            WHERE actor = $1
+            ORDER BY id
+            "#,
+            actor
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match feeds {
+            Ok(feeds) => feeds.into_iter().map(CalendarFeed::try_from).collect(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_feed_by_id(
+        id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<CalendarFeed>, DomainError> {
+        let feed = sqlx::query_as!(
+            CalendarFeedRow,
+            r#"
+            SELECT id AS "id!", token, actor, created_at
+            FROM calendar_feeds
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        match feed {
+            Ok(feed) => feed.map(CalendarFeed::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_feed_by_token(
+        token: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<CalendarFeed>, DomainError> {
+        let feed = sqlx::query_as!(
+            CalendarFeedRow,
+            r#"
+            SELECT id AS "id!", token, actor, created_at
+            FROM calendar_feeds
+            WHERE token = $1
+            "#,
+            token
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        match feed {
+            Ok(feed) => feed.map(CalendarFeed::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn delete_feed(id: i64, conn: &mut SqliteConnection) -> Result<(), DomainError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM calendar_feeds
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// Mirrors the `calendar_feeds` table from `migrations/`.
+    async fn prepare_calendar_feed_table(conn: &mut SqliteConnection) {
+        sqlx::query(
+            r#"
+            CREATE TABLE calendar_feeds (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                token TEXT NOT NULL UNIQUE,
+                actor TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    }
+
+    fn feed(token: &str, actor: &str) -> CalendarFeed {
+        CalendarFeed::new(
+            token.repeat(32),
+            actor.to_string(),
+            DateTime::from_timestamp(1_704_067_200, 0).unwrap(),
+        )
+        .ok()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_calendar_feeds() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_calendar_feed_table(&mut conn).await;
+
+        let mut ids = Vec::new();
+        for (token, actor) in [("a", "alice"), ("b", "bob"), ("c", "alice")] {
+            match InternalSqliteCalendarFeedRepository::create_feed(&feed(token, actor), &mut conn)
+                .await
+            {
+                Ok(id) => ids.push(id),
+                Err(_) => panic!("failed to create feed"),
+            }
+        }
+        // tokens are unique
+        let duplicate =
+            InternalSqliteCalendarFeedRepository::create_feed(&feed("a", "bob"), &mut conn).await;
+        assert!(duplicate.is_err());
+
+        match InternalSqliteCalendarFeedRepository::find_feeds_by_actor("alice", &mut conn).await {
+            Ok(feeds) => {
+                let tokens: Vec<String> = feeds.into_iter().map(|feed| feed.token).collect();
+                assert_eq!(tokens, vec!["a".repeat(32), "c".repeat(32)]);
+            }
+            Err(_) => panic!("failed to find feeds"),
+        }
+        match InternalSqliteCalendarFeedRepository::find_feed_by_token(&"b".repeat(32), &mut conn)
+            .await
+        {
+            Ok(Some(found)) => assert_eq!(
+                found,
+                CalendarFeed {
+                    id: ids[1],
+                    ..feed("b", "bob")
+                }
+            ),
+            _ => panic!("failed to find feed by token"),
+        }
+
+        InternalSqliteCalendarFeedRepository::delete_feed(ids[1], &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        match InternalSqliteCalendarFeedRepository::find_feed_by_id(ids[1], &mut conn).await {
+            Ok(None) => {}
+            _ => panic!("feed was not deleted"),
+        }
+    }
+}
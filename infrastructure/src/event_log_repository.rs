@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use domain::{
+    entity::event_log::LoggedEvent, error::DomainError,
+    repository::event_log_repository::EventLogRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::todo_repository::from_timestamp;
+
+/// `EventLogRepository` over `domain_events`, which `SqliteTransaction::commit` appends to.
+#[derive(Debug, Clone)]
+pub struct SqliteEventLogRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteEventLogRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventLogRepository for SqliteEventLogRepository {
+    async fn find_after(&self, after_id: i64, limit: i64) -> Result<Vec<LoggedEvent>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteEventLogRepository::find_after(after_id, limit, &mut conn).await
+    }
+
+    async fn find_bounds(&self) -> Result<Option<(i64, i64)>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteEventLogRepository::find_bounds(&mut conn).await
+    }
+
+    async fn prune(&self, keep: i64) -> Result<u64, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteEventLogRepository::prune(keep, &mut conn).await
+    }
+}
+
+/// Row shape of the `domain_events` table.
+type LoggedEventRow = (i64, String, i64, String, i64);
+
+fn into_logged_event(row: LoggedEventRow) -> Result<LoggedEvent, DomainError> {
+    let (id, event, todo_id, payload, occurred_at) = row;
+    let occurred_at = match from_timestamp(Some(occurred_at))? {
+        Some(occurred_at) => occurred_at,
+        None => return Err(DomainError::Unexpected(format!("event {} has no time", id))),
+    };
+    Ok(LoggedEvent {
+        id,
+        event,
+        todo_id,
+        payload,
+        occurred_at,
+    })
+}
+
+pub struct InternalSqliteEventLogRepository {}
+
+impl InternalSqliteEventLogRepository {
+    pub async fn find_after(
+        after_id: i64,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<LoggedEvent>, DomainError> {
+        let rows = sqlx::query_as::<_, LoggedEventRow>(
+            r#"
+            SELECT id, event, todo_id, payload, occurred_at
+            FROM domain_events
+            WHERE id > $1
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&mut *conn)
+        .await;
+        match rows {
+            Ok(rows) => rows.into_iter().map(into_logged_event).collect(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_bounds(
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<(i64, i64)>, DomainError> {
+        let bounds = sqlx::query_as::<_, (Option<i64>, Option<i64>)>(
+            r#"
+            SELECT MIN(id), MAX(id)
+            FROM domain_events
+            "#,
+        )
+        .fetch_one(&mut *conn)
+        .await;
+        match bounds {
+            Ok((Some(oldest), Some(newest))) => Ok(Some((oldest, newest))),
+            Ok(_) => Ok(None),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Deletes every event but the newest `keep`. Ids are never reused, so readers behind
+    /// the oldest id kept know they missed events.
+    pub async fn prune(keep: i64, conn: &mut SqliteConnection) -> Result<u64, DomainError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM domain_events
+            WHERE id <= (SELECT MAX(id) FROM domain_events) - $1
+            "#,
+        )
+        .bind(keep)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(result) => Ok(result.rows_affected()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo_repository::{tests::prepare_table, InternalSqliteTodoRepository};
+    use chrono::{DateTime, Utc};
+    use domain::{entity::todo::Todo, event::TodoEvent};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_find_after_and_prune() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_table(&mut conn).await;
+
+        let repository = SqliteEventLogRepository::new(pool.clone());
+        match repository.find_bounds().await {
+            Ok(bounds) => assert!(bounds.is_none()),
+            Err(_) => panic!("failed to fetch bounds"),
+        }
+
+        let occurred_at: DateTime<Utc> = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        for id in 1..=5 {
+            let todo = Todo {
+                id,
+                title: Some(format!("task{}", id)),
+                ..Default::default()
+            };
+            let event = TodoEvent::TodoCreated { todo };
+            InternalSqliteTodoRepository::append_event(&event, occurred_at, &mut conn)
+                .await
+                .ok()
+                .unwrap();
+        }
+
+        match repository.find_after(2, 2).await {
+            Ok(events) => {
+                assert_eq!(
+                    events.iter().map(|event| event.id).collect::<Vec<_>>(),
+                    vec![3, 4]
+                );
+                assert_eq!(events[0].event, "TodoCreated");
+                assert_eq!(events[0].todo_id, 3);
+                assert_eq!(events[0].occurred_at, occurred_at);
+                assert!(events[0].payload.contains("task3"));
+            }
+            Err(_) => panic!("failed to fetch events"),
+        }
+
+        match repository.prune(2).await {
+            Ok(pruned) => assert_eq!(pruned, 3),
+            Err(_) => panic!("failed to prune events"),
+        }
+        match repository.find_bounds().await {
+            Ok(bounds) => assert_eq!(bounds, Some((4, 5))),
+            Err(_) => panic!("failed to fetch bounds"),
+        }
+        match repository.prune(2).await {
+            Ok(pruned) => assert_eq!(pruned, 0),
+            Err(_) => panic!("failed to prune events"),
+        }
+    }
+}
@@ -0,0 +1,806 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{
+        recurrence::Recurrence,
+        todo::{Priority, Todo},
+    },
+    error::DomainError,
+    repository::todo_repository::{TodoRepository, TodoStream},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::{
+    todo_repository::{
+        create_recorded, delete_recorded, from_timestamp, stream_all, update_recorded,
+        InternalSqliteTodoRepository,
+    },
+    trash_repository::InternalSqliteTrashRepository,
+    unit_of_work::{SqliteTransaction, TodoPersistence},
+};
+
+/// `TodoRepository` backed by per-todo event streams. Writes append to `todo_events` and
+/// keep `todos` up to date as a projection in the same transaction; `find_by_id` replays the
+/// stream from its latest snapshot while the list views read the projection.
+#[derive(Debug, Clone)]
+pub struct EventSourcedTodoRepository {
+    pool: Pool<Sqlite>,
+    snapshot_every: i64,
+}
+
+impl EventSourcedTodoRepository {
+    pub const DEFAULT_SNAPSHOT_EVERY: i64 = 50;
+
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self {
+            pool,
+            snapshot_every: Self::DEFAULT_SNAPSHOT_EVERY,
+        }
+    }
+
+    /// Sets how many events of a stream are replayed at most before a snapshot is taken.
+    pub fn with_snapshot_every(mut self, snapshot_every: i64) -> Self {
+        self.snapshot_every = snapshot_every.max(1);
+        self
+    }
+
+    pub fn persistence(&self) -> TodoPersistence {
+        TodoPersistence::EventSourced {
+            snapshot_every: self.snapshot_every,
+        }
+    }
+
+    async fn begin_transaction(&self) -> Result<SqliteTransaction, DomainError> {
+        let tx = self.pool.begin().await;
+        match tx {
+            Ok(tx) => Ok(SqliteTransaction::new(tx, self.persistence())),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[async_trait]
+impl TodoRepository for EventSourcedTodoRepository {
+    async fn create(&self, todo: &Todo) -> Result<i64, DomainError> {
+        create_recorded(self.begin_transaction().await?, todo).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteTodoRepository::find_all(&mut conn).await
+    }
+
+    fn stream_all(&self) -> TodoStream {
+        stream_all(self.pool.clone())
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalEventSourcedTodoRepository::find_by_id(id, &mut conn).await
+    }
+
+    async fn find_by_id_at(&self, id: i64, at: DateTime<Utc>) -> Result<Option<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalEventSourcedTodoRepository::find_by_id_at(id, at, &mut conn).await
+    }
+
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        // the projection is written in the same transaction as the streams
+        InternalSqliteTodoRepository::find_by_ids(ids, &mut conn).await
+    }
+
+    async fn update(&self, todo: &Todo) -> Result<(), DomainError> {
+        update_recorded(self.begin_transaction().await?, todo).await
+    }
+
+    async fn delete(&self, todo_id: i64) -> Result<(), DomainError> {
+        delete_recorded(self.begin_transaction().await?, todo_id).await
+    }
+
+    async fn find_due_before(&self, before: DateTime<Utc>) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteTodoRepository::find_due_before(before, &mut conn).await
+    }
+
+    async fn find_due_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteTodoRepository::find_due_between(from, to, &mut conn).await
+    }
+
+    async fn find_by_min_priority(&self, priority: Priority) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteTodoRepository::find_by_min_priority(priority, &mut conn).await
+    }
+}
+
+/// Todo fields as stored in events and snapshots; timestamps are unix seconds, like the
+/// `todos` table, so replayed and projected todos compare equal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TodoState {
+    title: Option<String>,
+    due_at: Option<i64>,
+    priority: i64,
+    recurrence: Option<String>,
+    completed_at: Option<i64>,
+    /// Absent from states stored before todos were versioned.
+    #[serde(default = "first_version")]
+    version: i64,
+}
+
+fn first_version() -> i64 {
+    Todo::FIRST_VERSION
+}
+
+impl From<&Todo> for TodoState {
+    fn from(todo: &Todo) -> Self {
+        TodoState {
+            title: todo.title.clone(),
+            due_at: todo.due_at.map(|due_at| due_at.timestamp()),
+            priority: todo.priority.as_i64(),
+            recurrence: todo
+                .recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.to_string()),
+            completed_at: todo
+                .completed_at
+                .map(|completed_at| completed_at.timestamp()),
+            version: todo.version,
+        }
+    }
+}
+
+impl TodoState {
+    pub(crate) fn into_todo(self, id: i64) -> Result<Todo, DomainError> {
+        let recurrence = match self.recurrence {
+            Some(rule) => Some(rule.parse::<Recurrence>()?),
+            None => None,
+        };
+        Ok(Todo {
+            id,
+            title: self.title,
+            due_at: from_timestamp(self.due_at)?,
+            priority: Priority::try_from(self.priority)?,
+            recurrence,
+            completed_at: from_timestamp(self.completed_at)?,
+            version: self.version,
+        })
+    }
+}
+
+/// An entry of a todo's stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum StreamEvent {
+    #[serde(rename = "TodoCreated")]
+    Created(TodoState),
+    #[serde(rename = "TodoUpdated")]
+    Updated(TodoState),
+    #[serde(rename = "TodoCompleted")]
+    Completed { completed_at: i64 },
+    #[serde(rename = "TodoDeleted")]
+    Deleted,
+    #[serde(rename = "TodoRestored")]
+    Restored(TodoState),
+}
+
+impl StreamEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            StreamEvent::Created(_) => "TodoCreated",
+            StreamEvent::Updated(_) => "TodoUpdated",
+            StreamEvent::Completed { .. } => "TodoCompleted",
+            StreamEvent::Deleted => "TodoDeleted",
+            StreamEvent::Restored(_) => "TodoRestored",
+        }
+    }
+
+    /// The event that turns `current` into `todo`.
+    fn between(current: &Todo, todo: &Todo) -> Self {
+        let only_completed = Todo {
+            completed_at: current.completed_at,
+            version: current.version,
+            ..todo.clone()
+        } == *current;
+        match todo.completed_at {
+            Some(completed_at) if !current.is_completed() && only_completed => {
+                StreamEvent::Completed {
+                    completed_at: completed_at.timestamp(),
+                }
+            }
+            _ => StreamEvent::Updated(todo.into()),
+        }
+    }
+
+    fn apply(self, id: i64, state: Option<Todo>) -> Result<Option<Todo>, DomainError> {
+        match self {
+            StreamEvent::Created(state)
+            | StreamEvent::Updated(state)
+            | StreamEvent::Restored(state) => Ok(Some(state.into_todo(id)?)),
+            StreamEvent::Completed { completed_at } => match state {
+                Some(todo) => Ok(Some(Todo {
+                    completed_at: from_timestamp(Some(completed_at))?,
+                    version: todo.version + 1,
+                    ..todo
+                })),
+                None => Err(DomainError::Unexpected(format!(
+                    "todo {} completed before it was created",
+                    id
+                ))),
+            },
+            StreamEvent::Deleted => Ok(None),
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, DomainError> {
+    match serde_json::to_string(value) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(DomainError::Infrastructure(e.into())),
+    }
+}
+
+fn from_json<'a, T: Deserialize<'a>>(json: &'a str) -> Result<T, DomainError> {
+    match serde_json::from_str(json) {
+        Ok(value) => Ok(value),
+        Err(e) => Err(DomainError::Infrastructure(e.into())),
+    }
+}
+
+struct SnapshotRow {
+    version: i64,
+    state: Option<String>,
+}
+
+struct EventRow {
+    payload: String,
+}
+
+fn replay(
+    id: i64,
+    mut state: Option<Todo>,
+    rows: Vec<EventRow>,
+) -> Result<Option<Todo>, DomainError> {
+    for row in rows {
+        state = from_json::<StreamEvent>(&row.payload)?.apply(id, state)?;
+    }
+    Ok(state)
+}
+
+pub struct InternalEventSourcedTodoRepository {}
+
+impl InternalEventSourcedTodoRepository {
+    /// Projects `todo` into `todos`, which assigns its id, and starts its stream.
+    pub async fn create(
+        todo: &Todo,
+        snapshot_every: i64,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, DomainError> {
+        let id = InternalSqliteTodoRepository::create(todo, conn).await?;
+        let created = Todo { id, ..todo.clone() };
+        let event = StreamEvent::Created((&created).into());
+        Self::append(id, 1, &event, Some(&created), snapshot_every, now, conn).await?;
+        Ok(id)
+    }
+
+    /// Replays the todo's stream from its latest snapshot. Todos written before the event
+    /// store was selected have no stream and are read from the projection.
+    pub async fn find_by_id(
+        id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Todo>, DomainError> {
+        let (_, todo) = Self::load(id, conn).await?;
+        Ok(todo)
+    }
+
+    /// Replays the events of the todo up to `at` from the start of its stream. A todo written
+    /// before the event store was selected has a history only from its first change after.
+    pub async fn find_by_id_at(
+        id: i64,
+        at: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Todo>, DomainError> {
+        let at = at.timestamp();
+        let rows = sqlx::query_as!(
+            EventRow,
+            r#"
+            SELECT payload
+            FROM todo_events
+            WHERE todo_id = $1 AND occurred_at <= $2
+            ORDER BY version
+            "#,
+            id,
+            at
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match rows {
+            Ok(rows) => replay(id, None, rows),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Appends the change from the current state to `todo` and projects it. Updating a
+    /// missing todo is a no-op, as with `UPDATE`, and one no longer at `todo.version` fails
+    /// with `Conflict`.
+    pub async fn update(
+        todo: &Todo,
+        snapshot_every: i64,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let (version, current) = Self::load(todo.id, conn).await?;
+        let current = match current {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+        if current.version != todo.version {
+            return Err(DomainError::Conflict {
+                entity_type: "todo".to_string(),
+                entity_id: todo.id,
+            });
+        }
+        let version = Self::adopt(version, &current, snapshot_every, now, conn).await?;
+        let updated = Todo {
+            version: todo.version + 1,
+            ..todo.clone()
+        };
+        let event = StreamEvent::between(&current, &updated);
+        Self::append(
+            todo.id,
+            version + 1,
+            &event,
+            Some(&updated),
+            snapshot_every,
+            now,
+            conn,
+        )
+        .await?;
+        InternalSqliteTodoRepository::update(todo, conn).await
+    }
+
+    pub async fn delete(
+        todo_id: i64,
+        expected_version: Option<i64>,
+        snapshot_every: i64,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let (version, current) = Self::load(todo_id, conn).await?;
+        let current = match current {
+            Some(current) => current,
+            None => return Ok(()),
+        };
+        if expected_version.is_some_and(|expected_version| expected_version != current.version) {
+            return Err(DomainError::Conflict {
+                entity_type: "todo".to_string(),
+                entity_id: todo_id,
+            });
+        }
+        let version = Self::adopt(version, &current, snapshot_every, now, conn).await?;
+        let event = StreamEvent::Deleted;
+        Self::append(
+            todo_id,
+            version + 1,
+            &event,
+            None,
+            snapshot_every,
+            now,
+            conn,
+        )
+        .await?;
+        InternalSqliteTodoRepository::delete(todo_id, expected_version, now, conn).await
+    }
+
+    /// Takes the todo out of the trash in the projection and appends its restored state.
+    /// Returns `None` if the todo is not in the trash.
+    pub async fn restore(
+        todo_id: i64,
+        snapshot_every: i64,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Todo>, DomainError> {
+        let (version, _) = Self::load(todo_id, conn).await?;
+        let restored = match InternalSqliteTrashRepository::restore(todo_id, conn).await? {
+            Some(restored) => restored,
+            None => return Ok(None),
+        };
+        // a todo trashed before event sourcing has no stream; its next change adopts it
+        if version > 0 {
+            let event = StreamEvent::Restored((&restored).into());
+            Self::append(
+                todo_id,
+                version + 1,
+                &event,
+                Some(&restored),
+                snapshot_every,
+                now,
+                conn,
+            )
+            .await?;
+        }
+        Ok(Some(restored))
+    }
+
+    /// The stream's last version and the state it replays to.
+    async fn load(
+        id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<(i64, Option<Todo>), DomainError> {
+        let snapshot = sqlx::query_as!(
+            SnapshotRow,
+            r#"
+            SELECT version, state
+            FROM todo_snapshots
+            WHERE todo_id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        let (mut version, state) = match snapshot {
+            Ok(Some(snapshot)) => match snapshot.state {
+                Some(state) => (
+                    snapshot.version,
+                    Some(from_json::<TodoState>(&state)?.into_todo(id)?),
+                ),
+                None => (snapshot.version, None),
+            },
+            Ok(None) => (0, None),
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let rows = sqlx::query_as!(
+            EventRow,
+            r#"
+            SELECT payload
+            FROM todo_events
+            WHERE todo_id = $1 AND version > $2
+            ORDER BY version
+            "#,
+            id,
+            version
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        if version == 0 && rows.is_empty() {
+            let todo = InternalSqliteTodoRepository::find_by_id(id, conn).await?;
+            return Ok((0, todo));
+        }
+        version += rows.len() as i64;
+        let todo = match replay(id, state, rows)? {
+            Some(todo) => Some(Self::with_projected_version(todo, conn).await?),
+            None => None,
+        };
+        Ok((version, todo))
+    }
+
+    /// Takes the version from the projection, which guards updates; states stored before
+    /// todos were versioned do not carry it.
+    async fn with_projected_version(
+        todo: Todo,
+        conn: &mut SqliteConnection,
+    ) -> Result<Todo, DomainError> {
+        let version = sqlx::query_scalar::<_, i64>("SELECT version FROM todos WHERE id = $1")
+            .bind(todo.id)
+            .fetch_optional(&mut *conn)
+            .await;
+        match version {
+            Ok(Some(version)) => Ok(Todo { version, ..todo }),
+            Ok(None) => Ok(todo),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Starts a stream for a todo that only exists in the projection, so its later events
+    /// replay onto a known state. Returns the stream's version.
+    async fn adopt(
+        version: i64,
+        current: &Todo,
+        snapshot_every: i64,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, DomainError> {
+        if version > 0 {
+            return Ok(version);
+        }
+        let event = StreamEvent::Created(current.into());
+        Self::append(
+            current.id,
+            1,
+            &event,
+            Some(current),
+            snapshot_every,
+            now,
+            conn,
+        )
+        .await?;
+        Ok(1)
+    }
+
+    /// Writes `event` as `version` of the stream; the unique (todo_id, version) key rejects
+    /// concurrent writers. Every `snapshot_every` versions `state` is saved as a snapshot.
+    async fn append(
+        todo_id: i64,
+        version: i64,
+        event: &StreamEvent,
+        state: Option<&Todo>,
+        snapshot_every: i64,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO todo_events (todo_id, version, event, payload, occurred_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(todo_id)
+        .bind(version)
+        .bind(event.name())
+        .bind(to_json(event)?)
+        .bind(now.timestamp())
+        .execute(&mut *conn)
+        .await;
+        if let Err(e) = result {
+            return Err(DomainError::Infrastructure(e.into()));
+        }
+        if version % snapshot_every != 0 {
+            return Ok(());
+        }
+        let state = match state {
+            Some(todo) => Some(to_json(&TodoState::from(todo))?),
+            None => None,
+        };
+        let result = sqlx::query(
+            r#"
+            INSERT INTO todo_snapshots (todo_id, version, state)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (todo_id) DO UPDATE SET version = excluded.version, state = excluded.state
+            "#,
+        )
+        .bind(todo_id)
+        .bind(version)
+        .bind(state)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::todo_repository::tests::prepare_table;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    pub(crate) async fn prepare_tables(conn: &mut SqliteConnection) {
+        prepare_table(conn).await;
+        for statement in [
+            r#"
+            CREATE TABLE todo_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                todo_id INTEGER NOT NULL,
+                version INTEGER NOT NULL,
+                event TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                occurred_at INTEGER NOT NULL,
+                UNIQUE (todo_id, version)
+            )
+            "#,
+            r#"
+            CREATE TABLE todo_snapshots (
+                todo_id INTEGER PRIMARY KEY,
+                version INTEGER NOT NULL,
+                state TEXT
+            )
+            "#,
+        ] {
+            sqlx::query(statement).execute(&mut *conn).await.unwrap();
+        }
+    }
+
+    fn instant(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    async fn stream(todo_id: i64, conn: &mut SqliteConnection) -> Vec<String> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT event FROM todo_events WHERE todo_id = $1 ORDER BY version",
+        )
+        .bind(todo_id)
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replay_snapshots_and_time_travel() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_tables(&mut conn).await;
+
+        let mut todo = Todo {
+            title: Some("draft".to_string()),
+            due_at: Some(instant(50_000)),
+            ..Default::default()
+        };
+        todo.id = InternalEventSourcedTodoRepository::create(&todo, 2, instant(1_000), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let draft = todo.clone();
+        todo.title = Some("final".to_string());
+        InternalEventSourcedTodoRepository::update(&todo, 2, instant(2_000), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        todo.version += 1;
+        let edited = todo.clone();
+        todo.completed_at = Some(instant(3_000));
+        InternalEventSourcedTodoRepository::update(&todo, 2, instant(3_000), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        todo.version += 1;
+        assert!(matches!(
+            InternalEventSourcedTodoRepository::update(&edited, 2, instant(3_500), &mut conn).await,
+            Err(DomainError::Conflict { .. })
+        ));
+
+        assert_eq!(
+            stream(todo.id, &mut conn).await,
+            vec!["TodoCreated", "TodoUpdated", "TodoCompleted"]
+        );
+        let snapshot_version =
+            sqlx::query_scalar::<_, i64>("SELECT version FROM todo_snapshots WHERE todo_id = $1")
+                .bind(todo.id)
+                .fetch_one(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(snapshot_version, 2);
+
+        match InternalEventSourcedTodoRepository::find_by_id(todo.id, &mut conn).await {
+            Ok(replayed) => assert_eq!(replayed, Some(todo.clone())),
+            Err(_) => panic!("failed to replay todo"),
+        }
+        match InternalSqliteTodoRepository::find_by_id(todo.id, &mut conn).await {
+            Ok(projected) => assert_eq!(projected, Some(todo.clone())),
+            Err(_) => panic!("failed to fetch projection"),
+        }
+        for (at, expected) in [
+            (500, None),
+            (1_500, Some(draft)),
+            (2_000, Some(edited)),
+            (9_000, Some(todo.clone())),
+        ] {
+            match InternalEventSourcedTodoRepository::find_by_id_at(todo.id, instant(at), &mut conn)
+                .await
+            {
+                Ok(found) => assert_eq!(found, expected),
+                Err(_) => panic!("failed to replay todo at {}", at),
+            }
+        }
+
+        InternalEventSourcedTodoRepository::delete(todo.id, None, 2, instant(4_000), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        match InternalEventSourcedTodoRepository::find_by_id(todo.id, &mut conn).await {
+            Ok(replayed) => assert_eq!(replayed, None),
+            Err(_) => panic!("failed to replay todo"),
+        }
+        match InternalSqliteTodoRepository::find_all(&mut conn).await {
+            Ok(todos) => assert!(todos.is_empty()),
+            Err(_) => panic!("failed to fetch projection"),
+        }
+        match InternalEventSourcedTodoRepository::find_by_id_at(todo.id, instant(3_500), &mut conn)
+            .await
+        {
+            Ok(found) => assert_eq!(found, Some(todo.clone())),
+            Err(_) => panic!("failed to replay todo"),
+        }
+
+        match InternalEventSourcedTodoRepository::restore(todo.id, 2, instant(5_000), &mut conn)
+            .await
+        {
+            Ok(restored) => assert_eq!(restored, Some(todo.clone())),
+            Err(_) => panic!("failed to restore todo"),
+        }
+        match InternalEventSourcedTodoRepository::find_by_id(todo.id, &mut conn).await {
+            Ok(replayed) => assert_eq!(replayed, Some(todo.clone())),
+            Err(_) => panic!("failed to replay todo"),
+        }
+        match InternalSqliteTodoRepository::find_all(&mut conn).await {
+            Ok(todos) => assert_eq!(todos, vec![todo.clone()]),
+            Err(_) => panic!("failed to fetch projection"),
+        }
+        assert_eq!(
+            stream(todo.id, &mut conn).await.last().map(String::as_str),
+            Some("TodoRestored")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adopts_todos_written_before_event_sourcing() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_tables(&mut conn).await;
+
+        let mut todo = Todo {
+            title: Some("legacy".to_string()),
+            ..Default::default()
+        };
+        todo.id = InternalSqliteTodoRepository::create(&todo, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+
+        let repository = EventSourcedTodoRepository::new(pool);
+        match repository.find_by_id(todo.id).await {
+            Ok(found) => assert_eq!(found, Some(todo.clone())),
+            Err(_) => panic!("failed to fetch legacy todo"),
+        }
+        todo.priority = Priority::High;
+        assert!(repository.update(&todo).await.is_ok());
+        todo.version += 1;
+        assert_eq!(
+            stream(todo.id, &mut conn).await,
+            vec!["TodoCreated", "TodoUpdated"]
+        );
+        match repository.find_by_id(todo.id).await {
+            Ok(found) => assert_eq!(found, Some(todo.clone())),
+            Err(_) => panic!("failed to replay todo"),
+        }
+        match repository.find_by_min_priority(Priority::High).await {
+            Ok(todos) => assert_eq!(todos, vec![todo]),
+            Err(_) => panic!("failed to fetch projection"),
+        }
+    }
+}
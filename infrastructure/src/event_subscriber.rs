@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use domain::{
+    error::DomainError,
+    event::{EventSubscriber, TodoEvent},
+};
+
+/// Logs every committed todo event.
+#[derive(Debug, Clone, Default)]
+pub struct LogEventSubscriber {}
+
+#[async_trait]
+impl EventSubscriber for LogEventSubscriber {
+    async fn handle(&self, event: &TodoEvent) -> Result<(), DomainError> {
+        let todo = event.todo();
+        log::info!("event {}: todo {} {:?}", event.name(), todo.id, todo.title);
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{idempotency::IdempotencyRecord, todo::Todo},
+    error::DomainError,
+};
+use sqlx::SqliteConnection;
+
+use crate::{event_store::TodoState, todo_repository::from_timestamp};
+
+/// Row shape of the `idempotency_keys` table; `todo` holds the created todo as JSON.
+struct IdempotencyRow {
+    key: String,
+    fingerprint: String,
+    todo_id: i64,
+    todo: String,
+    created_at: i64,
+    expires_at: i64,
+}
+
+impl TryFrom<IdempotencyRow> for IdempotencyRecord {
+    type Error = DomainError;
+
+    fn try_from(row: IdempotencyRow) -> Result<Self, Self::Error> {
+        let todo = match serde_json::from_str::<TodoState>(&row.todo) {
+            Ok(state) => state.into_todo(row.todo_id)?,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let (created_at, expires_at) = match (
+            from_timestamp(Some(row.created_at))?,
+            from_timestamp(Some(row.expires_at))?,
+        ) {
+            (Some(created_at), Some(expires_at)) => (created_at, expires_at),
+            _ => {
+                return Err(DomainError::Unexpected(format!(
+                    "idempotency key {} has no timestamps",
+                    row.key
+                )))
+            }
+        };
+        Ok(IdempotencyRecord {
+            key: row.key,
+            fingerprint: row.fingerprint,
+            todo,
+            created_at,
+            expires_at,
+        })
+    }
+}
+
+fn encode(todo: &Todo) -> Result<String, DomainError> {
+    match serde_json::to_string(&TodoState::from(todo)) {
+        Ok(state) => Ok(state),
+        Err(e) => Err(DomainError::Infrastructure(e.into())),
+    }
+}
+
+pub struct InternalSqliteIdempotencyRepository {}
+
+impl InternalSqliteIdempotencyRepository {
+    pub async fn find(
+        key: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<IdempotencyRecord>, DomainError> {
+        let record = sqlx::query_as!(
+            IdempotencyRow,
+            r#"
+            SELECT key AS "key!", fingerprint, todo_id, todo, created_at, expires_at
+            FROM idempotency_keys
+            WHERE key = $1
+            "#,
+            key
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        match record {
+            Ok(record) => record.map(IdempotencyRecord::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Drops the records expired by `now`. Run first in a transaction, it takes the write
+    /// lock of the database before anything is read, so that two transactions creating
+    /// under one key queue up instead of failing to upgrade their read locks.
+    pub async fn prune(now: DateTime<Utc>, conn: &mut SqliteConnection) -> Result<(), DomainError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE expires_at <= $1
+            "#,
+        )
+        .bind(now.timestamp())
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Inserts `record` unless a record under the same key, saved by a concurrent request,
+    /// is kept instead. Returns whether `record` was inserted.
+    pub async fn save(
+        record: &IdempotencyRecord,
+        conn: &mut SqliteConnection,
+    ) -> Result<bool, DomainError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO idempotency_keys (key, fingerprint, todo_id, todo, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (key) DO NOTHING
+            "#,
+        )
+        .bind(&record.key)
+        .bind(&record.fingerprint)
+        .bind(record.todo.id)
+        .bind(encode(&record.todo)?)
+        .bind(record.created_at.timestamp())
+        .bind(record.expires_at.timestamp())
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use domain::entity::todo::Priority;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn prepare_idempotency_table(conn: &mut SqliteConnection) {
+        sqlx::query(
+            r#"
+            CREATE TABLE idempotency_keys (
+                key TEXT PRIMARY KEY NOT NULL,
+                fingerprint TEXT NOT NULL,
+                todo_id INTEGER NOT NULL,
+                todo TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    }
+
+    fn record(key: &str, todo_id: i64, created_at: DateTime<Utc>) -> IdempotencyRecord {
+        IdempotencyRecord {
+            key: key.to_string(),
+            fingerprint: format!("fingerprint of {}", key),
+            todo: Todo {
+                id: todo_id,
+                title: Some(format!("todo {}", todo_id)),
+                priority: Priority::High,
+                ..Default::default()
+            },
+            created_at,
+            expires_at: created_at + Duration::hours(24),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_find() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_idempotency_table(&mut conn).await;
+
+        let created_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let first = record("retry-1", 1, created_at);
+        assert!(InternalSqliteIdempotencyRepository::save(&first, &mut conn)
+            .await
+            .ok()
+            .unwrap());
+        match InternalSqliteIdempotencyRepository::find("retry-1", &mut conn).await {
+            Ok(found) => assert_eq!(found, Some(first.clone())),
+            Err(_) => panic!("failed to find idempotency record"),
+        }
+        match InternalSqliteIdempotencyRepository::find("retry-2", &mut conn).await {
+            Ok(found) => assert!(found.is_none()),
+            Err(_) => panic!("failed to find idempotency record"),
+        }
+
+        // an unexpired key cannot be taken twice; the first record is kept
+        let duplicate = record("retry-1", 2, created_at + Duration::hours(1));
+        assert!(
+            !InternalSqliteIdempotencyRepository::save(&duplicate, &mut conn)
+                .await
+                .ok()
+                .unwrap()
+        );
+        match InternalSqliteIdempotencyRepository::find("retry-1", &mut conn).await {
+            Ok(found) => assert_eq!(found, Some(first.clone())),
+            Err(_) => panic!("failed to find idempotency record"),
+        }
+
+        // once expired, it is pruned and may be reused
+        let reused = record("retry-1", 3, first.expires_at);
+        InternalSqliteIdempotencyRepository::prune(reused.created_at, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        assert!(
+            InternalSqliteIdempotencyRepository::save(&reused, &mut conn)
+                .await
+                .ok()
+                .unwrap()
+        );
+        match InternalSqliteIdempotencyRepository::find("retry-1", &mut conn).await {
+            Ok(found) => assert_eq!(found, Some(reused)),
+            Err(_) => panic!("failed to find idempotency record"),
+        }
+    }
+}
@@ -1,4 +1,18 @@
+pub mod audit_repository;
+pub mod caldav_resource_repository;
+pub mod calendar_feed_repository;
+pub mod event_log_repository;
+pub mod event_store;
+pub mod event_subscriber;
+pub mod idempotency_repository;
+pub mod notifier;
+pub mod persisted_query_repository;
+pub mod reminder_repository;
+pub mod revision_repository;
 pub mod todo_repository;
+pub mod trash_repository;
+pub mod unit_of_work;
+pub mod webhook_repository;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
@@ -0,0 +1,319 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{
+        reminder::Reminder,
+        todo::Todo,
+        webhook::{WebhookDelivery, WebhookSubscription},
+    },
+    error::DomainError,
+    notifier::{Notifier, WebhookSender},
+};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+/// Logs reminders; the default when no webhook is configured.
+#[derive(Debug, Clone, Default)]
+pub struct LogNotifier {}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, reminder: &Reminder, todo: &Todo) -> Result<(), DomainError> {
+        log::info!(
+            "reminder {}: todo {} {:?} (due {:?})",
+            reminder.id,
+            todo.id,
+            todo.title,
+            todo.due_at
+        );
+        Ok(())
+    }
+}
+
+/// JSON body posted by [`WebhookNotifier`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ReminderPayload {
+    pub reminder_id: i64,
+    pub todo_id: i64,
+    pub title: Option<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub fire_at: Option<DateTime<Utc>>,
+}
+
+/// POSTs each reminder as JSON to a fixed URL. Any non-2xx response counts as a failure
+/// so the scheduler retries it.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, timeout: Duration) -> Result<Self, DomainError> {
+        let client = reqwest::Client::builder().timeout(timeout).build();
+        match client {
+            Ok(client) => Ok(Self { client, url }),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, reminder: &Reminder, todo: &Todo) -> Result<(), DomainError> {
+        let payload = ReminderPayload {
+            reminder_id: reminder.id,
+            todo_id: todo.id,
+            title: todo.title.clone(),
+            due_at: todo.due_at,
+            fire_at: reminder.fire_at(todo),
+        };
+        let response = self.client.post(&self.url).json(&payload).send().await;
+        match response.and_then(|response| response.error_for_status()) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Signature sent in [`SIGNATURE_HEADER`]: `sha256=` followed by the hex HMAC-SHA256 of
+/// `"{timestamp}.{body}"` keyed with the subscription secret. Binding the timestamp lets
+/// receivers reject replayed requests.
+pub fn webhook_signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Sends signed webhook deliveries over HTTP.
+#[derive(Debug, Clone)]
+pub struct HttpWebhookSender {
+    client: reqwest::Client,
+}
+
+impl HttpWebhookSender {
+    pub fn new(timeout: Duration) -> Result<Self, DomainError> {
+        let client = reqwest::Client::builder().timeout(timeout).build();
+        match client {
+            Ok(client) => Ok(Self { client }),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(
+        &self,
+        subscription: &WebhookSubscription,
+        delivery: &WebhookDelivery,
+    ) -> Result<u16, DomainError> {
+        let timestamp = Utc::now().timestamp();
+        let signature = webhook_signature(&subscription.secret, timestamp, &delivery.payload);
+        let response = self
+            .client
+            .post(&subscription.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, delivery.event.as_str())
+            .header(DELIVERY_HEADER, delivery.id)
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+        match response {
+            Ok(response) => Ok(response.status().as_u16()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+    use domain::entity::{reminder::ReminderTrigger, webhook::WebhookEvent};
+    use serde_json::{json, Value};
+    use std::{
+        net::{SocketAddr, TcpListener},
+        sync::{Arc, Mutex},
+    };
+
+    type Received = Arc<Mutex<Vec<Value>>>;
+
+    /// Spawns a local HTTP endpoint standing in for the webhook receiver.
+    fn spawn_receiver(status: StatusCode) -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(received): State<Received>, Json(body): Json<Value>| async move {
+                        received.lock().unwrap().push(body);
+                        status
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        });
+        (format!("http://{}/hook", addr), received)
+    }
+
+    fn reminder_for(todo: &Todo) -> Reminder {
+        let mut reminder = Reminder::new(
+            todo,
+            ReminderTrigger::BeforeDue(chrono::Duration::seconds(60)),
+        )
+        .ok()
+        .unwrap();
+        reminder.id = 7;
+        reminder
+    }
+
+    #[tokio::test]
+    async fn test_webhook_notifier_posts_payload() {
+        let (url, received) = spawn_receiver(StatusCode::NO_CONTENT);
+        let notifier = WebhookNotifier::new(url, Duration::from_secs(5))
+            .ok()
+            .unwrap();
+        let todo = Todo {
+            id: 3,
+            title: Some("call mom".to_string()),
+            due_at: DateTime::from_timestamp(1_700_000_000, 0),
+            ..Default::default()
+        };
+
+        assert!(notifier.notify(&reminder_for(&todo), &todo).await.is_ok());
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(
+            received[0],
+            json!({
+                "reminder_id": 7,
+                "todo_id": 3,
+                "title": "call mom",
+                "due_at": "2023-11-14T22:13:20Z",
+                "fire_at": "2023-11-14T22:12:20Z",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_notifier_fails_on_error_status() {
+        let (url, received) = spawn_receiver(StatusCode::SERVICE_UNAVAILABLE);
+        let notifier = WebhookNotifier::new(url, Duration::from_secs(5))
+            .ok()
+            .unwrap();
+        let todo = Todo {
+            id: 3,
+            due_at: DateTime::from_timestamp(1_700_000_000, 0),
+            ..Default::default()
+        };
+
+        assert!(notifier.notify(&reminder_for(&todo), &todo).await.is_err());
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    type Signed = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    #[tokio::test]
+    async fn test_http_webhook_sender_signs_body() {
+        let signed = Signed::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    |State(signed): State<Signed>, headers: HeaderMap, body: String| async move {
+                        signed.lock().unwrap().push((headers, body));
+                        StatusCode::ACCEPTED
+                    },
+                ),
+            )
+            .with_state(signed.clone());
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        });
+
+        let subscription = WebhookSubscription::new(
+            format!("http://{}/hook", addr),
+            "s3cret".to_string(),
+            vec![],
+            Utc::now(),
+        )
+        .ok()
+        .unwrap();
+        let delivery = WebhookDelivery {
+            id: 42,
+            subscription_id: 1,
+            event: WebhookEvent::TodoCreated,
+            payload: r#"{"event":"todo.created"}"#.to_string(),
+            attempts: 0,
+            next_attempt_at: Utc::now(),
+            delivered_at: None,
+            failed_at: None,
+            response_status: None,
+            last_error: None,
+            created_at: Utc::now(),
+        };
+        let sender = HttpWebhookSender::new(Duration::from_secs(5)).ok().unwrap();
+        assert!(matches!(
+            sender.send(&subscription, &delivery).await,
+            Ok(202)
+        ));
+
+        let signed = signed.lock().unwrap();
+        let (headers, body) = &signed[0];
+        assert_eq!(body, &delivery.payload);
+        assert_eq!(headers[EVENT_HEADER], "todo.created");
+        assert_eq!(headers[DELIVERY_HEADER], "42");
+        let timestamp = headers[TIMESTAMP_HEADER]
+            .to_str()
+            .unwrap()
+            .parse::<i64>()
+            .unwrap();
+        assert_eq!(
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            webhook_signature("s3cret", timestamp, body)
+        );
+    }
+
+    #[test]
+    fn test_webhook_signature() {
+        // HMAC-SHA256("key", "1.body"), computed independently.
+        assert_eq!(
+            webhook_signature("key", 1, "body"),
+            "sha256=91b5374b153842ad05b2c4eab9349b8321b14703165bd3fb8b034dfb8be98ae5"
+        );
+    }
+}
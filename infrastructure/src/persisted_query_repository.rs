@@ -0,0 +1,288 @@
+use async_trait::async_trait;
+use domain::{
+    entity::persisted_query::{PersistedQuery, QuerySource},
+    error::DomainError,
+    repository::persisted_query_repository::PersistedQueryRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::todo_repository::from_timestamp;
+
+#[derive(Debug, Clone)]
+pub struct SqlitePersistedQueryRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqlitePersistedQueryRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PersistedQueryRepository for SqlitePersistedQueryRepository {
+    async fn find_by_hash(&self, hash: &str) -> Result<Option<PersistedQuery>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqlitePersistedQueryRepository::find_by_hash(hash, &mut conn).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<PersistedQuery>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqlitePersistedQueryRepository::find_all(&mut conn).await
+    }
+
+    async fn save(&self, query: &PersistedQuery) -> Result<(), DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqlitePersistedQueryRepository::save(query, &mut conn).await
+    }
+
+    async fn prune_automatic(&self, keep: i64) -> Result<u64, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqlitePersistedQueryRepository::prune_automatic(keep, &mut conn).await
+    }
+}
+
+/// Row shape of the `persisted_queries` table; `operation_names` is a JSON array.
+#[derive(sqlx::FromRow)]
+struct PersistedQueryRow {
+    hash: String,
+    document: String,
+    operation_names: String,
+    source: String,
+    created_at: i64,
+}
+
+impl TryFrom<PersistedQueryRow> for PersistedQuery {
+    type Error = DomainError;
+
+    fn try_from(row: PersistedQueryRow) -> Result<Self, Self::Error> {
+        let operation_names = match serde_json::from_str(&row.operation_names) {
+            Ok(operation_names) => operation_names,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let created_at = match from_timestamp(Some(row.created_at))? {
+            Some(created_at) => created_at,
+            None => {
+                return Err(DomainError::Unexpected(format!(
+                    "persisted query {} has no creation time",
+                    row.hash
+                )))
+            }
+        };
+        Ok(PersistedQuery {
+            hash: row.hash,
+            document: row.document,
+            operation_names,
+            source: row.source.parse::<QuerySource>()?,
+            created_at,
+        })
+    }
+}
+
+pub struct InternalSqlitePersistedQueryRepository {}
+
+impl InternalSqlitePersistedQueryRepository {
+    pub async fn find_by_hash(
+        hash: &str,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<PersistedQuery>, DomainError> {
+        let query = sqlx::query_as::<_, PersistedQueryRow>(
+            r#"
+            SELECT hash, document, operation_names, source, created_at
+            FROM persisted_queries
+            WHERE hash = $1
+            "#,
+        )
+        .bind(hash)
+        .fetch_optional(&mut *conn)
+        .await;
+        match query {
+            Ok(query) => query.map(PersistedQuery::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<PersistedQuery>, DomainError> {
+        let queries = sqlx::query_as::<_, PersistedQueryRow>(
+            r#"
+            SELECT hash, document, operation_names, source, created_at
+            FROM persisted_queries
+            ORDER BY source = 'automatic', created_at, hash
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match queries {
+            Ok(queries) => queries.into_iter().map(PersistedQuery::try_from).collect(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn save(
+        query: &PersistedQuery,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let operation_names = match serde_json::to_string(&query.operation_names) {
+            Ok(operation_names) => operation_names,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let result = sqlx::query(
+            r#"
+            INSERT INTO persisted_queries (hash, document, operation_names, source, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (hash) DO UPDATE
+            SET operation_names = excluded.operation_names, source = excluded.source,
+                created_at = excluded.created_at
+            WHERE excluded.source = 'manifest' AND persisted_queries.source = 'automatic'
+            "#,
+        )
+        .bind(&query.hash)
+        .bind(&query.document)
+        .bind(operation_names)
+        .bind(query.source.as_str())
+        .bind(query.created_at.timestamp())
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn prune_automatic(
+        keep: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<u64, DomainError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM persisted_queries
+            WHERE source = 'automatic' AND hash NOT IN (
+                SELECT hash
+                FROM persisted_queries
+                WHERE source = 'automatic'
+                ORDER BY created_at DESC, rowid DESC
+                LIMIT $1
+            )
+            "#,
+        )
+        .bind(keep)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(result) => Ok(result.rows_affected()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn prepare_table(conn: &mut SqliteConnection) {
+        sqlx::query(
+            r#"
+            CREATE TABLE persisted_queries (
+                hash TEXT PRIMARY KEY NOT NULL,
+                document TEXT NOT NULL,
+                operation_names TEXT NOT NULL,
+                source TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_promote_and_prune() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_table(&mut conn).await;
+        let repository = SqlitePersistedQueryRepository::new(pool.clone());
+
+        let query = |document: &str, source: QuerySource, at: i64| {
+            PersistedQuery::new(
+                document.to_string(),
+                vec![],
+                source,
+                DateTime::from_timestamp(at, 0).unwrap(),
+            )
+        };
+        let manifest = PersistedQuery {
+            operation_names: vec!["getTodos".to_string()],
+            ..query(
+                "query getTodos { todos { id } }",
+                QuerySource::Manifest,
+                1_000,
+            )
+        };
+        let first = query("{ todos { id } }", QuerySource::Automatic, 2_000);
+        let second = query("{ todos { title } }", QuerySource::Automatic, 3_000);
+        for query in [&manifest, &first, &second] {
+            repository.save(query).await.ok().unwrap();
+        }
+
+        match repository.find_by_hash(&manifest.hash).await {
+            Ok(found) => assert_eq!(found, Some(manifest.clone())),
+            Err(_) => panic!("failed to fetch persisted query"),
+        }
+        // an automatic save does not demote a registered query
+        repository
+            .save(&PersistedQuery {
+                source: QuerySource::Automatic,
+                ..manifest.clone()
+            })
+            .await
+            .ok()
+            .unwrap();
+        let promoted = PersistedQuery {
+            source: QuerySource::Manifest,
+            created_at: DateTime::from_timestamp(4_000, 0).unwrap(),
+            ..first.clone()
+        };
+        repository.save(&promoted).await.ok().unwrap();
+        match repository.find_all().await {
+            Ok(queries) => assert_eq!(queries, vec![manifest, promoted, second.clone()]),
+            Err(_) => panic!("failed to fetch persisted queries"),
+        }
+
+        let third = query("{ todos { version } }", QuerySource::Automatic, 5_000);
+        repository.save(&third).await.ok().unwrap();
+        match repository.prune_automatic(1).await {
+            Ok(pruned) => assert_eq!(pruned, 1),
+            Err(_) => panic!("failed to prune persisted queries"),
+        }
+        match repository.find_by_hash(&second.hash).await {
+            Ok(found) => assert!(found.is_none()),
+            Err(_) => panic!("failed to fetch persisted query"),
+        }
+        match repository.find_all().await {
+            Ok(queries) => assert_eq!(queries.len(), 3),
+            Err(_) => panic!("failed to fetch persisted queries"),
+        }
+    }
+}
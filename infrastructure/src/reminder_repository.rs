@@ -0,0 +1,575 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use domain::{
+    entity::reminder::{Reminder, ReminderTrigger},
+    error::DomainError,
+    repository::reminder_repository::ReminderRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::todo_repository::from_timestamp;
+
+#[derive(Debug, Clone)]
+pub struct SqliteReminderRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteReminderRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ReminderRepository for SqliteReminderRepository {
+    async fn create(&self, reminder: &Reminder) -> Result<i64, DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let id = InternalSqliteReminderRepository::create(reminder, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(id),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<Reminder>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteReminderRepository::find_by_id(id, &mut conn).await
+    }
+
+    async fn find_by_todo_id(&self, todo_id: i64) -> Result<Vec<Reminder>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteReminderRepository::find_by_todo_id(todo_id, &mut conn).await
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteReminderRepository::delete(id, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn claim_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Reminder>, DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let reminders =
+            InternalSqliteReminderRepository::claim_due(now, lease_until, limit, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(reminders),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn mark_fired(&self, id: i64, fired_at: DateTime<Utc>) -> Result<(), DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteReminderRepository::mark_fired(id, fired_at, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn mark_failed(
+        &self,
+        id: i64,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteReminderRepository::mark_failed(id, error, retry_at, now, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+/// Row shape of the `reminders` table; timestamps are stored as unix seconds.
+struct ReminderRow {
+    id: i64,
+    todo_id: i64,
+    remind_at: Option<i64>,
+    offset_seconds: Option<i64>,
+    fired_at: Option<i64>,
+    failed_at: Option<i64>,
+    attempts: i64,
+    last_error: Option<String>,
+}
+
+impl TryFrom<ReminderRow> for Reminder {
+    type Error = DomainError;
+
+    fn try_from(row: ReminderRow) -> Result<Self, Self::Error> {
+        let trigger = match (from_timestamp(row.remind_at)?, row.offset_seconds) {
+            (Some(at), None) => ReminderTrigger::At(at),
+            (None, Some(seconds)) => ReminderTrigger::BeforeDue(Duration::seconds(seconds)),
+            _ => {
+                return Err(DomainError::Unexpected(format!(
+                    "reminder {} has an invalid trigger",
+                    row.id
+                )))
+            }
+        };
+        Ok(Reminder {
+            id: row.id,
+            todo_id: row.todo_id,
+            trigger,
+            fired_at: from_timestamp(row.fired_at)?,
+            failed_at: from_timestamp(row.failed_at)?,
+            attempts: row.attempts,
+            last_error: row.last_error,
+        })
+    }
+}
+
+fn into_reminders(rows: Vec<ReminderRow>) -> Result<Vec<Reminder>, DomainError> {
+    rows.into_iter().map(Reminder::try_from).collect()
+}
+
+pub struct InternalSqliteReminderRepository {}
+
+impl InternalSqliteReminderRepository {
+    pub async fn create(
+        reminder: &Reminder,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, DomainError> {
+        let (remind_at, offset_seconds) = match reminder.trigger {
+            ReminderTrigger::At(at) => (Some(at.timestamp()), None),
+            ReminderTrigger::BeforeDue(offset) => (None, Some(offset.num_seconds())),
+        };
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO reminders (todo_id, remind_at, offset_seconds, fired_at, failed_at, attempts, last_error)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(reminder.todo_id)
+        .bind(remind_at)
+        .bind(offset_seconds)
+        .bind(reminder.fired_at.map(|fired_at| fired_at.timestamp()))
+        .bind(reminder.failed_at.map(|failed_at| failed_at.timestamp()))
+        .bind(reminder.attempts)
+        .bind(reminder.last_error.as_ref())
+        .fetch_one(&mut *conn)
+        .await;
+        match id {
+            Ok(id) => Ok(id),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_by_id(
+        id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Reminder>, DomainError> {
+        let reminder = sqlx::query_as!(
+            ReminderRow,
+            r#"
+            SELECT id AS "id!", todo_id, remind_at, offset_seconds, fired_at, failed_at, attempts, last_error
+            FROM reminders
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        match reminder {
+            Ok(reminder) => reminder.map(Reminder::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_by_todo_id(
+        todo_id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Reminder>, DomainError> {
+        let reminders = sqlx::query_as!(
+            ReminderRow,
+            r#"
+            SELECT id AS "id!", todo_id, remind_at, offset_seconds, fired_at, failed_at, attempts, last_error
+            FROM reminders
+            WHERE todo_id = $1
+            ORDER BY id
+            "#,
+            todo_id
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match reminders {
+            Ok(reminders) => into_reminders(reminders),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn delete(id: i64, conn: &mut SqliteConnection) -> Result<(), DomainError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM reminders
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Leases due reminders in a single statement, so two schedulers sharing the database
+    /// can never claim the same row.
+    pub async fn claim_due(
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Reminder>, DomainError> {
+        let now = now.timestamp();
+        let lease_until = lease_until.timestamp();
+        let reminders = sqlx::query_as!(
+            ReminderRow,
+            r#"
+            UPDATE reminders
+            SET claimed_until = $1
+            WHERE id IN (
+                SELECT r.id
+                FROM reminders r
+                JOIN todos t ON t.id = r.todo_id
+                WHERE r.fired_at IS NULL
+                  AND r.failed_at IS NULL
+                  AND t.completed_at IS NULL
+                  AND t.deleted_at IS NULL
+                  AND COALESCE(r.remind_at, t.due_at - r.offset_seconds) <= $2
+                  AND (r.claimed_until IS NULL OR r.claimed_until <= $2)
+                ORDER BY r.id
+                LIMIT $3
+            )
+            RETURNING id AS "id!", todo_id, remind_at, offset_seconds, fired_at, failed_at, attempts, last_error
+            "#,
+            lease_until,
+            now,
+            limit
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match reminders {
+            Ok(reminders) => into_reminders(reminders),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn mark_fired(
+        id: i64,
+        fired_at: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE reminders
+            SET fired_at = $1, claimed_until = NULL, last_error = NULL
+            WHERE id = $2
+            "#,
+        )
+        .bind(fired_at.timestamp())
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Keeps the reminder leased until `retry_at`, or abandons it at `now` when `retry_at` is
+    /// `None`.
+    pub async fn mark_failed(
+        id: i64,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let (claimed_until, failed_at) = match retry_at {
+            Some(retry_at) => (Some(retry_at.timestamp()), None),
+            None => (None, Some(now.timestamp())),
+        };
+        let result = sqlx::query(
+            r#"
+            UPDATE reminders
+            SET claimed_until = $1, failed_at = $2, attempts = attempts + 1, last_error = $3
+            WHERE id = $4
+            "#,
+        )
+        .bind(claimed_until)
+        .bind(failed_at)
+        .bind(error)
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo_repository::InternalSqliteTodoRepository;
+    use domain::entity::todo::Todo;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn prepare_tables(conn: &mut SqliteConnection) {
+        for statement in [
+            r#"
+            CREATE TABLE todos (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                due_at INTEGER,
+                priority INTEGER NOT NULL DEFAULT 2,
+                recurrence TEXT,
+                completed_at INTEGER,
+                deleted_at INTEGER
+            )
+            "#,
+            r#"
+            CREATE TABLE reminders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                todo_id INTEGER NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+                remind_at INTEGER,
+                offset_seconds INTEGER,
+                claimed_until INTEGER,
+                fired_at INTEGER,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                failed_at INTEGER,
+                CHECK ((remind_at IS NULL) <> (offset_seconds IS NULL))
+            )
+            "#,
+        ] {
+            sqlx::query(statement).execute(&mut *conn).await.unwrap();
+        }
+    }
+
+    fn instant(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_claim_due_leases_each_reminder_once() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_tables(&mut conn).await;
+
+        let todo = Todo {
+            title: Some("file taxes".to_string()),
+            due_at: Some(instant(10_000)),
+            ..Default::default()
+        };
+        let todo_id = InternalSqliteTodoRepository::create(&todo, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let todo = Todo {
+            id: todo_id,
+            ..todo
+        };
+
+        let absolute = Reminder::new(&todo, ReminderTrigger::At(instant(5_000)))
+            .ok()
+            .unwrap();
+        let relative = Reminder::new(&todo, ReminderTrigger::BeforeDue(Duration::seconds(600)))
+            .ok()
+            .unwrap();
+        let absolute_id = InternalSqliteReminderRepository::create(&absolute, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let relative_id = InternalSqliteReminderRepository::create(&relative, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+
+        match InternalSqliteReminderRepository::find_by_todo_id(todo_id, &mut conn).await {
+            Ok(reminders) => {
+                assert_eq!(reminders.len(), 2);
+                assert_eq!(reminders[1].trigger, relative.trigger);
+            }
+            Err(_) => panic!("failed to fetch reminders"),
+        }
+
+        // Only the absolute reminder is due; a second claim inside the lease sees nothing.
+        let claimed = InternalSqliteReminderRepository::claim_due(
+            instant(5_000),
+            instant(5_300),
+            10,
+            &mut conn,
+        )
+        .await;
+        match claimed {
+            Ok(reminders) => {
+                assert_eq!(reminders.len(), 1);
+                assert_eq!(reminders[0].id, absolute_id);
+            }
+            Err(_) => panic!("failed to claim reminders"),
+        }
+        let claimed = InternalSqliteReminderRepository::claim_due(
+            instant(5_100),
+            instant(5_400),
+            10,
+            &mut conn,
+        )
+        .await;
+        assert!(matches!(claimed, Ok(reminders) if reminders.is_empty()));
+
+        // A failed delivery becomes claimable again once its retry time has passed.
+        InternalSqliteReminderRepository::mark_failed(
+            absolute_id,
+            "boom",
+            Some(instant(5_030)),
+            instant(5_000),
+            &mut conn,
+        )
+        .await
+        .ok()
+        .unwrap();
+        let claimed = InternalSqliteReminderRepository::claim_due(
+            instant(9_400),
+            instant(9_700),
+            10,
+            &mut conn,
+        )
+        .await;
+        match claimed {
+            Ok(reminders) => {
+                assert_eq!(reminders.len(), 2);
+                assert_eq!(reminders[0].attempts, 1);
+                assert_eq!(reminders[0].last_error, Some("boom".to_string()));
+                assert_eq!(reminders[1].id, relative_id);
+            }
+            Err(_) => panic!("failed to claim reminders"),
+        }
+
+        // Fired and abandoned reminders are never claimed again, even after the lease expires.
+        InternalSqliteReminderRepository::mark_fired(absolute_id, instant(9_400), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        InternalSqliteReminderRepository::mark_failed(
+            relative_id,
+            "gone",
+            None,
+            instant(9_400),
+            &mut conn,
+        )
+        .await
+        .ok()
+        .unwrap();
+        let claimed = InternalSqliteReminderRepository::claim_due(
+            instant(20_000),
+            instant(20_300),
+            10,
+            &mut conn,
+        )
+        .await;
+        assert!(matches!(claimed, Ok(reminders) if reminders.is_empty()));
+
+        match InternalSqliteReminderRepository::find_by_id(absolute_id, &mut conn).await {
+            Ok(Some(reminder)) => {
+                assert_eq!(reminder.fired_at, Some(instant(9_400)));
+                assert_eq!(reminder.last_error, None);
+            }
+            _ => panic!("failed to fetch reminder"),
+        }
+        match InternalSqliteReminderRepository::find_by_id(relative_id, &mut conn).await {
+            Ok(Some(reminder)) => {
+                assert_eq!(reminder.failed_at, Some(instant(9_400)));
+                assert_eq!(reminder.fired_at, None);
+            }
+            _ => panic!("failed to fetch reminder"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_due_skips_completed_todos() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_tables(&mut conn).await;
+
+        let todo = Todo {
+            title: Some("done already".to_string()),
+            completed_at: Some(instant(100)),
+            ..Default::default()
+        };
+        let todo_id = InternalSqliteTodoRepository::create(&todo, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let reminder = Reminder::new(
+            &Todo {
+                id: todo_id,
+                ..todo
+            },
+            ReminderTrigger::At(instant(200)),
+        )
+        .ok()
+        .unwrap();
+        InternalSqliteReminderRepository::create(&reminder, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+
+        let claimed =
+            InternalSqliteReminderRepository::claim_due(instant(300), instant(600), 10, &mut conn)
+                .await;
+        assert!(matches!(claimed, Ok(reminders) if reminders.is_empty()));
+    }
+}
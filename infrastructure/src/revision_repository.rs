@@ -0,0 +1,259 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{revision::TodoRevision, todo::Todo},
+    error::DomainError,
+    repository::revision_repository::RevisionRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::todo_repository::TodoRow;
+
+#[derive(Debug, Clone)]
+pub struct SqliteRevisionRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteRevisionRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RevisionRepository for SqliteRevisionRepository {
+    async fn find_by_todo_id(&self, todo_id: i64) -> Result<Vec<TodoRevision>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteRevisionRepository::find_by_todo_id(todo_id, &mut conn).await
+    }
+
+    async fn find(&self, todo_id: i64, revision: i64) -> Result<Option<TodoRevision>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteRevisionRepository::find(todo_id, revision, &mut conn).await
+    }
+}
+
+/// Row shape of the `todo_revisions` table; timestamps are stored as unix seconds.
+struct RevisionRow {
+    todo_id: i64,
+    revision: i64,
+    title: Option<String>,
+    due_at: Option<i64>,
+    priority: i64,
+    recurrence: Option<String>,
+    completed_at: Option<i64>,
+    version: i64,
+    created_at: i64,
+}
+
+impl TryFrom<RevisionRow> for TodoRevision {
+    type Error = DomainError;
+
+    fn try_from(row: RevisionRow) -> Result<Self, Self::Error> {
+        let created_at = match DateTime::from_timestamp(row.created_at, 0) {
+            Some(created_at) => created_at,
+            None => {
+                return Err(DomainError::Unexpected(format!(
+                    "invalid timestamp: {}",
+                    row.created_at
+                )))
+            }
+        };
+        let todo = Todo::try_from(TodoRow {
+            id: row.todo_id,
+            title: row.title,
+            due_at: row.due_at,
+            priority: row.priority,
+            recurrence: row.recurrence,
+            completed_at: row.completed_at,
+            version: row.version,
+        })?;
+        Ok(TodoRevision {
+            todo_id: row.todo_id,
+            revision: row.revision,
+            todo,
+            created_at,
+        })
+    }
+}
+
+pub struct InternalSqliteRevisionRepository {}
+
+impl InternalSqliteRevisionRepository {
+    /// Stores `todo` as the revision after its latest one and returns the new revision
+    /// number.
+    pub async fn append(
+        todo: &Todo,
+        created_at: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, DomainError> {
+        let revision = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO todo_revisions (todo_id, revision, title, due_at, priority, recurrence, completed_at, version, created_at)
+            SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3, $4, $5, $6, $7, $8
+            FROM todo_revisions
+            WHERE todo_id = $1
+            RETURNING revision
+            "#,
+        )
+        .bind(todo.id)
+        .bind(todo.title.as_ref().unwrap_or(&"".to_string()))
+        .bind(todo.due_at.map(|due_at| due_at.timestamp()))
+        .bind(todo.priority.as_i64())
+        .bind(
+            todo.recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.to_string()),
+        )
+        .bind(
+            todo.completed_at
+                .map(|completed_at| completed_at.timestamp()),
+        )
+        .bind(todo.version)
+        .bind(created_at.timestamp())
+        .fetch_one(&mut *conn)
+        .await;
+        match revision {
+            Ok(revision) => Ok(revision),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_by_todo_id(
+        todo_id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<TodoRevision>, DomainError> {
+        let revisions = sqlx::query_as!(
+            RevisionRow,
+            r#"
+            SELECT todo_id, revision, title, due_at, priority, recurrence, completed_at, version, created_at
+            FROM todo_revisions
+            WHERE todo_id = $1
+            ORDER BY revision
+            "#,
+            todo_id
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match revisions {
+            Ok(revisions) => revisions.into_iter().map(TodoRevision::try_from).collect(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find(
+        todo_id: i64,
+        revision: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<TodoRevision>, DomainError> {
+        let revision = sqlx::query_as!(
+            RevisionRow,
+            r#"
+            SELECT todo_id, revision, title, due_at, priority, recurrence, completed_at, version, created_at
+            FROM todo_revisions
+            WHERE todo_id = $1 AND revision = $2
+            "#,
+            todo_id,
+            revision
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        match revision {
+            Ok(revision) => revision.map(TodoRevision::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use domain::entity::todo::Priority;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    pub(crate) async fn prepare_revision_table(conn: &mut SqliteConnection) {
+        sqlx::query(
+            r#"
+            CREATE TABLE todo_revisions (
+                todo_id INTEGER NOT NULL,
+                revision INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                due_at INTEGER,
+                priority INTEGER NOT NULL,
+                recurrence TEXT,
+                completed_at INTEGER,
+                version INTEGER NOT NULL DEFAULT 1,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (todo_id, revision)
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_and_find() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_revision_table(&mut conn).await;
+
+        let created_at = DateTime::from_timestamp(1_000, 0).unwrap();
+        let first = Todo {
+            id: 1,
+            title: Some("buy milk".to_string()),
+            ..Default::default()
+        };
+        let second = Todo {
+            title: Some("buy oat milk".to_string()),
+            priority: Priority::High,
+            ..first.clone()
+        };
+        let other = Todo {
+            id: 2,
+            title: Some("other".to_string()),
+            ..Default::default()
+        };
+        for (todo, expected) in [(&first, 1), (&other, 1), (&second, 2)] {
+            match InternalSqliteRevisionRepository::append(todo, created_at, &mut conn).await {
+                Ok(revision) => assert_eq!(revision, expected),
+                Err(_) => panic!("failed to append revision"),
+            }
+        }
+
+        let repository = SqliteRevisionRepository::new(pool.clone());
+        match repository.find_by_todo_id(1).await {
+            Ok(revisions) => {
+                assert_eq!(
+                    revisions
+                        .iter()
+                        .map(|revision| (revision.revision, revision.todo.clone()))
+                        .collect::<Vec<_>>(),
+                    vec![(1, first), (2, second.clone())]
+                );
+                assert_eq!(revisions[1].created_at, created_at);
+            }
+            Err(_) => panic!("failed to fetch revisions"),
+        }
+        match repository.find(1, 2).await {
+            Ok(revision) => assert_eq!(revision.map(|revision| revision.todo), Some(second)),
+            Err(_) => panic!("failed to fetch revision"),
+        }
+        match repository.find(1, 3).await {
+            Ok(revision) => assert!(revision.is_none()),
+            Err(_) => panic!("failed to fetch revision"),
+        }
+    }
+}
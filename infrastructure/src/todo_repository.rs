@@ -1,7 +1,25 @@
+use async_stream::try_stream;
 use async_trait::async_trait;
-use domain::{entity::todo::Todo, error::DomainError, repository::todo_repository::TodoRepository};
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{
+        recurrence::Recurrence,
+        todo::{Priority, Todo},
+    },
+    error::DomainError,
+    event::TodoEvent,
+    repository::todo_repository::{TodoRepository, TodoStream},
+    unit_of_work::Transaction,
+};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
 use sqlx::{Pool, Sqlite, SqliteConnection};
 
+use crate::{
+    unit_of_work::{SqliteTransaction, TodoPersistence},
+    webhook_repository::TodoPayload,
+};
+
 #[derive(Debug, Clone)]
 pub struct SqliteTodoRepository {
     pool: Pool<Sqlite>,
@@ -11,23 +29,21 @@ impl SqliteTodoRepository {
     pub fn new(pool: Pool<Sqlite>) -> Self {
         Self { pool }
     }
-}
 
-#[async_trait]
-impl TodoRepository for SqliteTodoRepository {
-    async fn create(&self, todo: &Todo) -> Result<(), DomainError> {
+    async fn begin_transaction(&self) -> Result<SqliteTransaction, DomainError> {
         let tx = self.pool.begin().await;
-        let mut tx = match tx {
-            Ok(tx) => tx,
-            Err(e) => return Err(DomainError::Infrastructure(e.into())),
-        };
-        InternalSqliteTodoRepository::create(todo, &mut tx).await?;
-        let result = tx.commit().await;
-        match result {
-            Ok(_) => Ok(()),
+        match tx {
+            Ok(tx) => Ok(SqliteTransaction::new(tx, TodoPersistence::State)),
             Err(e) => Err(DomainError::Infrastructure(e.into())),
         }
     }
+}
+
+#[async_trait]
+impl TodoRepository for SqliteTodoRepository {
+    async fn create(&self, todo: &Todo) -> Result<i64, DomainError> {
+        create_recorded(self.begin_transaction().await?, todo).await
+    }
 
     async fn find_all(&self) -> Result<Vec<Todo>, DomainError> {
         let conn = self.pool.acquire().await;
@@ -38,6 +54,10 @@ impl TodoRepository for SqliteTodoRepository {
         InternalSqliteTodoRepository::find_all(&mut conn).await
     }
 
+    fn stream_all(&self) -> TodoStream {
+        stream_all(self.pool.clone())
+    }
+
     async fn find_by_id(&self, id: i64) -> Result<Option<Todo>, DomainError> {
         let conn = self.pool.acquire().await;
         let mut conn = match conn {
@@ -47,148 +67,562 @@ impl TodoRepository for SqliteTodoRepository {
         InternalSqliteTodoRepository::find_by_id(id, &mut conn).await
     }
 
-    async fn update(&self, todo: &Todo) -> Result<(), DomainError> {
-        let tx = self.pool.begin().await;
-        let mut tx = match tx {
-            Ok(tx) => tx,
+    /// Only the current state of a todo is kept.
+    async fn find_by_id_at(
+        &self,
+        _id: i64,
+        _at: DateTime<Utc>,
+    ) -> Result<Option<Todo>, DomainError> {
+        Err(DomainError::Validation(
+            "the history of todos is kept only by the event-sourced persistence".to_string(),
+        ))
+    }
+
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
             Err(e) => return Err(DomainError::Infrastructure(e.into())),
         };
-        InternalSqliteTodoRepository::update(todo, &mut tx).await?;
-        let result = tx.commit().await;
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => Err(DomainError::Infrastructure(e.into())),
-        }
+        InternalSqliteTodoRepository::find_by_ids(ids, &mut conn).await
+    }
+
+    async fn update(&self, todo: &Todo) -> Result<(), DomainError> {
+        update_recorded(self.begin_transaction().await?, todo).await
     }
 
     async fn delete(&self, todo_id: i64) -> Result<(), DomainError> {
-        let tx = self.pool.begin().await;
-        let mut tx = match tx {
-            Ok(tx) => tx,
+        delete_recorded(self.begin_transaction().await?, todo_id).await
+    }
+
+    async fn find_due_before(&self, before: DateTime<Utc>) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
             Err(e) => return Err(DomainError::Infrastructure(e.into())),
         };
-        InternalSqliteTodoRepository::delete(todo_id, &mut tx).await?;
-        let result = tx.commit().await;
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        InternalSqliteTodoRepository::find_due_before(before, &mut conn).await
+    }
+
+    async fn find_due_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteTodoRepository::find_due_between(from, to, &mut conn).await
+    }
+
+    async fn find_by_min_priority(&self, priority: Priority) -> Result<Vec<Todo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteTodoRepository::find_by_min_priority(priority, &mut conn).await
+    }
+}
+
+/// Streams the live todos on a connection of `pool`, held until the stream ends.
+pub(crate) fn stream_all(pool: Pool<Sqlite>) -> TodoStream {
+    Box::pin(try_stream! {
+        let mut conn = pool
+            .acquire()
+            .await
+            .map_err(|e| DomainError::Infrastructure(e.into()))?;
+        let mut todos = InternalSqliteTodoRepository::stream_all(&mut conn);
+        while let Some(todo) = todos.next().await {
+            yield todo?;
         }
+    })
+}
+
+/// Creates `todo` in `tx` as a standalone change, recording `TodoCreated` and its first
+/// revision.
+pub(crate) async fn create_recorded(
+    mut tx: SqliteTransaction,
+    todo: &Todo,
+) -> Result<i64, DomainError> {
+    let id = tx.create_todo(todo).await?;
+    let todo = Todo { id, ..todo.clone() };
+    tx.append_revision(&todo).await?;
+    tx.record(TodoEvent::TodoCreated { todo });
+    Box::new(tx).commit().await?;
+    Ok(id)
+}
+
+/// Updates `todo` in `tx` as a standalone change, recording `TodoCompleted` when it
+/// becomes completed and `TodoUpdated` otherwise, and a new revision. Updating a missing
+/// todo records nothing; one no longer at `todo.version` fails with `Conflict`.
+pub(crate) async fn update_recorded(
+    mut tx: SqliteTransaction,
+    todo: &Todo,
+) -> Result<(), DomainError> {
+    let previous = tx.find_todo_by_id(todo.id).await?;
+    tx.update_todo(todo).await?;
+    if let Some(previous) = previous {
+        let todo = &Todo {
+            version: todo.version + 1,
+            ..todo.clone()
+        };
+        tx.append_revision(todo).await?;
+        let event = if !previous.is_completed() && todo.is_completed() {
+            TodoEvent::TodoCompleted {
+                todo: todo.clone(),
+                next: None,
+            }
+        } else {
+            TodoEvent::TodoUpdated {
+                before: previous,
+                after: todo.clone(),
+            }
+        };
+        tx.record(event);
+    }
+    Box::new(tx).commit().await?;
+    Ok(())
+}
+
+/// Moves the todo to the trash in `tx` as a standalone change, recording `TodoDeleted` if
+/// it existed.
+pub(crate) async fn delete_recorded(
+    mut tx: SqliteTransaction,
+    todo_id: i64,
+) -> Result<(), DomainError> {
+    if let Some(previous) = tx.find_todo_by_id(todo_id).await? {
+        tx.delete_todo(todo_id, None).await?;
+        tx.record(TodoEvent::TodoDeleted { todo: previous });
     }
+    Box::new(tx).commit().await?;
+    Ok(())
+}
+
+/// Row payload of `domain_events`.
+#[derive(Debug, Clone, Serialize)]
+struct DomainEventPayload<'a> {
+    event: &'static str,
+    occurred_at: DateTime<Utc>,
+    todo: TodoPayload<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before: Option<TodoPayload<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<TodoPayload<'a>>,
+}
+
+fn domain_event_payload(
+    event: &TodoEvent,
+    occurred_at: DateTime<Utc>,
+) -> Result<String, DomainError> {
+    let (before, next) = match event {
+        TodoEvent::TodoUpdated { before, .. } => (Some(before.into()), None),
+        TodoEvent::TodoCompleted { next, .. } => (None, next.as_ref().map(TodoPayload::from)),
+        _ => (None, None),
+    };
+    let payload = DomainEventPayload {
+        event: event.name(),
+        occurred_at,
+        todo: event.todo().into(),
+        before,
+        next,
+    };
+    match serde_json::to_string(&payload) {
+        Ok(payload) => Ok(payload),
+        Err(e) => Err(DomainError::Infrastructure(e.into())),
+    }
+}
+
+/// Row shape of the `todos` table; timestamps are stored as unix seconds.
+#[derive(sqlx::FromRow)]
+pub(crate) struct TodoRow {
+    pub(crate) id: i64,
+    pub(crate) title: Option<String>,
+    pub(crate) due_at: Option<i64>,
+    pub(crate) priority: i64,
+    pub(crate) recurrence: Option<String>,
+    pub(crate) completed_at: Option<i64>,
+    pub(crate) version: i64,
+}
+
+pub(crate) fn from_timestamp(seconds: Option<i64>) -> Result<Option<DateTime<Utc>>, DomainError> {
+    match seconds {
+        Some(seconds) => match DateTime::from_timestamp(seconds, 0) {
+            Some(date_time) => Ok(Some(date_time)),
+            None => Err(DomainError::Unexpected(format!(
+                "invalid timestamp: {}",
+                seconds
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+impl TryFrom<TodoRow> for Todo {
+    type Error = DomainError;
+
+    fn try_from(row: TodoRow) -> Result<Self, Self::Error> {
+        let recurrence = match row.recurrence {
+            Some(rule) => Some(rule.parse::<Recurrence>()?),
+            None => None,
+        };
+        Ok(Todo {
+            id: row.id,
+            title: row.title,
+            due_at: from_timestamp(row.due_at)?,
+            priority: Priority::try_from(row.priority)?,
+            recurrence,
+            completed_at: from_timestamp(row.completed_at)?,
+            version: row.version,
+        })
+    }
+}
+
+fn into_todos(rows: Vec<TodoRow>) -> Result<Vec<Todo>, DomainError> {
+    rows.into_iter().map(Todo::try_from).collect()
 }
 
 pub struct InternalSqliteTodoRepository {}
 
 impl InternalSqliteTodoRepository {
-    pub async fn create(todo: &Todo, conn: &mut SqliteConnection) -> Result<(), DomainError> {
-        let todo = sqlx::query(
+    /// Appends `event` to `domain_events`. Call it on the connection of the transaction
+    /// that performs the change.
+    pub async fn append_event(
+        event: &TodoEvent,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let payload = domain_event_payload(event, now)?;
+        let result = sqlx::query(
             r#"
-            INSERT INTO todos (title)
-            VALUES ($1)
-            RETURNING id, title
+            INSERT INTO domain_events (event, todo_id, payload, occurred_at)
+            VALUES ($1, $2, $3, $4)
             "#,
         )
-        .bind(todo.title.as_ref().unwrap_or(&"".to_string()))
+        .bind(event.name())
+        .bind(event.todo().id)
+        .bind(payload)
+        .bind(now.timestamp())
         .execute(&mut *conn)
         .await;
-        println!("todo: {:?}", todo);
-        match todo {
+        match result {
             Ok(_) => Ok(()),
             Err(e) => Err(DomainError::Infrastructure(e.into())),
         }
     }
 
+    pub async fn create(todo: &Todo, conn: &mut SqliteConnection) -> Result<i64, DomainError> {
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO todos (title, due_at, priority, recurrence, completed_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(todo.title.as_ref().unwrap_or(&"".to_string()))
+        .bind(todo.due_at.map(|due_at| due_at.timestamp()))
+        .bind(todo.priority.as_i64())
+        .bind(
+            todo.recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.to_string()),
+        )
+        .bind(
+            todo.completed_at
+                .map(|completed_at| completed_at.timestamp()),
+        )
+        .fetch_one(&mut *conn)
+        .await;
+        match id {
+            Ok(id) => Ok(id),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
     pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<Todo>, DomainError> {
         let todos = sqlx::query_as!(
-            Todo,
+            TodoRow,
             r#"
-            SELECT id, title
+            SELECT id AS "id!", title, due_at, priority AS "priority!", recurrence, completed_at,
+                version AS "version!"
             FROM todos
+            WHERE deleted_at IS NULL
             ORDER BY id
             "#,
         )
         .fetch_all(&mut *conn)
         .await;
         match todos {
-            Ok(todos) => Ok(todos),
+            Ok(todos) => into_todos(todos),
             Err(e) => Err(DomainError::Infrastructure(e.into())),
         }
     }
 
+    /// Like [`InternalSqliteTodoRepository::find_all`], decoding rows as they are read.
+    pub fn stream_all(
+        conn: &mut SqliteConnection,
+    ) -> impl Stream<Item = Result<Todo, DomainError>> + Send + '_ {
+        sqlx::query_as!(
+            TodoRow,
+            r#"
+            SELECT id AS "id!", title, due_at, priority AS "priority!", recurrence, completed_at,
+                version AS "version!"
+            FROM todos
+            WHERE deleted_at IS NULL
+            ORDER BY id
+            "#,
+        )
+        .fetch(conn)
+        .map(|row| match row {
+            Ok(row) => Todo::try_from(row),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        })
+    }
+
     pub async fn find_by_id(
         id: i64,
         conn: &mut SqliteConnection,
     ) -> Result<Option<Todo>, DomainError> {
         let todo = sqlx::query_as!(
-            Todo,
+            TodoRow,
             r#"
-            SELECT id, title
+            SELECT id, title, due_at, priority, recurrence, completed_at, version
             FROM todos
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
         .fetch_optional(&mut *conn)
         .await;
         match todo {
-            Ok(todo) => Ok(todo),
+            Ok(todo) => todo.map(Todo::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_by_ids(
+        ids: &[i64],
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Todo>, DomainError> {
+        let ids = match serde_json::to_string(ids) {
+            Ok(ids) => ids,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let todos = sqlx::query_as::<_, TodoRow>(
+            r#"
+            SELECT id, title, due_at, priority, recurrence, completed_at, version
+            FROM todos
+            WHERE id IN (SELECT value FROM json_each($1)) AND deleted_at IS NULL
+            ORDER BY id
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&mut *conn)
+        .await;
+        match todos {
+            Ok(todos) => into_todos(todos),
             Err(e) => Err(DomainError::Infrastructure(e.into())),
         }
     }
 
+    /// Stores `todo` if it is still at `todo.version`, moving it to the next version.
+    /// Updating a missing todo is a no-op; one that changed since fails with `Conflict`.
     pub async fn update(todo: &Todo, conn: &mut SqliteConnection) -> Result<(), DomainError> {
-        let todo = sqlx::query(
+        let result = sqlx::query(
             r#"
             UPDATE todos
-            SET title = $1
-            WHERE id = $2
+            SET title = $1, due_at = $2, priority = $3, recurrence = $4, completed_at = $5,
+                version = version + 1
+            WHERE id = $6 AND version = $7 AND deleted_at IS NULL
             "#,
         )
         .bind(todo.title.as_ref().unwrap_or(&"".to_string()))
+        .bind(todo.due_at.map(|due_at| due_at.timestamp()))
+        .bind(todo.priority.as_i64())
+        .bind(
+            todo.recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.to_string()),
+        )
+        .bind(
+            todo.completed_at
+                .map(|completed_at| completed_at.timestamp()),
+        )
         .bind(todo.id)
+        .bind(todo.version)
         .execute(&mut *conn)
         .await;
-        println!("todo: {:?}", todo);
-        match todo {
-            Ok(_) => Ok(()),
+        match result {
+            Ok(result) if result.rows_affected() > 0 => Ok(()),
+            Ok(_) => match Self::find_by_id(todo.id, conn).await? {
+                Some(_) => Err(DomainError::Conflict {
+                    entity_type: "todo".to_string(),
+                    entity_id: todo.id,
+                }),
+                None => Ok(()),
+            },
             Err(e) => Err(DomainError::Infrastructure(e.into())),
         }
     }
 
-    pub async fn delete(todo_id: i64, conn: &mut SqliteConnection) -> Result<(), DomainError> {
+    /// Moves the todo to the trash by stamping `deleted_at`; trashed todos are left alone.
+    /// Given `expected_version`, the todo is only moved if still at it and otherwise fails
+    /// with `Conflict`, in the same statement.
+    pub async fn delete(
+        todo_id: i64,
+        expected_version: Option<i64>,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
         let todo = sqlx::query(
             r#"
-            DELETE FROM todos
-            WHERE id = $1
+            UPDATE todos
+            SET deleted_at = $1
+            WHERE id = $2 AND deleted_at IS NULL AND ($3 IS NULL OR version = $3)
             "#,
         )
+        .bind(now.timestamp())
         .bind(todo_id)
+        .bind(expected_version)
         .execute(&mut *conn)
         .await;
         match todo {
-            Ok(_) => Ok(()),
+            Ok(result) if result.rows_affected() > 0 || expected_version.is_none() => Ok(()),
+            Ok(_) => match Self::find_by_id(todo_id, conn).await? {
+                Some(_) => Err(DomainError::Conflict {
+                    entity_type: "todo".to_string(),
+                    entity_id: todo_id,
+                }),
+                None => Ok(()),
+            },
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_due_before(
+        before: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Todo>, DomainError> {
+        let before = before.timestamp();
+        let todos = sqlx::query_as!(
+            TodoRow,
+            r#"
+            SELECT id AS "id!", title, due_at, priority AS "priority!", recurrence, completed_at,
+                version AS "version!"
+            FROM todos
+            WHERE due_at < $1 AND completed_at IS NULL AND deleted_at IS NULL
+            ORDER BY due_at, id
+            "#,
+            before
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match todos {
+            Ok(todos) => into_todos(todos),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_due_between(
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Todo>, DomainError> {
+        let from = from.timestamp();
+        let to = to.timestamp();
+        let todos = sqlx::query_as!(
+            TodoRow,
+            r#"
+            SELECT id AS "id!", title, due_at, priority AS "priority!", recurrence, completed_at,
+                version AS "version!"
+            FROM todos
+            WHERE due_at >= $1 AND due_at < $2 AND completed_at IS NULL AND deleted_at IS NULL
+            ORDER BY due_at, id
+            "#,
+            from,
+            to
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match todos {
+            Ok(todos) => into_todos(todos),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_by_min_priority(
+        priority: Priority,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<Todo>, DomainError> {
+        let priority = priority.as_i64();
+        let todos = sqlx::query_as!(
+            TodoRow,
+            r#"
+            SELECT id AS "id!", title, due_at, priority AS "priority!", recurrence, completed_at,
+                version AS "version!"
+            FROM todos
+            WHERE priority >= $1 AND completed_at IS NULL AND deleted_at IS NULL
+            ORDER BY priority DESC, id
+            "#,
+            priority
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match todos {
+            Ok(todos) => into_todos(todos),
             Err(e) => Err(DomainError::Infrastructure(e.into())),
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::{
+        audit_repository::tests::prepare_audit_table,
+        revision_repository::tests::prepare_revision_table,
+        webhook_repository::tests::prepare_webhook_tables,
+    };
     use domain::entity::todo::Todo;
     use sqlx::sqlite::SqlitePoolOptions;
 
-    async fn prepare_table(conn: &mut SqliteConnection) {
+    pub(crate) async fn prepare_table(conn: &mut SqliteConnection) {
         sqlx::query(
             r#"
             CREATE TABLE todos (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                title TEXT NOT NULL
+                title TEXT NOT NULL,
+                due_at INTEGER,
+                priority INTEGER NOT NULL DEFAULT 2,
+                recurrence TEXT,
+                completed_at INTEGER,
+                deleted_at INTEGER,
+                version INTEGER NOT NULL DEFAULT 1
             )
             "#,
         )
         .execute(&mut *conn)
         .await
         .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE domain_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event TEXT NOT NULL,
+                todo_id INTEGER NOT NULL,
+                payload TEXT NOT NULL,
+                occurred_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        prepare_webhook_tables(conn).await;
+        prepare_audit_table(conn).await;
+        prepare_revision_table(conn).await;
     }
 
     #[tokio::test]
@@ -212,6 +646,7 @@ mod tests {
         let todo = Todo {
             id: 0,
             title: Some("task1".to_string()),
+            ..Default::default()
         };
         let result = InternalSqliteTodoRepository::create(&todo, &mut conn).await;
         let todo = match result {
@@ -241,6 +676,13 @@ mod tests {
             Err(_) => panic!("failed to fetch todo"),
         };
 
+        let result =
+            InternalSqliteTodoRepository::find_by_ids(&[todo.id + 1, todo.id], &mut conn).await;
+        match result {
+            Ok(todos) => assert_eq!(todos, vec![todo.clone()]),
+            Err(_) => panic!("failed to fetch todos"),
+        };
+
         let result = InternalSqliteTodoRepository::update(&todo, &mut conn).await;
         match result {
             Ok(_) => {
@@ -257,7 +699,8 @@ mod tests {
             Err(_) => panic!("failed to update todo"),
         };
 
-        let result = InternalSqliteTodoRepository::delete(todo.id, &mut conn).await;
+        let result =
+            InternalSqliteTodoRepository::delete(todo.id, None, Utc::now(), &mut conn).await;
         match result {
             Ok(_) => {
                 let todos = InternalSqliteTodoRepository::find_all(&mut conn).await;
@@ -286,6 +729,7 @@ mod tests {
         let todo = Todo {
             id: 0,
             title: Some("task1".to_string()),
+            ..Default::default()
         };
         let result = repository.create(&todo).await;
         match result {
@@ -318,6 +762,7 @@ mod tests {
         let todo = Todo {
             id: 0,
             title: Some("task1".to_string()),
+            ..Default::default()
         };
         let result = repository.create(&todo).await;
         let todo = match result {
@@ -339,6 +784,7 @@ mod tests {
         let todo = Todo {
             id: todo.id,
             title: Some("task2".to_string()),
+            ..Default::default()
         };
         let result = repository.update(&todo).await;
         match result {
@@ -371,6 +817,7 @@ mod tests {
         let todo = Todo {
             id: 0,
             title: Some("task1".to_string()),
+            ..Default::default()
         };
         let result = repository.create(&todo).await;
         let todo = match result {
@@ -427,6 +874,7 @@ mod tests {
         let todo = Todo {
             id: 0,
             title: Some("task1".to_string()),
+            ..Default::default()
         };
         let result = InternalSqliteTodoRepository::create(&todo, &mut conn).await;
         match result {
@@ -446,6 +894,84 @@ mod tests {
         };
     }
 
+    #[tokio::test]
+    async fn test_delete_expecting_a_version() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        prepare_table(&mut conn).await;
+
+        let todo = Todo {
+            title: Some("task1".to_string()),
+            ..Default::default()
+        };
+        let id = InternalSqliteTodoRepository::create(&todo, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let stale = todo.version + 1;
+        match InternalSqliteTodoRepository::delete(id, Some(stale), Utc::now(), &mut conn).await {
+            Err(DomainError::Conflict { entity_id, .. }) => assert_eq!(entity_id, id),
+            _ => panic!("deleted a todo at another version"),
+        }
+        InternalSqliteTodoRepository::delete(id, Some(todo.version), Utc::now(), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        match InternalSqliteTodoRepository::find_by_id(id, &mut conn).await {
+            Ok(None) => {}
+            _ => panic!("failed to delete todo"),
+        }
+        // a todo already gone is left alone whatever version is expected
+        InternalSqliteTodoRepository::delete(id, Some(stale), Utc::now(), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_all() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        prepare_table(&mut conn).await;
+
+        for title in ["task1", "task2", "task3"] {
+            let todo = Todo {
+                title: Some(title.to_string()),
+                ..Default::default()
+            };
+            InternalSqliteTodoRepository::create(&todo, &mut conn)
+                .await
+                .ok()
+                .unwrap();
+        }
+        InternalSqliteTodoRepository::delete(2, None, Utc::now(), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+
+        let repository = SqliteTodoRepository::new(pool);
+        let mut todos = repository.stream_all();
+        let mut titles = Vec::new();
+        while let Some(todo) = todos.next().await {
+            match todo {
+                Ok(todo) => titles.push(todo.title),
+                Err(_) => panic!("failed to stream todos"),
+            }
+        }
+        assert_eq!(
+            titles,
+            vec![Some("task1".to_string()), Some("task3".to_string())]
+        );
+    }
+
     #[tokio::test]
     async fn test_find_by_id() {
         let pool = SqlitePoolOptions::new()
@@ -471,6 +997,7 @@ mod tests {
         let todo = Todo {
             id: 0,
             title: Some("task1".to_string()),
+            ..Default::default()
         };
         let result = InternalSqliteTodoRepository::create(&todo, &mut conn).await;
         match result {
@@ -490,4 +1017,182 @@ mod tests {
             Err(_) => panic!("failed to create todo"),
         };
     }
+
+    #[tokio::test]
+    async fn test_smart_view_queries() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        prepare_table(&mut conn).await;
+
+        let at = |seconds| DateTime::from_timestamp(seconds, 0);
+        for (title, due_at, priority) in [
+            ("past", at(1_000), Priority::High),
+            ("window", at(2_000), Priority::Low),
+            ("future", at(3_000), Priority::Medium),
+            ("undated", None, Priority::High),
+        ] {
+            let todo = Todo {
+                id: 0,
+                title: Some(title.to_string()),
+                due_at,
+                priority,
+                ..Default::default()
+            };
+            InternalSqliteTodoRepository::create(&todo, &mut conn)
+                .await
+                .unwrap_or_else(|_| panic!("failed to create todo"));
+        }
+
+        let repository = SqliteTodoRepository::new(pool);
+        let titles = |todos: Vec<Todo>| {
+            todos
+                .into_iter()
+                .map(|todo| todo.title.unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        match repository.find_due_before(at(2_000).unwrap()).await {
+            Ok(todos) => assert_eq!(titles(todos), vec!["past"]),
+            Err(_) => panic!("failed to fetch overdue todos"),
+        };
+        match repository
+            .find_due_between(at(2_000).unwrap(), at(3_000).unwrap())
+            .await
+        {
+            Ok(todos) => {
+                let todo = todos[0].clone();
+                assert_eq!(todos.len(), 1);
+                assert_eq!(todo.due_at, at(2_000));
+                assert_eq!(todo.priority, Priority::Low);
+            }
+            Err(_) => panic!("failed to fetch todos in window"),
+        };
+        match repository.find_by_min_priority(Priority::Medium).await {
+            Ok(todos) => assert_eq!(titles(todos), vec!["past", "undated", "future"]),
+            Err(_) => panic!("failed to fetch prioritized todos"),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_recurrence_and_completion_round_trip() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        prepare_table(&mut conn).await;
+
+        let repository = SqliteTodoRepository::new(pool);
+        let mut todo = Todo {
+            id: 0,
+            title: Some("task1".to_string()),
+            due_at: DateTime::from_timestamp(1_000, 0),
+            recurrence: "FREQ=WEEKLY;BYDAY=MO,TH".parse().ok(),
+            ..Default::default()
+        };
+        todo.id = match repository.create(&todo).await {
+            Ok(id) => id,
+            Err(_) => panic!("failed to create todo"),
+        };
+        assert_eq!(todo.id, 1);
+
+        todo.completed_at = DateTime::from_timestamp(2_000, 0);
+        if repository.update(&todo).await.is_err() {
+            panic!("failed to update todo");
+        }
+        todo.version += 1;
+        match repository.find_by_id(todo.id).await {
+            Ok(found) => assert_eq!(found, Some(todo)),
+            Err(_) => panic!("failed to fetch todo"),
+        };
+
+        // completed todos drop out of the smart views
+        match repository.find_due_before(Utc::now()).await {
+            Ok(todos) => assert!(todos.is_empty()),
+            Err(_) => panic!("failed to fetch overdue todos"),
+        };
+    }
+
+    #[tokio::test]
+    async fn test_mutations_write_outbox_events() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        prepare_table(&mut conn).await;
+
+        let repository = SqliteTodoRepository::new(pool);
+        let mut todo = Todo {
+            title: Some("task1".to_string()),
+            ..Default::default()
+        };
+        todo.id = match repository.create(&todo).await {
+            Ok(id) => id,
+            Err(_) => panic!("failed to create todo"),
+        };
+        todo.title = Some("task2".to_string());
+        assert!(repository.update(&todo).await.is_ok());
+        // an update made from a stale read is rejected and records nothing
+        assert!(matches!(
+            repository.update(&todo).await,
+            Err(DomainError::Conflict { .. })
+        ));
+        todo.version += 1;
+        todo.completed_at = DateTime::from_timestamp(2_000, 0);
+        assert!(repository.update(&todo).await.is_ok());
+        assert!(repository.delete(todo.id).await.is_ok());
+        // Deleting a missing todo records nothing.
+        assert!(repository.delete(todo.id).await.is_ok());
+
+        let events = sqlx::query_scalar::<_, String>(
+            "SELECT event FROM webhook_outbox WHERE todo_id = $1 ORDER BY id",
+        )
+        .bind(todo.id)
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                "todo.created",
+                "todo.updated",
+                "todo.completed",
+                "todo.deleted"
+            ]
+        );
+        let events = sqlx::query_scalar::<_, String>(
+            "SELECT event FROM domain_events WHERE todo_id = $1 ORDER BY id",
+        )
+        .bind(todo.id)
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(
+            events,
+            vec!["TodoCreated", "TodoUpdated", "TodoCompleted", "TodoDeleted"]
+        );
+        // the history outlives the todo
+        let revisions = sqlx::query_as::<_, (i64, String)>(
+            "SELECT revision, title FROM todo_revisions WHERE todo_id = $1 ORDER BY revision",
+        )
+        .bind(todo.id)
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(
+            revisions,
+            vec![
+                (1, "task1".to_string()),
+                (2, "task2".to_string()),
+                (3, "task2".to_string())
+            ]
+        );
+    }
 }
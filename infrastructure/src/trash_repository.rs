@@ -0,0 +1,295 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{todo::Todo, trash::TrashedTodo},
+    error::DomainError,
+    repository::trash_repository::TrashRepository,
+};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::todo_repository::{from_timestamp, TodoRow};
+
+#[derive(Debug, Clone)]
+pub struct SqliteTrashRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteTrashRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TrashRepository for SqliteTrashRepository {
+    async fn find_all(&self) -> Result<Vec<TrashedTodo>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteTrashRepository::find_all(&mut conn).await
+    }
+
+    async fn purge_deleted_before(&self, before: DateTime<Utc>) -> Result<u64, DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let purged = InternalSqliteTrashRepository::purge_deleted_before(before, &mut tx).await?;
+        match tx.commit().await {
+            Ok(_) => Ok(purged),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+/// Row shape of a trashed row of the `todos` table.
+struct TrashedTodoRow {
+    id: i64,
+    title: Option<String>,
+    due_at: Option<i64>,
+    priority: i64,
+    recurrence: Option<String>,
+    completed_at: Option<i64>,
+    version: i64,
+    deleted_at: i64,
+}
+
+impl TryFrom<TrashedTodoRow> for TrashedTodo {
+    type Error = DomainError;
+
+    fn try_from(row: TrashedTodoRow) -> Result<Self, Self::Error> {
+        let deleted_at = match from_timestamp(Some(row.deleted_at))? {
+            Some(deleted_at) => deleted_at,
+            None => {
+                return Err(DomainError::Unexpected(format!(
+                    "todo {} has no deletion time",
+                    row.id
+                )))
+            }
+        };
+        let todo = Todo::try_from(TodoRow {
+            id: row.id,
+            title: row.title,
+            due_at: row.due_at,
+            priority: row.priority,
+            recurrence: row.recurrence,
+            completed_at: row.completed_at,
+            version: row.version,
+        })?;
+        Ok(TrashedTodo { todo, deleted_at })
+    }
+}
+
+pub struct InternalSqliteTrashRepository {}
+
+impl InternalSqliteTrashRepository {
+    pub async fn find_all(conn: &mut SqliteConnection) -> Result<Vec<TrashedTodo>, DomainError> {
+        let todos = sqlx::query_as!(
+            TrashedTodoRow,
+            r#"
+            SELECT id AS "id!", title, due_at, priority AS "priority!", recurrence, completed_at,
+                version AS "version!", deleted_at AS "deleted_at!"
+            FROM todos
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC, id DESC
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match todos {
+            Ok(todos) => todos.into_iter().map(TrashedTodo::try_from).collect(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Clears `deleted_at` and returns the restored todo, or `None` if it is not trashed.
+    pub async fn restore(
+        todo_id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Todo>, DomainError> {
+        let todo = sqlx::query_as!(
+            TodoRow,
+            r#"
+            UPDATE todos
+            SET deleted_at = NULL
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            RETURNING id AS "id!", title AS "title?", due_at, priority AS "priority!", recurrence,
+                completed_at, version AS "version!"
+            "#,
+            todo_id
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        match todo {
+            Ok(todo) => todo.map(Todo::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Deletes the todos trashed before `before` together with their revisions, event
+    /// streams and CalDAV names; reminders go with them through their foreign key. The
+    /// audit log is kept.
+    /// Call it inside a transaction.
+    pub async fn purge_deleted_before(
+        before: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<u64, DomainError> {
+        let before = before.timestamp();
+        for statement in [
+            r#"
+            DELETE FROM todo_revisions
+            WHERE todo_id IN (SELECT id FROM todos WHERE deleted_at < $1)
+            "#,
+            r#"
+            DELETE FROM todo_snapshots
+            WHERE todo_id IN (SELECT id FROM todos WHERE deleted_at < $1)
+            "#,
+            r#"
+            DELETE FROM todo_events
+            WHERE todo_id IN (SELECT id FROM todos WHERE deleted_at < $1)
+            "#,
+            r#"
+            DELETE FROM caldav_resources
+            WHERE todo_id IN (SELECT id FROM todos WHERE deleted_at < $1)
+            "#,
+        ] {
+            let result = sqlx::query(statement)
+                .bind(before)
+                .execute(&mut *conn)
+                .await;
+            if let Err(e) = result {
+                return Err(DomainError::Infrastructure(e.into()));
+            }
+        }
+        let result = sqlx::query(
+            r#"
+            DELETE FROM todos
+            WHERE deleted_at < $1
+            "#,
+        )
+        .bind(before)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(result) => Ok(result.rows_affected()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        caldav_resource_repository::{
+            tests::prepare_caldav_resource_table, InternalSqliteCalDavResourceRepository,
+        },
+        event_store::tests::prepare_tables,
+        revision_repository::InternalSqliteRevisionRepository,
+        todo_repository::InternalSqliteTodoRepository,
+    };
+    use domain::entity::caldav_resource::CalDavResource;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_trash_restore_and_purge() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_tables(&mut conn).await;
+        prepare_caldav_resource_table(&mut conn).await;
+
+        let mut todos = vec![];
+        for title in ["old", "recent", "kept"] {
+            let todo = Todo {
+                title: Some(title.to_string()),
+                ..Default::default()
+            };
+            let id = InternalSqliteTodoRepository::create(&todo, &mut conn)
+                .await
+                .ok()
+                .unwrap();
+            let todo = Todo { id, ..todo };
+            InternalSqliteRevisionRepository::append(&todo, Utc::now(), &mut conn)
+                .await
+                .ok()
+                .unwrap();
+            todos.push(todo);
+        }
+        let resource = CalDavResource::new("0f8fad5b.ics".to_string(), todos[0].id)
+            .ok()
+            .unwrap();
+        InternalSqliteCalDavResourceRepository::create(&resource, &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let old_deleted_at = DateTime::from_timestamp(1_000, 0).unwrap();
+        let recent_deleted_at = DateTime::from_timestamp(5_000, 0).unwrap();
+        for (todo, deleted_at) in [(&todos[0], old_deleted_at), (&todos[1], recent_deleted_at)] {
+            InternalSqliteTodoRepository::delete(todo.id, None, deleted_at, &mut conn)
+                .await
+                .ok()
+                .unwrap();
+        }
+
+        let repository = SqliteTrashRepository::new(pool.clone());
+        match repository.find_all().await {
+            Ok(trash) => assert_eq!(
+                trash,
+                vec![
+                    TrashedTodo {
+                        todo: todos[1].clone(),
+                        deleted_at: recent_deleted_at,
+                    },
+                    TrashedTodo {
+                        todo: todos[0].clone(),
+                        deleted_at: old_deleted_at,
+                    },
+                ]
+            ),
+            Err(_) => panic!("failed to fetch trash"),
+        }
+        match InternalSqliteTodoRepository::find_by_id(todos[0].id, &mut conn).await {
+            Ok(found) => assert!(found.is_none()),
+            Err(_) => panic!("failed to fetch todo"),
+        }
+
+        match InternalSqliteTrashRepository::restore(todos[1].id, &mut conn).await {
+            Ok(restored) => assert_eq!(restored, Some(todos[1].clone())),
+            Err(_) => panic!("failed to restore todo"),
+        }
+        match InternalSqliteTrashRepository::restore(todos[2].id, &mut conn).await {
+            Ok(restored) => assert!(restored.is_none()),
+            Err(_) => panic!("failed to restore todo"),
+        }
+
+        match repository
+            .purge_deleted_before(DateTime::from_timestamp(2_000, 0).unwrap())
+            .await
+        {
+            Ok(purged) => assert_eq!(purged, 1),
+            Err(_) => panic!("failed to purge trash"),
+        }
+        match repository.find_all().await {
+            Ok(trash) => assert!(trash.is_empty()),
+            Err(_) => panic!("failed to fetch trash"),
+        }
+        match InternalSqliteRevisionRepository::find_by_todo_id(todos[0].id, &mut conn).await {
+            Ok(revisions) => assert!(revisions.is_empty()),
+            Err(_) => panic!("failed to fetch revisions"),
+        }
+        match InternalSqliteCalDavResourceRepository::find_all(&mut conn).await {
+            Ok(resources) => assert!(resources.is_empty()),
+            Err(_) => panic!("failed to fetch resources"),
+        }
+        match InternalSqliteTodoRepository::find_all(&mut conn).await {
+            Ok(found) => assert_eq!(found, vec![todos[1].clone(), todos[2].clone()]),
+            Err(_) => panic!("failed to fetch todos"),
+        }
+    }
+}
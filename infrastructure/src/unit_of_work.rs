@@ -0,0 +1,466 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{
+        audit::AuditEntry, caldav_resource::CalDavResource, idempotency::IdempotencyRecord,
+        reminder::Reminder, todo::Todo, webhook::WebhookEvent,
+    },
+    error::DomainError,
+    event::TodoEvent,
+    unit_of_work::{Transaction, UnitOfWork},
+};
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    audit_repository::InternalSqliteAuditRepository,
+    caldav_resource_repository::InternalSqliteCalDavResourceRepository,
+    event_store::InternalEventSourcedTodoRepository,
+    idempotency_repository::InternalSqliteIdempotencyRepository,
+    reminder_repository::InternalSqliteReminderRepository,
+    revision_repository::InternalSqliteRevisionRepository,
+    todo_repository::InternalSqliteTodoRepository, trash_repository::InternalSqliteTrashRepository,
+    webhook_repository::InternalSqliteWebhookRepository,
+};
+
+/// Where the state of a todo is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TodoPersistence {
+    /// Rows of `todos` are the source of truth.
+    #[default]
+    State,
+    /// Event streams in `todo_events` are the source of truth and `todos` is their
+    /// projection; a snapshot is taken every `snapshot_every` events of a stream.
+    EventSourced { snapshot_every: i64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteUnitOfWork {
+    pool: Pool<Sqlite>,
+    persistence: TodoPersistence,
+}
+
+impl SqliteUnitOfWork {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self {
+            pool,
+            persistence: TodoPersistence::State,
+        }
+    }
+
+    /// Sets how todo changes are persisted; must match the `TodoRepository` in use.
+    pub fn with_persistence(mut self, persistence: TodoPersistence) -> Self {
+        self.persistence = persistence;
+        self
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for SqliteUnitOfWork {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, DomainError> {
+        let tx = self.pool.begin().await;
+        match tx {
+            Ok(tx) => Ok(Box::new(SqliteTransaction::new(tx, self.persistence))),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+/// One SQLite transaction driven through the `InternalSqlite*Repository` functions. On
+/// commit every recorded event is appended to `domain_events` and to the webhook outbox
+/// before the transaction commits.
+pub struct SqliteTransaction {
+    tx: sqlx::Transaction<'static, Sqlite>,
+    persistence: TodoPersistence,
+    events: Vec<TodoEvent>,
+    /// How many events were recorded when each open savepoint was taken, innermost last.
+    savepoints: Vec<usize>,
+}
+
+impl SqliteTransaction {
+    pub fn new(tx: sqlx::Transaction<'static, Sqlite>, persistence: TodoPersistence) -> Self {
+        Self {
+            tx,
+            persistence,
+            events: Vec::new(),
+            savepoints: Vec::new(),
+        }
+    }
+
+    async fn execute(&mut self, statement: &str) -> Result<(), DomainError> {
+        match sqlx::query(statement).execute(&mut *self.tx).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[async_trait]
+impl Transaction for SqliteTransaction {
+    async fn create_todo(&mut self, todo: &Todo) -> Result<i64, DomainError> {
+        match self.persistence {
+            TodoPersistence::State => {
+                InternalSqliteTodoRepository::create(todo, &mut self.tx).await
+            }
+            TodoPersistence::EventSourced { snapshot_every } => {
+                InternalEventSourcedTodoRepository::create(
+                    todo,
+                    snapshot_every,
+                    Utc::now(),
+                    &mut self.tx,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn find_todo_by_id(&mut self, id: i64) -> Result<Option<Todo>, DomainError> {
+        match self.persistence {
+            TodoPersistence::State => {
+                InternalSqliteTodoRepository::find_by_id(id, &mut self.tx).await
+            }
+            TodoPersistence::EventSourced { .. } => {
+                InternalEventSourcedTodoRepository::find_by_id(id, &mut self.tx).await
+            }
+        }
+    }
+
+    async fn update_todo(&mut self, todo: &Todo) -> Result<(), DomainError> {
+        match self.persistence {
+            TodoPersistence::State => {
+                InternalSqliteTodoRepository::update(todo, &mut self.tx).await
+            }
+            TodoPersistence::EventSourced { snapshot_every } => {
+                InternalEventSourcedTodoRepository::update(
+                    todo,
+                    snapshot_every,
+                    Utc::now(),
+                    &mut self.tx,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn delete_todo(
+        &mut self,
+        todo_id: i64,
+        expected_version: Option<i64>,
+    ) -> Result<(), DomainError> {
+        match self.persistence {
+            TodoPersistence::State => {
+                InternalSqliteTodoRepository::delete(
+                    todo_id,
+                    expected_version,
+                    Utc::now(),
+                    &mut self.tx,
+                )
+                .await
+            }
+            TodoPersistence::EventSourced { snapshot_every } => {
+                InternalEventSourcedTodoRepository::delete(
+                    todo_id,
+                    expected_version,
+                    snapshot_every,
+                    Utc::now(),
+                    &mut self.tx,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn restore_todo(&mut self, todo_id: i64) -> Result<Option<Todo>, DomainError> {
+        match self.persistence {
+            TodoPersistence::State => {
+                InternalSqliteTrashRepository::restore(todo_id, &mut self.tx).await
+            }
+            TodoPersistence::EventSourced { snapshot_every } => {
+                InternalEventSourcedTodoRepository::restore(
+                    todo_id,
+                    snapshot_every,
+                    Utc::now(),
+                    &mut self.tx,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn create_reminder(&mut self, reminder: &Reminder) -> Result<i64, DomainError> {
+        InternalSqliteReminderRepository::create(reminder, &mut self.tx).await
+    }
+
+    async fn find_reminders_by_todo_id(
+        &mut self,
+        todo_id: i64,
+    ) -> Result<Vec<Reminder>, DomainError> {
+        InternalSqliteReminderRepository::find_by_todo_id(todo_id, &mut self.tx).await
+    }
+
+    async fn append_revision(&mut self, todo: &Todo) -> Result<i64, DomainError> {
+        InternalSqliteRevisionRepository::append(todo, Utc::now(), &mut self.tx).await
+    }
+
+    async fn append_audit(&mut self, entry: &AuditEntry) -> Result<i64, DomainError> {
+        InternalSqliteAuditRepository::append(entry, &mut self.tx).await
+    }
+
+    async fn find_idempotency_record(
+        &mut self,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<IdempotencyRecord>, DomainError> {
+        InternalSqliteIdempotencyRepository::prune(now, &mut self.tx).await?;
+        InternalSqliteIdempotencyRepository::find(key, &mut self.tx).await
+    }
+
+    async fn save_idempotency_record(
+        &mut self,
+        record: &IdempotencyRecord,
+    ) -> Result<bool, DomainError> {
+        InternalSqliteIdempotencyRepository::save(record, &mut self.tx).await
+    }
+
+    async fn create_caldav_resource(
+        &mut self,
+        resource: &CalDavResource,
+    ) -> Result<bool, DomainError> {
+        InternalSqliteCalDavResourceRepository::create(resource, &mut self.tx).await
+    }
+
+    async fn savepoint(&mut self) -> Result<(), DomainError> {
+        // SQLite rolls back and releases the innermost savepoint of a repeated name
+        self.execute("SAVEPOINT item").await?;
+        self.savepoints.push(self.events.len());
+        Ok(())
+    }
+
+    async fn release_savepoint(&mut self) -> Result<(), DomainError> {
+        self.execute("RELEASE item").await?;
+        self.savepoints.pop();
+        Ok(())
+    }
+
+    async fn rollback_to_savepoint(&mut self) -> Result<(), DomainError> {
+        self.execute("ROLLBACK TO item").await?;
+        self.execute("RELEASE item").await?;
+        if let Some(recorded) = self.savepoints.pop() {
+            self.events.truncate(recorded);
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, event: TodoEvent) {
+        self.events.push(event);
+    }
+
+    async fn commit(self: Box<Self>) -> Result<Vec<TodoEvent>, DomainError> {
+        let SqliteTransaction { mut tx, events, .. } = *self;
+        let now = Utc::now();
+        for event in events.iter() {
+            InternalSqliteTodoRepository::append_event(event, now, &mut tx).await?;
+            for (webhook_event, todo) in webhook_events(event) {
+                InternalSqliteWebhookRepository::enqueue(webhook_event, todo, now, &mut tx).await?;
+            }
+        }
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(events),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+/// The webhook notifications a domain event translates to.
+fn webhook_events(event: &TodoEvent) -> Vec<(WebhookEvent, &Todo)> {
+    match event {
+        TodoEvent::TodoCreated { todo } => vec![(WebhookEvent::TodoCreated, todo)],
+        TodoEvent::TodoUpdated { after, .. } => vec![(WebhookEvent::TodoUpdated, after)],
+        TodoEvent::TodoCompleted { todo, next } => {
+            let mut events = vec![(WebhookEvent::TodoCompleted, todo)];
+            if let Some(next) = next {
+                events.push((WebhookEvent::TodoCreated, next));
+            }
+            events
+        }
+        TodoEvent::TodoDeleted { todo } => vec![(WebhookEvent::TodoDeleted, todo)],
+        TodoEvent::TodoRestored { todo } => vec![(WebhookEvent::TodoRestored, todo)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo_repository::{tests::prepare_table, SqliteTodoRepository};
+    use chrono::{DateTime, Duration};
+    use domain::entity::audit::{AuditAction, Protocol};
+    use domain::{entity::reminder::ReminderTrigger, repository::todo_repository::TodoRepository};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn audit(before: Option<&Todo>, after: Option<&Todo>) -> AuditEntry {
+        AuditEntry {
+            id: 0,
+            todo_id: after.or(before).map(|todo| todo.id).unwrap_or_default(),
+            action: AuditAction::Update,
+            actor: "tester".to_string(),
+            protocol: Protocol::System,
+            request_id: "req".to_string(),
+            before: before.cloned(),
+            after: after.cloned(),
+            occurred_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commits_changes_with_events() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        prepare_table(&mut conn).await;
+        sqlx::query(
+            r#"
+            CREATE TABLE reminders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                todo_id INTEGER NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+                remind_at INTEGER,
+                offset_seconds INTEGER,
+                claimed_until INTEGER,
+                fired_at INTEGER,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                failed_at INTEGER
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+
+        let unit_of_work = SqliteUnitOfWork::new(pool.clone());
+        let todo = Todo {
+            title: Some("discarded".to_string()),
+            ..Default::default()
+        };
+        let mut tx = unit_of_work.begin().await.ok().unwrap();
+        let id = tx.create_todo(&todo).await.ok().unwrap();
+        let todo = Todo { id, ..todo };
+        assert!(tx.append_audit(&audit(None, Some(&todo))).await.is_ok());
+        tx.record(TodoEvent::TodoCreated { todo });
+        drop(tx);
+
+        let mut tx = unit_of_work.begin().await.ok().unwrap();
+        let mut todo = Todo {
+            title: Some("kept".to_string()),
+            ..Default::default()
+        };
+        todo.id = tx.create_todo(&todo).await.ok().unwrap();
+        let mut next = Todo {
+            title: Some("next".to_string()),
+            due_at: DateTime::from_timestamp(10_000, 0),
+            ..Default::default()
+        };
+        next.id = tx.create_todo(&next).await.ok().unwrap();
+        let reminder = Reminder::new(&next, ReminderTrigger::BeforeDue(Duration::minutes(5)))
+            .ok()
+            .unwrap();
+        assert!(tx.create_reminder(&reminder).await.is_ok());
+        todo.completed_at = DateTime::from_timestamp(2_000, 0);
+        assert!(tx.update_todo(&todo).await.is_ok());
+        todo.version += 1;
+        assert!(tx.append_audit(&audit(None, Some(&todo))).await.is_ok());
+        tx.record(TodoEvent::TodoCompleted {
+            todo: todo.clone(),
+            next: Some(next.clone()),
+        });
+        match tx.commit().await {
+            Ok(events) => assert_eq!(events.len(), 1),
+            Err(_) => panic!("failed to commit"),
+        }
+
+        let repository = SqliteTodoRepository::new(pool);
+        match repository.find_all().await {
+            Ok(todos) => assert_eq!(todos, vec![todo.clone(), next.clone()]),
+            Err(_) => panic!("failed to fetch todos"),
+        }
+        match InternalSqliteReminderRepository::find_by_todo_id(next.id, &mut conn).await {
+            Ok(reminders) => assert_eq!(reminders.len(), 1),
+            Err(_) => panic!("failed to fetch reminders"),
+        }
+        let events = sqlx::query_as::<_, (String, i64, String)>(
+            "SELECT event, todo_id, payload FROM domain_events ORDER BY id",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "TodoCompleted");
+        assert_eq!(events[0].1, todo.id);
+        let payload: serde_json::Value = serde_json::from_str(&events[0].2).unwrap();
+        assert_eq!(payload["next"]["title"], "next");
+        let webhook_events = sqlx::query_as::<_, (String, i64)>(
+            "SELECT event, todo_id FROM webhook_outbox ORDER BY id",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(
+            webhook_events,
+            vec![
+                ("todo.completed".to_string(), todo.id),
+                ("todo.created".to_string(), next.id)
+            ]
+        );
+        let audit_log = sqlx::query_as::<_, (i64, String)>(
+            "SELECT todo_id, request_id FROM audit_log ORDER BY id",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(audit_log, vec![(todo.id, "req".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_to_savepoint_keeps_the_earlier_changes() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_table(&mut conn).await;
+
+        let unit_of_work = SqliteUnitOfWork::new(pool.clone());
+        let mut tx = unit_of_work.begin().await.ok().unwrap();
+        let mut todos = vec![];
+        for title in ["kept", "undone", "released"] {
+            assert!(tx.savepoint().await.is_ok());
+            let mut todo = Todo {
+                title: Some(title.to_string()),
+                ..Default::default()
+            };
+            todo.id = tx.create_todo(&todo).await.ok().unwrap();
+            tx.record(TodoEvent::TodoCreated { todo: todo.clone() });
+            if title == "undone" {
+                assert!(tx.rollback_to_savepoint().await.is_ok());
+            } else {
+                assert!(tx.release_savepoint().await.is_ok());
+                todos.push(todo);
+            }
+        }
+        match tx.commit().await {
+            Ok(events) => assert_eq!(events.len(), 2),
+            Err(_) => panic!("failed to commit"),
+        }
+
+        let repository = SqliteTodoRepository::new(pool);
+        match repository.find_all().await {
+            Ok(found) => assert_eq!(
+                found.into_iter().map(|todo| todo.title).collect::<Vec<_>>(),
+                todos.into_iter().map(|todo| todo.title).collect::<Vec<_>>()
+            ),
+            Err(_) => panic!("failed to fetch todos"),
+        }
+    }
+}
@@ -0,0 +1,831 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entity::{
+        todo::{Priority, Todo},
+        webhook::{WebhookDelivery, WebhookEvent, WebhookSubscription},
+    },
+    error::DomainError,
+    repository::webhook_repository::WebhookRepository,
+};
+use serde::Serialize;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::todo_repository::from_timestamp;
+
+#[derive(Debug, Clone)]
+pub struct SqliteWebhookRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteWebhookRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for SqliteWebhookRepository {
+    async fn create_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+    ) -> Result<i64, DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let id =
+            InternalSqliteWebhookRepository::create_subscription(subscription, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(id),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn find_subscriptions(&self) -> Result<Vec<WebhookSubscription>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteWebhookRepository::find_subscriptions(&mut conn).await
+    }
+
+    async fn find_subscription_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<WebhookSubscription>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteWebhookRepository::find_subscription_by_id(id, &mut conn).await
+    }
+
+    async fn delete_subscription(&self, id: i64) -> Result<(), DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteWebhookRepository::delete_subscription(id, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, DomainError> {
+        let conn = self.pool.acquire().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteWebhookRepository::find_deliveries(subscription_id, limit, &mut conn).await
+    }
+
+    async fn fan_out(&self, now: DateTime<Utc>) -> Result<usize, DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let queued = InternalSqliteWebhookRepository::fan_out(now, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(queued),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn claim_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        let deliveries =
+            InternalSqliteWebhookRepository::claim_due_deliveries(now, lease_until, limit, &mut tx)
+                .await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(deliveries),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn mark_delivered(
+        &self,
+        id: i64,
+        status: i64,
+        delivered_at: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteWebhookRepository::mark_delivered(id, status, delivered_at, &mut tx).await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    async fn mark_failed(
+        &self,
+        id: i64,
+        status: Option<i64>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        let tx = self.pool.begin().await;
+        let mut tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+        InternalSqliteWebhookRepository::mark_failed(id, status, error, retry_at, now, &mut tx)
+            .await?;
+        let result = tx.commit().await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+/// Row shape of the `webhook_subscriptions` table.
+struct SubscriptionRow {
+    id: i64,
+    url: String,
+    secret: String,
+    events: String,
+    created_at: i64,
+}
+
+/// Row shape of the `webhook_deliveries` table.
+struct DeliveryRow {
+    id: i64,
+    subscription_id: i64,
+    event: String,
+    payload: String,
+    attempts: i64,
+    next_attempt_at: i64,
+    delivered_at: Option<i64>,
+    failed_at: Option<i64>,
+    response_status: Option<i64>,
+    last_error: Option<String>,
+    created_at: i64,
+}
+
+pub(crate) fn required_timestamp(seconds: i64) -> Result<DateTime<Utc>, DomainError> {
+    match from_timestamp(Some(seconds))? {
+        Some(date_time) => Ok(date_time),
+        None => Err(DomainError::Unexpected("missing timestamp".to_string())),
+    }
+}
+
+fn events_to_column(events: &[WebhookEvent]) -> String {
+    events
+        .iter()
+        .map(|event| event.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl TryFrom<SubscriptionRow> for WebhookSubscription {
+    type Error = DomainError;
+
+    fn try_from(row: SubscriptionRow) -> Result<Self, Self::Error> {
+        let events = row
+            .events
+            .split(',')
+            .filter(|event| !event.is_empty())
+            .map(|event| event.parse::<WebhookEvent>())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(WebhookSubscription {
+            id: row.id,
+            url: row.url,
+            secret: row.secret,
+            events,
+            created_at: required_timestamp(row.created_at)?,
+        })
+    }
+}
+
+impl TryFrom<DeliveryRow> for WebhookDelivery {
+    type Error = DomainError;
+
+    fn try_from(row: DeliveryRow) -> Result<Self, Self::Error> {
+        Ok(WebhookDelivery {
+            id: row.id,
+            subscription_id: row.subscription_id,
+            event: row.event.parse()?,
+            payload: row.payload,
+            attempts: row.attempts,
+            next_attempt_at: required_timestamp(row.next_attempt_at)?,
+            delivered_at: from_timestamp(row.delivered_at)?,
+            failed_at: from_timestamp(row.failed_at)?,
+            response_status: row.response_status,
+            last_error: row.last_error,
+            created_at: required_timestamp(row.created_at)?,
+        })
+    }
+}
+
+fn into_deliveries(rows: Vec<DeliveryRow>) -> Result<Vec<WebhookDelivery>, DomainError> {
+    rows.into_iter().map(WebhookDelivery::try_from).collect()
+}
+
+/// The todo as it appears in webhook and domain event payloads.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TodoPayload<'a> {
+    id: i64,
+    title: Option<&'a str>,
+    due_at: Option<DateTime<Utc>>,
+    priority: &'static str,
+    recurrence: Option<String>,
+    completed_at: Option<DateTime<Utc>>,
+    version: i64,
+}
+
+impl<'a> From<&'a Todo> for TodoPayload<'a> {
+    fn from(todo: &'a Todo) -> Self {
+        TodoPayload {
+            id: todo.id,
+            title: todo.title.as_deref(),
+            due_at: todo.due_at,
+            priority: match todo.priority {
+                Priority::Low => "low",
+                Priority::Medium => "medium",
+                Priority::High => "high",
+            },
+            recurrence: todo
+                .recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.to_string()),
+            completed_at: todo.completed_at,
+            version: todo.version,
+        }
+    }
+}
+
+/// Webhook request body; stored in the outbox and sent verbatim.
+#[derive(Debug, Clone, Serialize)]
+struct EventPayload<'a> {
+    event: &'static str,
+    occurred_at: DateTime<Utc>,
+    todo: TodoPayload<'a>,
+}
+
+fn event_payload(
+    event: WebhookEvent,
+    todo: &Todo,
+    occurred_at: DateTime<Utc>,
+) -> Result<String, DomainError> {
+    let payload = EventPayload {
+        event: event.as_str(),
+        occurred_at,
+        todo: todo.into(),
+    };
+    match serde_json::to_string(&payload) {
+        Ok(payload) => Ok(payload),
+        Err(e) => Err(DomainError::Infrastructure(e.into())),
+    }
+}
+
+pub struct InternalSqliteWebhookRepository {}
+
+impl InternalSqliteWebhookRepository {
+    /// Records `event` for `todo` in the outbox. Call it on the connection of the
+    /// transaction that performs the change, so the event exists exactly when the change does.
+    pub async fn enqueue(
+        event: WebhookEvent,
+        todo: &Todo,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let payload = event_payload(event, todo, now)?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO webhook_outbox (event, todo_id, payload, created_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(event.as_str())
+        .bind(todo.id)
+        .bind(payload)
+        .bind(now.timestamp())
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn create_subscription(
+        subscription: &WebhookSubscription,
+        conn: &mut SqliteConnection,
+    ) -> Result<i64, DomainError> {
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO webhook_subscriptions (url, secret, events, created_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+        )
+        .bind(&subscription.url)
+        .bind(&subscription.secret)
+        .bind(events_to_column(&subscription.events))
+        .bind(subscription.created_at.timestamp())
+        .fetch_one(&mut *conn)
+        .await;
+        match id {
+            Ok(id) => Ok(id),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_subscriptions(
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<WebhookSubscription>, DomainError> {
+        let subscriptions = sqlx::query_as!(
+            SubscriptionRow,
+            r#"
+            SELECT id, url, secret, events, created_at
+            FROM webhook_subscriptions
+            ORDER BY id
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match subscriptions {
+            Ok(subscriptions) => subscriptions
+                .into_iter()
+                .map(WebhookSubscription::try_from)
+                .collect(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_subscription_by_id(
+        id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<WebhookSubscription>, DomainError> {
+        let subscription = sqlx::query_as!(
+            SubscriptionRow,
+            r#"
+            SELECT id AS "id!", url, secret, events, created_at
+            FROM webhook_subscriptions
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .await;
+        match subscription {
+            Ok(subscription) => subscription.map(WebhookSubscription::try_from).transpose(),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn delete_subscription(
+        id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM webhook_subscriptions
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn find_deliveries(
+        subscription_id: i64,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<WebhookDelivery>, DomainError> {
+        let deliveries = sqlx::query_as!(
+            DeliveryRow,
+            r#"
+            SELECT id AS "id!", subscription_id, event, payload, attempts, next_attempt_at,
+                   delivered_at, failed_at, response_status, last_error, created_at
+            FROM webhook_deliveries
+            WHERE subscription_id = $1
+            ORDER BY id DESC
+            LIMIT $2
+            "#,
+            subscription_id,
+            limit
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match deliveries {
+            Ok(deliveries) => into_deliveries(deliveries),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    /// Must run inside a transaction: the outbox high-water mark is read first so events
+    /// committed while fanning out are left for the next run.
+    pub async fn fan_out(
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<usize, DomainError> {
+        let last_id = sqlx::query_scalar!(
+            r#"
+            SELECT MAX(id) AS "last_id: i64"
+            FROM webhook_outbox
+            WHERE processed_at IS NULL
+            "#,
+        )
+        .fetch_one(&mut *conn)
+        .await;
+        let last_id = match last_id {
+            Ok(Some(last_id)) => last_id,
+            Ok(None) => return Ok(0),
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+
+        let now = now.timestamp();
+        let queued = sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries
+                (subscription_id, outbox_id, event, payload, next_attempt_at, created_at)
+            SELECT s.id, o.id, o.event, o.payload, $1, $1
+            FROM webhook_outbox o
+            JOIN webhook_subscriptions s
+              ON s.events = '' OR instr(',' || s.events || ',', ',' || o.event || ',') > 0
+            WHERE o.processed_at IS NULL AND o.id <= $2 AND s.created_at <= o.created_at
+            ORDER BY o.id, s.id
+            "#,
+        )
+        .bind(now)
+        .bind(last_id)
+        .execute(&mut *conn)
+        .await;
+        let queued = match queued {
+            Ok(result) => result.rows_affected() as usize,
+            Err(e) => return Err(DomainError::Infrastructure(e.into())),
+        };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE webhook_outbox
+            SET processed_at = $1
+            WHERE processed_at IS NULL AND id <= $2
+            "#,
+        )
+        .bind(now)
+        .bind(last_id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(queued),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn claim_due_deliveries(
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<WebhookDelivery>, DomainError> {
+        let now = now.timestamp();
+        let lease_until = lease_until.timestamp();
+        let deliveries = sqlx::query_as!(
+            DeliveryRow,
+            r#"
+            UPDATE webhook_deliveries
+            SET next_attempt_at = $1
+            WHERE id IN (
+                SELECT id
+                FROM webhook_deliveries
+                WHERE delivered_at IS NULL AND failed_at IS NULL AND next_attempt_at <= $2
+                ORDER BY id
+                LIMIT $3
+            )
+            RETURNING id AS "id!", subscription_id, event, payload, attempts, next_attempt_at,
+                      delivered_at, failed_at, response_status, last_error, created_at
+            "#,
+            lease_until,
+            now,
+            limit
+        )
+        .fetch_all(&mut *conn)
+        .await;
+        match deliveries {
+            Ok(deliveries) => into_deliveries(deliveries),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn mark_delivered(
+        id: i64,
+        status: i64,
+        delivered_at: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = attempts + 1, delivered_at = $1, response_status = $2, last_error = NULL
+            WHERE id = $3
+            "#,
+        )
+        .bind(delivered_at.timestamp())
+        .bind(status)
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+
+    pub async fn mark_failed(
+        id: i64,
+        status: Option<i64>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), DomainError> {
+        let (next_attempt_at, failed_at) = match retry_at {
+            Some(retry_at) => (retry_at.timestamp(), None),
+            None => (now.timestamp(), Some(now.timestamp())),
+        };
+        let result = sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = attempts + 1, next_attempt_at = $1, failed_at = $2,
+                response_status = $3, last_error = $4
+            WHERE id = $5
+            "#,
+        )
+        .bind(next_attempt_at)
+        .bind(failed_at)
+        .bind(status)
+        .bind(error)
+        .bind(id)
+        .execute(&mut *conn)
+        .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(DomainError::Infrastructure(e.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// Mirrors the webhook tables from `migrations/`.
+    pub(crate) async fn prepare_webhook_tables(conn: &mut SqliteConnection) {
+        for statement in [
+            r#"
+            CREATE TABLE webhook_subscriptions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                events TEXT NOT NULL DEFAULT '',
+                created_at INTEGER NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE webhook_outbox (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event TEXT NOT NULL,
+                todo_id INTEGER NOT NULL,
+                payload TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                processed_at INTEGER
+            )
+            "#,
+            r#"
+            CREATE TABLE webhook_deliveries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                subscription_id INTEGER NOT NULL
+                    REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+                outbox_id INTEGER NOT NULL,
+                event TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at INTEGER NOT NULL,
+                delivered_at INTEGER,
+                failed_at INTEGER,
+                response_status INTEGER,
+                last_error TEXT,
+                created_at INTEGER NOT NULL
+            )
+            "#,
+        ] {
+            sqlx::query(statement).execute(&mut *conn).await.unwrap();
+        }
+    }
+
+    fn instant(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    async fn subscribe(events: Vec<WebhookEvent>, conn: &mut SqliteConnection) -> i64 {
+        let subscription = WebhookSubscription::new(
+            "http://127.0.0.1:9/hook".to_string(),
+            "secret".to_string(),
+            events,
+            instant(0),
+        )
+        .ok()
+        .unwrap();
+        InternalSqliteWebhookRepository::create_subscription(&subscription, conn)
+            .await
+            .ok()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_matches_event_filters() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_webhook_tables(&mut conn).await;
+
+        let everything = subscribe(vec![], &mut conn).await;
+        let deletions = subscribe(vec![WebhookEvent::TodoDeleted], &mut conn).await;
+
+        match InternalSqliteWebhookRepository::find_subscription_by_id(deletions, &mut conn).await {
+            Ok(Some(subscription)) => {
+                assert_eq!(subscription.events, vec![WebhookEvent::TodoDeleted])
+            }
+            _ => panic!("failed to fetch subscription"),
+        }
+
+        let todo = Todo {
+            id: 1,
+            title: Some("task1".to_string()),
+            ..Default::default()
+        };
+        for event in [WebhookEvent::TodoCreated, WebhookEvent::TodoDeleted] {
+            InternalSqliteWebhookRepository::enqueue(event, &todo, instant(100), &mut conn)
+                .await
+                .ok()
+                .unwrap();
+        }
+
+        let queued = InternalSqliteWebhookRepository::fan_out(instant(200), &mut conn).await;
+        assert!(matches!(queued, Ok(3)));
+        // Processed events are not fanned out twice.
+        let queued = InternalSqliteWebhookRepository::fan_out(instant(300), &mut conn).await;
+        assert!(matches!(queued, Ok(0)));
+
+        match InternalSqliteWebhookRepository::find_deliveries(everything, 10, &mut conn).await {
+            Ok(deliveries) => {
+                assert_eq!(deliveries.len(), 2);
+                assert_eq!(deliveries[0].event, WebhookEvent::TodoDeleted);
+                assert!(deliveries[1].payload.contains(r#""event":"todo.created""#));
+                assert!(deliveries[1].payload.contains(r#""title":"task1""#));
+            }
+            Err(_) => panic!("failed to fetch deliveries"),
+        }
+        match InternalSqliteWebhookRepository::find_deliveries(deletions, 10, &mut conn).await {
+            Ok(deliveries) => {
+                assert_eq!(deliveries.len(), 1);
+                assert_eq!(deliveries[0].event, WebhookEvent::TodoDeleted);
+            }
+            Err(_) => panic!("failed to fetch deliveries"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_and_retry_deliveries() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        prepare_webhook_tables(&mut conn).await;
+
+        let subscription_id = subscribe(vec![], &mut conn).await;
+        let todo = Todo {
+            id: 1,
+            ..Default::default()
+        };
+        InternalSqliteWebhookRepository::enqueue(
+            WebhookEvent::TodoCreated,
+            &todo,
+            instant(100),
+            &mut conn,
+        )
+        .await
+        .ok()
+        .unwrap();
+        InternalSqliteWebhookRepository::fan_out(instant(100), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+
+        let claimed = InternalSqliteWebhookRepository::claim_due_deliveries(
+            instant(100),
+            instant(160),
+            10,
+            &mut conn,
+        )
+        .await;
+        let delivery = match claimed {
+            Ok(mut deliveries) => {
+                assert_eq!(deliveries.len(), 1);
+                deliveries.remove(0)
+            }
+            Err(_) => panic!("failed to claim deliveries"),
+        };
+        // Leased deliveries are invisible to other workers.
+        let claimed = InternalSqliteWebhookRepository::claim_due_deliveries(
+            instant(120),
+            instant(180),
+            10,
+            &mut conn,
+        )
+        .await;
+        assert!(matches!(claimed, Ok(deliveries) if deliveries.is_empty()));
+
+        InternalSqliteWebhookRepository::mark_failed(
+            delivery.id,
+            Some(503),
+            "service unavailable",
+            Some(instant(130)),
+            instant(120),
+            &mut conn,
+        )
+        .await
+        .ok()
+        .unwrap();
+        let claimed = InternalSqliteWebhookRepository::claim_due_deliveries(
+            instant(130),
+            instant(190),
+            10,
+            &mut conn,
+        )
+        .await;
+        match claimed {
+            Ok(deliveries) => {
+                assert_eq!(deliveries.len(), 1);
+                assert_eq!(deliveries[0].attempts, 1);
+                assert_eq!(deliveries[0].response_status, Some(503));
+            }
+            Err(_) => panic!("failed to claim deliveries"),
+        }
+
+        InternalSqliteWebhookRepository::mark_delivered(delivery.id, 200, instant(140), &mut conn)
+            .await
+            .ok()
+            .unwrap();
+        let claimed = InternalSqliteWebhookRepository::claim_due_deliveries(
+            instant(1_000),
+            instant(1_060),
+            10,
+            &mut conn,
+        )
+        .await;
+        assert!(matches!(claimed, Ok(deliveries) if deliveries.is_empty()));
+
+        match InternalSqliteWebhookRepository::find_deliveries(subscription_id, 10, &mut conn).await
+        {
+            Ok(deliveries) => {
+                assert_eq!(deliveries[0].attempts, 2);
+                assert_eq!(deliveries[0].delivered_at, Some(instant(140)));
+                assert_eq!(deliveries[0].last_error, None);
+            }
+            Err(_) => panic!("failed to fetch deliveries"),
+        }
+    }
+}
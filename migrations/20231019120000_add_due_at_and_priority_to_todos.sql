@@ -0,0 +1,6 @@
+-- due_at is stored as unix seconds so range queries compare integers
+alter table todos add column due_at INTEGER;
+alter table todos add column priority INTEGER NOT NULL DEFAULT 2;
+
+create index idx_todos_due_at on todos (due_at);
+create index idx_todos_priority on todos (priority);
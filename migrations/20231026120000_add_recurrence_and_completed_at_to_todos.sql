@@ -0,0 +1,3 @@
+-- recurrence holds an RFC 5545 RRULE string such as FREQ=WEEKLY;BYDAY=MO
+alter table todos add column recurrence TEXT;
+alter table todos add column completed_at INTEGER;
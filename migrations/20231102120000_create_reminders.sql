@@ -0,0 +1,15 @@
+-- a reminder fires either at remind_at or offset_seconds before its todo's due_at.
+-- claimed_until is a lease taken by the scheduler so a reminder is never fired twice.
+create table reminders (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  todo_id INTEGER NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+  remind_at INTEGER,
+  offset_seconds INTEGER,
+  claimed_until INTEGER,
+  fired_at INTEGER,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  last_error TEXT,
+  CHECK ((remind_at IS NULL) <> (offset_seconds IS NULL))
+);
+
+create index idx_reminders_pending on reminders (fired_at, todo_id);
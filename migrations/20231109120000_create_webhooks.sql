@@ -0,0 +1,39 @@
+-- events is a comma-separated list of event names such as todo.created; empty means all
+create table webhook_subscriptions (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  url TEXT NOT NULL,
+  secret TEXT NOT NULL,
+  events TEXT NOT NULL DEFAULT '',
+  created_at INTEGER NOT NULL
+);
+
+-- rows are written in the same transaction as the todo change they describe
+create table webhook_outbox (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  event TEXT NOT NULL,
+  todo_id INTEGER NOT NULL,
+  payload TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  processed_at INTEGER
+);
+
+create index idx_webhook_outbox_processed_at on webhook_outbox (processed_at);
+
+-- next_attempt_at doubles as the worker's lease while a delivery is in flight
+create table webhook_deliveries (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  subscription_id INTEGER NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+  outbox_id INTEGER NOT NULL,
+  event TEXT NOT NULL,
+  payload TEXT NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at INTEGER NOT NULL,
+  delivered_at INTEGER,
+  failed_at INTEGER,
+  response_status INTEGER,
+  last_error TEXT,
+  created_at INTEGER NOT NULL
+);
+
+create index idx_webhook_deliveries_pending on webhook_deliveries (delivered_at, failed_at, next_attempt_at);
+create index idx_webhook_deliveries_subscription_id on webhook_deliveries (subscription_id, id);
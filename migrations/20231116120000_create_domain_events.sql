@@ -0,0 +1,10 @@
+-- append-only log of todo domain events, written in the same transaction as the change
+create table domain_events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  event TEXT NOT NULL,
+  todo_id INTEGER NOT NULL,
+  payload TEXT NOT NULL,
+  occurred_at INTEGER NOT NULL
+);
+
+create index idx_domain_events_todo_id on domain_events (todo_id);
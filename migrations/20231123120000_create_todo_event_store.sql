@@ -0,0 +1,18 @@
+-- per-todo event streams; the source of truth when the event-sourced store is selected,
+-- with todos kept as a projection of them
+create table todo_events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  todo_id INTEGER NOT NULL,
+  version INTEGER NOT NULL,
+  event TEXT NOT NULL,
+  payload TEXT NOT NULL,
+  occurred_at INTEGER NOT NULL,
+  UNIQUE (todo_id, version)
+);
+
+-- state after replaying a stream up to version; state is NULL once the todo is deleted
+create table todo_snapshots (
+  todo_id INTEGER PRIMARY KEY,
+  version INTEGER NOT NULL,
+  state TEXT
+);
@@ -0,0 +1,25 @@
+-- who changed which todo, through which API and how; rows are never updated or removed
+create table audit_log (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  todo_id INTEGER NOT NULL,
+  action TEXT NOT NULL,
+  actor TEXT NOT NULL,
+  protocol TEXT NOT NULL,
+  request_id TEXT NOT NULL,
+  before TEXT,
+  after TEXT,
+  occurred_at INTEGER NOT NULL
+);
+
+create index idx_audit_log_todo_id on audit_log (todo_id);
+create index idx_audit_log_actor on audit_log (actor);
+
+create trigger audit_log_no_update before update on audit_log
+begin
+  select raise(abort, 'audit_log is append-only');
+end;
+
+create trigger audit_log_no_delete before delete on audit_log
+begin
+  select raise(abort, 'audit_log is append-only');
+end;
@@ -0,0 +1,17 @@
+-- the state of a todo as of each of its revisions; revision 1 is its creation
+create table todo_revisions (
+  todo_id INTEGER NOT NULL,
+  revision INTEGER NOT NULL,
+  title TEXT NOT NULL,
+  due_at INTEGER,
+  priority INTEGER NOT NULL,
+  recurrence TEXT,
+  completed_at INTEGER,
+  created_at INTEGER NOT NULL,
+  PRIMARY KEY (todo_id, revision)
+);
+
+-- existing todos start their history at their current state
+insert into todo_revisions (todo_id, revision, title, due_at, priority, recurrence, completed_at, created_at)
+select id, 1, title, due_at, priority, recurrence, completed_at, cast(strftime('%s', 'now') as integer)
+from todos;
@@ -0,0 +1,4 @@
+-- deleted todos stay in the trash, with deleted_at set, until they are restored or purged
+alter table todos add column deleted_at INTEGER;
+
+create index idx_todos_deleted_at on todos (deleted_at);
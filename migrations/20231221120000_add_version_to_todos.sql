@@ -0,0 +1,11 @@
+-- version goes up by one with every update and guards updates against lost writes.
+-- every update so far also added a revision, so existing todos start at their latest one
+alter table todos add column version INTEGER NOT NULL DEFAULT 1;
+
+update todos
+set version = coalesce((select max(revision) from todo_revisions where todo_id = todos.id), 1);
+
+-- the version of the todo as of each revision
+alter table todo_revisions add column version INTEGER NOT NULL DEFAULT 1;
+
+update todo_revisions set version = revision;
@@ -0,0 +1,11 @@
+-- todos created under a client-supplied idempotency key, so retried creates are replayed
+create table idempotency_keys (
+  key TEXT PRIMARY KEY NOT NULL,
+  fingerprint TEXT NOT NULL,
+  todo_id INTEGER NOT NULL,
+  todo TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  expires_at INTEGER NOT NULL
+);
+
+create index idx_idempotency_keys_expires_at on idempotency_keys (expires_at);
@@ -0,0 +1,9 @@
+-- private iCalendar feed URLs; the token is the secret part of the URL
+create table calendar_feeds (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  token TEXT NOT NULL UNIQUE,
+  actor TEXT NOT NULL,
+  created_at INTEGER NOT NULL
+);
+
+create index idx_calendar_feeds_actor on calendar_feeds (actor, id);
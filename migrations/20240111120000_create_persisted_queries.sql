@@ -0,0 +1,11 @@
+-- GraphQL documents runnable by hash: registered from the client manifest, or saved by
+-- automatic persisted queries
+create table persisted_queries (
+  hash TEXT PRIMARY KEY NOT NULL,
+  document TEXT NOT NULL,
+  operation_names TEXT NOT NULL,
+  source TEXT NOT NULL,
+  created_at INTEGER NOT NULL
+);
+
+create index idx_persisted_queries_source on persisted_queries (source, created_at);
@@ -0,0 +1,7 @@
+-- rules may have at most INTERVAL=1000. longer intervals overflowed the date arithmetic of
+-- completing the todo, so such rules never produced an occurrence; drop them so that the
+-- todos still load
+update todos
+set recurrence = null
+where instr(upper(recurrence), 'INTERVAL=') > 0
+  and cast(substr(recurrence, instr(upper(recurrence), 'INTERVAL=') + 9) as integer) > 1000;
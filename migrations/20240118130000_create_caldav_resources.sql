@@ -0,0 +1,5 @@
+-- the names CalDAV clients gave the todos they created; other todos are served as <id>.ics
+create table caldav_resources (
+  name TEXT PRIMARY KEY,
+  todo_id INTEGER NOT NULL UNIQUE
+);
@@ -0,0 +1,2 @@
+-- set when a reminder is abandoned after too many failed deliveries; it is not claimed again
+alter table reminders add column failed_at INTEGER;
@@ -0,0 +1,85 @@
+//! Changes to todos that take more than one call of [`TodoUseCase`], shared by every
+//! version of the REST and gRPC APIs so that they all behave the same way.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use use_case::{
+    dto::{audit::RequestContext, todo::TodoDto},
+    error::UseCaseError,
+    traits::todo::TodoUseCase,
+};
+
+/// Todo `id`, or `NotFound`.
+pub(crate) async fn find_todo<TU: TodoUseCase>(tu: &TU, id: i64) -> Result<TodoDto, UseCaseError> {
+    match tu.find_by_id(id).await? {
+        Some(todo) => Ok(todo),
+        None => Err(UseCaseError::NotFound {
+            entity_type: "todo".to_string(),
+            entity_id: id,
+        }),
+    }
+}
+
+/// Todo `id` as it was at `at`, or `NotFound` if it did not exist then.
+pub(crate) async fn find_todo_at<TU: TodoUseCase>(
+    tu: &TU,
+    id: i64,
+    at: DateTime<Utc>,
+) -> Result<TodoDto, UseCaseError> {
+    match tu.find_by_id_at(id, at).await? {
+        Some(todo) => Ok(todo),
+        None => Err(UseCaseError::NotFound {
+            entity_type: "todo".to_string(),
+            entity_id: id,
+        }),
+    }
+}
+
+/// Updates todo `id` with `change` applied to its current fields. The update fails with
+/// `Conflict` if the todo changed since it was read or, given `expected_version`, if the
+/// todo is not at it; a rejected `change` fails with `Validation`.
+pub(crate) async fn patch_todo<TU: TodoUseCase>(
+    tu: &TU,
+    ctx: RequestContext,
+    id: i64,
+    expected_version: Option<i64>,
+    change: impl FnOnce(TodoDto) -> Result<TodoDto, String>,
+) -> Result<TodoDto, UseCaseError> {
+    let todo = find_todo(tu, id).await?;
+    let version = todo.version;
+    check_version(id, version, expected_version)?;
+    let todo = change(todo).map_err(UseCaseError::Validation)?;
+    tu.update(ctx, todo.into(), Some(version)).await
+}
+
+/// Moves todo `id` to the trash, or fails with `NotFound`; given `expected_version`, only
+/// if the todo is still at it when deleted.
+pub(crate) async fn delete_todo<TU: TodoUseCase>(
+    tu: &TU,
+    ctx: RequestContext,
+    id: i64,
+    expected_version: Option<i64>,
+) -> Result<i64, UseCaseError> {
+    find_todo(tu, id).await?;
+    tu.delete(ctx, id, expected_version).await
+}
+
+fn check_version(id: i64, version: i64, expected_version: Option<i64>) -> Result<(), UseCaseError> {
+    match expected_version {
+        Some(expected_version) if expected_version != version => Err(UseCaseError::Conflict {
+            entity_type: "todo".to_string(),
+            entity_id: id,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Tells a member set to `null`, `Some(None)`, apart from a missing one, `None`; used with
+/// `#[serde(default, deserialize_with = "present")]`.
+pub(crate) fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
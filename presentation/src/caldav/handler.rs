@@ -0,0 +1,744 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use chrono::Utc;
+use domain::entity::{audit::Protocol, caldav_resource::CalDavResource, todo::Todo};
+use use_case::{
+    dto::{
+        audit::RequestContext,
+        import_export::TodoFormat,
+        todo::{CreateTodoDto, TodoDto},
+    },
+    error::UseCaseError,
+    icalendar,
+    traits::{caldav_resource::CalDavResourceUseCase, todo::TodoUseCase},
+};
+
+use crate::{
+    context::from_headers,
+    rest::handler::{etag, expected_version, status_code},
+};
+
+use super::xml;
+
+/// The principal, and the calendar home holding the one calendar of todos.
+const ROOT: &str = "/caldav/";
+const CALENDAR: &str = "/caldav/todos/";
+const ALLOW: &str = "OPTIONS, GET, HEAD, PUT, DELETE, PROPFIND, REPORT";
+
+/// What a CalDAV request path names.
+enum Target {
+    Root,
+    Calendar,
+    /// A `.ics` resource in the calendar, named by a client or `<id>.ics` by default.
+    Resource(String),
+}
+
+impl Target {
+    /// Parses a request path or an `href`, which may be a full URL.
+    fn parse(path: &str) -> Option<Self> {
+        let path = match path.find("://") {
+            Some(scheme) => {
+                let rest = &path[scheme + 3..];
+                &rest[rest.find('/')?..]
+            }
+            None => path,
+        };
+        let rest = path.strip_prefix(ROOT.trim_end_matches('/'))?;
+        match rest.trim_start_matches('/') {
+            "" => Some(Target::Root),
+            "todos" | "todos/" => Some(Target::Calendar),
+            rest => {
+                let name = rest.strip_prefix("todos/")?;
+                if name.ends_with(".ics") && !name.contains('/') {
+                    Some(Target::Resource(name.to_string()))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn href(name: &str) -> String {
+    format!("{}{}", CALENDAR, name)
+}
+
+/// The href of a todo, under the name a client gave it if any.
+fn todo_href(names: &HashMap<i64, String>, todo_id: i64) -> String {
+    match names.get(&todo_id) {
+        Some(name) => href(name),
+        None => href(&CalDavResource::default_name(todo_id)),
+    }
+}
+
+/// `/.well-known/caldav` (RFC 6764), where clients start looking for calendars. The
+/// redirect is temporary so that it keeps the method, PROPFIND included.
+pub async fn well_known_caldav() -> impl IntoResponse {
+    (StatusCode::TEMPORARY_REDIRECT, [(header::LOCATION, ROOT)])
+}
+
+/// A minimal CalDAV server over `/caldav/`: one calendar of VTODOs, `/caldav/todos/`,
+/// holding every todo. Clients discover it with PROPFIND, sync it with REPORT
+/// `calendar-query` or `calendar-multiget` and change todos with GET, PUT and DELETE,
+/// guarded by the same version ETags as the REST API.
+///
+/// A VTODO PUT under a new name creates a todo served under that name from then on, so
+/// the client finds its resource where it put it. Todos created through other APIs are
+/// served as `<id>.ics`, a name clients cannot create.
+pub async fn caldav<TU: TodoUseCase, CU: CalDavResourceUseCase>(
+    Extension(tu): Extension<TU>,
+    Extension(cu): Extension<CU>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let target = match Target::parse(uri.path()) {
+        Some(target) => target,
+        None => return StatusCode::NOT_FOUND.into_response(),
+    };
+    let ctx = from_headers(&headers, Protocol::CalDav);
+    let response = match (method.as_str(), target) {
+        ("OPTIONS", _) => Ok(options()),
+        ("PROPFIND", target) => propfind(&tu, &cu, target, &headers).await,
+        ("REPORT", Target::Calendar) => report(&tu, &cu, &body).await,
+        ("GET" | "HEAD", Target::Resource(name)) => get(&tu, &cu, &name).await,
+        ("PUT", Target::Resource(name)) => put(&tu, &cu, ctx, &headers, &name, &body).await,
+        ("DELETE", Target::Resource(name)) => delete(&tu, &cu, ctx, &headers, &name).await,
+        _ => Ok((StatusCode::METHOD_NOT_ALLOWED, [(header::ALLOW, ALLOW)]).into_response()),
+    };
+    response.unwrap_or_else(|err| status_code(&err).into_response())
+}
+
+fn options() -> Response {
+    (
+        StatusCode::OK,
+        [
+            (header::ALLOW, ALLOW),
+            (header::HeaderName::from_static("dav"), "1, calendar-access"),
+        ],
+    )
+        .into_response()
+}
+
+fn multistatus(responses: Vec<String>) -> Response {
+    (
+        StatusCode::MULTI_STATUS,
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        format!(
+            "{}{}{}",
+            xml::MULTISTATUS_START,
+            responses.concat(),
+            xml::MULTISTATUS_END
+        ),
+    )
+        .into_response()
+}
+
+fn root_props() -> Vec<String> {
+    vec![
+        "<D:resourcetype><D:collection/></D:resourcetype>".to_string(),
+        format!(
+            "<D:current-user-principal><D:href>{}</D:href></D:current-user-principal>",
+            ROOT
+        ),
+        format!(
+            "<D:principal-URL><D:href>{}</D:href></D:principal-URL>",
+            ROOT
+        ),
+        format!(
+            "<C:calendar-home-set><D:href>{}</D:href></C:calendar-home-set>",
+            ROOT
+        ),
+    ]
+}
+
+fn calendar_props(todos: &[TodoDto]) -> Vec<String> {
+    // changes whenever a todo is created, updated or deleted
+    let mut hasher = DefaultHasher::new();
+    for todo in todos {
+        (todo.id, todo.version).hash(&mut hasher);
+    }
+    vec![
+        "<D:resourcetype><D:collection/><C:calendar/></D:resourcetype>".to_string(),
+        "<D:displayname>Todos</D:displayname>".to_string(),
+        format!(
+            "<D:current-user-principal><D:href>{}</D:href></D:current-user-principal>",
+            ROOT
+        ),
+        "<C:supported-calendar-component-set><C:comp name=\"VTODO\"/>\
+         </C:supported-calendar-component-set>"
+            .to_string(),
+        "<D:supported-report-set>\
+         <D:supported-report><D:report><C:calendar-query/></D:report></D:supported-report>\
+         <D:supported-report><D:report><C:calendar-multiget/></D:report></D:supported-report>\
+         </D:supported-report-set>"
+            .to_string(),
+        format!("<CS:getctag>{:x}</CS:getctag>", hasher.finish()),
+    ]
+}
+
+/// `getetag` and the other properties of a todo's resource, plus `calendar-data` when
+/// `with_data`.
+fn resource_props(todo: TodoDto, with_data: bool) -> Result<Vec<String>, UseCaseError> {
+    let mut props = vec![
+        format!("<D:getetag>{}</D:getetag>", xml::escape(&etag_value(&todo))),
+        "<D:getcontenttype>text/calendar; charset=utf-8; component=VTODO</D:getcontenttype>"
+            .to_string(),
+        "<D:resourcetype/>".to_string(),
+    ];
+    if with_data {
+        props.push(format!(
+            "<C:calendar-data>{}</C:calendar-data>",
+            xml::escape(&calendar(todo)?)
+        ));
+    }
+    Ok(props)
+}
+
+fn etag_value(todo: &TodoDto) -> String {
+    format!("\"{}\"", todo.version)
+}
+
+/// A todo as an iCalendar file of its own.
+fn calendar(todo: TodoDto) -> Result<String, UseCaseError> {
+    let todo = Todo::try_from(todo)?;
+    Ok(format!(
+        "{}{}{}",
+        icalendar::HEADER,
+        icalendar::encode_vtodo(&todo, Utc::now()),
+        icalendar::FOOTER
+    ))
+}
+
+/// Depth 0 describes the target alone and depth 1 its members too; `infinity` is served
+/// as 1, which already reaches every resource.
+async fn propfind<TU: TodoUseCase, CU: CalDavResourceUseCase>(
+    tu: &TU,
+    cu: &CU,
+    target: Target,
+    headers: &HeaderMap,
+) -> Result<Response, UseCaseError> {
+    let depth = headers
+        .get("depth")
+        .and_then(|depth| depth.to_str().ok())
+        .unwrap_or("infinity");
+    let members = depth.trim() != "0";
+    let mut responses = Vec::new();
+    match target {
+        Target::Root => {
+            responses.push(xml::response(ROOT, &root_props()));
+            if members {
+                let todos = tu.find_all().await?;
+                responses.push(xml::response(CALENDAR, &calendar_props(&todos)));
+            }
+        }
+        Target::Calendar => {
+            let todos = tu.find_all().await?;
+            responses.push(xml::response(CALENDAR, &calendar_props(&todos)));
+            if members {
+                let names = cu.find_names().await?;
+                for todo in todos {
+                    responses.push(xml::response(
+                        &todo_href(&names, todo.id),
+                        &resource_props(todo, false)?,
+                    ));
+                }
+            }
+        }
+        Target::Resource(name) => match find(tu, cu, &name).await? {
+            Some(todo) => {
+                responses.push(xml::response(&href(&name), &resource_props(todo, false)?))
+            }
+            None => return Ok(StatusCode::NOT_FOUND.into_response()),
+        },
+    }
+    Ok(multistatus(responses))
+}
+
+/// `calendar-query` answers every todo unless its filter only asks for other
+/// components; time ranges and property filters are not applied. `calendar-multiget`
+/// answers the resources it names.
+async fn report<TU: TodoUseCase, CU: CalDavResourceUseCase>(
+    tu: &TU,
+    cu: &CU,
+    body: &str,
+) -> Result<Response, UseCaseError> {
+    let document = match xml::Document::parse(body) {
+        Ok(document) => document,
+        Err(err) => return Ok((StatusCode::BAD_REQUEST, err.to_string()).into_response()),
+    };
+    let with_data = document.has_element("calendar-data");
+    let mut responses = Vec::new();
+    match document.root.as_deref() {
+        Some("calendar-query") => {
+            let filters = &document.comp_filters;
+            let wants_todos = filters
+                .iter()
+                .all(|name| name.eq_ignore_ascii_case("VCALENDAR"))
+                || filters
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case("VTODO"));
+            if wants_todos {
+                let names = cu.find_names().await?;
+                for todo in tu.find_all().await? {
+                    responses.push(xml::response(
+                        &todo_href(&names, todo.id),
+                        &resource_props(todo, with_data)?,
+                    ));
+                }
+            }
+        }
+        Some("calendar-multiget") => {
+            for href in &document.hrefs {
+                let todo = match Target::parse(href) {
+                    Some(Target::Resource(name)) => find(tu, cu, &name).await?,
+                    _ => None,
+                };
+                match todo {
+                    Some(todo) => {
+                        responses.push(xml::response(href, &resource_props(todo, with_data)?))
+                    }
+                    None => responses.push(xml::not_found(href)),
+                }
+            }
+        }
+        _ => {
+            return Ok((
+                StatusCode::FORBIDDEN,
+                [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+                 <D:error xmlns:D=\"DAV:\"><D:supported-report/></D:error>",
+            )
+                .into_response())
+        }
+    }
+    Ok(multistatus(responses))
+}
+
+async fn find<TU: TodoUseCase, CU: CalDavResourceUseCase>(
+    tu: &TU,
+    cu: &CU,
+    name: &str,
+) -> Result<Option<TodoDto>, UseCaseError> {
+    match cu.find_todo_id(name.to_string()).await? {
+        Some(id) => tu.find_by_id(id).await,
+        None => Ok(None),
+    }
+}
+
+async fn get<TU: TodoUseCase, CU: CalDavResourceUseCase>(
+    tu: &TU,
+    cu: &CU,
+    name: &str,
+) -> Result<Response, UseCaseError> {
+    let todo = match find(tu, cu, name).await? {
+        Some(todo) => todo,
+        None => return Ok(StatusCode::NOT_FOUND.into_response()),
+    };
+    let headers = etag(todo.version);
+    Ok((
+        StatusCode::OK,
+        headers,
+        [(header::CONTENT_TYPE, TodoFormat::ICalendar.content_type())],
+        calendar(todo)?,
+    )
+        .into_response())
+}
+
+/// Creates or replaces a todo from a calendar holding one VTODO. `If-Match` must name
+/// the current version of an existing todo and `If-None-Match: *` forbids replacing one.
+/// A completed VTODO completes its todo; a completed todo cannot be reopened, so it stays
+/// completed whatever the VTODO says. A new resource may take any `.ics` name but the
+/// default name of another todo, which is forbidden. The todo, its name and its completion
+/// are saved in one transaction, so a name taken by a concurrent PUT fails with 412.
+async fn put<TU: TodoUseCase, CU: CalDavResourceUseCase>(
+    tu: &TU,
+    cu: &CU,
+    ctx: RequestContext,
+    headers: &HeaderMap,
+    name: &str,
+    body: &str,
+) -> Result<Response, UseCaseError> {
+    let mut vtodos = icalendar::decode_vtodos(body);
+    let todo = match (vtodos.pop(), vtodos.is_empty()) {
+        (Some((_, Ok(todo))), true) => todo,
+        (Some((line, Err(message))), true) => {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                format!("line {}: {}", line, message),
+            )
+                .into_response())
+        }
+        _ => {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                "the calendar must hold exactly one VTODO",
+            )
+                .into_response())
+        }
+    };
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| value.as_bytes() == b"*");
+    let expected_version = match expected_version(headers) {
+        Ok(expected_version) => expected_version,
+        Err(_) => return Ok(StatusCode::BAD_REQUEST.into_response()),
+    };
+    let completed = todo.is_completed();
+    let dto = TodoDto::from(todo);
+
+    let (status, saved) = match find(tu, cu, name).await? {
+        Some(_) if if_none_match => return Ok(StatusCode::PRECONDITION_FAILED.into_response()),
+        Some(existing) => {
+            let todo_data = TodoDto {
+                id: existing.id,
+                version: existing.version,
+                ..dto
+            };
+            let replaced = cu
+                .replace_todo(ctx, todo_data.into(), expected_version, completed)
+                .await?;
+            (StatusCode::NO_CONTENT, replaced)
+        }
+        None if headers.contains_key(header::IF_MATCH) => {
+            return Ok(StatusCode::PRECONDITION_FAILED.into_response())
+        }
+        None => {
+            if cu.validate_name(name).is_err() {
+                return Ok(StatusCode::FORBIDDEN.into_response());
+            }
+            let todo_data = CreateTodoDto {
+                title: dto.title.unwrap_or_default(),
+                due_at: dto.due_at,
+                priority: dto.priority,
+                recurrence: dto.recurrence,
+            };
+            let created = cu
+                .create_todo(ctx, name.to_string(), todo_data, completed)
+                .await?;
+            (StatusCode::CREATED, created)
+        }
+    };
+
+    let mut headers = etag(saved.version);
+    if status == StatusCode::CREATED {
+        if let Ok(location) = HeaderValue::from_str(&href(name)) {
+            headers.insert(header::LOCATION, location);
+        }
+    }
+    Ok((status, headers).into_response())
+}
+
+async fn delete<TU: TodoUseCase, CU: CalDavResourceUseCase>(
+    tu: &TU,
+    cu: &CU,
+    ctx: RequestContext,
+    headers: &HeaderMap,
+    name: &str,
+) -> Result<Response, UseCaseError> {
+    let todo = match find(tu, cu, name).await? {
+        Some(todo) => todo,
+        None => return Ok(StatusCode::NOT_FOUND.into_response()),
+    };
+    let expected_version = match expected_version(headers) {
+        Ok(expected_version) => expected_version,
+        Err(_) => return Ok(StatusCode::BAD_REQUEST.into_response()),
+    };
+    tu.delete(ctx, todo.id, expected_version).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTodoUseCase;
+    use axum::{async_trait, http::HeaderName};
+    use std::sync::{Arc, Mutex};
+    use use_case::dto::todo::UpdateTodoDto;
+
+    /// Keeps the names clients gave todos in memory and changes the todos through `tu`.
+    #[derive(Clone)]
+    struct MockCalDavResourceUseCase {
+        tu: MockTodoUseCase,
+        names: Arc<Mutex<HashMap<i64, String>>>,
+    }
+
+    #[async_trait]
+    impl CalDavResourceUseCase for MockCalDavResourceUseCase {
+        async fn find_todo_id(&self, name: String) -> Result<Option<i64>, UseCaseError> {
+            let names = self.names.lock().unwrap();
+            match names.iter().find(|(_, saved)| **saved == name) {
+                Some((todo_id, _)) => Ok(Some(*todo_id)),
+                None => Ok(CalDavResource::default_name_id(&name)),
+            }
+        }
+
+        async fn find_names(&self) -> Result<HashMap<i64, String>, UseCaseError> {
+            Ok(self.names.lock().unwrap().clone())
+        }
+
+        fn validate_name(&self, name: &str) -> Result<(), UseCaseError> {
+            Ok(CalDavResource::validate_name(name)?)
+        }
+
+        async fn create_todo(
+            &self,
+            ctx: RequestContext,
+            name: String,
+            todo_data: CreateTodoDto,
+            completed: bool,
+        ) -> Result<TodoDto, UseCaseError> {
+            let todo = self.tu.create(ctx.clone(), todo_data, None).await?;
+            self.names.lock().unwrap().insert(todo.id, name);
+            if completed {
+                return Ok(self.tu.complete(ctx, todo.id).await?.todo);
+            }
+            Ok(todo)
+        }
+
+        async fn replace_todo(
+            &self,
+            ctx: RequestContext,
+            todo_data: UpdateTodoDto,
+            expected_version: Option<i64>,
+            completed: bool,
+        ) -> Result<TodoDto, UseCaseError> {
+            let todo = self
+                .tu
+                .update(ctx.clone(), todo_data, expected_version)
+                .await?;
+            if completed && todo.completed_at.is_none() {
+                return Ok(self.tu.complete(ctx, todo.id).await?.todo);
+            }
+            Ok(todo)
+        }
+    }
+
+    const VTODO: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTODO\r\n\
+                         UID:0f8fad5b@example.com\r\nSUMMARY:Water plants\r\n\
+                         END:VTODO\r\nEND:VCALENDAR\r\n";
+
+    struct Server {
+        tu: MockTodoUseCase,
+        cu: MockCalDavResourceUseCase,
+    }
+
+    impl Server {
+        fn new(titles: &[&str]) -> Self {
+            let tu = MockTodoUseCase::with_titles(titles);
+            let cu = MockCalDavResourceUseCase {
+                tu: tu.clone(),
+                names: Arc::default(),
+            };
+            Self { tu, cu }
+        }
+
+        async fn send(
+            &self,
+            method: &str,
+            path: &str,
+            headers: &[(&'static str, &str)],
+            body: &str,
+        ) -> (StatusCode, HeaderMap, String) {
+            let mut header_map = HeaderMap::new();
+            for (name, value) in headers {
+                header_map.insert(
+                    HeaderName::from_static(name),
+                    HeaderValue::from_str(value).unwrap(),
+                );
+            }
+            let response = caldav(
+                Extension(self.tu.clone()),
+                Extension(self.cu.clone()),
+                Method::from_bytes(method.as_bytes()).unwrap(),
+                path.parse().unwrap(),
+                header_map,
+                body.to_string(),
+            )
+            .await;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (status, headers, String::from_utf8(body.to_vec()).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_propfind_depth() {
+        let server = Server::new(&["Buy milk", "Call Mom"]);
+
+        let (status, _, body) = server
+            .send("PROPFIND", "/caldav/todos/", &[("depth", "0")], "")
+            .await;
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert!(body.contains("<D:href>/caldav/todos/</D:href>"));
+        assert!(body.contains("<C:calendar/>"));
+        assert!(!body.contains(".ics"));
+
+        let (status, _, body) = server
+            .send("PROPFIND", "/caldav/todos/", &[("depth", "1")], "")
+            .await;
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        for href in ["/caldav/todos/1.ics", "/caldav/todos/2.ics"] {
+            assert!(
+                body.contains(&format!("<D:href>{}</D:href>", href)),
+                "{}",
+                body
+            );
+        }
+        assert!(body.contains("<D:getetag>&quot;1&quot;</D:getetag>"));
+
+        let (status, _, body) = server
+            .send("PROPFIND", "/caldav/", &[("depth", "0")], "")
+            .await;
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert!(body.contains("<C:calendar-home-set>"));
+        assert!(!body.contains("/caldav/todos/"));
+    }
+
+    #[tokio::test]
+    async fn test_calendar_multiget() {
+        let server = Server::new(&["Buy milk", "Call Mom"]);
+        let report = r#"<?xml version="1.0" encoding="utf-8"?>
+            <C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+              <D:prop><D:getetag/><C:calendar-data/></D:prop>
+              <D:href>/caldav/todos/2.ics</D:href>
+              <D:href>http://localhost:8080/caldav/todos/9.ics</D:href>
+            </C:calendar-multiget>"#;
+
+        let (status, _, body) = server.send("REPORT", "/caldav/todos/", &[], report).await;
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert!(body.contains(
+            "<D:response><D:href>/caldav/todos/2.ics</D:href><D:propstat><D:prop>\
+             <D:getetag>&quot;1&quot;</D:getetag>"
+        ));
+        assert!(body.contains("SUMMARY:Call Mom"));
+        assert!(!body.contains("SUMMARY:Buy milk"));
+        assert!(body.contains(
+            "<D:response><D:href>http://localhost:8080/caldav/todos/9.ics</D:href>\
+             <D:status>HTTP/1.1 404 Not Found</D:status>"
+        ));
+
+        let (status, _, _) = server
+            .send("REPORT", "/caldav/todos/", &[], "<C:calendar-multiget>")
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_put_with_if_none_match_keeps_the_client_name() {
+        let server = Server::new(&["Buy milk"]);
+        let path = "/caldav/todos/0f8fad5b.ics";
+
+        let (status, headers, _) = server
+            .send("PUT", path, &[("if-none-match", "*")], VTODO)
+            .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(headers[header::LOCATION], path);
+        assert_eq!(headers[header::ETAG], "\"1\"");
+
+        let (status, _, body) = server.send("GET", path, &[], "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("SUMMARY:Water plants"));
+        let (_, _, body) = server
+            .send("PROPFIND", "/caldav/todos/", &[("depth", "1")], "")
+            .await;
+        assert!(body.contains(&format!("<D:href>{}</D:href>", path)));
+        assert!(!body.contains("/caldav/todos/2.ics"));
+
+        let (status, _, _) = server
+            .send("PUT", path, &[("if-none-match", "*")], VTODO)
+            .await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        let (status, _, _) = server
+            .send(
+                "PUT",
+                "/caldav/todos/1.ics",
+                &[("if-none-match", "*")],
+                VTODO,
+            )
+            .await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+
+        // the default name of a todo that does not exist is not the client's to take
+        let (status, _, _) = server.send("PUT", "/caldav/todos/7.ics", &[], VTODO).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(server.tu.todos().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_put_with_if_match() {
+        let server = Server::new(&["Buy milk"]);
+
+        let (status, headers, _) = server
+            .send(
+                "PUT",
+                "/caldav/todos/1.ics",
+                &[("if-match", "\"1\"")],
+                VTODO,
+            )
+            .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(headers[header::ETAG], "\"2\"");
+        assert_eq!(server.tu.todos()[0].title.as_deref(), Some("Water plants"));
+
+        let (status, _, _) = server
+            .send(
+                "PUT",
+                "/caldav/todos/1.ics",
+                &[("if-match", "\"1\"")],
+                VTODO,
+            )
+            .await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        let (status, _, _) = server
+            .send(
+                "PUT",
+                "/caldav/todos/new.ics",
+                &[("if-match", "\"1\"")],
+                VTODO,
+            )
+            .await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        assert_eq!(server.tu.todos().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let server = Server::new(&["Buy milk", "Call Mom"]);
+
+        let (status, _, _) = server
+            .send(
+                "DELETE",
+                "/caldav/todos/1.ics",
+                &[("if-match", "\"5\"")],
+                "",
+            )
+            .await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        let (status, _, _) = server
+            .send(
+                "DELETE",
+                "/caldav/todos/1.ics",
+                &[("if-match", "\"1\"")],
+                "",
+            )
+            .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _, _) = server.send("GET", "/caldav/todos/1.ics", &[], "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _, _) = server.send("DELETE", "/caldav/todos/1.ics", &[], "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(server.tu.todos().len(), 1);
+    }
+}
@@ -0,0 +1,145 @@
+//! Just enough XML for WebDAV: request bodies are parsed for the few elements CalDAV
+//! clients send, by local name so any namespace prefix works, and responses are written
+//! as `207 Multi-Status` documents.
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+pub(crate) const MULTISTATUS_START: &str = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+     <D:multistatus xmlns:D=\"DAV:\" xmlns:C=\"urn:ietf:params:xml:ns:caldav\" \
+     xmlns:CS=\"http://calendarserver.org/ns/\">";
+pub(crate) const MULTISTATUS_END: &str = "</D:multistatus>";
+
+/// The parts of a request body the handler reads, found by local name so any namespace
+/// prefix works.
+#[derive(Debug, Default)]
+pub(crate) struct Document {
+    /// The local name of the document element, e.g. `calendar-multiget`.
+    pub root: Option<String>,
+    /// The local name of every element, in document order.
+    elements: Vec<String>,
+    /// The text of every `href` element.
+    pub hrefs: Vec<String>,
+    /// The `name` attribute of every `comp-filter` element, e.g. `VCALENDAR` and `VTODO`.
+    pub comp_filters: Vec<String>,
+}
+
+impl Document {
+    /// Reads `body`, or fails when it is not well-formed XML.
+    pub(crate) fn parse(body: &str) -> Result<Self, quick_xml::Error> {
+        let mut reader = Reader::from_str(body);
+        reader.trim_text(true);
+        let mut document = Document::default();
+        let mut in_href = false;
+        let mut depth = 0;
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => {
+                    in_href = document.open(&element)? == "href";
+                    depth += 1;
+                }
+                Event::Empty(element) => {
+                    document.open(&element)?;
+                    in_href = false;
+                }
+                Event::Text(text) if in_href => document.hrefs.push(text.unescape()?.into()),
+                Event::End(_) => {
+                    in_href = false;
+                    depth -= 1;
+                }
+                Event::Eof if depth > 0 => {
+                    return Err(quick_xml::Error::UnexpectedEof("an element".to_string()))
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(document)
+    }
+
+    pub(crate) fn has_element(&self, name: &str) -> bool {
+        self.elements.iter().any(|element| element == name)
+    }
+
+    /// Records `element` and returns its local name.
+    fn open(&mut self, element: &BytesStart) -> Result<String, quick_xml::Error> {
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+        if name == "comp-filter" {
+            for attribute in element.attributes() {
+                let attribute = attribute?;
+                if attribute.key.local_name().as_ref() == b"name" {
+                    self.comp_filters.push(attribute.unescape_value()?.into());
+                }
+            }
+        }
+        self.root.get_or_insert_with(|| name.clone());
+        self.elements.push(name.clone());
+        Ok(name)
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    quick_xml::escape::escape(text).into_owned()
+}
+
+/// A `response` of a multistatus with `props`, already-serialized property elements.
+pub(crate) fn response(href: &str, props: &[String]) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>{}</D:prop>\
+         <D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        escape(href),
+        props.concat()
+    )
+}
+
+/// A `response` of a multistatus for a resource that does not exist.
+pub(crate) fn not_found(href: &str) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href><D:status>HTTP/1.1 404 Not Found</D:status>\
+         </D:response>",
+        escape(href)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reads_elements_by_local_name() {
+        let document = Document::parse(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+              <D:prop><D:getetag/><C:calendar-data/></D:prop>
+              <D:href>/caldav/todos/1.ics</D:href>
+              <href xmlns="DAV:">/caldav/todos/a&amp;b.ics</href>
+            </C:calendar-multiget>"#,
+        )
+        .unwrap();
+        assert_eq!(document.root.as_deref(), Some("calendar-multiget"));
+        assert!(document.has_element("calendar-data"));
+        assert!(!document.has_element("calendar-query"));
+        assert_eq!(
+            document.hrefs,
+            vec!["/caldav/todos/1.ics", "/caldav/todos/a&b.ics"]
+        );
+    }
+
+    #[test]
+    fn test_parse_reads_attributes_holding_angle_brackets() {
+        let document = Document::parse(
+            r#"<C:calendar-query xmlns:C="urn:ietf:params:xml:ns:caldav" note="a > b">
+              <C:filter><C:comp-filter name="VCALENDAR"><C:comp-filter test="x>y" name="VTODO"/>
+              </C:comp-filter></C:filter>
+            </C:calendar-query>"#,
+        )
+        .unwrap();
+        assert_eq!(document.root.as_deref(), Some("calendar-query"));
+        assert_eq!(document.comp_filters, vec!["VCALENDAR", "VTODO"]);
+
+        assert!(Document::parse("<D:propfind><D:prop></D:propfind>").is_err());
+        assert!(Document::parse("<D:propfind><D:prop/>").is_err());
+    }
+}
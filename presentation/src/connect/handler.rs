@@ -0,0 +1,590 @@
+// tonic::Status is large by design and every RPC helper returns it.
+#![allow(clippy::result_large_err)]
+
+use std::{convert::Infallible, future::Future, sync::OnceLock, time::Duration};
+
+use axum::{
+    body::{Bytes, StreamBody},
+    extract::Path,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use futures_util::{stream, Stream, StreamExt};
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor};
+use serde_json::{json, Value};
+use tonic::{metadata::MetadataMap, Code, Extensions, Status};
+use use_case::traits::todo::TodoUseCase;
+
+use crate::grpc::{
+    proto_impl::{
+        todo::{self, todo_service_server::TodoService},
+        TodoServiceImpl,
+    },
+    transcoding::http_status,
+};
+
+const SERVICE: &str = "todo.TodoService";
+const PROTOCOL_VERSION_HEADER: &str = "connect-protocol-version";
+const TIMEOUT_HEADER: &str = "connect-timeout-ms";
+const STREAMING_ENCODING_HEADER: &str = "connect-content-encoding";
+
+/// The flags of a streaming envelope.
+const COMPRESSED: u8 = 0b01;
+const END_STREAM: u8 = 0b10;
+
+#[derive(Debug, Clone, Copy)]
+enum Codec {
+    Json,
+    Proto,
+}
+
+impl Codec {
+    /// The codec of a unary or, with `streaming`, a streaming request of `content_type`.
+    fn parse(content_type: &str, streaming: bool) -> Option<Self> {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        let prefix = if streaming {
+            "application/connect+"
+        } else {
+            "application/"
+        };
+        match essence.to_ascii_lowercase().strip_prefix(prefix) {
+            Some("json") => Some(Self::Json),
+            Some("proto") => Some(Self::Proto),
+            _ => None,
+        }
+    }
+
+    fn content_type(self, streaming: bool) -> &'static str {
+        match (self, streaming) {
+            (Self::Json, false) => "application/json",
+            (Self::Proto, false) => "application/proto",
+            (Self::Json, true) => "application/connect+json",
+            (Self::Proto, true) => "application/connect+proto",
+        }
+    }
+
+    /// `bytes` as a `T`, which `descriptor` describes. JSON follows the canonical JSON
+    /// mapping of protobuf.
+    fn decode<T: Message + Default>(
+        self,
+        descriptor: MessageDescriptor,
+        bytes: &[u8],
+    ) -> Result<T, Status> {
+        match self {
+            Self::Proto => T::decode(bytes).map_err(|e| Status::invalid_argument(e.to_string())),
+            Self::Json => {
+                let bytes = if bytes.is_empty() { b"{}" } else { bytes };
+                let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+                let message = DynamicMessage::deserialize(descriptor, &mut deserializer)
+                    .and_then(|message| deserializer.end().map(|()| message))
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?;
+                message
+                    .transcode_to()
+                    .map_err(|e| Status::invalid_argument(e.to_string()))
+            }
+        }
+    }
+
+    fn encode<T: Message>(
+        self,
+        descriptor: MessageDescriptor,
+        message: &T,
+    ) -> Result<Vec<u8>, Status> {
+        let bytes = message.encode_to_vec();
+        match self {
+            Self::Proto => Ok(bytes),
+            Self::Json => DynamicMessage::decode(descriptor, bytes.as_slice())
+                .map_err(|e| e.to_string())
+                .and_then(|message| serde_json::to_vec(&message).map_err(|e| e.to_string()))
+                .map_err(Status::internal),
+        }
+    }
+}
+
+/// A call of one of the methods of [`SERVICE`].
+struct Call {
+    descriptor: MethodDescriptor,
+    codec: Codec,
+    metadata: MetadataMap,
+    body: Bytes,
+    timeout: Option<Duration>,
+}
+
+fn find_method(name: &str) -> Option<MethodDescriptor> {
+    static POOL: OnceLock<Option<DescriptorPool>> = OnceLock::new();
+    let pool = POOL
+        .get_or_init(|| DescriptorPool::decode(todo::FILE_DESCRIPTOR_SET).ok())
+        .as_ref()?;
+    let service = pool.get_service_by_name(SERVICE)?;
+    let method = service.methods().find(|method| method.name() == name);
+    method
+}
+
+/// Serves `POST /todo.TodoService/:method`. Unary calls send and answer a bare JSON or
+/// protobuf message; server-streaming calls send one enveloped message and answer a stream
+/// of them, ended by an end-of-stream message that carries the error, if any. Client
+/// streaming is left to gRPC.
+pub async fn connect<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Path(method): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(descriptor) = find_method(&method) else {
+        // Connect answers a procedure it does not know with 404 rather than 501
+        let status = Status::unimplemented(format!("{} has no method {}", SERVICE, method));
+        let mut response = reject(Codec::Json, false, status);
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        return response;
+    };
+    let streaming = descriptor.is_client_streaming() || descriptor.is_server_streaming();
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .unwrap_or_default();
+    let Some(codec) = Codec::parse(content_type, streaming) else {
+        let accepted = [Codec::Json, Codec::Proto].map(|codec| codec.content_type(streaming));
+        let accept_post = HeaderValue::from_str(&accepted.join(", ")).ok();
+        let mut response = StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+        if let Some(accept_post) = accept_post {
+            response.headers_mut().insert("accept-post", accept_post);
+        }
+        return response;
+    };
+    if headers
+        .get(PROTOCOL_VERSION_HEADER)
+        .is_some_and(|version| version != "1")
+    {
+        let status = Status::invalid_argument("connect-protocol-version must be 1");
+        return reject(codec, streaming, status);
+    }
+    let encoding = if streaming {
+        STREAMING_ENCODING_HEADER
+    } else {
+        header::CONTENT_ENCODING.as_str()
+    };
+    if headers
+        .get(encoding)
+        .is_some_and(|encoding| encoding != "identity")
+    {
+        let status = Status::unimplemented("compressed messages are not supported");
+        return reject(codec, streaming, status);
+    }
+    let timeout = headers
+        .get(TIMEOUT_HEADER)
+        .and_then(|timeout| timeout.to_str().ok())
+        .and_then(|timeout| timeout.parse::<u64>().ok())
+        .map(Duration::from_millis);
+    let call = Call {
+        descriptor,
+        codec,
+        metadata: MetadataMap::from_headers(headers),
+        body,
+        timeout,
+    };
+
+    let service = TodoServiceImpl { tu };
+    match method.as_str() {
+        "CreateTodo" => unary(call, |request| service.create_todo(request)).await,
+        "GetTodos" => unary(call, |request| service.get_todos(request)).await,
+        "StreamTodos" => server_streaming(call, |request| service.stream_todos(request)).await,
+        "FindTodoById" => unary(call, |request| service.find_todo_by_id(request)).await,
+        "UpdateTodo" => unary(call, |request| service.update_todo(request)).await,
+        "DeleteTodo" => unary(call, |request| service.delete_todo(request)).await,
+        "GetOverdueTodos" => unary(call, |request| service.get_overdue_todos(request)).await,
+        "GetTodosDueToday" => unary(call, |request| service.get_todos_due_today(request)).await,
+        "GetTodosDueThisWeek" => {
+            unary(call, |request| service.get_todos_due_this_week(request)).await
+        }
+        "GetHighPriorityTodos" => {
+            unary(call, |request| service.get_high_priority_todos(request)).await
+        }
+        "CompleteTodo" => unary(call, |request| service.complete_todo(request)).await,
+        _ => {
+            let status = Status::unimplemented(format!("{} is only served over gRPC", method));
+            reject(codec, streaming, status)
+        }
+    }
+}
+
+async fn unary<Req, Res, F, Fut>(call: Call, rpc: F) -> Response
+where
+    Req: Message + Default,
+    Res: Message,
+    F: FnOnce(tonic::Request<Req>) -> Fut,
+    Fut: Future<Output = Result<tonic::Response<Res>, Status>>,
+{
+    let codec = call.codec;
+    let result = async {
+        let message = codec.decode(call.descriptor.input(), &call.body)?;
+        let request = tonic::Request::from_parts(call.metadata, Extensions::default(), message);
+        let response = match call.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, rpc(request))
+                .await
+                .unwrap_or_else(|_| Err(Status::deadline_exceeded("the call timed out"))),
+            None => rpc(request).await,
+        };
+        let (metadata, message, _) = response?.into_parts();
+        Ok((metadata, codec.encode(call.descriptor.output(), &message)?))
+    }
+    .await;
+    match result {
+        Ok((metadata, body)) => {
+            let mut headers = metadata.into_headers();
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(codec.content_type(false)),
+            );
+            (StatusCode::OK, headers, body).into_response()
+        }
+        Err(status) => reject(codec, false, status),
+    }
+}
+
+async fn server_streaming<Req, Res, S, F, Fut>(call: Call, rpc: F) -> Response
+where
+    Req: Message + Default,
+    Res: Message + 'static,
+    S: Stream<Item = Result<Res, Status>> + Unpin + Send + 'static,
+    F: FnOnce(tonic::Request<Req>) -> Fut,
+    Fut: Future<Output = Result<tonic::Response<S>, Status>>,
+{
+    let codec = call.codec;
+    let output = call.descriptor.output();
+    let started = async {
+        let message = unenvelope(&call.body)?;
+        let message = codec.decode(call.descriptor.input(), message)?;
+        rpc(tonic::Request::from_parts(
+            call.metadata,
+            Extensions::default(),
+            message,
+        ))
+        .await
+    }
+    .await;
+    let (metadata, messages, _) = match started {
+        Ok(response) => response.into_parts(),
+        Err(status) => return reject(codec, true, status),
+    };
+    let mut headers = metadata.into_headers();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(codec.content_type(true)),
+    );
+    let frames = stream::unfold(Some(messages), move |messages| {
+        let output = output.clone();
+        async move {
+            let mut messages = messages?;
+            let status = match messages.next().await {
+                Some(Ok(message)) => match codec.encode(output, &message) {
+                    Ok(message) => {
+                        return Some((Ok::<_, Infallible>(envelope(0, &message)), Some(messages)))
+                    }
+                    Err(status) => Some(status),
+                },
+                Some(Err(status)) => Some(status),
+                None => None,
+            };
+            Some((Ok(end_stream(status.as_ref())), None))
+        }
+    });
+    (StatusCode::OK, headers, StreamBody::new(frames)).into_response()
+}
+
+/// The single message of a streaming request body.
+fn unenvelope(body: &[u8]) -> Result<&[u8], Status> {
+    match body {
+        [flags, ..] if flags & COMPRESSED != 0 => Err(Status::unimplemented(
+            "compressed messages are not supported",
+        )),
+        [_, a, b, c, d, message @ ..]
+            if message.len() == u32::from_be_bytes([*a, *b, *c, *d]) as usize =>
+        {
+            Ok(message)
+        }
+        _ => Err(Status::invalid_argument(
+            "the body must be exactly one enveloped message",
+        )),
+    }
+}
+
+fn envelope(flags: u8, message: &[u8]) -> Vec<u8> {
+    let mut envelope = Vec::with_capacity(message.len() + 5);
+    envelope.push(flags);
+    envelope.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    envelope.extend_from_slice(message);
+    envelope
+}
+
+/// The end-of-stream message, always JSON, of a stream that ended with `status`, if it
+/// failed.
+fn end_stream(status: Option<&Status>) -> Vec<u8> {
+    let end = match status {
+        Some(status) => json!({ "error": error(status) }),
+        None => json!({}),
+    };
+    envelope(END_STREAM, end.to_string().as_bytes())
+}
+
+/// A failed call: a JSON error with the HTTP status of its code when unary, an
+/// end-of-stream message after no others when streaming.
+fn reject(codec: Codec, streaming: bool, status: Status) -> Response {
+    let mut headers = status.metadata().clone().into_headers();
+    if streaming {
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(codec.content_type(true)),
+        );
+        return (StatusCode::OK, headers, end_stream(Some(&status))).into_response();
+    }
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    (
+        http_status(status.code()),
+        headers,
+        error(&status).to_string(),
+    )
+        .into_response()
+}
+
+fn error(status: &Status) -> Value {
+    let mut error = json!({ "code": code_name(status.code()) });
+    if !status.message().is_empty() {
+        error["message"] = json!(status.message());
+    }
+    error
+}
+
+fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Cancelled => "canceled",
+        Code::Ok | Code::Unknown => "unknown",
+        Code::InvalidArgument => "invalid_argument",
+        Code::DeadlineExceeded => "deadline_exceeded",
+        Code::NotFound => "not_found",
+        Code::AlreadyExists => "already_exists",
+        Code::PermissionDenied => "permission_denied",
+        Code::ResourceExhausted => "resource_exhausted",
+        Code::FailedPrecondition => "failed_precondition",
+        Code::Aborted => "aborted",
+        Code::OutOfRange => "out_of_range",
+        Code::Unimplemented => "unimplemented",
+        Code::Internal => "internal",
+        Code::Unavailable => "unavailable",
+        Code::DataLoss => "data_loss",
+        Code::Unauthenticated => "unauthenticated",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTodoUseCase;
+
+    async fn call(
+        tu: MockTodoUseCase,
+        method: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> (StatusCode, HeaderMap, Bytes) {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(content_type).unwrap(),
+        );
+        headers.insert(PROTOCOL_VERSION_HEADER, HeaderValue::from_static("1"));
+        let response = connect(
+            Extension(tu),
+            Path(method.to_string()),
+            headers,
+            Bytes::from(body),
+        )
+        .await;
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body).await.unwrap();
+        (parts.status, parts.headers, body)
+    }
+
+    /// The messages of a streaming response body, the end-of-stream message last.
+    fn frames(mut body: &[u8]) -> Vec<(u8, Value)> {
+        let mut frames = Vec::new();
+        while !body.is_empty() {
+            let length = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+            let message = serde_json::from_slice(&body[5..5 + length]).unwrap();
+            frames.push((body[0], message));
+            body = &body[5 + length..];
+        }
+        frames
+    }
+
+    #[test]
+    fn test_content_types_pick_the_codec_of_the_call() {
+        assert!(matches!(
+            Codec::parse("application/json; charset=utf-8", false),
+            Some(Codec::Json)
+        ));
+        assert!(matches!(
+            Codec::parse("application/connect+proto", true),
+            Some(Codec::Proto)
+        ));
+        assert!(Codec::parse("application/connect+json", false).is_none());
+        assert!(Codec::parse("application/json", true).is_none());
+    }
+
+    #[test]
+    fn test_streaming_requests_carry_exactly_one_uncompressed_message() {
+        let message = envelope(0, b"{}");
+        assert_eq!(unenvelope(&message).unwrap(), b"{}");
+        assert_eq!(
+            unenvelope(&message[..6]).unwrap_err().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            unenvelope(&envelope(COMPRESSED, b"{}")).unwrap_err().code(),
+            Code::Unimplemented
+        );
+    }
+
+    #[test]
+    fn test_end_stream_carries_the_error_in_connect_form() {
+        let end = end_stream(Some(&Status::cancelled("gone")));
+        assert_eq!(end[0], END_STREAM);
+        let error: Value = serde_json::from_slice(unenvelope(&end[..]).unwrap()).unwrap();
+        assert_eq!(
+            error,
+            json!({ "error": { "code": "canceled", "message": "gone" } })
+        );
+        assert_eq!(&end_stream(None)[5..], b"{}");
+    }
+
+    #[tokio::test]
+    async fn test_unary_json() {
+        let tu = MockTodoUseCase::with_titles(&["Buy milk"]);
+        let body = json!({ "title": "Water plants", "priority": "PRIORITY_HIGH" });
+        let (status, headers, body) = call(
+            tu.clone(),
+            "CreateTodo",
+            "application/json",
+            body.to_string().into_bytes(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        let response: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["todo"]["id"], "2");
+        assert_eq!(response["todo"]["title"], "Water plants");
+        assert_eq!(response["todo"]["priority"], "PRIORITY_HIGH");
+        assert_eq!(tu.todos().len(), 2);
+
+        let (status, _, body) = call(
+            tu,
+            "FindTodoById",
+            "application/json",
+            br#"{ "id": "9", "unknown": true }"#.to_vec(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], "invalid_argument");
+    }
+
+    #[tokio::test]
+    async fn test_unary_proto() {
+        let tu = MockTodoUseCase::with_titles(&["Buy milk", "Water plants"]);
+        let request = todo::FindTodoByIdRequest { id: 2 };
+        let (status, headers, body) = call(
+            tu,
+            "FindTodoById",
+            "application/proto",
+            request.encode_to_vec(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/proto");
+        let response = todo::FindTodoByIdResponse::decode(body).unwrap();
+        let todo = response.todo.unwrap();
+        assert_eq!((todo.id, todo.title.as_str()), (2, "Water plants"));
+    }
+
+    #[tokio::test]
+    async fn test_server_streaming() {
+        let tu = MockTodoUseCase::with_titles(&["Buy milk", "Water plants"]);
+        let (status, headers, body) = call(
+            tu,
+            "StreamTodos",
+            "application/connect+json",
+            envelope(0, b"{}"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/connect+json");
+        let messages = frames(&body);
+        let titles = messages[..2]
+            .iter()
+            .map(|(flags, todo)| (*flags, todo["title"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            vec![(0, json!("Buy milk")), (0, json!("Water plants"))]
+        );
+        assert_eq!(messages[2..], [(END_STREAM, json!({}))]);
+
+        let (status, _, body) = call(
+            MockTodoUseCase::default(),
+            "StreamTodos",
+            "application/connect+json",
+            envelope(0, b"{}")[..6].to_vec(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let end = frames(&body);
+        assert_eq!(end.len(), 1);
+        assert_eq!(end[0].0, END_STREAM);
+        assert_eq!(end[0].1["error"]["code"], "invalid_argument");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method() {
+        let (status, headers, body) = call(
+            MockTodoUseCase::default(),
+            "RemoveTodo",
+            "application/json",
+            b"{}".to_vec(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        let error: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], "unimplemented");
+    }
+
+    /// Every method of the service answers something other than the error of a method
+    /// left to gRPC, except the client-streaming ones.
+    #[tokio::test]
+    async fn test_every_method_is_routed() {
+        let service = find_method("CreateTodo").unwrap().parent_service().clone();
+        for descriptor in service.methods() {
+            let streaming = descriptor.is_client_streaming() || descriptor.is_server_streaming();
+            let (content_type, body) = if streaming {
+                ("application/connect+json", envelope(0, b"{}"))
+            } else {
+                ("application/json", b"{}".to_vec())
+            };
+            let tu = MockTodoUseCase::with_titles(&["Buy milk"]);
+            let (_, _, body) = call(tu, descriptor.name(), content_type, body).await;
+            let left_to_grpc = body
+                .windows(b"only served over gRPC".len())
+                .any(|window| window == b"only served over gRPC");
+            assert_eq!(
+                left_to_grpc,
+                descriptor.is_client_streaming(),
+                "{}",
+                descriptor.name()
+            );
+        }
+    }
+}
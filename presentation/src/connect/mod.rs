@@ -0,0 +1,3 @@
+//! `todo.TodoService` over the Connect protocol (https://connectrpc.com/docs/protocol) on
+//! the HTTP port, answered by the same `TodoServiceImpl` as the gRPC server.
+pub mod handler;
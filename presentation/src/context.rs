@@ -0,0 +1,68 @@
+use axum::http::HeaderMap;
+use domain::entity::audit::Protocol;
+use use_case::dto::audit::RequestContext;
+
+/// Header (or gRPC metadata key) naming the caller recorded in the audit log.
+pub const ACTOR_HEADER: &str = "x-actor";
+/// Header (or gRPC metadata key) correlating a change with the request that caused it.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// Header (or gRPC metadata key) making a create safe to retry.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const ANONYMOUS: &str = "anonymous";
+
+/// The context of a request from its actor and request id, defaulting to an anonymous
+/// actor and a freshly generated request id.
+pub fn request_context(
+    protocol: Protocol,
+    actor: Option<&str>,
+    request_id: Option<&str>,
+) -> RequestContext {
+    let actor = match actor.map(str::trim) {
+        Some(actor) if !actor.is_empty() => actor,
+        _ => ANONYMOUS,
+    };
+    let request_id = match request_id.map(str::trim) {
+        Some(request_id) if !request_id.is_empty() => request_id.to_string(),
+        _ => uuid::Uuid::new_v4().to_string(),
+    };
+    RequestContext::new(actor, protocol, request_id)
+}
+
+pub fn from_headers(headers: &HeaderMap, protocol: Protocol) -> RequestContext {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    request_context(protocol, header(ACTOR_HEADER), header(REQUEST_ID_HEADER))
+}
+
+pub fn from_metadata(metadata: &tonic::metadata::MetadataMap) -> RequestContext {
+    let entry = |name: &str| metadata.get(name).and_then(|value| value.to_str().ok());
+    request_context(
+        Protocol::Grpc,
+        entry(ACTOR_HEADER),
+        entry(REQUEST_ID_HEADER),
+    )
+}
+
+/// The idempotency key of a create request; blank keys count as absent.
+pub fn normalize_idempotency_key(key: Option<&str>) -> Option<String> {
+    match key.map(str::trim) {
+        Some(key) if !key.is_empty() => Some(key.to_string()),
+        _ => None,
+    }
+}
+
+pub fn idempotency_key_from_headers(headers: &HeaderMap) -> Option<String> {
+    normalize_idempotency_key(
+        headers
+            .get(IDEMPOTENCY_KEY_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    )
+}
+
+pub fn idempotency_key_from_metadata(metadata: &tonic::metadata::MetadataMap) -> Option<String> {
+    normalize_idempotency_key(
+        metadata
+            .get(IDEMPOTENCY_KEY_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    )
+}
@@ -0,0 +1,115 @@
+//! Marks the responses of deprecated endpoints, REST routes and gRPC services alike, with
+//! a `Deprecation` header (RFC 9745) and, where the version that replaces them has a URL,
+//! a `Link` to it.
+
+use std::task::{Context, Poll};
+
+use axum::http::{header, HeaderName, HeaderValue, Request, Response};
+use futures_util::future::BoxFuture;
+use tonic::server::NamedService;
+use tower::{Layer, Service};
+
+/// When the v1 todo endpoints were deprecated, 2026-10-19T00:00:00Z, as a structured
+/// field date.
+pub const V1_DEPRECATED_AT: &str = "@1792368000";
+
+#[derive(Debug, Clone)]
+pub struct DeprecationLayer {
+    deprecated_at: HeaderValue,
+    link: Option<HeaderValue>,
+}
+
+impl DeprecationLayer {
+    pub fn new(deprecated_at: &'static str) -> Self {
+        Self {
+            deprecated_at: HeaderValue::from_static(deprecated_at),
+            link: None,
+        }
+    }
+
+    /// Links the URL to use instead, such as `/v2/todos`. A gRPC service has no URL to
+    /// link: clients find its successor through reflection.
+    pub fn with_successor(mut self, successor: &str) -> Self {
+        let link = format!("<{}>; rel=\"successor-version\"", successor);
+        self.link = HeaderValue::from_str(&link).ok();
+        self
+    }
+}
+
+impl<S> Layer<S> for DeprecationLayer {
+    type Service = Deprecated<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Deprecated {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Deprecated<S> {
+    inner: S,
+    layer: DeprecationLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for Deprecated<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let layer = self.layer.clone();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let mut response = response.await?;
+            let headers = response.headers_mut();
+            headers.insert(HeaderName::from_static("deprecation"), layer.deprecated_at);
+            if let Some(link) = layer.link {
+                headers.insert(header::LINK, link);
+            }
+            Ok(response)
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for Deprecated<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use std::convert::Infallible;
+    use tower::{service_fn, ServiceExt};
+
+    async fn response_headers(layer: DeprecationLayer) -> axum::http::HeaderMap {
+        let service = layer.layer(service_fn(|_: Request<Body>| async {
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+        let response = service.oneshot(Request::new(Body::empty())).await.unwrap();
+        response.headers().clone()
+    }
+
+    #[tokio::test]
+    async fn test_links_only_a_successor_with_a_url() {
+        let headers = response_headers(DeprecationLayer::new(V1_DEPRECATED_AT)).await;
+        assert_eq!(headers["deprecation"], V1_DEPRECATED_AT);
+        assert!(!headers.contains_key(header::LINK));
+
+        let layer = DeprecationLayer::new(V1_DEPRECATED_AT).with_successor("/v2/todos");
+        assert_eq!(
+            response_headers(layer).await[header::LINK],
+            "</v2/todos>; rel=\"successor-version\""
+        );
+    }
+}
@@ -4,17 +4,26 @@ use use_case::error::UseCaseError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PresentationalError {
+    BadRequest,
     NotFound,
+    Conflict,
+    IdempotencyKeyReused,
     InternalServerError,
 }
 
 impl From<UseCaseError> for PresentationalError {
     fn from(error: UseCaseError) -> Self {
         match error {
+            UseCaseError::Validation(_) => Self::BadRequest,
             UseCaseError::NotFound {
                 entity_type: _,
                 entity_id: _,
             } => Self::NotFound,
+            UseCaseError::Conflict {
+                entity_type: _,
+                entity_id: _,
+            } => Self::Conflict,
+            UseCaseError::IdempotencyKeyReused(_) => Self::IdempotencyKeyReused,
             _ => Self::InternalServerError,
         }
     }
@@ -23,7 +32,10 @@ impl From<UseCaseError> for PresentationalError {
 impl Display for PresentationalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            PresentationalError::BadRequest => write!(f, "Bad Request"),
             PresentationalError::NotFound => write!(f, "Not Found"),
+            PresentationalError::Conflict => write!(f, "Conflict"),
+            PresentationalError::IdempotencyKeyReused => write!(f, "Idempotency Key Reused"),
             PresentationalError::InternalServerError => write!(f, "Internal Server Error"),
         }
     }
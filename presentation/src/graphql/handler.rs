@@ -1,25 +1,35 @@
-use async_graphql::{
-    http::{playground_source, GraphQLPlaygroundConfig},
-    EmptySubscription, Schema,
-};
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{
+    http::HeaderMap,
     response::{Html, IntoResponse},
     Extension,
 };
+use domain::entity::audit::Protocol;
 
-use super::schema::{Mutation, Query};
-use use_case::traits::todo::{MutationUseCase, QueryUseCase};
+use super::schema::TodoSchema;
+use crate::context::from_headers;
+use use_case::traits::{
+    audit::AuditUseCase,
+    revision::RevisionUseCase,
+    todo::{MutationUseCase, QueryUseCase},
+    trash::TrashUseCase,
+};
 
-pub async fn graphql_handler<QUC, MUC>(
-    schema: Extension<Schema<Query<QUC>, Mutation<MUC>, EmptySubscription>>,
+pub async fn graphql_handler<QUC, AUC, RUC, MUC, TUC>(
+    schema: Extension<TodoSchema<QUC, AUC, RUC, MUC, TUC>>,
+    headers: HeaderMap,
     req: GraphQLRequest,
 ) -> GraphQLResponse
 where
     QUC: QueryUseCase + Clone,
+    AUC: AuditUseCase,
+    RUC: RevisionUseCase,
     MUC: MutationUseCase,
+    TUC: TrashUseCase,
 {
-    schema.execute(req.into_inner()).await.into()
+    let ctx = from_headers(&headers, Protocol::GraphQL);
+    schema.execute(req.into_inner().data(ctx)).await.into()
 }
 
 pub async fn graphql_playground_handler() -> impl IntoResponse {
@@ -0,0 +1,172 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{
+    async_trait,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    Response, SchemaBuilder, ServerError,
+};
+
+/// Bounds on the operations the schema runs, and whether it answers introspection.
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaLimits {
+    max_depth: usize,
+    max_complexity: usize,
+    timeout: Duration,
+    introspection: bool,
+}
+
+impl SchemaLimits {
+    /// Deep enough for the introspection query of GraphiQL.
+    pub const DEFAULT_MAX_DEPTH: usize = 16;
+    pub const DEFAULT_MAX_COMPLEXITY: usize = 500;
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_complexity: Self::DEFAULT_MAX_COMPLEXITY,
+            timeout: Self::DEFAULT_TIMEOUT,
+            introspection: true,
+        }
+    }
+
+    /// Sets how deeply the selections of an operation may nest.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets how many fields an operation may select, counting each field under a list
+    /// field as many times as the list's complexity multiplier.
+    pub fn with_max_complexity(mut self, max_complexity: usize) -> Self {
+        self.max_complexity = max_complexity;
+        self
+    }
+
+    /// Sets how long an operation may run before it is answered with an error. The fields
+    /// of a mutation that completed before then stay committed.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets whether `__schema` and `__type` are answered; production may turn them off.
+    pub fn with_introspection(mut self, introspection: bool) -> Self {
+        self.introspection = introspection;
+        self
+    }
+
+    pub(crate) fn apply<Q, M, S>(self, builder: SchemaBuilder<Q, M, S>) -> SchemaBuilder<Q, M, S> {
+        let builder = builder
+            .limit_depth(self.max_depth)
+            .limit_complexity(self.max_complexity)
+            .extension(OperationTimeout(self.timeout));
+        match self.introspection {
+            true => builder,
+            false => builder.disable_introspection(),
+        }
+    }
+}
+
+impl Default for SchemaLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cuts an operation short after the given time.
+struct OperationTimeout(Duration);
+
+impl ExtensionFactory for OperationTimeout {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(OperationTimeout(self.0))
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for OperationTimeout {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        match tokio::time::timeout(self.0, next.run(ctx, operation_name)).await {
+            Ok(response) => response,
+            Err(_) => Response::from_errors(vec![ServerError::new(
+                format!("the operation did not finish within {:?}", self.0),
+                None,
+            )]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+
+    #[derive(SimpleObject)]
+    struct Node {
+        id: i64,
+        child: Option<Box<Node>>,
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn node(&self) -> Node {
+            Node {
+                id: 1,
+                child: Some(Box::new(Node { id: 2, child: None })),
+            }
+        }
+
+        async fn slow(&self) -> bool {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            true
+        }
+    }
+
+    fn schema(limits: SchemaLimits) -> Schema<Query, EmptyMutation, EmptySubscription> {
+        limits
+            .apply(Schema::build(Query, EmptyMutation, EmptySubscription))
+            .finish()
+    }
+
+    async fn errors(
+        schema: &Schema<Query, EmptyMutation, EmptySubscription>,
+        query: &str,
+    ) -> usize {
+        schema.execute(query).await.errors.len()
+    }
+
+    #[tokio::test]
+    async fn test_limits_reject_operations_beyond_them() {
+        let schema = schema(
+            SchemaLimits::new()
+                .with_max_depth(2)
+                .with_max_complexity(3)
+                .with_timeout(Duration::from_millis(50))
+                .with_introspection(false),
+        );
+        assert_eq!(errors(&schema, "{ node { id } }").await, 0);
+        assert_eq!(errors(&schema, "{ node { child { id } } }").await, 1);
+        assert_eq!(
+            errors(&schema, "{ a: node { id } b: node { id } }").await,
+            1
+        );
+        assert_eq!(errors(&schema, "{ slow }").await, 1);
+        assert_eq!(
+            errors(&schema, "{ __schema { queryType { name } } }").await,
+            1
+        );
+
+        let schema = self::schema(SchemaLimits::new());
+        assert_eq!(
+            errors(&schema, "{ __schema { queryType { name } } }").await,
+            0
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::{async_trait, dataloader::Loader};
+use use_case::{dto::todo::TodoDto, traits::todo::QueryUseCase};
+
+use crate::error::PresentationalError;
+
+/// Looks up the todos that the fields of a request ask for by id in one use case call,
+/// whether they come from several `todo` fields or from the `todo` of every audit entry.
+pub struct TodoLoader {
+    query_use_case: Arc<dyn QueryUseCase>,
+}
+
+impl TodoLoader {
+    pub fn new(query_use_case: Arc<dyn QueryUseCase>) -> Self {
+        Self { query_use_case }
+    }
+}
+
+#[async_trait::async_trait]
+impl Loader<i64> for TodoLoader {
+    type Value = TodoDto;
+    type Error = PresentationalError;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, TodoDto>, PresentationalError> {
+        let todos = self.query_use_case.find_by_ids(keys.to_vec()).await?;
+        Ok(todos.into_iter().map(|todo| (todo.id, todo)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::dataloader::DataLoader;
+    use domain::entity::todo::Todo;
+    use std::sync::Mutex;
+    use use_case::error::UseCaseError;
+
+    /// Knows the todos 1 and 2 and records the ids of every lookup.
+    #[derive(Default)]
+    struct MockQueryUseCase {
+        lookups: Mutex<Vec<Vec<i64>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl QueryUseCase for MockQueryUseCase {
+        async fn find_all(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+            Ok(vec![])
+        }
+
+        async fn find_by_id(&self, todo_id: i64) -> Result<Option<TodoDto>, UseCaseError> {
+            Ok(self.find_by_ids(vec![todo_id]).await?.pop())
+        }
+
+        async fn find_by_ids(&self, todo_ids: Vec<i64>) -> Result<Vec<TodoDto>, UseCaseError> {
+            let mut sorted = todo_ids.clone();
+            sorted.sort();
+            self.lookups.lock().unwrap().push(sorted);
+            Ok(todo_ids
+                .into_iter()
+                .filter(|id| [1, 2].contains(id))
+                .map(|id| {
+                    Todo {
+                        id,
+                        title: Some(format!("task{}", id)),
+                        ..Default::default()
+                    }
+                    .into()
+                })
+                .collect())
+        }
+
+        async fn find_overdue(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+            Ok(vec![])
+        }
+
+        async fn find_due_today(&self, _: Option<String>) -> Result<Vec<TodoDto>, UseCaseError> {
+            Ok(vec![])
+        }
+
+        async fn find_due_this_week(
+            &self,
+            _: Option<String>,
+        ) -> Result<Vec<TodoDto>, UseCaseError> {
+            Ok(vec![])
+        }
+
+        async fn find_high_priority(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_lookups_are_batched() {
+        let query_use_case = Arc::new(MockQueryUseCase::default());
+        let loader = DataLoader::new(TodoLoader::new(query_use_case.clone()), tokio::spawn);
+
+        let (first, second, missing) =
+            tokio::join!(loader.load_one(1), loader.load_one(2), loader.load_one(3));
+        assert_eq!(first.ok().flatten().map(|todo| todo.id), Some(1));
+        assert_eq!(second.ok().flatten().map(|todo| todo.id), Some(2));
+        assert!(matches!(missing, Ok(None)));
+        assert_eq!(*query_use_case.lookups.lock().unwrap(), vec![vec![1, 2, 3]]);
+
+        // nothing is cached between lookups
+        assert!(loader.load_one(1).await.is_ok());
+        assert_eq!(query_use_case.lookups.lock().unwrap().len(), 2);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod handler;
+pub mod limits;
+pub mod loader;
 pub mod object;
+pub mod persisted;
 pub mod schema;
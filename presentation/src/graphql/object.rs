@@ -1,13 +1,315 @@
-use async_graphql::SimpleObject;
+use crate::{error::PresentationalError, graphql::schema::todo_loader};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, MaybeUndefined, SimpleObject};
+use chrono::{DateTime, Utc};
+use use_case::dto::{
+    audit::{AuditEntryDto, FieldChangeDto},
+    batch::{BatchItemResultDto, BatchOutcomeDto, BatchResultDto},
+    revision::{RevisionDiffDto, TodoRevisionDto},
+    todo::{CompletedTodoDto, CreateTodoDto, TodoDto, UpdateTodoDto},
+    trash::TrashedTodoDto,
+};
+
+#[derive(Enum, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl From<domain::entity::todo::Priority> for Priority {
+    fn from(priority: domain::entity::todo::Priority) -> Self {
+        match priority {
+            domain::entity::todo::Priority::Low => Self::Low,
+            domain::entity::todo::Priority::Medium => Self::Medium,
+            domain::entity::todo::Priority::High => Self::High,
+        }
+    }
+}
+
+impl From<Priority> for domain::entity::todo::Priority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => Self::Low,
+            Priority::Medium => Self::Medium,
+            Priority::High => Self::High,
+        }
+    }
+}
 
 #[derive(SimpleObject)]
 pub struct Todo {
     id: i64,
     title: Option<String>,
+    due_at: Option<DateTime<Utc>>,
+    priority: Priority,
+    /// RFC 5545 RRULE, e.g. `FREQ=WEEKLY;BYDAY=MO`.
+    recurrence: Option<String>,
+    completed_at: Option<DateTime<Utc>>,
+    /// Bumped by every update; pass it back as `expectedVersion` to `updateTodo`.
+    version: i64,
+}
+
+impl From<TodoDto> for Todo {
+    fn from(todo: TodoDto) -> Self {
+        Self {
+            id: todo.id,
+            title: todo.title,
+            due_at: todo.due_at,
+            priority: todo.priority.into(),
+            recurrence: todo.recurrence,
+            completed_at: todo.completed_at,
+            version: todo.version,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CompletedTodo {
+    todo: Todo,
+    /// The next occurrence spawned by a recurring todo.
+    next: Option<Todo>,
+}
+
+impl From<CompletedTodoDto> for CompletedTodo {
+    fn from(completed: CompletedTodoDto) -> Self {
+        Self {
+            todo: completed.todo.into(),
+            next: completed.next.map(|next| next.into()),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct FieldChange {
+    field: String,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl From<FieldChangeDto> for FieldChange {
+    fn from(change: FieldChangeDto) -> Self {
+        Self {
+            field: change.field,
+            before: change.before,
+            after: change.after,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct AuditEntry {
+    id: i64,
+    todo_id: i64,
+    /// `create`, `update`, `complete` or `delete`.
+    action: String,
+    actor: String,
+    /// `rest`, `graphql`, `grpc` or `system`.
+    protocol: String,
+    request_id: String,
+    before: Option<Todo>,
+    after: Option<Todo>,
+    changes: Vec<FieldChange>,
+    occurred_at: DateTime<Utc>,
+}
+
+impl From<AuditEntryDto> for AuditEntry {
+    fn from(entry: AuditEntryDto) -> Self {
+        Self {
+            id: entry.id,
+            todo_id: entry.todo_id,
+            action: entry.action,
+            actor: entry.actor,
+            protocol: entry.protocol,
+            request_id: entry.request_id,
+            before: entry.before.map(|todo| todo.into()),
+            after: entry.after.map(|todo| todo.into()),
+            changes: entry
+                .changes
+                .into_iter()
+                .map(|change| change.into())
+                .collect(),
+            occurred_at: entry.occurred_at,
+        }
+    }
+}
+
+#[ComplexObject]
+impl AuditEntry {
+    /// The todo as it is now; null once it is deleted.
+    async fn todo(&self, context: &Context<'_>) -> Result<Option<Todo>, PresentationalError> {
+        let todo = todo_loader(context).load_one(self.todo_id).await?;
+        Ok(todo.map(|todo| todo.into()))
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TodoRevision {
+    revision: i64,
+    todo: Todo,
+    created_at: DateTime<Utc>,
+}
+
+impl From<TodoRevisionDto> for TodoRevision {
+    fn from(revision: TodoRevisionDto) -> Self {
+        Self {
+            revision: revision.revision,
+            todo: revision.todo.into(),
+            created_at: revision.created_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct RevisionDiff {
+    todo_id: i64,
+    from: i64,
+    to: i64,
+    changes: Vec<FieldChange>,
+}
+
+impl From<RevisionDiffDto> for RevisionDiff {
+    fn from(diff: RevisionDiffDto) -> Self {
+        Self {
+            todo_id: diff.todo_id,
+            from: diff.from,
+            to: diff.to,
+            changes: diff
+                .changes
+                .into_iter()
+                .map(|change| change.into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TrashedTodo {
+    todo: Todo,
+    deleted_at: DateTime<Utc>,
+    /// When the todo will be purged unless it is restored first.
+    purge_at: DateTime<Utc>,
+}
+
+impl From<TrashedTodoDto> for TrashedTodo {
+    fn from(trashed: TrashedTodoDto) -> Self {
+        Self {
+            todo: trashed.todo.into(),
+            deleted_at: trashed.deleted_at,
+            purge_at: trashed.purge_at,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateTodoInput {
+    title: String,
+    due_at: Option<DateTime<Utc>>,
+    priority: Option<Priority>,
+    recurrence: Option<String>,
+}
+
+impl From<CreateTodoInput> for CreateTodoDto {
+    fn from(input: CreateTodoInput) -> Self {
+        Self {
+            title: input.title,
+            due_at: input.due_at,
+            priority: input.priority.unwrap_or_default().into(),
+            recurrence: input.recurrence,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct UpdateTodoInput {
+    pub id: i64,
+    title: String,
+    /// Left out, the stored due date is kept; `null` clears it.
+    due_at: MaybeUndefined<DateTime<Utc>>,
+    /// Left out, the stored priority is kept.
+    priority: Option<Priority>,
+    /// Left out, the stored rule is kept; `null` clears it.
+    recurrence: MaybeUndefined<String>,
+    pub expected_version: i64,
+}
+
+impl From<UpdateTodoInput> for UpdateTodoDto {
+    fn from(input: UpdateTodoInput) -> Self {
+        Self {
+            id: input.id,
+            title: Some(input.title),
+            due_at: input.due_at.into(),
+            priority: input.priority.map(|priority| priority.into()),
+            recurrence: input.recurrence.into(),
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Default, Eq, PartialEq)]
+pub enum BatchMode {
+    /// Roll the whole batch back when one operation is rejected.
+    #[default]
+    Atomic,
+    /// Commit the accepted operations and report the rejected ones.
+    PerItem,
+}
+
+impl From<BatchMode> for use_case::dto::batch::BatchMode {
+    fn from(mode: BatchMode) -> Self {
+        match mode {
+            BatchMode::Atomic => Self::Atomic,
+            BatchMode::PerItem => Self::PerItem,
+        }
+    }
+}
+
+#[derive(SimpleObject, Default)]
+pub struct BatchItemResult {
+    index: i32,
+    /// The todo the operation affected; null when it was rejected.
+    id: Option<i64>,
+    todo: Option<Todo>,
+    next: Option<Todo>,
+    error: Option<String>,
+}
+
+impl From<BatchItemResultDto> for BatchItemResult {
+    fn from(item: BatchItemResultDto) -> Self {
+        let mut result = Self {
+            index: item.index as i32,
+            ..Default::default()
+        };
+        match item.result {
+            Ok(BatchOutcomeDto::Created(todo)) | Ok(BatchOutcomeDto::Updated(todo)) => {
+                result.id = Some(todo.id);
+                result.todo = Some(todo.into());
+            }
+            Ok(BatchOutcomeDto::Deleted(todo_id)) => result.id = Some(todo_id),
+            Ok(BatchOutcomeDto::Completed(completed)) => {
+                result.id = Some(completed.todo.id);
+                result.todo = Some(completed.todo.into());
+                result.next = completed.next.map(|next| next.into());
+            }
+            Err(err) => result.error = Some(PresentationalError::from(err).to_string()),
+        }
+        result
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct BatchResult {
+    /// False when an atomic batch was rolled back; `items` then ends with the rejected
+    /// operation.
+    committed: bool,
+    items: Vec<BatchItemResult>,
 }
 
-impl Todo {
-    pub fn new(id: i64, title: Option<String>) -> Self {
-        Self { id, title }
+impl From<BatchResultDto> for BatchResult {
+    fn from(result: BatchResultDto) -> Self {
+        Self {
+            committed: result.committed,
+            items: result.items.into_iter().map(|item| item.into()).collect(),
+        }
     }
 }
@@ -0,0 +1,312 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    async_trait,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest},
+    from_value,
+    parser::parse_query,
+    ErrorExtensionValues, Request, ServerError, ServerResult,
+};
+use domain::entity::persisted_query::PersistedQuery;
+use serde::Deserialize;
+use use_case::{
+    dto::persisted_query::PersistedQueryDto, traits::persisted_query::PersistedQueryUseCase,
+};
+
+use crate::error::PresentationalError;
+
+/// Runs the documents clients persisted by their hash, following the Automatic Persisted
+/// Queries protocol of Apollo. In strict mode only the documents registered from a client
+/// manifest may run, whether they are sent whole or by hash.
+#[derive(Clone)]
+pub struct PersistedQueries {
+    persisted_query_use_case: Arc<dyn PersistedQueryUseCase>,
+    strict: bool,
+}
+
+impl PersistedQueries {
+    pub fn new(persisted_query_use_case: Arc<dyn PersistedQueryUseCase>) -> Self {
+        Self {
+            persisted_query_use_case,
+            strict: false,
+        }
+    }
+
+    /// Sets whether operations outside the registered manifests are rejected.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    async fn find(&self, hash: String) -> ServerResult<Option<PersistedQueryDto>> {
+        let query = self
+            .persisted_query_use_case
+            .find(hash)
+            .await
+            .map_err(|error| {
+                ServerError::new(PresentationalError::from(error).to_string(), None)
+            })?;
+        Ok(query.filter(|query| !self.strict || query.is_registered()))
+    }
+
+    async fn find_registered(&self, document: &str) -> ServerResult<()> {
+        match self.find(PersistedQuery::hash_of(document)).await? {
+            Some(_) => Ok(()),
+            None => Err(error(
+                "the operation is not registered with the server",
+                "OPERATION_NOT_ALLOWED",
+            )),
+        }
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(self.clone())
+    }
+}
+
+/// The `persistedQuery` entry of the extensions of a request.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQueryExtension {
+    version: i32,
+    sha256_hash: String,
+}
+
+#[async_trait::async_trait]
+impl Extension for PersistedQueries {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        mut request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let extension = match request.extensions.remove("persistedQuery") {
+            Some(value) => Some(from_value::<PersistedQueryExtension>(value).map_err(|_| {
+                ServerError::new("Invalid \"PersistedQuery\" extension configuration.", None)
+            })?),
+            None => None,
+        };
+        if let Some(extension) = &extension {
+            if extension.version != 1 {
+                return Err(ServerError::new(
+                    format!(
+                        "Only version 1 of the \"PersistedQuery\" extension is supported, not {}.",
+                        extension.version
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        match extension {
+            Some(extension) if request.query.is_empty() => {
+                match self.find(extension.sha256_hash).await? {
+                    Some(query) => request.query = query.document,
+                    None => {
+                        return Err(error("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND"))
+                    }
+                }
+            }
+            Some(extension) => {
+                if PersistedQuery::hash_of(&request.query) != extension.sha256_hash {
+                    return Err(ServerError::new("provided sha does not match query", None));
+                }
+                if self.strict {
+                    self.find_registered(&request.query).await?;
+                } else if self.find(extension.sha256_hash).await?.is_none() {
+                    let operation_names = operation_names(&request.query)?;
+                    self.persisted_query_use_case
+                        .save_automatic(request.query.clone(), operation_names)
+                        .await
+                        .map_err(|error| {
+                            ServerError::new(PresentationalError::from(error).to_string(), None)
+                        })?;
+                }
+            }
+            None if self.strict => self.find_registered(&request.query).await?,
+            None => {}
+        }
+        next.run(ctx, request).await
+    }
+}
+
+fn error(message: &str, code: &str) -> ServerError {
+    let mut extensions = ErrorExtensionValues::default();
+    extensions.set("code", code);
+    let mut error = ServerError::new(message, None);
+    error.extensions = Some(extensions);
+    error
+}
+
+/// The names of the operations of a document, as a manifest registers them. A document
+/// with an anonymous operation has none.
+pub fn operation_names(document: &str) -> ServerResult<Vec<String>> {
+    let document = parse_query(document)?;
+    let mut names = document
+        .operations
+        .iter()
+        .filter_map(|(name, _)| name.map(|name| name.to_string()))
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{value, EmptyMutation, EmptySubscription, Object, Schema};
+    use chrono::Utc;
+    use std::sync::Mutex;
+    use use_case::error::UseCaseError;
+
+    /// Keeps the persisted queries in memory.
+    #[derive(Default)]
+    struct MockPersistedQueryUseCase {
+        queries: Mutex<Vec<PersistedQueryDto>>,
+    }
+
+    impl MockPersistedQueryUseCase {
+        fn save(
+            &self,
+            document: String,
+            operation_names: Vec<String>,
+            source: &str,
+        ) -> PersistedQueryDto {
+            let query = PersistedQueryDto {
+                hash: PersistedQuery::hash_of(&document),
+                document,
+                operation_names,
+                source: source.to_string(),
+                created_at: Utc::now(),
+            };
+            self.queries.lock().unwrap().push(query.clone());
+            query
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PersistedQueryUseCase for MockPersistedQueryUseCase {
+        async fn find(&self, hash: String) -> Result<Option<PersistedQueryDto>, UseCaseError> {
+            let queries = self.queries.lock().unwrap();
+            Ok(queries.iter().find(|query| query.hash == hash).cloned())
+        }
+
+        async fn find_all(&self) -> Result<Vec<PersistedQueryDto>, UseCaseError> {
+            Ok(self.queries.lock().unwrap().clone())
+        }
+
+        async fn register(
+            &self,
+            document: String,
+            operation_names: Vec<String>,
+        ) -> Result<PersistedQueryDto, UseCaseError> {
+            Ok(self.save(document, operation_names, "manifest"))
+        }
+
+        async fn save_automatic(
+            &self,
+            document: String,
+            operation_names: Vec<String>,
+        ) -> Result<PersistedQueryDto, UseCaseError> {
+            Ok(self.save(document, operation_names, "automatic"))
+        }
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn value(&self) -> i32 {
+            100
+        }
+    }
+
+    fn schema(
+        use_case: Arc<MockPersistedQueryUseCase>,
+        strict: bool,
+    ) -> Schema<Query, EmptyMutation, EmptySubscription> {
+        Schema::build(Query, EmptyMutation, EmptySubscription)
+            .extension(PersistedQueries::new(use_case).with_strict(strict))
+            .finish()
+    }
+
+    fn request(query: &str, hash: &str) -> Request {
+        let mut request = Request::new(query);
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            value!({ "version": 1, "sha256Hash": hash }),
+        );
+        request
+    }
+
+    fn code(response: &async_graphql::Response) -> Option<String> {
+        let extensions = response.errors.first()?.extensions.as_ref()?;
+        match extensions.get("code")? {
+            async_graphql::Value::String(code) => Some(code.clone()),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_automatic_persisted_queries() {
+        let use_case = Arc::new(MockPersistedQueryUseCase::default());
+        let schema = schema(use_case.clone(), false);
+        let query = "query getValue { value }";
+        let hash = PersistedQuery::hash_of(query);
+
+        let response = schema.execute(request("", &hash)).await;
+        assert_eq!(
+            code(&response).as_deref(),
+            Some("PERSISTED_QUERY_NOT_FOUND")
+        );
+
+        let response = schema.execute(request(query, "0000")).await;
+        assert_eq!(response.errors.len(), 1);
+
+        let response = schema.execute(request(query, &hash)).await;
+        assert_eq!(response.data, value!({ "value": 100 }));
+        let response = schema.execute(request("", &hash)).await;
+        assert_eq!(response.data, value!({ "value": 100 }));
+
+        let queries = use_case.queries.lock().unwrap().clone();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].operation_names, vec!["getValue".to_string()]);
+        assert!(!queries[0].is_registered());
+
+        let response = schema.execute("{ value }").await;
+        assert_eq!(response.data, value!({ "value": 100 }));
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_runs_only_registered_operations() {
+        let use_case = Arc::new(MockPersistedQueryUseCase::default());
+        let schema = schema(use_case.clone(), true);
+        let manifest = "query first { value }\nquery second { value }";
+        let names = operation_names(manifest).ok().unwrap();
+        assert_eq!(names, vec!["first".to_string(), "second".to_string()]);
+        use_case.save(manifest.to_string(), names, "manifest");
+        use_case.save("{ value }".to_string(), vec![], "automatic");
+
+        let response = schema
+            .execute(Request::new(manifest).operation_name("second"))
+            .await;
+        assert_eq!(response.data, value!({ "value": 100 }));
+        let response = schema
+            .execute(request("", &PersistedQuery::hash_of(manifest)).operation_name("first"))
+            .await;
+        assert_eq!(response.data, value!({ "value": 100 }));
+
+        let response = schema.execute("{ value }").await;
+        assert_eq!(code(&response).as_deref(), Some("OPERATION_NOT_ALLOWED"));
+        let response = schema
+            .execute(request("", &PersistedQuery::hash_of("{ value }")))
+            .await;
+        assert_eq!(
+            code(&response).as_deref(),
+            Some("PERSISTED_QUERY_NOT_FOUND")
+        );
+        assert_eq!(use_case.queries.lock().unwrap().len(), 2);
+    }
+}
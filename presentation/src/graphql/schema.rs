@@ -1,113 +1,547 @@
-use crate::{error::PresentationalError, graphql::object::Todo};
-use async_graphql::{Context, EmptySubscription, Object, Schema};
+use std::sync::Arc;
+
+use crate::{
+    context::normalize_idempotency_key,
+    error::PresentationalError,
+    graphql::{
+        limits::SchemaLimits,
+        loader::TodoLoader,
+        object::{
+            AuditEntry, BatchMode, BatchResult, CompletedTodo, CreateTodoInput, Priority,
+            RevisionDiff, Todo, TodoRevision, TrashedTodo, UpdateTodoInput,
+        },
+        persisted::PersistedQueries,
+    },
+};
+use async_graphql::{
+    dataloader::DataLoader, Context, EmptySubscription, MaybeUndefined, Object, Schema,
+};
+use chrono::{DateTime, Utc};
+use domain::entity::audit::Protocol;
 use use_case::{
-    dto::todo::{CreateTodoDto, TodoDto},
-    traits::todo::{MutationUseCase, QueryUseCase},
+    dto::{
+        audit::{AuditLogFilterDto, RequestContext},
+        batch::BatchOperationDto,
+        todo::{CreateTodoDto, UpdateTodoDto},
+    },
+    traits::{
+        audit::AuditUseCase,
+        revision::RevisionUseCase,
+        todo::{MutationUseCase, QueryUseCase},
+        trash::TrashUseCase,
+    },
 };
 
-pub struct Query<QUC> {
+/// How many items a list field counts as towards the complexity limit, whatever its length,
+/// so that selecting many fields over a list costs more than over a single todo.
+const LIST_COMPLEXITY: usize = 10;
+
+pub struct Query<QUC, AUC, RUC, TUC> {
     query_use_case: QUC,
+    audit_use_case: AUC,
+    revision_use_case: RUC,
+    trash_use_case: TUC,
 }
 
-impl<QUC> Query<QUC>
+impl<QUC, AUC, RUC, TUC> Query<QUC, AUC, RUC, TUC>
 where
     QUC: QueryUseCase,
+    AUC: AuditUseCase,
+    RUC: RevisionUseCase,
+    TUC: TrashUseCase,
 {
-    pub fn new(query_use_case: QUC) -> Self {
-        Self { query_use_case }
+    pub fn new(
+        query_use_case: QUC,
+        audit_use_case: AUC,
+        revision_use_case: RUC,
+        trash_use_case: TUC,
+    ) -> Self {
+        Self {
+            query_use_case,
+            audit_use_case,
+            revision_use_case,
+            trash_use_case,
+        }
     }
 }
 
 #[Object]
-impl<QUC> Query<QUC>
+impl<QUC, AUC, RUC, TUC> Query<QUC, AUC, RUC, TUC>
 where
     QUC: QueryUseCase,
+    AUC: AuditUseCase,
+    RUC: RevisionUseCase,
+    TUC: TrashUseCase,
 {
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
     async fn todos(&self, _context: &Context<'_>) -> Result<Vec<Todo>, PresentationalError> {
         let todos = self.query_use_case.find_all().await?;
-        let todo_objects = todos
-            .into_iter()
-            .map(|todo| Todo::new(todo.id, todo.title))
-            .collect();
+        let todo_objects = todos.into_iter().map(|todo| todo.into()).collect();
         Ok(todo_objects)
     }
 
+    /// Lookups of several todos in one request are batched into one.
     async fn todo(
         &self,
-        _context: &Context<'_>,
+        context: &Context<'_>,
         id: i64,
     ) -> Result<Option<Todo>, PresentationalError> {
-        let todo = self.query_use_case.find_by_id(id).await?;
-        match todo {
-            Some(todo) => Ok(Some(Todo::new(todo.id, todo.title))),
-            None => Ok(None),
-        }
+        let todo = todo_loader(context).load_one(id).await?;
+        Ok(todo.map(|todo| todo.into()))
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn overdue_todos(
+        &self,
+        _context: &Context<'_>,
+    ) -> Result<Vec<Todo>, PresentationalError> {
+        let todos = self.query_use_case.find_overdue().await?;
+        Ok(todos.into_iter().map(|todo| todo.into()).collect())
+    }
+
+    /// Todos due on the current day in `timezone` (an IANA name such as `Asia/Tokyo`).
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn todos_due_today(
+        &self,
+        _context: &Context<'_>,
+        timezone: Option<String>,
+    ) -> Result<Vec<Todo>, PresentationalError> {
+        let todos = self.query_use_case.find_due_today(timezone).await?;
+        Ok(todos.into_iter().map(|todo| todo.into()).collect())
+    }
+
+    /// Todos due in the current Monday-to-Sunday week in `timezone`.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn todos_due_this_week(
+        &self,
+        _context: &Context<'_>,
+        timezone: Option<String>,
+    ) -> Result<Vec<Todo>, PresentationalError> {
+        let todos = self.query_use_case.find_due_this_week(timezone).await?;
+        Ok(todos.into_iter().map(|todo| todo.into()).collect())
+    }
+
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn high_priority_todos(
+        &self,
+        _context: &Context<'_>,
+    ) -> Result<Vec<Todo>, PresentationalError> {
+        let todos = self.query_use_case.find_high_priority().await?;
+        Ok(todos.into_iter().map(|todo| todo.into()).collect())
+    }
+
+    /// Recorded changes, newest first, optionally narrowed to one todo and/or actor.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn audit_log(
+        &self,
+        _context: &Context<'_>,
+        todo_id: Option<i64>,
+        actor: Option<String>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AuditEntry>, PresentationalError> {
+        let entries = self
+            .audit_use_case
+            .find_audit_log(AuditLogFilterDto {
+                todo_id,
+                actor,
+                limit,
+            })
+            .await?;
+        Ok(entries.into_iter().map(|entry| entry.into()).collect())
+    }
+
+    /// Every revision of a todo, oldest first.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn revisions(
+        &self,
+        _context: &Context<'_>,
+        todo_id: i64,
+    ) -> Result<Vec<TodoRevision>, PresentationalError> {
+        let revisions = self.revision_use_case.find_revisions(todo_id).await?;
+        Ok(revisions
+            .into_iter()
+            .map(|revision| revision.into())
+            .collect())
+    }
+
+    async fn revision_diff(
+        &self,
+        _context: &Context<'_>,
+        todo_id: i64,
+        from: i64,
+        to: i64,
+    ) -> Result<RevisionDiff, PresentationalError> {
+        let diff = self
+            .revision_use_case
+            .diff_revisions(todo_id, from, to)
+            .await?;
+        Ok(diff.into())
+    }
+
+    /// Deleted todos still in the trash, most recently deleted first.
+    #[graphql(complexity = "LIST_COMPLEXITY * child_complexity")]
+    async fn trash(&self, _context: &Context<'_>) -> Result<Vec<TrashedTodo>, PresentationalError> {
+        let todos = self.trash_use_case.find_trash().await?;
+        Ok(todos.into_iter().map(|todo| todo.into()).collect())
     }
 }
 
-pub struct Mutation<MUC> {
+pub struct Mutation<MUC, RUC, TUC> {
     mutation_use_case: MUC,
+    revision_use_case: RUC,
+    trash_use_case: TUC,
 }
 
-impl<MUC> Mutation<MUC>
+impl<MUC, RUC, TUC> Mutation<MUC, RUC, TUC>
 where
     MUC: MutationUseCase,
+    RUC: RevisionUseCase,
+    TUC: TrashUseCase,
 {
-    pub fn new(mutation_use_case: MUC) -> Self {
-        Self { mutation_use_case }
+    pub fn new(mutation_use_case: MUC, revision_use_case: RUC, trash_use_case: TUC) -> Self {
+        Self {
+            mutation_use_case,
+            revision_use_case,
+            trash_use_case,
+        }
     }
 }
 
 #[Object]
-impl<MUC> Mutation<MUC>
+impl<MUC, RUC, TUC> Mutation<MUC, RUC, TUC>
 where
     MUC: MutationUseCase,
+    RUC: RevisionUseCase,
+    TUC: TrashUseCase,
 {
+    /// Retrying with the same `idempotency_key` returns the todo created by the first call.
     async fn create_todo(
         &self,
-        _context: &Context<'_>,
+        context: &Context<'_>,
         title: String,
+        due_at: Option<DateTime<Utc>>,
+        priority: Option<Priority>,
+        recurrence: Option<String>,
+        idempotency_key: Option<String>,
     ) -> Result<Todo, PresentationalError> {
         let todo = self
             .mutation_use_case
-            .create(CreateTodoDto { title })
+            .create(
+                request_context(context),
+                CreateTodoDto {
+                    title,
+                    due_at,
+                    priority: priority.unwrap_or_default().into(),
+                    recurrence,
+                },
+                normalize_idempotency_key(idempotency_key.as_deref()),
+            )
             .await?;
-        Ok(Todo::new(todo.id, todo.title))
+        Ok(todo.into())
     }
 
+    /// Fails with a conflict unless the todo is still at `expected_version`. Arguments left
+    /// out keep the stored value, and `null` clears `dueAt` and `recurrence`.
+    #[allow(clippy::too_many_arguments)]
     async fn update_todo(
         &self,
-        _context: &Context<'_>,
+        context: &Context<'_>,
         id: i64,
         title: String,
+        due_at: MaybeUndefined<DateTime<Utc>>,
+        priority: Option<Priority>,
+        recurrence: MaybeUndefined<String>,
+        expected_version: i64,
     ) -> Result<Todo, PresentationalError> {
         let todo = self
             .mutation_use_case
-            .update(TodoDto {
-                id,
-                title: Some(title),
-            })
+            .update(
+                request_context(context),
+                UpdateTodoDto {
+                    id,
+                    title: Some(title),
+                    due_at: due_at.into(),
+                    priority: priority.map(|priority| priority.into()),
+                    recurrence: recurrence.into(),
+                },
+                Some(expected_version),
+            )
             .await?;
-        Ok(Todo::new(todo.id, todo.title))
+        Ok(todo.into())
+    }
+
+    /// Completes a todo; recurring todos spawn their next occurrence.
+    async fn complete_todo(
+        &self,
+        context: &Context<'_>,
+        id: i64,
+    ) -> Result<CompletedTodo, PresentationalError> {
+        let completed = self
+            .mutation_use_case
+            .complete(request_context(context), id)
+            .await?;
+        Ok(completed.into())
     }
 
     async fn delete_todo(
         &self,
-        _context: &Context<'_>,
+        context: &Context<'_>,
         id: i64,
     ) -> Result<i64, PresentationalError> {
-        self.mutation_use_case.delete(id).await?;
+        self.mutation_use_case
+            .delete(request_context(context), id, None)
+            .await?;
         Ok(id)
     }
+
+    /// Creates the todos in one transaction.
+    async fn create_todos(
+        &self,
+        context: &Context<'_>,
+        todos: Vec<CreateTodoInput>,
+        #[graphql(default)] mode: BatchMode,
+    ) -> Result<BatchResult, PresentationalError> {
+        let operations = todos
+            .into_iter()
+            .map(|todo| BatchOperationDto::Create(todo.into()))
+            .collect();
+        self.batch(context, operations, mode).await
+    }
+
+    /// Updates the todos in one transaction, each only if still at its `expectedVersion`.
+    async fn update_todos(
+        &self,
+        context: &Context<'_>,
+        todos: Vec<UpdateTodoInput>,
+        #[graphql(default)] mode: BatchMode,
+    ) -> Result<BatchResult, PresentationalError> {
+        let operations = todos
+            .into_iter()
+            .map(|todo| {
+                let expected_version = Some(todo.expected_version);
+                BatchOperationDto::Update {
+                    todo: todo.into(),
+                    expected_version,
+                }
+            })
+            .collect();
+        self.batch(context, operations, mode).await
+    }
+
+    /// Completes the todos in one transaction.
+    async fn complete_todos(
+        &self,
+        context: &Context<'_>,
+        ids: Vec<i64>,
+        #[graphql(default)] mode: BatchMode,
+    ) -> Result<BatchResult, PresentationalError> {
+        let operations = ids.into_iter().map(BatchOperationDto::Complete).collect();
+        self.batch(context, operations, mode).await
+    }
+
+    /// Deletes the todos in one transaction.
+    async fn delete_todos(
+        &self,
+        context: &Context<'_>,
+        ids: Vec<i64>,
+        #[graphql(default)] mode: BatchMode,
+    ) -> Result<BatchResult, PresentationalError> {
+        let operations = ids.into_iter().map(BatchOperationDto::Delete).collect();
+        self.batch(context, operations, mode).await
+    }
+
+    /// Updates a todo back to the fields of one of its revisions, as a new revision.
+    async fn restore_revision(
+        &self,
+        context: &Context<'_>,
+        todo_id: i64,
+        revision: i64,
+    ) -> Result<Todo, PresentationalError> {
+        let todo = self
+            .revision_use_case
+            .restore_revision(request_context(context), todo_id, revision)
+            .await?;
+        Ok(todo.into())
+    }
+
+    /// Takes a deleted todo back out of the trash.
+    async fn restore_todo(
+        &self,
+        context: &Context<'_>,
+        id: i64,
+    ) -> Result<Todo, PresentationalError> {
+        let todo = self
+            .trash_use_case
+            .restore(request_context(context), id)
+            .await?;
+        Ok(todo.into())
+    }
 }
 
-pub fn build_schema<QUC, MUC>(
-    query: Query<QUC>,
-    mutation: Mutation<MUC>,
-) -> Schema<Query<QUC>, Mutation<MUC>, EmptySubscription>
+impl<MUC, RUC, TUC> Mutation<MUC, RUC, TUC>
 where
-    QUC: QueryUseCase,
     MUC: MutationUseCase,
+    RUC: RevisionUseCase,
+    TUC: TrashUseCase,
 {
-    Schema::build(query, mutation, EmptySubscription).finish()
+    async fn batch(
+        &self,
+        context: &Context<'_>,
+        operations: Vec<BatchOperationDto>,
+        mode: BatchMode,
+    ) -> Result<BatchResult, PresentationalError> {
+        let result = self
+            .mutation_use_case
+            .batch(request_context(context), operations, mode.into())
+            .await?;
+        Ok(result.into())
+    }
+}
+
+/// The context the handler attached to the request; schemas executed without one
+/// (e.g. in tests) act anonymously.
+fn request_context(context: &Context<'_>) -> RequestContext {
+    match context.data_opt::<RequestContext>() {
+        Some(ctx) => ctx.clone(),
+        None => crate::context::request_context(Protocol::GraphQL, None, None),
+    }
+}
+
+/// The loader [`build_schema`] registers, shared by the requests the schema runs. It
+/// caches nothing, so every request reads the todos as they are.
+pub(crate) fn todo_loader<'a>(context: &Context<'a>) -> &'a DataLoader<TodoLoader> {
+    context.data_unchecked::<DataLoader<TodoLoader>>()
+}
+
+pub type TodoSchema<QUC, AUC, RUC, MUC, TUC> =
+    Schema<Query<QUC, AUC, RUC, TUC>, Mutation<MUC, RUC, TUC>, EmptySubscription>;
+
+pub fn build_schema<QUC, AUC, RUC, MUC, TUC>(
+    query: Query<QUC, AUC, RUC, TUC>,
+    mutation: Mutation<MUC, RUC, TUC>,
+    limits: SchemaLimits,
+    persisted_queries: PersistedQueries,
+) -> TodoSchema<QUC, AUC, RUC, MUC, TUC>
+where
+    QUC: QueryUseCase + Clone,
+    AUC: AuditUseCase,
+    RUC: RevisionUseCase,
+    MUC: MutationUseCase,
+    TUC: TrashUseCase,
+{
+    let loader = TodoLoader::new(Arc::new(query.query_use_case.clone()));
+    let builder = Schema::build(query, mutation, EmptySubscription)
+        .data(DataLoader::new(loader, tokio::spawn))
+        .extension(persisted_queries);
+    limits.apply(builder).finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTodoUseCase;
+    use async_graphql::{async_trait, EmptyMutation};
+    use use_case::{
+        dto::{
+            audit::AuditEntryDto,
+            revision::{RevisionDiffDto, TodoRevisionDto},
+            todo::TodoDto,
+            trash::TrashedTodoDto,
+        },
+        error::UseCaseError,
+    };
+
+    /// Has no history: no audit entries, revisions or trashed todos.
+    #[derive(Clone)]
+    struct NoHistory;
+
+    #[async_trait::async_trait]
+    impl AuditUseCase for NoHistory {
+        async fn find_audit_log(
+            &self,
+            _filter: AuditLogFilterDto,
+        ) -> Result<Vec<AuditEntryDto>, UseCaseError> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl RevisionUseCase for NoHistory {
+        async fn find_revisions(
+            &self,
+            _todo_id: i64,
+        ) -> Result<Vec<TodoRevisionDto>, UseCaseError> {
+            Ok(vec![])
+        }
+
+        async fn diff_revisions(
+            &self,
+            todo_id: i64,
+            _from: i64,
+            _to: i64,
+        ) -> Result<RevisionDiffDto, UseCaseError> {
+            Err(UseCaseError::NotFound {
+                entity_type: "revision".to_string(),
+                entity_id: todo_id,
+            })
+        }
+
+        async fn restore_revision(
+            &self,
+            _ctx: RequestContext,
+            todo_id: i64,
+            _revision: i64,
+        ) -> Result<TodoDto, UseCaseError> {
+            Err(UseCaseError::NotFound {
+                entity_type: "revision".to_string(),
+                entity_id: todo_id,
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TrashUseCase for NoHistory {
+        async fn find_trash(&self) -> Result<Vec<TrashedTodoDto>, UseCaseError> {
+            Ok(vec![])
+        }
+
+        async fn restore(
+            &self,
+            _ctx: RequestContext,
+            todo_id: i64,
+        ) -> Result<TodoDto, UseCaseError> {
+            Err(UseCaseError::NotFound {
+                entity_type: "todo".to_string(),
+                entity_id: todo_id,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_fields_multiply_the_complexity_of_their_items() {
+        let query = Query::new(
+            MockTodoUseCase::with_titles(&["Buy milk"]),
+            NoHistory,
+            NoHistory,
+            NoHistory,
+        );
+        let schema = SchemaLimits::new()
+            .apply(Schema::build(query, EmptyMutation, EmptySubscription))
+            .finish();
+        let fields = "id title dueAt priority recurrence completedAt version";
+
+        let response = schema
+            .execute(format!("{{ todos {{ {} }} }}", fields))
+            .await;
+        assert!(response.errors.is_empty());
+
+        // 8 lists of 7 fields count as 568, each field of the items 10 times
+        let wide = (0..8)
+            .map(|index| format!("list{}: todos {{ {} }}", index, fields))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let response = schema.execute(format!("{{ {} }}", wide)).await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("too complex"));
+    }
 }
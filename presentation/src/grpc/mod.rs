@@ -1 +1,4 @@
 pub mod proto_impl;
+pub mod transcoding;
+pub mod v2;
+pub mod web;
@@ -1,16 +1,53 @@
-use todo::todo_service_server::TodoService;
+// tonic::Status is large by design and every RPC helper returns it.
+#![allow(clippy::result_large_err)]
+
+use std::pin::Pin;
+
+use chrono::DateTime;
+use futures_util::{stream, Stream};
+use todo::batch_todos_request::Operation;
+use todo::{
+    audit_service_server::AuditService, revision_service_server::RevisionService,
+    todo_service_server::TodoService, trash_service_server::TrashService,
+};
 pub use todo::{
-    CreateTodoRequest, CreateTodoResponse, DeleteTodoRequest, DeleteTodoResponse,
-    FindTodoByIdRequest, FindTodoByIdResponse, GetTodosRequest, GetTodosResponse, Todo,
-    UpdateTodoRequest, UpdateTodoResponse,
+    AuditEntry, BatchItemResult, BatchMode, BatchTodosRequest, BatchTodosResponse,
+    CompleteTodoRequest, CompleteTodoResponse, CreateTodoRequest, CreateTodoResponse,
+    DeleteTodoRequest, DeleteTodoResponse, DiffRevisionsRequest, DiffRevisionsResponse,
+    FieldChange, FindTodoByIdRequest, FindTodoByIdResponse, GetAuditLogRequest,
+    GetAuditLogResponse, GetHighPriorityTodosRequest, GetOverdueTodosRequest,
+    GetTodosDueThisWeekRequest, GetTodosDueTodayRequest, GetTodosRequest, GetTodosResponse,
+    ListRevisionsRequest, ListRevisionsResponse, ListTrashRequest, ListTrashResponse, Priority,
+    RestoreRevisionRequest, RestoreRevisionResponse, RestoreTodoRequest, RestoreTodoResponse, Todo,
+    TodoRevision, TrashedTodo, UpdateTodoRequest, UpdateTodoResponse,
 };
 use use_case::{
-    dto::todo::{CreateTodoDto, TodoDto},
-    traits::todo::TodoUseCase,
+    dto::{
+        audit::{AuditEntryDto, AuditLogFilterDto, FieldChangeDto},
+        batch::{
+            BatchItemResultDto, BatchMode as BatchModeDto, BatchOperationDto, BatchOutcomeDto,
+            MAX_BATCH_SIZE,
+        },
+        revision::TodoRevisionDto,
+        todo::{CreateTodoDto, TodoDto, UpdateTodoDto},
+        trash::TrashedTodoDto,
+    },
+    error::UseCaseError,
+    traits::{
+        audit::AuditUseCase, revision::RevisionUseCase, todo::TodoUseCase, trash::TrashUseCase,
+    },
 };
 
+use crate::context::{from_metadata, idempotency_key_from_metadata};
+
+pub use todo::audit_service_client::AuditServiceClient;
+pub use todo::audit_service_server::AuditServiceServer;
+pub use todo::revision_service_client::RevisionServiceClient;
+pub use todo::revision_service_server::RevisionServiceServer;
 pub use todo::todo_service_client::TodoServiceClient;
 pub use todo::todo_service_server::TodoServiceServer;
+pub use todo::trash_service_client::TrashServiceClient;
+pub use todo::trash_service_server::TrashServiceServer;
 
 pub mod todo {
     tonic::include_proto!("todo");
@@ -18,6 +55,228 @@ pub mod todo {
     pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("todo_descriptor");
 }
 
+impl From<TodoDto> for Todo {
+    fn from(todo: TodoDto) -> Self {
+        Self {
+            id: todo.id,
+            title: todo.title.unwrap_or("".to_string()),
+            due_at: todo.due_at.map(|due_at| due_at.timestamp()),
+            priority: Priority::from(todo.priority).into(),
+            recurrence: todo.recurrence.unwrap_or_default(),
+            completed_at: todo
+                .completed_at
+                .map(|completed_at| completed_at.timestamp()),
+            version: todo.version,
+        }
+    }
+}
+
+impl From<FieldChangeDto> for FieldChange {
+    fn from(change: FieldChangeDto) -> Self {
+        Self {
+            field: change.field,
+            before: change.before,
+            after: change.after,
+        }
+    }
+}
+
+impl From<AuditEntryDto> for AuditEntry {
+    fn from(entry: AuditEntryDto) -> Self {
+        Self {
+            id: entry.id,
+            todo_id: entry.todo_id,
+            action: entry.action,
+            actor: entry.actor,
+            protocol: entry.protocol,
+            request_id: entry.request_id,
+            before: entry.before.map(|todo| todo.into()),
+            after: entry.after.map(|todo| todo.into()),
+            changes: entry
+                .changes
+                .into_iter()
+                .map(|change| change.into())
+                .collect(),
+            occurred_at: entry.occurred_at.timestamp(),
+        }
+    }
+}
+
+impl From<TodoRevisionDto> for TodoRevision {
+    fn from(revision: TodoRevisionDto) -> Self {
+        Self {
+            revision: revision.revision,
+            todo: Some(revision.todo.into()),
+            created_at: revision.created_at.timestamp(),
+        }
+    }
+}
+
+impl From<TrashedTodoDto> for TrashedTodo {
+    fn from(trashed: TrashedTodoDto) -> Self {
+        Self {
+            todo: Some(trashed.todo.into()),
+            deleted_at: trashed.deleted_at.timestamp(),
+            purge_at: trashed.purge_at.timestamp(),
+        }
+    }
+}
+
+impl From<domain::entity::todo::Priority> for Priority {
+    fn from(priority: domain::entity::todo::Priority) -> Self {
+        match priority {
+            domain::entity::todo::Priority::Low => Self::Low,
+            domain::entity::todo::Priority::Medium => Self::Medium,
+            domain::entity::todo::Priority::High => Self::High,
+        }
+    }
+}
+
+impl From<Priority> for domain::entity::todo::Priority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => Self::Low,
+            Priority::Unspecified | Priority::Medium => Self::Medium,
+            Priority::High => Self::High,
+        }
+    }
+}
+
+impl TryFrom<CreateTodoRequest> for CreateTodoDto {
+    type Error = tonic::Status;
+
+    fn try_from(request: CreateTodoRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            due_at: parse_due_at(request.due_at)?,
+            priority: request.priority().into(),
+            title: request.title,
+            recurrence: non_empty(request.recurrence),
+        })
+    }
+}
+
+/// Fields left unset keep their stored value: an unset `due_at`, an unspecified `priority`
+/// and an empty `recurrence`. Clearing them takes an `update_mask` of the v2 service.
+impl TryFrom<UpdateTodoRequest> for UpdateTodoDto {
+    type Error = tonic::Status;
+
+    fn try_from(request: UpdateTodoRequest) -> Result<Self, Self::Error> {
+        let priority = match request.priority() {
+            Priority::Unspecified => None,
+            priority => Some(priority.into()),
+        };
+        Ok(Self {
+            id: request.id,
+            due_at: parse_due_at(request.due_at)?.map(Some),
+            priority,
+            title: Some(request.title),
+            recurrence: non_empty(request.recurrence).map(Some),
+        })
+    }
+}
+
+impl TryFrom<BatchTodosRequest> for BatchOperationDto {
+    type Error = tonic::Status;
+
+    fn try_from(request: BatchTodosRequest) -> Result<Self, Self::Error> {
+        match request.operation {
+            Some(Operation::Create(request)) => Ok(Self::Create(request.try_into()?)),
+            Some(Operation::Update(request)) => Ok(Self::Update {
+                expected_version: request.expected_version,
+                todo: request.try_into()?,
+            }),
+            Some(Operation::Delete(request)) => Ok(Self::Delete(request.id)),
+            Some(Operation::Complete(request)) => Ok(Self::Complete(request.id)),
+            None => Err(tonic::Status::invalid_argument(
+                "batch operation is missing",
+            )),
+        }
+    }
+}
+
+impl From<BatchMode> for BatchModeDto {
+    fn from(mode: BatchMode) -> Self {
+        match mode {
+            BatchMode::Atomic => Self::Atomic,
+            BatchMode::PerItem => Self::PerItem,
+        }
+    }
+}
+
+impl From<BatchItemResultDto> for BatchItemResult {
+    fn from(item: BatchItemResultDto) -> Self {
+        let mut result = Self {
+            index: item.index as i64,
+            ..Default::default()
+        };
+        match item.result {
+            Ok(BatchOutcomeDto::Created(todo)) | Ok(BatchOutcomeDto::Updated(todo)) => {
+                result.id = Some(todo.id);
+                result.todo = Some(todo.into());
+            }
+            Ok(BatchOutcomeDto::Deleted(todo_id)) => result.id = Some(todo_id),
+            Ok(BatchOutcomeDto::Completed(completed)) => {
+                result.id = Some(completed.todo.id);
+                result.todo = Some(completed.todo.into());
+                result.next = completed.next.map(|next| next.into());
+            }
+            Err(err) => result.error = to_status(err).message().to_string(),
+        }
+        result
+    }
+}
+
+fn parse_due_at(due_at: Option<i64>) -> Result<Option<DateTime<chrono::Utc>>, tonic::Status> {
+    match due_at {
+        Some(seconds) => DateTime::from_timestamp(seconds, 0)
+            .map(Some)
+            .ok_or_else(|| tonic::Status::invalid_argument("due_at is out of range")),
+        None => Ok(None),
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+pub(crate) fn to_status(err: UseCaseError) -> tonic::Status {
+    match err {
+        UseCaseError::Validation(message) => tonic::Status::invalid_argument(message),
+        UseCaseError::NotFound {
+            entity_type,
+            entity_id,
+        } => tonic::Status::not_found(format!("{} {} not found", entity_type, entity_id)),
+        UseCaseError::IdempotencyKeyReused(key) => tonic::Status::failed_precondition(format!(
+            "idempotency key {} was used for a different request",
+            key
+        )),
+        UseCaseError::Conflict {
+            entity_type,
+            entity_id,
+        } => tonic::Status::aborted(format!(
+            "{} {} was modified concurrently",
+            entity_type, entity_id
+        )),
+        _ => tonic::Status::internal("Internal Server Error".to_string()),
+    }
+}
+
+fn todos_response(
+    todos: Result<Vec<TodoDto>, UseCaseError>,
+) -> Result<tonic::Response<GetTodosResponse>, tonic::Status> {
+    match todos {
+        Ok(todos) => {
+            let todos = todos.into_iter().map(|todo| todo.into()).collect();
+            Ok(tonic::Response::new(GetTodosResponse { todos }))
+        }
+        Err(err) => Err(to_status(err)),
+    }
+}
+
 #[derive(Default)]
 pub struct TodoServiceImpl<TU: TodoUseCase> {
     pub tu: TU,
@@ -25,6 +284,8 @@ pub struct TodoServiceImpl<TU: TodoUseCase> {
 
 #[tonic::async_trait]
 impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
+    type StreamTodosStream = Pin<Box<dyn Stream<Item = Result<Todo, tonic::Status>> + Send>>;
+
     async fn get_todos(
         &self,
         _request: tonic::Request<GetTodosRequest>,
@@ -32,13 +293,7 @@ impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
         let todos = self.tu.find_all().await;
         match todos {
             Ok(todos) => {
-                let todos: Vec<Todo> = todos
-                    .into_iter()
-                    .map(|todo| Todo {
-                        id: todo.id,
-                        title: todo.title.unwrap_or("".to_string()),
-                    })
-                    .collect();
+                let todos: Vec<Todo> = todos.into_iter().map(|todo| todo.into()).collect();
                 let response = GetTodosResponse { todos };
                 return Ok(tonic::Response::new(response));
             }
@@ -48,6 +303,15 @@ impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
         }
     }
 
+    async fn stream_todos(
+        &self,
+        _request: tonic::Request<GetTodosRequest>,
+    ) -> Result<tonic::Response<Self::StreamTodosStream>, tonic::Status> {
+        let todos = self.tu.find_all().await.map_err(to_status)?;
+        let todos = todos.into_iter().map(|todo| Ok(todo.into()));
+        Ok(tonic::Response::new(Box::pin(stream::iter(todos))))
+    }
+
     async fn find_todo_by_id(
         &self,
         request: tonic::Request<FindTodoByIdRequest>,
@@ -57,11 +321,9 @@ impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
         match todo {
             Ok(todo) => match todo {
                 Some(todo) => {
-                    let todo = Todo {
-                        id: todo.id,
-                        title: todo.title.unwrap_or("".to_string()),
+                    let response = FindTodoByIdResponse {
+                        todo: Some(todo.into()),
                     };
-                    let response = FindTodoByIdResponse { todo: Some(todo) };
                     return Ok(tonic::Response::new(response));
                 }
                 None => {
@@ -79,19 +341,19 @@ impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
         &self,
         request: tonic::Request<CreateTodoRequest>,
     ) -> Result<tonic::Response<CreateTodoResponse>, tonic::Status> {
-        let title = request.into_inner().title;
-        let todo = self.tu.create(CreateTodoDto { title }).await;
+        let ctx = from_metadata(request.metadata());
+        let idempotency_key = idempotency_key_from_metadata(request.metadata());
+        let todo_data = CreateTodoDto::try_from(request.into_inner())?;
+        let todo = self.tu.create(ctx, todo_data, idempotency_key).await;
         match todo {
             Ok(todo) => {
-                let todo = Todo {
-                    id: todo.id,
-                    title: todo.title.unwrap_or("".to_string()),
+                let response = CreateTodoResponse {
+                    todo: Some(todo.into()),
                 };
-                let response = CreateTodoResponse { todo: Some(todo) };
                 return Ok(tonic::Response::new(response));
             }
-            Err(_) => {
-                return Err(tonic::Status::internal("Internal Server Error".to_string()));
+            Err(err) => {
+                return Err(to_status(err));
             }
         }
     }
@@ -100,26 +362,20 @@ impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
         &self,
         request: tonic::Request<UpdateTodoRequest>,
     ) -> Result<tonic::Response<UpdateTodoResponse>, tonic::Status> {
-        let id = request.get_ref().id;
-        let title = request.get_ref().title.to_string();
-        let todo = self
-            .tu
-            .update(TodoDto {
-                id,
-                title: Some(title),
-            })
-            .await;
+        let ctx = from_metadata(request.metadata());
+        let request = request.into_inner();
+        let expected_version = request.expected_version;
+        let todo_data = UpdateTodoDto::try_from(request)?;
+        let todo = self.tu.update(ctx, todo_data, expected_version).await;
         match todo {
             Ok(todo) => {
-                let todo = Todo {
-                    id: todo.id,
-                    title: todo.title.unwrap_or("".to_string()),
+                let response = UpdateTodoResponse {
+                    todo: Some(todo.into()),
                 };
-                let response = UpdateTodoResponse { todo: Some(todo) };
                 return Ok(tonic::Response::new(response));
             }
-            Err(_) => {
-                return Err(tonic::Status::internal("Internal Server Error".to_string()));
+            Err(err) => {
+                return Err(to_status(err));
             }
         }
     }
@@ -131,24 +387,20 @@ impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
         let id = request.get_ref().id;
         let find_todo_result = self.tu.find_by_id(id).await;
 
-        let todo = match find_todo_result {
-            Ok(todo) => todo,
-            Err(_) => None,
-        };
+        let todo = find_todo_result.ok().flatten();
 
         if todo.is_none() {
             return Err(tonic::Status::internal("Not Found".to_string()));
         }
         let todo = todo.unwrap();
 
-        let todo_id = self.tu.delete(id).await;
+        let ctx = from_metadata(request.metadata());
+        let todo_id = self.tu.delete(ctx, id, None).await;
         match todo_id {
             Ok(_) => {
-                let todo = Todo {
-                    id: todo.id,
-                    title: todo.title.unwrap_or("".to_string()),
+                let response = DeleteTodoResponse {
+                    todo: Some(todo.into()),
                 };
-                let response = DeleteTodoResponse { todo: Some(todo) };
                 return Ok(tonic::Response::new(response));
             }
             Err(_) => {
@@ -156,4 +408,189 @@ impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
             }
         }
     }
+
+    async fn get_overdue_todos(
+        &self,
+        _request: tonic::Request<GetOverdueTodosRequest>,
+    ) -> Result<tonic::Response<GetTodosResponse>, tonic::Status> {
+        todos_response(self.tu.find_overdue().await)
+    }
+
+    async fn get_todos_due_today(
+        &self,
+        request: tonic::Request<GetTodosDueTodayRequest>,
+    ) -> Result<tonic::Response<GetTodosResponse>, tonic::Status> {
+        let timezone = request.into_inner().timezone;
+        todos_response(self.tu.find_due_today(timezone).await)
+    }
+
+    async fn get_todos_due_this_week(
+        &self,
+        request: tonic::Request<GetTodosDueThisWeekRequest>,
+    ) -> Result<tonic::Response<GetTodosResponse>, tonic::Status> {
+        let timezone = request.into_inner().timezone;
+        todos_response(self.tu.find_due_this_week(timezone).await)
+    }
+
+    async fn get_high_priority_todos(
+        &self,
+        _request: tonic::Request<GetHighPriorityTodosRequest>,
+    ) -> Result<tonic::Response<GetTodosResponse>, tonic::Status> {
+        todos_response(self.tu.find_high_priority().await)
+    }
+
+    async fn complete_todo(
+        &self,
+        request: tonic::Request<CompleteTodoRequest>,
+    ) -> Result<tonic::Response<CompleteTodoResponse>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let id = request.into_inner().id;
+        let completed = self.tu.complete(ctx, id).await.map_err(to_status)?;
+        let response = CompleteTodoResponse {
+            todo: Some(completed.todo.into()),
+            next: completed.next.map(|next| next.into()),
+        };
+        Ok(tonic::Response::new(response))
+    }
+
+    async fn batch_todos(
+        &self,
+        request: tonic::Request<tonic::Streaming<BatchTodosRequest>>,
+    ) -> Result<tonic::Response<BatchTodosResponse>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let mut stream = request.into_inner();
+        let mut mode = None;
+        let mut operations = Vec::new();
+        while let Some(message) = stream.message().await? {
+            if operations.len() == MAX_BATCH_SIZE {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "a batch holds at most {} operations",
+                    MAX_BATCH_SIZE
+                )));
+            }
+            mode.get_or_insert(message.mode());
+            operations.push(BatchOperationDto::try_from(message)?);
+        }
+        let mode = mode.unwrap_or_default().into();
+        let result = self
+            .tu
+            .batch(ctx, operations, mode)
+            .await
+            .map_err(to_status)?;
+        let response = BatchTodosResponse {
+            committed: result.committed,
+            results: result.items.into_iter().map(|item| item.into()).collect(),
+        };
+        Ok(tonic::Response::new(response))
+    }
+}
+
+#[derive(Default)]
+pub struct AuditServiceImpl<AU: AuditUseCase> {
+    pub au: AU,
+}
+
+#[tonic::async_trait]
+impl<AU: AuditUseCase> AuditService for AuditServiceImpl<AU> {
+    async fn get_audit_log(
+        &self,
+        request: tonic::Request<GetAuditLogRequest>,
+    ) -> Result<tonic::Response<GetAuditLogResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let entries = self
+            .au
+            .find_audit_log(AuditLogFilterDto {
+                todo_id: request.todo_id,
+                actor: request.actor,
+                limit: request.limit,
+            })
+            .await
+            .map_err(to_status)?;
+        let entries = entries.into_iter().map(|entry| entry.into()).collect();
+        Ok(tonic::Response::new(GetAuditLogResponse { entries }))
+    }
+}
+
+#[derive(Default)]
+pub struct RevisionServiceImpl<RVU: RevisionUseCase> {
+    pub rvu: RVU,
+}
+
+#[tonic::async_trait]
+impl<RVU: RevisionUseCase> RevisionService for RevisionServiceImpl<RVU> {
+    async fn list_revisions(
+        &self,
+        request: tonic::Request<ListRevisionsRequest>,
+    ) -> Result<tonic::Response<ListRevisionsResponse>, tonic::Status> {
+        let todo_id = request.into_inner().todo_id;
+        let revisions = self.rvu.find_revisions(todo_id).await.map_err(to_status)?;
+        let revisions = revisions
+            .into_iter()
+            .map(|revision| revision.into())
+            .collect();
+        Ok(tonic::Response::new(ListRevisionsResponse { revisions }))
+    }
+
+    async fn diff_revisions(
+        &self,
+        request: tonic::Request<DiffRevisionsRequest>,
+    ) -> Result<tonic::Response<DiffRevisionsResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let diff = self
+            .rvu
+            .diff_revisions(request.todo_id, request.from, request.to)
+            .await
+            .map_err(to_status)?;
+        let changes = diff
+            .changes
+            .into_iter()
+            .map(|change| change.into())
+            .collect();
+        Ok(tonic::Response::new(DiffRevisionsResponse { changes }))
+    }
+
+    async fn restore_revision(
+        &self,
+        request: tonic::Request<RestoreRevisionRequest>,
+    ) -> Result<tonic::Response<RestoreRevisionResponse>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let request = request.into_inner();
+        let todo = self
+            .rvu
+            .restore_revision(ctx, request.todo_id, request.revision)
+            .await
+            .map_err(to_status)?;
+        Ok(tonic::Response::new(RestoreRevisionResponse {
+            todo: Some(todo.into()),
+        }))
+    }
+}
+
+#[derive(Default)]
+pub struct TrashServiceImpl<TSU: TrashUseCase> {
+    pub tsu: TSU,
+}
+
+#[tonic::async_trait]
+impl<TSU: TrashUseCase> TrashService for TrashServiceImpl<TSU> {
+    async fn list_trash(
+        &self,
+        _request: tonic::Request<ListTrashRequest>,
+    ) -> Result<tonic::Response<ListTrashResponse>, tonic::Status> {
+        let todos = self.tsu.find_trash().await.map_err(to_status)?;
+        let todos = todos.into_iter().map(|todo| todo.into()).collect();
+        Ok(tonic::Response::new(ListTrashResponse { todos }))
+    }
+
+    async fn restore_todo(
+        &self,
+        request: tonic::Request<RestoreTodoRequest>,
+    ) -> Result<tonic::Response<RestoreTodoResponse>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let id = request.into_inner().id;
+        let todo = self.tsu.restore(ctx, id).await.map_err(to_status)?;
+        Ok(tonic::Response::new(RestoreTodoResponse {
+            todo: Some(todo.into()),
+        }))
+    }
 }
@@ -0,0 +1,758 @@
+//! Serves the unary methods that have a `google.api.http` option as JSON over HTTP. A
+//! request matching one of their path templates becomes a gRPC call of the wrapped server,
+//! its fields bound from the path, the query string and the JSON body as the option says,
+//! and the reply, or the gRPC status, becomes the JSON response. Requests that are already
+//! gRPC pass through untouched.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::http::{
+    header, request::Parts, uri::PathAndQuery, HeaderMap, HeaderValue, Method, Request, Response,
+    StatusCode, Version,
+};
+use futures_util::future::BoxFuture;
+use http_body::{LengthLimitError, Limited};
+use hyper::body::{Bytes, HttpBody};
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, Kind, MessageDescriptor, MethodDescriptor, SerializeOptions,
+};
+use serde_json::{json, Map, Value};
+use tonic::{body::BoxBody, server::NamedService, Code};
+use tower::{Layer, Service};
+
+const JSON: &str = "application/json";
+
+/// Largest JSON body read, the same as the largest message the gRPC server decodes, which
+/// is 4 MiB unless set otherwise.
+pub const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// The request headers that describe the HTTP exchange rather than the call, and so are not
+/// passed on as gRPC metadata.
+const HTTP_HEADERS: [header::HeaderName; 7] = [
+    header::ACCEPT,
+    header::ACCEPT_ENCODING,
+    header::CONNECTION,
+    header::CONTENT_LENGTH,
+    header::CONTENT_TYPE,
+    header::HOST,
+    header::TRANSFER_ENCODING,
+];
+
+#[derive(Debug, Clone)]
+pub struct TranscodingLayer {
+    routes: Arc<Vec<Route>>,
+}
+
+impl TranscodingLayer {
+    /// Routes the methods of every service in `file_descriptor_set`, which must include the
+    /// imported `google/api/annotations.proto`.
+    pub fn new(file_descriptor_set: &[u8]) -> Result<Self, String> {
+        let pool = DescriptorPool::decode(file_descriptor_set).map_err(|e| e.to_string())?;
+        let Some(http) = pool.get_extension_by_name("google.api.http") else {
+            return Ok(Self {
+                routes: Arc::new(vec![]),
+            });
+        };
+        let mut routes = vec![];
+        for service in pool.services() {
+            for method in service.methods() {
+                let options = method.options();
+                if !options.has_extension(&http) {
+                    continue;
+                }
+                if method.is_client_streaming() || method.is_server_streaming() {
+                    return Err(format!(
+                        "{}: only unary methods can be transcoded",
+                        method.full_name()
+                    ));
+                }
+                let rule = options.get_extension(&http);
+                let Some(rule) = rule.as_message() else {
+                    continue;
+                };
+                Route::add(&mut routes, &method, rule)
+                    .map_err(|e| format!("{}: {}", method.full_name(), e))?;
+            }
+        }
+        // a custom verb is more specific than a variable that would also match it
+        routes.sort_by_key(|route| route.template.verb.is_none());
+        Ok(Self {
+            routes: Arc::new(routes),
+        })
+    }
+}
+
+impl<S> Layer<S> for TranscodingLayer {
+    type Service = Transcoding<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Transcoding {
+            inner,
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Route {
+    method: Method,
+    template: PathTemplate,
+    /// The path of the gRPC call, `/package.Service/Method`.
+    grpc_path: String,
+    descriptor: MethodDescriptor,
+    body: Body,
+}
+
+/// Where the fields of the request message come from besides the path.
+#[derive(Debug, Clone, PartialEq)]
+enum Body {
+    /// The query string; there is no body.
+    None,
+    /// The JSON body.
+    All,
+    /// The JSON body for this field, the query string for the others.
+    Field(String),
+}
+
+impl Route {
+    /// Adds the routes of `rule`, an `HttpRule`, and of its additional bindings.
+    fn add(
+        routes: &mut Vec<Route>,
+        descriptor: &MethodDescriptor,
+        rule: &DynamicMessage,
+    ) -> Result<(), String> {
+        let (method, template) = match ["get", "put", "post", "delete", "patch"]
+            .into_iter()
+            .find(|name| rule.has_field_by_name(name))
+        {
+            Some(name) => (name.to_uppercase(), string_field(rule, name)),
+            None => match rule
+                .get_field_by_name("custom")
+                .filter(|_| rule.has_field_by_name("custom"))
+                .as_deref()
+                .and_then(prost_reflect::Value::as_message)
+            {
+                Some(custom) => (string_field(custom, "kind"), string_field(custom, "path")),
+                None => return Err("the http rule has no pattern".to_string()),
+            },
+        };
+        let method = Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+        if !string_field(rule, "response_body").is_empty() {
+            return Err("response_body is not supported".to_string());
+        }
+        let input = descriptor.input();
+        let body = match string_field(rule, "body").as_str() {
+            "" => Body::None,
+            "*" => Body::All,
+            field => match input.get_field_by_name(field) {
+                Some(_) => Body::Field(field.to_string()),
+                None => return Err(format!("the body names an unknown field: {}", field)),
+            },
+        };
+        let template = PathTemplate::parse(&template)?;
+        for field in template.fields() {
+            field_kind(&input, field)?;
+        }
+        routes.push(Route {
+            method,
+            template,
+            grpc_path: format!(
+                "/{}/{}",
+                descriptor.parent_service().full_name(),
+                descriptor.name()
+            ),
+            descriptor: descriptor.clone(),
+            body,
+        });
+        if let Some(bindings) = rule.get_field_by_name("additional_bindings") {
+            for binding in bindings.as_list().unwrap_or_default() {
+                if let Some(binding) = binding.as_message() {
+                    Route::add(routes, descriptor, binding)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn string_field(message: &DynamicMessage, name: &str) -> String {
+    message
+        .get_field_by_name(name)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// A path template such as `/v1/todos/{id}:complete`. A variable binds one segment, or with
+/// `=**` the rest of the path; variables with other patterns are not supported.
+#[derive(Debug, Clone, PartialEq)]
+struct PathTemplate {
+    segments: Vec<Segment>,
+    verb: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Variable(String),
+    Rest(String),
+}
+
+impl PathTemplate {
+    fn parse(template: &str) -> Result<Self, String> {
+        let Some(path) = template.strip_prefix('/') else {
+            return Err(format!(
+                "the path template does not start with /: {}",
+                template
+            ));
+        };
+        let (path, verb) = split_verb(path);
+        let mut segments = vec![];
+        for segment in path.split('/') {
+            let segment = match segment
+                .strip_prefix('{')
+                .and_then(|variable| variable.strip_suffix('}'))
+            {
+                Some(variable) => match variable.split_once('=') {
+                    None => Segment::Variable(variable.to_string()),
+                    Some((field, "*")) => Segment::Variable(field.to_string()),
+                    Some((field, "**")) => Segment::Rest(field.to_string()),
+                    Some(_) => return Err(format!("unsupported path variable: {{{}}}", variable)),
+                },
+                None if segment.is_empty() || segment.contains(['{', '}', '*']) => {
+                    return Err(format!("unsupported path segment: {}", segment))
+                }
+                None => Segment::Literal(segment.to_string()),
+            };
+            if segments
+                .last()
+                .is_some_and(|last| matches!(last, Segment::Rest(_)))
+            {
+                return Err("a {field=**} variable must be the last segment".to_string());
+            }
+            segments.push(segment);
+        }
+        Ok(Self {
+            segments,
+            verb: verb.map(str::to_string),
+        })
+    }
+
+    fn fields(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Literal(_) => None,
+            Segment::Variable(field) | Segment::Rest(field) => Some(field.as_str()),
+        })
+    }
+
+    /// The variables of `path` bound to their percent-decoded values, if it matches.
+    fn matches(&self, path: &str) -> Option<Vec<(String, String)>> {
+        let path = path.strip_prefix('/')?;
+        let (path, verb) = split_verb(path);
+        if verb != self.verb.as_deref() {
+            return None;
+        }
+        let mut values: Vec<&str> = path.split('/').collect();
+        let mut bindings = vec![];
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Literal(literal) => {
+                    if values.get(index) != Some(&literal.as_str()) {
+                        return None;
+                    }
+                }
+                Segment::Variable(field) => {
+                    let value = values.get(index).filter(|value| !value.is_empty())?;
+                    bindings.push((field.clone(), decode(value)?));
+                }
+                Segment::Rest(field) => {
+                    if index >= values.len() {
+                        return None;
+                    }
+                    let rest = values.split_off(index).join("/");
+                    bindings.push((field.clone(), decode(&rest)?));
+                    return Some(bindings);
+                }
+            }
+        }
+        (values.len() == self.segments.len()).then_some(bindings)
+    }
+}
+
+/// Splits the custom verb off the last segment of `path`, as in `todos/{id}:complete`.
+fn split_verb(path: &str) -> (&str, Option<&str>) {
+    let last_segment = path.rfind('/').map_or(0, |index| index + 1);
+    match path[last_segment..].rfind(':') {
+        Some(colon) if !path[last_segment + colon..].contains('}') => (
+            &path[..last_segment + colon],
+            Some(&path[last_segment + colon + 1..]),
+        ),
+        _ => (path, None),
+    }
+}
+
+fn decode(value: &str) -> Option<String> {
+    percent_encoding::percent_decode_str(value)
+        .decode_utf8()
+        .ok()
+        .map(|value| value.into_owned())
+}
+
+/// The kind of the field at dotted `path` of `message`, which must not be a message, and
+/// whether it is repeated.
+fn field_kind(message: &MessageDescriptor, path: &str) -> Result<(Kind, bool), String> {
+    let mut message = message.clone();
+    let mut names = path.split('.').peekable();
+    while let Some(name) = names.next() {
+        let field = message
+            .get_field_by_name(name)
+            .or_else(|| message.get_field_by_json_name(name))
+            .ok_or_else(|| format!("unknown field: {}", path))?;
+        match (field.kind(), names.peek()) {
+            (Kind::Message(nested), Some(_)) if !field.is_list() => message = nested,
+            (Kind::Message(_), None) => {
+                return Err(format!(
+                    "{} is a message and cannot be bound to a string",
+                    path
+                ))
+            }
+            (kind, None) => return Ok((kind, field.is_list())),
+            (_, Some(_)) => return Err(format!("{} is not a field of a message", path)),
+        }
+    }
+    Err(format!("unknown field: {}", path))
+}
+
+/// Sets the field at dotted `path` of the JSON `message` to `value`, a path variable or query
+/// parameter, typed the way the JSON mapping of the field expects it.
+fn bind(
+    descriptor: &MessageDescriptor,
+    message: &mut Map<String, Value>,
+    path: &str,
+    value: String,
+) -> Result<(), String> {
+    let (kind, is_list) = field_kind(descriptor, path)?;
+    let value = match kind {
+        Kind::Bool => match value.as_str() {
+            "true" => json!(true),
+            "false" => json!(false),
+            _ => return Err(format!("{} must be true or false", path)),
+        },
+        Kind::Enum(_) => match value.parse::<i32>() {
+            Ok(number) => json!(number),
+            Err(_) => json!(value),
+        },
+        // the JSON mapping takes numbers as strings too
+        _ => json!(value),
+    };
+    let mut names: Vec<&str> = path.split('.').collect();
+    let last = names.pop().unwrap_or_default();
+    let mut message = message;
+    for name in names {
+        let nested = message.entry(name.to_string()).or_insert_with(|| json!({}));
+        message = nested
+            .as_object_mut()
+            .ok_or_else(|| format!("{} is not an object", name))?;
+    }
+    match (is_list, message.get_mut(last)) {
+        (true, Some(Value::Array(values))) => values.push(value),
+        (true, _) => {
+            message.insert(last.to_string(), json!([value]));
+        }
+        (false, _) => {
+            message.insert(last.to_string(), value);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct Transcoding<S> {
+    inner: S,
+    routes: Arc<Vec<Route>>,
+}
+
+impl<S> Service<Request<hyper::Body>> for Transcoding<S>
+where
+    S: Service<Request<hyper::Body>, Response = Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<hyper::Body>) -> Self::Future {
+        let is_grpc = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("application/grpc"));
+        if is_grpc {
+            return Box::pin(self.inner.call(request));
+        }
+        // the clone may not be ready, so the one that was polled serves this request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let routes = self.routes.clone();
+        Box::pin(async move {
+            let Some((route, bindings)) = routes.iter().find_map(|route| {
+                if route.method != request.method() {
+                    return None;
+                }
+                route
+                    .template
+                    .matches(request.uri().path())
+                    .map(|bindings| (route, bindings))
+            }) else {
+                let message = format!(
+                    "no method is bound to {} {}",
+                    request.method(),
+                    request.uri().path()
+                );
+                return Ok(error(Code::NotFound, &message, HeaderMap::new()));
+            };
+            let (parts, body) = request.into_parts();
+            let body = match hyper::body::to_bytes(Limited::new(body, MAX_BODY_SIZE)).await {
+                Ok(body) => body,
+                Err(e) if e.is::<LengthLimitError>() => {
+                    let message = format!("the body is larger than {} bytes", MAX_BODY_SIZE);
+                    let body = json!({
+                        "code": Code::ResourceExhausted as i32,
+                        "message": message,
+                        "details": [],
+                    });
+                    return Ok(respond(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        HeaderMap::new(),
+                        body.to_string().into_bytes(),
+                    ));
+                }
+                Err(e) => {
+                    return Ok(error(
+                        Code::InvalidArgument,
+                        &e.to_string(),
+                        HeaderMap::new(),
+                    ))
+                }
+            };
+            let request = match grpc_request(route, bindings, parts, body) {
+                Ok(request) => request,
+                Err(message) => {
+                    return Ok(error(Code::InvalidArgument, &message, HeaderMap::new()))
+                }
+            };
+            let response = inner.call(request).await?;
+            Ok(json_response(route, response).await)
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for Transcoding<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+/// The gRPC call of `route` for the HTTP request of `parts` and `body`.
+fn grpc_request(
+    route: &Route,
+    bindings: Vec<(String, String)>,
+    parts: Parts,
+    body: Bytes,
+) -> Result<Request<hyper::Body>, String> {
+    let input = route.descriptor.input();
+    let body: Value = if body.iter().all(u8::is_ascii_whitespace) {
+        json!({})
+    } else {
+        serde_json::from_slice(&body).map_err(|e| format!("invalid JSON body: {}", e))?
+    };
+    let mut message = match &route.body {
+        Body::None => Map::new(),
+        Body::All => match body {
+            Value::Object(message) => message,
+            _ => return Err("the body must be a JSON object".to_string()),
+        },
+        Body::Field(field) => Map::from_iter([(field.clone(), body)]),
+    };
+    if route.body != Body::All {
+        let query = parts.uri.query().unwrap_or_default();
+        for (name, value) in form_urlencoded::parse(query.as_bytes()) {
+            if route.template.fields().any(|field| field == name) {
+                return Err(format!("{} is bound by the path", name));
+            }
+            bind(&input, &mut message, &name, value.into_owned())?;
+        }
+    }
+    for (field, value) in bindings {
+        bind(&input, &mut message, &field, value)?;
+    }
+    let message =
+        DynamicMessage::deserialize(input, Value::Object(message)).map_err(|e| e.to_string())?;
+
+    let mut headers = parts.headers;
+    for name in HTTP_HEADERS {
+        headers.remove(name);
+    }
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/grpc"),
+    );
+    headers.insert(header::TE, HeaderValue::from_static("trailers"));
+    let mut grpc = Request::new(hyper::Body::from(frame(&message.encode_to_vec())));
+    *grpc.method_mut() = Method::POST;
+    // gRPC runs over HTTP/2, whichever version the JSON request came in
+    *grpc.version_mut() = Version::HTTP_2;
+    *grpc.uri_mut() = PathAndQuery::try_from(route.grpc_path.as_str())
+        .map_err(|e| e.to_string())?
+        .into();
+    *grpc.headers_mut() = headers;
+    Ok(grpc)
+}
+
+/// `message` as a gRPC length-prefixed message, uncompressed.
+fn frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(message.len() + 5);
+    frame.push(0);
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    frame
+}
+
+/// The JSON response for the reply of the gRPC call of `route`.
+async fn json_response(route: &Route, response: Response<BoxBody>) -> Response<BoxBody> {
+    let (parts, mut body) = response.into_parts();
+    let mut data = vec![];
+    while let Some(chunk) = body.data().await {
+        match chunk {
+            Ok(chunk) => data.extend_from_slice(&chunk),
+            Err(status) => return error(status.code(), status.message(), HeaderMap::new()),
+        }
+    }
+    let trailers = match body.trailers().await {
+        Ok(trailers) => trailers.unwrap_or_default(),
+        Err(status) => return error(status.code(), status.message(), HeaderMap::new()),
+    };
+    // a failed call sends its status with the headers, without a body or trailers
+    let status = tonic::Status::from_header_map(&trailers)
+        .or_else(|| tonic::Status::from_header_map(&parts.headers));
+    // the rest of the metadata the server sent, such as the deprecation of the service
+    let headers: HeaderMap = parts
+        .headers
+        .iter()
+        .filter(|(name, _)| {
+            !name.as_str().starts_with("grpc-")
+                && *name != header::CONTENT_TYPE
+                && *name != header::CONTENT_LENGTH
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    if let Some(status) = status.filter(|status| status.code() != Code::Ok) {
+        return error(status.code(), status.message(), headers);
+    }
+    let message = match data.get(..5) {
+        Some(&[0, a, b, c, d]) if data.len() == 5 + u32::from_be_bytes([a, b, c, d]) as usize => {
+            DynamicMessage::decode(route.descriptor.output(), Bytes::from(data.split_off(5)))
+                .map_err(|e| e.to_string())
+        }
+        _ => Err("the reply is not one uncompressed message".to_string()),
+    };
+    let message = match message {
+        Ok(message) => message,
+        Err(message) => return error(Code::Internal, &message, headers),
+    };
+    let options = SerializeOptions::new().skip_default_fields(false);
+    let mut serializer = serde_json::Serializer::new(vec![]);
+    match message.serialize_with_options(&mut serializer, &options) {
+        Ok(()) => respond(StatusCode::OK, headers, serializer.into_inner()),
+        Err(e) => error(Code::Internal, &e.to_string(), headers),
+    }
+}
+
+/// A failed call as a `google.rpc.Status` JSON object, with the HTTP status that
+/// `google.api.http` maps `code` to.
+fn error(code: Code, message: &str, headers: HeaderMap) -> Response<BoxBody> {
+    let body = json!({ "code": code as i32, "message": message, "details": [] });
+    respond(http_status(code), headers, body.to_string().into_bytes())
+}
+
+/// The HTTP status of a call that failed with `code`; the Connect protocol maps codes the
+/// same way.
+pub(crate) fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::Cancelled => StatusCode::from_u16(499).unwrap_or(StatusCode::BAD_REQUEST),
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn respond(status: StatusCode, headers: HeaderMap, body: Vec<u8>) -> Response<BoxBody> {
+    let body = hyper::Body::from(body)
+        .map_err(|e| tonic::Status::internal(e.to_string()))
+        .boxed_unsync();
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(JSON));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use tower::{service_fn, ServiceExt};
+
+    use super::*;
+    use crate::grpc::proto_impl::todo;
+
+    fn reply(headers: &[(&'static str, &'static str)], body: Vec<u8>) -> Response<BoxBody> {
+        let body = hyper::Body::from(body)
+            .map_err(|e| tonic::Status::internal(e.to_string()))
+            .boxed_unsync();
+        let mut response = Response::new(body);
+        for (name, value) in headers {
+            response
+                .headers_mut()
+                .insert(*name, HeaderValue::from_static(value));
+        }
+        response
+    }
+
+    async fn transcode(
+        request: Request<hyper::Body>,
+        inner: impl Fn(Request<()>, Bytes) -> Response<BoxBody> + Clone + Send + 'static,
+    ) -> (StatusCode, Value) {
+        let layer = TranscodingLayer::new(todo::FILE_DESCRIPTOR_SET).unwrap();
+        let service = layer.layer(service_fn(move |request: Request<hyper::Body>| {
+            let inner = inner.clone();
+            async move {
+                let (parts, body) = request.into_parts();
+                let body = hyper::body::to_bytes(body).await.unwrap();
+                Ok::<_, Infallible>(inner(Request::from_parts(parts, ()), body))
+            }
+        }));
+        let response = service.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn test_path_template_binds_variables_and_verbs() {
+        let complete = PathTemplate::parse("/v1/todos/{id}:complete").unwrap();
+        assert_eq!(
+            complete.matches("/v1/todos/7:complete"),
+            Some(vec![("id".to_string(), "7".to_string())])
+        );
+        assert_eq!(complete.matches("/v1/todos/7"), None);
+        let todos = PathTemplate::parse("/v1/todos").unwrap();
+        assert_eq!(todos.matches("/v1/todos"), Some(vec![]));
+        assert_eq!(todos.matches("/v1/todos:overdue"), None);
+        let rest = PathTemplate::parse("/v1/{name=**}").unwrap();
+        assert_eq!(
+            rest.matches("/v1/todos/a%20b"),
+            Some(vec![("name".to_string(), "todos/a b".to_string())])
+        );
+        assert!(PathTemplate::parse("/v1/{name=todos/*}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transcodes_path_body_and_reply() {
+        let request = Request::put("/v1/todos/3")
+            .header(header::CONTENT_TYPE, JSON)
+            .header("x-actor", "alice")
+            .body(hyper::Body::from(
+                r#"{"title": "water the plants", "expectedVersion": "2"}"#,
+            ))
+            .unwrap();
+        let (status, body) = transcode(request, |request, body| {
+            assert_eq!(request.uri().path(), "/todo.TodoService/UpdateTodo");
+            assert_eq!(request.headers()["x-actor"], "alice");
+            let update = todo::UpdateTodoRequest::decode(&body[5..]).unwrap();
+            assert_eq!(update.id, 3);
+            assert_eq!(update.title, "water the plants");
+            assert_eq!(update.expected_version, Some(2));
+            let todo = todo::Todo {
+                id: update.id,
+                title: update.title,
+                version: 3,
+                ..Default::default()
+            };
+            let reply_message = todo::UpdateTodoResponse { todo: Some(todo) };
+            reply(
+                &[("grpc-status", "0")],
+                frame(&reply_message.encode_to_vec()),
+            )
+        })
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["todo"]["id"], "3");
+        assert_eq!(body["todo"]["priority"], "PRIORITY_UNSPECIFIED");
+    }
+
+    #[tokio::test]
+    async fn test_maps_grpc_status_and_rejects_unbound_requests() {
+        let request = Request::get("/v1/todos:dueToday?timezone=Mars%2FOlympus")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let (status, body) = transcode(request, |_, _| {
+            reply(
+                &[("grpc-status", "3"), ("grpc-message", "unknown%20timezone")],
+                vec![],
+            )
+        })
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], Code::InvalidArgument as i32);
+        assert_eq!(body["message"], "unknown timezone");
+
+        let unbound = Request::get("/v1/trash")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let (status, _) = transcode(unbound, |_, _| unreachable!()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let unknown = Request::get("/v1/todos?sort=due")
+            .body(hyper::Body::empty())
+            .unwrap();
+        let (status, _) = transcode(unknown, |_, _| unreachable!()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bodies_larger_than_the_grpc_limit() {
+        let request = Request::post("/v1/todos")
+            .header(header::CONTENT_TYPE, JSON)
+            .body(hyper::Body::from(vec![b' '; MAX_BODY_SIZE + 1]))
+            .unwrap();
+        let (status, body) = transcode(request, |_, _| unreachable!()).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["code"], Code::ResourceExhausted as i32);
+
+        let request = Request::post("/v1/todos")
+            .header(header::CONTENT_TYPE, JSON)
+            .body(hyper::Body::from(vec![b' '; MAX_BODY_SIZE]))
+            .unwrap();
+        let (status, _) =
+            transcode(request, |_, _| reply(&[("grpc-status", "0")], frame(&[]))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
@@ -0,0 +1,213 @@
+// tonic::Status is large by design and every RPC helper returns it.
+#![allow(clippy::result_large_err)]
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use todo_v2::{
+    list_todos_request::Filter, todo_service_server::TodoService, CompleteTodoRequest,
+    CompleteTodoResponse, CreateTodoRequest, DeleteTodoRequest, DeleteTodoResponse, GetTodoRequest,
+    ListTodosRequest, ListTodosResponse, Priority, Todo, TodoFields, UpdateTodoRequest,
+};
+use use_case::{
+    dto::todo::{CreateTodoDto, TodoDto},
+    traits::todo::TodoUseCase,
+};
+
+use crate::{
+    adapter,
+    context::{from_metadata, idempotency_key_from_metadata},
+};
+
+use super::proto_impl::to_status;
+
+pub mod todo_v2 {
+    tonic::include_proto!("todo.v2");
+}
+
+pub use todo_v2::todo_service_client::TodoServiceClient;
+pub use todo_v2::todo_service_server::TodoServiceServer;
+
+impl From<TodoDto> for Todo {
+    fn from(todo: TodoDto) -> Self {
+        Self {
+            id: todo.id,
+            title: todo.title.unwrap_or_default(),
+            due_at: todo.due_at.map(format_timestamp),
+            priority: Priority::from(todo.priority).into(),
+            recurrence: todo.recurrence,
+            completed_at: todo.completed_at.map(format_timestamp),
+            version: todo.version,
+        }
+    }
+}
+
+impl From<domain::entity::todo::Priority> for Priority {
+    fn from(priority: domain::entity::todo::Priority) -> Self {
+        match priority {
+            domain::entity::todo::Priority::Low => Self::Low,
+            domain::entity::todo::Priority::Medium => Self::Medium,
+            domain::entity::todo::Priority::High => Self::High,
+        }
+    }
+}
+
+impl From<Priority> for domain::entity::todo::Priority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => Self::Low,
+            Priority::Unspecified | Priority::Medium => Self::Medium,
+            Priority::High => Self::High,
+        }
+    }
+}
+
+impl TryFrom<TodoFields> for CreateTodoDto {
+    type Error = tonic::Status;
+
+    fn try_from(fields: TodoFields) -> Result<Self, Self::Error> {
+        let priority = fields.priority().into();
+        Ok(Self {
+            due_at: parse_timestamp(fields.due_at)?,
+            priority,
+            title: fields.title,
+            recurrence: fields.recurrence,
+        })
+    }
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_timestamp(timestamp: Option<String>) -> Result<Option<DateTime<Utc>>, tonic::Status> {
+    match timestamp {
+        Some(timestamp) => DateTime::parse_from_rfc3339(&timestamp)
+            .map(|timestamp| Some(timestamp.with_timezone(&Utc)))
+            .map_err(|e| tonic::Status::invalid_argument(format!("{}: {}", timestamp, e))),
+        None => Ok(None),
+    }
+}
+
+/// `todo` with the fields of `fields` named in `mask` (all of them for an empty mask).
+fn apply_mask(mut todo: TodoDto, fields: TodoFields, mask: &[String]) -> Result<TodoDto, String> {
+    let masked = |field: &str| mask.is_empty() || mask.iter().any(|name| name == field);
+    if let Some(unknown) = mask
+        .iter()
+        .find(|name| !["title", "due_at", "priority", "recurrence"].contains(&name.as_str()))
+    {
+        return Err(format!("update_mask names an unknown field: {}", unknown));
+    }
+    if masked("title") {
+        todo.title = Some(fields.title.clone());
+    }
+    if masked("due_at") {
+        todo.due_at = parse_timestamp(fields.due_at.clone())
+            .map_err(|status| status.message().to_string())?;
+    }
+    if masked("priority") {
+        todo.priority = fields.priority().into();
+    }
+    if masked("recurrence") {
+        todo.recurrence = fields.recurrence;
+    }
+    Ok(todo)
+}
+
+#[derive(Default)]
+pub struct TodoServiceImpl<TU: TodoUseCase> {
+    pub tu: TU,
+}
+
+#[tonic::async_trait]
+impl<TU: TodoUseCase> TodoService for TodoServiceImpl<TU> {
+    async fn list_todos(
+        &self,
+        request: tonic::Request<ListTodosRequest>,
+    ) -> Result<tonic::Response<ListTodosResponse>, tonic::Status> {
+        let request = request.into_inner();
+        let todos = match request.filter() {
+            Filter::All => self.tu.find_all().await,
+            Filter::Overdue => self.tu.find_overdue().await,
+            Filter::DueToday => self.tu.find_due_today(request.timezone).await,
+            Filter::DueThisWeek => self.tu.find_due_this_week(request.timezone).await,
+            Filter::HighPriority => self.tu.find_high_priority().await,
+        }
+        .map_err(to_status)?;
+        let todos = todos.into_iter().map(|todo| todo.into()).collect();
+        Ok(tonic::Response::new(ListTodosResponse { todos }))
+    }
+
+    async fn get_todo(
+        &self,
+        request: tonic::Request<GetTodoRequest>,
+    ) -> Result<tonic::Response<Todo>, tonic::Status> {
+        let id = request.into_inner().id;
+        let todo = adapter::find_todo(&self.tu, id).await.map_err(to_status)?;
+        Ok(tonic::Response::new(todo.into()))
+    }
+
+    async fn create_todo(
+        &self,
+        request: tonic::Request<CreateTodoRequest>,
+    ) -> Result<tonic::Response<Todo>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let idempotency_key = idempotency_key_from_metadata(request.metadata());
+        let fields = request
+            .into_inner()
+            .todo
+            .ok_or_else(|| tonic::Status::invalid_argument("todo is missing"))?;
+        let todo = self
+            .tu
+            .create(ctx, fields.try_into()?, idempotency_key)
+            .await
+            .map_err(to_status)?;
+        Ok(tonic::Response::new(todo.into()))
+    }
+
+    async fn update_todo(
+        &self,
+        request: tonic::Request<UpdateTodoRequest>,
+    ) -> Result<tonic::Response<Todo>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let request = request.into_inner();
+        let fields = request
+            .todo
+            .ok_or_else(|| tonic::Status::invalid_argument("todo is missing"))?;
+        let mask = request.update_mask;
+        let todo = adapter::patch_todo(
+            &self.tu,
+            ctx,
+            request.id,
+            request.expected_version,
+            |todo| apply_mask(todo, fields, &mask),
+        )
+        .await
+        .map_err(to_status)?;
+        Ok(tonic::Response::new(todo.into()))
+    }
+
+    async fn delete_todo(
+        &self,
+        request: tonic::Request<DeleteTodoRequest>,
+    ) -> Result<tonic::Response<DeleteTodoResponse>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let request = request.into_inner();
+        adapter::delete_todo(&self.tu, ctx, request.id, request.expected_version)
+            .await
+            .map_err(to_status)?;
+        Ok(tonic::Response::new(DeleteTodoResponse {}))
+    }
+
+    async fn complete_todo(
+        &self,
+        request: tonic::Request<CompleteTodoRequest>,
+    ) -> Result<tonic::Response<CompleteTodoResponse>, tonic::Status> {
+        let ctx = from_metadata(request.metadata());
+        let id = request.into_inner().id;
+        let completed = self.tu.complete(ctx, id).await.map_err(to_status)?;
+        let response = CompleteTodoResponse {
+            todo: Some(completed.todo.into()),
+            next: completed.next.map(|next| next.into()),
+        };
+        Ok(tonic::Response::new(response))
+    }
+}
@@ -0,0 +1,79 @@
+//! Lets browsers call the gRPC server directly: gRPC-Web, translated into gRPC by
+//! [`GrpcWebLayer`], and the CORS both gRPC-Web and the transcoded JSON methods need.
+
+use std::time::Duration;
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+pub use tonic_web::GrpcWebLayer;
+
+use crate::context::{ACTOR_HEADER, IDEMPOTENCY_KEY_HEADER, REQUEST_ID_HEADER};
+
+/// Allows the `allowed_origins`, and no other origin, to call with credentials and the
+/// metadata the services read, and to read the gRPC status and the deprecation of the
+/// deprecated services.
+pub fn cors(allowed_origins: Vec<HeaderValue>) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(allowed_origins))
+        .allow_credentials(true)
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            HeaderName::from_static("x-grpc-web"),
+            HeaderName::from_static("x-user-agent"),
+            HeaderName::from_static("grpc-timeout"),
+            HeaderName::from_static(ACTOR_HEADER),
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+        ])
+        .expose_headers([
+            HeaderName::from_static("grpc-status"),
+            HeaderName::from_static("grpc-message"),
+            HeaderName::from_static("grpc-status-details-bin"),
+            HeaderName::from_static("deprecation"),
+        ])
+        .max_age(Duration::from_secs(24 * 60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use std::convert::Infallible;
+    use tower::{service_fn, Layer, ServiceExt};
+
+    async fn allowed_origin(origin: &str) -> Option<HeaderValue> {
+        let service = cors(vec![HeaderValue::from_static("https://app.example.com")]).layer(
+            service_fn(|_: Request<Body>| async {
+                Ok::<_, Infallible>(axum::http::Response::new(Body::empty()))
+            }),
+        );
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/todo.TodoService/ListTodos")
+            .header(header::ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_only_the_configured_origins() {
+        assert_eq!(
+            allowed_origin("https://app.example.com").await,
+            Some(HeaderValue::from_static("https://app.example.com"))
+        );
+        assert_eq!(allowed_origin("https://evil.example.com").await, None);
+    }
+}
@@ -0,0 +1,381 @@
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response as HttpResponse},
+    Extension, Json,
+};
+use domain::entity::audit::Protocol;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use use_case::{
+    dto::{batch::MAX_BATCH_SIZE, todo::TodoDto},
+    traits::todo::TodoUseCase,
+};
+
+use crate::{
+    adapter,
+    context::{from_headers, idempotency_key_from_headers, normalize_idempotency_key},
+    rest::{
+        object::{BatchTodosPayload, Todo},
+        v2::object::CompletedTodo,
+    },
+};
+
+use super::{
+    object::{
+        BatchResult, CreateTodoParams, DeleteTodoParams, DeletedTodo, ErrorObject, NoParams,
+        Response, TimezoneParams, TodoIdParams, UpdateTodoParams, VERSION,
+    },
+    openrpc::openrpc,
+};
+
+/// The methods of the endpoint, one per method of [`TodoUseCase`] plus `rpc.discover`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Create,
+    Get,
+    List,
+    Update,
+    Delete,
+    Complete,
+    Batch,
+    ListOverdue,
+    ListDueToday,
+    ListDueThisWeek,
+    ListHighPriority,
+    Discover,
+}
+
+impl Method {
+    pub const ALL: &'static [Method] = &[
+        Method::Create,
+        Method::Get,
+        Method::List,
+        Method::Update,
+        Method::Delete,
+        Method::Complete,
+        Method::Batch,
+        Method::ListOverdue,
+        Method::ListDueToday,
+        Method::ListDueThisWeek,
+        Method::ListHighPriority,
+        Method::Discover,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Method::Create => "todo.create",
+            Method::Get => "todo.get",
+            Method::List => "todo.list",
+            Method::Update => "todo.update",
+            Method::Delete => "todo.delete",
+            Method::Complete => "todo.complete",
+            Method::Batch => "todo.batch",
+            Method::ListOverdue => "todo.listOverdue",
+            Method::ListDueToday => "todo.listDueToday",
+            Method::ListDueThisWeek => "todo.listDueThisWeek",
+            Method::ListHighPriority => "todo.listHighPriority",
+            Method::Discover => "rpc.discover",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|method| method.name() == name)
+    }
+}
+
+/// Serves `POST /rpc`: a single request or a batch of up to `MAX_BATCH_SIZE` of them, run in
+/// order. Params are passed by name. Answers `204 No Content` when every request was a
+/// notification, and `200 OK` otherwise, whether the calls succeeded or not.
+pub async fn rpc<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> HttpResponse {
+    let call = match serde_json::from_slice::<Value>(&body) {
+        Ok(call) => call,
+        Err(e) => {
+            let error = ErrorObject::parse_error(e.to_string());
+            return Json(Response::new(Value::Null, Err(error))).into_response();
+        }
+    };
+    let Value::Array(calls) = call else {
+        return match handle(&tu, &headers, call).await {
+            Some(response) => Json(response).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        };
+    };
+    if calls.is_empty() || calls.len() > MAX_BATCH_SIZE {
+        let error = ErrorObject::invalid_request(&format!(
+            "a batch holds 1 to {} requests",
+            MAX_BATCH_SIZE
+        ));
+        return Json(Response::new(Value::Null, Err(error))).into_response();
+    }
+    let mut responses = vec![];
+    for call in calls {
+        responses.extend(handle(&tu, &headers, call).await);
+    }
+    if responses.is_empty() {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+    Json(responses).into_response()
+}
+
+/// Runs one request; `None` for a notification, i.e. a valid request without an `id`.
+async fn handle<TU: TodoUseCase>(tu: &TU, headers: &HeaderMap, call: Value) -> Option<Response> {
+    let invalid = |id: Value, detail: &str| {
+        Some(Response::new(id, Err(ErrorObject::invalid_request(detail))))
+    };
+    let Value::Object(mut call) = call else {
+        return invalid(Value::Null, "a request must be an object");
+    };
+    let id = call.remove("id");
+    if !matches!(
+        id,
+        None | Some(Value::Null | Value::String(_) | Value::Number(_))
+    ) {
+        return invalid(Value::Null, "id must be a string, a number or null");
+    }
+    let reply_to = id.clone().unwrap_or(Value::Null);
+    if call.remove("jsonrpc") != Some(Value::from(VERSION)) {
+        return invalid(reply_to, "jsonrpc must be \"2.0\"");
+    }
+    let Some(Value::String(method)) = call.remove("method") else {
+        return invalid(reply_to, "method must be a string");
+    };
+    let outcome = match call.remove("params") {
+        None => dispatch(tu, headers, &method, Value::Object(Map::new())).await,
+        Some(params @ Value::Object(_)) => dispatch(tu, headers, &method, params).await,
+        Some(Value::Array(_)) => Err(ErrorObject::invalid_params(
+            "params must be passed by name".to_string(),
+        )),
+        Some(_) => return invalid(reply_to, "params must be an object or an array"),
+    };
+    id.map(|id| Response::new(id, outcome))
+}
+
+async fn dispatch<TU: TodoUseCase>(
+    tu: &TU,
+    headers: &HeaderMap,
+    method: &str,
+    params: Value,
+) -> Result<Value, ErrorObject> {
+    let Some(method) = Method::from_name(method) else {
+        return Err(ErrorObject::method_not_found(method));
+    };
+    let ctx = || from_headers(headers, Protocol::JsonRpc);
+    match method {
+        Method::Create => {
+            let params: CreateTodoParams = parse(params)?;
+            let idempotency_key = normalize_idempotency_key(params.idempotency_key.as_deref())
+                .or_else(|| idempotency_key_from_headers(headers));
+            let todo = tu.create(ctx(), params.into(), idempotency_key).await?;
+            result(Todo::from(todo))
+        }
+        Method::Get => {
+            let TodoIdParams { id } = parse(params)?;
+            result(Todo::from(adapter::find_todo(tu, id).await?))
+        }
+        Method::List => {
+            let NoParams {} = parse(params)?;
+            todos(tu.find_all().await?)
+        }
+        Method::Update => {
+            let params: UpdateTodoParams = parse(params)?;
+            let expected_version = params.expected_version;
+            let todo = tu.update(ctx(), params.into(), expected_version).await?;
+            result(Todo::from(todo))
+        }
+        Method::Delete => {
+            let DeleteTodoParams {
+                id,
+                expected_version,
+            } = parse(params)?;
+            let id = adapter::delete_todo(tu, ctx(), id, expected_version).await?;
+            result(DeletedTodo { id })
+        }
+        Method::Complete => {
+            let TodoIdParams { id } = parse(params)?;
+            result(CompletedTodo::from(tu.complete(ctx(), id).await?))
+        }
+        Method::Batch => {
+            let BatchTodosPayload { mode, operations } = parse(params)?;
+            let operations = operations
+                .into_iter()
+                .map(|operation| operation.into())
+                .collect();
+            let batch = tu.batch(ctx(), operations, mode.into()).await?;
+            result(BatchResult {
+                committed: batch.committed,
+                results: batch.items.into_iter().map(|item| item.into()).collect(),
+            })
+        }
+        Method::ListOverdue => {
+            let NoParams {} = parse(params)?;
+            todos(tu.find_overdue().await?)
+        }
+        Method::ListDueToday => {
+            let TimezoneParams { timezone } = parse(params)?;
+            todos(tu.find_due_today(timezone).await?)
+        }
+        Method::ListDueThisWeek => {
+            let TimezoneParams { timezone } = parse(params)?;
+            todos(tu.find_due_this_week(timezone).await?)
+        }
+        Method::ListHighPriority => {
+            let NoParams {} = parse(params)?;
+            todos(tu.find_high_priority().await?)
+        }
+        Method::Discover => {
+            let NoParams {} = parse(params)?;
+            Ok(openrpc())
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, ErrorObject> {
+    serde_json::from_value(params).map_err(|e| ErrorObject::invalid_params(e.to_string()))
+}
+
+fn result<T: Serialize>(result: T) -> Result<Value, ErrorObject> {
+    serde_json::to_value(result).map_err(|_| ErrorObject::internal_error())
+}
+
+fn todos(todos: Vec<TodoDto>) -> Result<Value, ErrorObject> {
+    result(todos.into_iter().map(Todo::from).collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockTodoUseCase;
+    use serde_json::json;
+
+    /// The status and the JSON body, `Null` when empty, of `body` posted to the endpoint.
+    async fn post(tu: &MockTodoUseCase, body: &str) -> (StatusCode, Value) {
+        let response = rpc(
+            Extension(tu.clone()),
+            HeaderMap::new(),
+            Bytes::from(body.to_string()),
+        )
+        .await;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        if body.is_empty() {
+            return (status, Value::Null);
+        }
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error() {
+        let tu = MockTodoUseCase::default();
+        let (status, response) = post(&tu, r#"{"jsonrpc": "2.0", "method""#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["error"]["code"], -32700);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request() {
+        let tu = MockTodoUseCase::default();
+        let (_, response) =
+            post(&tu, r#"{"jsonrpc": "1.0", "method": "todo.list", "id": 1}"#).await;
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], 1);
+        let (_, response) = post(&tu, r#"{"jsonrpc": "2.0", "method": 7, "id": "a"}"#).await;
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], "a");
+        let (_, response) = post(
+            &tu,
+            r#"{"jsonrpc": "2.0", "method": "todo.list", "id": {}}"#,
+        )
+        .await;
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_method_not_found() {
+        let tu = MockTodoUseCase::default();
+        let (_, response) = post(
+            &tu,
+            r#"{"jsonrpc": "2.0", "method": "todo.remove", "id": 1}"#,
+        )
+        .await;
+        assert_eq!(response["error"]["code"], -32601);
+        assert_eq!(response["id"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_params() {
+        let tu = MockTodoUseCase::default();
+        let calls = [
+            r#"{"jsonrpc": "2.0", "method": "todo.create", "params": {}, "id": 1}"#,
+            r#"{"jsonrpc": "2.0", "method": "todo.get", "params": [1], "id": 1}"#,
+            r#"{"jsonrpc": "2.0", "method": "todo.list", "params": {"page": 2}, "id": 1}"#,
+        ];
+        for call in calls {
+            let (_, response) = post(&tu, call).await;
+            assert_eq!(response["error"]["code"], -32602, "{}", call);
+        }
+        assert!(tu.todos().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_notifications_answer_no_content() {
+        let tu = MockTodoUseCase::default();
+        let notification =
+            r#"{"jsonrpc": "2.0", "method": "todo.create", "params": {"title": "Buy milk"}}"#;
+        let (status, response) = post(&tu, notification).await;
+        assert_eq!((status, response), (StatusCode::NO_CONTENT, Value::Null));
+        let batch = format!("[{}, {}]", notification, notification);
+        let (status, response) = post(&tu, &batch).await;
+        assert_eq!((status, response), (StatusCode::NO_CONTENT, Value::Null));
+        assert_eq!(tu.todos().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_empty_batch() {
+        let tu = MockTodoUseCase::default();
+        let (status, response) = post(&tu, "[]").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["error"]["code"], -32600);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_mixed_batch_keeps_the_ids() {
+        let tu = MockTodoUseCase::with_titles(&["Buy milk"]);
+        let batch = json!([
+            { "jsonrpc": "2.0", "method": "todo.get", "params": { "id": 1 }, "id": "get" },
+            { "jsonrpc": "2.0", "method": "todo.create", "params": { "title": "Water plants" } },
+            { "jsonrpc": "2.0", "method": "todo.get", "params": { "id": 9 }, "id": 2 },
+            { "jsonrpc": "2.0", "method": "todo.remove", "id": 3 },
+            1,
+            { "jsonrpc": "2.0", "method": "todo.list", "id": null },
+        ]);
+        let (status, response) = post(&tu, &batch.to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        let Value::Array(responses) = response else {
+            panic!("a batch is answered with an array");
+        };
+        let ids = responses
+            .iter()
+            .map(|response| response["id"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![json!("get"), json!(2), json!(3), Value::Null, Value::Null]
+        );
+        assert_eq!(responses[0]["result"]["title"], "Buy milk");
+        assert!(responses[1]["error"].is_object());
+        assert_eq!(responses[2]["error"]["code"], -32601);
+        assert_eq!(responses[3]["error"]["code"], -32600);
+        assert_eq!(responses[4]["result"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,6 @@
+//! JSON-RPC 2.0 (https://www.jsonrpc.org/specification) at `POST /rpc`, with methods
+//! mirroring [`use_case::traits::todo::TodoUseCase`] and an OpenRPC document answering
+//! `rpc.discover`.
+pub mod handler;
+pub mod object;
+pub mod openrpc;
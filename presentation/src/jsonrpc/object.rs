@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use use_case::{
+    dto::todo::{CreateTodoDto, UpdateTodoDto},
+    error::UseCaseError,
+};
+
+use crate::{
+    adapter::present,
+    error::PresentationalError,
+    rest::object::{BatchItemResult, Priority},
+};
+
+pub const VERSION: &str = "2.0";
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Codes of the errors of the todo methods, from the range left to implementations.
+pub const NOT_FOUND: i64 = -32004;
+pub const CONFLICT: i64 = -32009;
+pub const IDEMPOTENCY_KEY_REUSED: i64 = -32022;
+
+/// The answer to a request with an `id`; notifications get none.
+#[derive(Debug, Clone, Serialize)]
+pub struct Response {
+    pub jsonrpc: &'static str,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+    pub id: Value,
+}
+
+impl Response {
+    pub fn new(id: Value, outcome: Result<Value, ErrorObject>) -> Self {
+        let outcome = match outcome {
+            Ok(result) => Outcome::Result(result),
+            Err(error) => Outcome::Error(error),
+        };
+        Self {
+            jsonrpc: VERSION,
+            outcome,
+            id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Result(Value),
+    Error(ErrorObject),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorObject {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl ErrorObject {
+    pub fn new(code: i64, message: &str, data: Option<Value>) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data,
+        }
+    }
+
+    pub fn parse_error(detail: String) -> Self {
+        Self::new(PARSE_ERROR, "Parse error", Some(json!(detail)))
+    }
+
+    pub fn invalid_request(detail: &str) -> Self {
+        Self::new(INVALID_REQUEST, "Invalid Request", Some(json!(detail)))
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, "Method not found", Some(json!(method)))
+    }
+
+    pub fn invalid_params(detail: String) -> Self {
+        Self::new(INVALID_PARAMS, "Invalid params", Some(json!(detail)))
+    }
+
+    pub fn internal_error() -> Self {
+        Self::new(INTERNAL_ERROR, "Internal error", None)
+    }
+}
+
+impl From<UseCaseError> for ErrorObject {
+    fn from(error: UseCaseError) -> Self {
+        let entity = |entity_type: String, entity_id: i64| {
+            Some(json!({ "entity_type": entity_type, "entity_id": entity_id }))
+        };
+        match error {
+            UseCaseError::Validation(detail) => Self::invalid_params(detail),
+            UseCaseError::NotFound {
+                entity_type,
+                entity_id,
+            } => Self::new(
+                NOT_FOUND,
+                &PresentationalError::NotFound.to_string(),
+                entity(entity_type, entity_id),
+            ),
+            UseCaseError::Conflict {
+                entity_type,
+                entity_id,
+            } => Self::new(
+                CONFLICT,
+                &PresentationalError::Conflict.to_string(),
+                entity(entity_type, entity_id),
+            ),
+            UseCaseError::IdempotencyKeyReused(key) => Self::new(
+                IDEMPOTENCY_KEY_REUSED,
+                &PresentationalError::IdempotencyKeyReused.to_string(),
+                Some(json!(key)),
+            ),
+            _ => Self::internal_error(),
+        }
+    }
+}
+
+/// Params of the methods that take none.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NoParams {}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateTodoParams {
+    pub title: String,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    /// Also read from the `Idempotency-Key` header, which this takes precedence over.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+impl From<CreateTodoParams> for CreateTodoDto {
+    fn from(params: CreateTodoParams) -> Self {
+        Self {
+            title: params.title,
+            due_at: params.due_at,
+            priority: params.priority.into(),
+            recurrence: params.recurrence,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateTodoParams {
+    pub id: i64,
+    pub title: String,
+    /// Left out, the stored value is kept; `null` clears it.
+    #[serde(default, deserialize_with = "present")]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// Left out, the stored value is kept; `null` clears it.
+    #[serde(default, deserialize_with = "present")]
+    pub recurrence: Option<Option<String>>,
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
+
+impl From<UpdateTodoParams> for UpdateTodoDto {
+    fn from(params: UpdateTodoParams) -> Self {
+        Self {
+            id: params.id,
+            title: Some(params.title),
+            due_at: params.due_at,
+            priority: params.priority.map(|priority| priority.into()),
+            recurrence: params.recurrence,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TodoIdParams {
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteTodoParams {
+    pub id: i64,
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimezoneParams {
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeletedTodo {
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+    /// `false` when an atomic batch was rolled back; `results` then ends with the rejected
+    /// operation.
+    pub committed: bool,
+    pub results: Vec<BatchItemResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_responses_carry_either_a_result_or_an_error() {
+        let response = Response::new(json!(1), Ok(json!({ "id": 7 })));
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({ "jsonrpc": "2.0", "result": { "id": 7 }, "id": 1 })
+        );
+        let not_found = UseCaseError::NotFound {
+            entity_type: "todo".to_string(),
+            entity_id: 7,
+        };
+        let response = Response::new(json!("a"), Err(not_found.into()));
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({
+                "jsonrpc": "2.0",
+                "error": {
+                    "code": NOT_FOUND,
+                    "message": "Not Found",
+                    "data": { "entity_type": "todo", "entity_id": 7 },
+                },
+                "id": "a",
+            })
+        );
+    }
+}
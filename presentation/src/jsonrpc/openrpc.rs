@@ -0,0 +1,302 @@
+//! The OpenRPC 1.2 document of the JSON-RPC endpoint, which `rpc.discover` answers with.
+//! Schemas are the ones of the OpenAPI document, through [`ApiSchema`]; params are listed
+//! field by field and destructured the same way, so they follow [`super::object`].
+
+use axum::{response::IntoResponse, Json};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::{
+    context::{ACTOR_HEADER, IDEMPOTENCY_KEY_HEADER, REQUEST_ID_HEADER},
+    error::PresentationalError,
+    rest::{
+        object::{BatchItemResult, BatchModePayload, BatchOperationPayload, BatchTodosPayload},
+        object::{Priority, Todo},
+        openapi::{defaulted, define, required, ApiSchema, Schemas},
+        v2::object::CompletedTodo,
+    },
+};
+
+use super::{
+    handler::Method,
+    object::{
+        BatchResult, CreateTodoParams, DeleteTodoParams, DeletedTodo, NoParams, TimezoneParams,
+        TodoIdParams, UpdateTodoParams, CONFLICT, IDEMPOTENCY_KEY_REUSED, INVALID_PARAMS,
+        NOT_FOUND,
+    },
+};
+
+const NOT_FOUND_ERROR: (i64, PresentationalError) = (NOT_FOUND, PresentationalError::NotFound);
+const CONFLICT_ERROR: (i64, PresentationalError) = (CONFLICT, PresentationalError::Conflict);
+
+/// The params of a method, as OpenRPC content descriptors.
+trait Params {
+    fn params(schemas: &mut Schemas) -> Vec<Value>;
+}
+
+/// A content descriptor per field of each params struct, listed as `name: Type`.
+macro_rules! params {
+    ($($name:ident { $($(#[$default:ident])? $field:ident: $ty:ty),* $(,)? })*) => {$(
+        impl Params for $name {
+            fn params(#[allow(unused_variables)] schemas: &mut Schemas) -> Vec<Value> {
+                vec![$(
+                    json!({
+                        "name": stringify!($field),
+                        "schema": <$ty as ApiSchema>::schema(schemas),
+                        "required": required::<$ty>(defaulted!($($default)?)),
+                    })
+                ),*]
+            }
+        }
+
+        const _: fn(&$name) = |value| {
+            let $name { $($field),* } = value;
+            $(let _: &$ty = $field;)*
+        };
+    )*};
+}
+
+params! {
+    NoParams {}
+    CreateTodoParams {
+        title: String,
+        #[default] due_at: Option<DateTime<Utc>>,
+        #[default] priority: Priority,
+        #[default] recurrence: Option<String>,
+        #[default] idempotency_key: Option<String>,
+    }
+    UpdateTodoParams {
+        id: i64,
+        title: String,
+        #[default] due_at: Option<Option<DateTime<Utc>>>,
+        #[default] priority: Option<Priority>,
+        #[default] recurrence: Option<Option<String>>,
+        #[default] expected_version: Option<i64>,
+    }
+    TodoIdParams { id: i64 }
+    DeleteTodoParams { id: i64, #[default] expected_version: Option<i64> }
+    TimezoneParams { #[default] timezone: Option<String> }
+    BatchTodosPayload {
+        #[default] mode: BatchModePayload,
+        operations: Vec<BatchOperationPayload>,
+    }
+}
+
+impl ApiSchema for DeletedTodo {
+    fn schema(schemas: &mut Schemas) -> Value {
+        define(schemas, "DeletedTodo", |schemas| {
+            json!({
+                "type": "object",
+                "properties": { "id": i64::schema(schemas) },
+                "required": ["id"],
+            })
+        })
+    }
+}
+
+const _: fn(&DeletedTodo) = |value| {
+    let DeletedTodo { id } = value;
+    let _: &i64 = id;
+};
+
+impl ApiSchema for BatchResult {
+    fn schema(schemas: &mut Schemas) -> Value {
+        define(schemas, "BatchResult", |schemas| {
+            json!({
+                "type": "object",
+                "properties": {
+                    "committed": bool::schema(schemas),
+                    "results": Vec::<BatchItemResult>::schema(schemas),
+                },
+                "required": ["committed", "results"],
+            })
+        })
+    }
+}
+
+const _: fn(&BatchResult) = |value| {
+    let BatchResult { committed, results } = value;
+    let _: &bool = committed;
+    let _: &Vec<BatchItemResult> = results;
+};
+
+fn method<P: Params, R: ApiSchema>(
+    schemas: &mut Schemas,
+    method: Method,
+    summary: &str,
+    result: &str,
+    errors: &[(i64, PresentationalError)],
+) -> Value {
+    let errors: Vec<Value> = [(INVALID_PARAMS, "Invalid params".to_string())]
+        .into_iter()
+        .chain(
+            errors
+                .iter()
+                .map(|(code, error)| (*code, error.to_string())),
+        )
+        .map(|(code, message)| json!({ "code": code, "message": message }))
+        .collect();
+    json!({
+        "name": method.name(),
+        "summary": summary,
+        "paramStructure": "by-name",
+        "params": P::params(schemas),
+        "result": { "name": result, "schema": R::schema(schemas) },
+        "errors": errors,
+    })
+}
+
+/// The OpenRPC document of the JSON-RPC endpoint, as `rpc.discover` answers it.
+pub fn openrpc() -> Value {
+    let mut schemas = Schemas::new();
+    let schemas = &mut schemas;
+    let methods: Vec<Value> = Method::ALL
+        .iter()
+        .map(|&name| match name {
+            Method::Create => method::<CreateTodoParams, Todo>(
+                schemas,
+                name,
+                "Create a todo",
+                "todo",
+                &[(IDEMPOTENCY_KEY_REUSED, PresentationalError::IdempotencyKeyReused)],
+            ),
+            Method::Get => {
+                method::<TodoIdParams, Todo>(schemas, name, "Get a todo", "todo", &[NOT_FOUND_ERROR])
+            }
+            Method::List => method::<NoParams, Vec<Todo>>(schemas, name, "List todos", "todos", &[]),
+            Method::Update => method::<UpdateTodoParams, Todo>(
+                schemas,
+                name,
+                "Update a todo",
+                "todo",
+                &[NOT_FOUND_ERROR, CONFLICT_ERROR],
+            ),
+            Method::Delete => method::<DeleteTodoParams, DeletedTodo>(
+                schemas,
+                name,
+                "Move a todo to the trash",
+                "deleted",
+                &[NOT_FOUND_ERROR, CONFLICT_ERROR],
+            ),
+            Method::Complete => method::<TodoIdParams, CompletedTodo>(
+                schemas,
+                name,
+                "Complete a todo, spawning the next occurrence of a recurring one",
+                "completed",
+                &[NOT_FOUND_ERROR],
+            ),
+            Method::Batch => method::<BatchTodosPayload, BatchResult>(
+                schemas,
+                name,
+                "Run several operations in one transaction",
+                "batch",
+                &[],
+            ),
+            Method::ListOverdue => method::<NoParams, Vec<Todo>>(
+                schemas,
+                name,
+                "List todos past their due time",
+                "todos",
+                &[],
+            ),
+            Method::ListDueToday => method::<TimezoneParams, Vec<Todo>>(
+                schemas,
+                name,
+                "List todos due today in a timezone",
+                "todos",
+                &[],
+            ),
+            Method::ListDueThisWeek => method::<TimezoneParams, Vec<Todo>>(
+                schemas,
+                name,
+                "List todos due this week in a timezone",
+                "todos",
+                &[],
+            ),
+            Method::ListHighPriority => method::<NoParams, Vec<Todo>>(
+                schemas,
+                name,
+                "List high priority todos",
+                "todos",
+                &[],
+            ),
+            Method::Discover => json!({
+                "name": name.name(),
+                "summary": "This document",
+                "params": [],
+                "result": {
+                    "name": "openrpc",
+                    "schema": {
+                        "$ref": "https://raw.githubusercontent.com/open-rpc/meta-schema/master/schema.json",
+                    },
+                },
+            }),
+        })
+        .collect();
+
+    json!({
+        "openrpc": "1.2.6",
+        "info": {
+            "title": "clean-architecture-playground",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": format!(
+                "Changes are audited under the `{}` and `{}` headers of the HTTP request; \
+                 `todo.create` also reads `{}` when its params carry no `idempotency_key`.",
+                ACTOR_HEADER, REQUEST_ID_HEADER, IDEMPOTENCY_KEY_HEADER
+            ),
+        },
+        "servers": [{ "name": "default", "url": "/rpc" }],
+        "methods": methods,
+        "components": { "schemas": schemas },
+    })
+}
+
+pub async fn openrpc_json() -> impl IntoResponse {
+    Json(openrpc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The committed document; run the tests with `UPDATE_OPENRPC=1` to rewrite it.
+    const SNAPSHOT: &str = include_str!("../../openrpc.json");
+
+    #[test]
+    fn test_openrpc_matches_snapshot() {
+        let document = serde_json::to_string_pretty(&openrpc()).unwrap() + "\n";
+        if std::env::var_os("UPDATE_OPENRPC").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/openrpc.json");
+            std::fs::write(path, &document).unwrap();
+            return;
+        }
+        assert!(
+            document == SNAPSHOT,
+            "the OpenRPC document drifted from presentation/openrpc.json; \
+             review the change and rerun with UPDATE_OPENRPC=1 to accept it"
+        );
+    }
+
+    #[test]
+    fn test_every_method_and_local_reference_is_defined() {
+        let document = openrpc();
+        let names: Vec<&str> = document["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|method| method["name"].as_str().unwrap())
+            .collect();
+        let expected: Vec<&str> = Method::ALL.iter().map(|method| method.name()).collect();
+        assert_eq!(names, expected);
+
+        let text = document.to_string();
+        for reference in text.split("\"#/components/schemas/").skip(1) {
+            let name = &reference[..reference.find('"').unwrap()];
+            assert!(
+                document["components"]["schemas"].get(name).is_some(),
+                "{} is not defined",
+                name
+            );
+        }
+    }
+}
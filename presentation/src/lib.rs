@@ -1,4 +1,12 @@
+mod adapter;
+pub mod caldav;
+pub mod connect;
+pub mod context;
+pub mod deprecation;
 pub mod error;
 pub mod graphql;
 pub mod grpc;
+pub mod jsonrpc;
 pub mod rest;
+#[cfg(test)]
+mod testing;
@@ -1,11 +1,42 @@
-use axum::{extract::Path, http::StatusCode, response::IntoResponse, Extension, Json};
-use use_case::{error::UseCaseError, traits::todo::TodoUseCase};
+use std::{collections::VecDeque, convert::Infallible, time::Duration};
 
-use crate::error::PresentationalError;
+use axum::{
+    body::StreamBody,
+    extract::{rejection::JsonRejection, Path, Query},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Extension, Json,
+};
+use domain::entity::audit::Protocol;
+use futures_util::{stream, StreamExt};
+use use_case::{
+    dto::{event_log::LoggedEventDto, import_export::TodoFormat, todo::TodoDto},
+    error::UseCaseError,
+    event_bus::EventListener,
+    traits::{
+        audit::AuditUseCase, calendar_feed::CalendarFeedUseCase, event_log::EventLogUseCase,
+        import_export::ImportExportUseCase, reminder::ReminderUseCase, revision::RevisionUseCase,
+        todo::TodoUseCase, trash::TrashUseCase, webhook::WebhookUseCase,
+    },
+};
+
+use crate::{
+    context::{from_headers, idempotency_key_from_headers},
+    error::PresentationalError,
+};
 
 use super::object::{
-    CreateTodoPayload, CreateTodoResponse, DeleteTodoPayload, DeleteTodoResponse, Todo,
-    TodoResponse, TodosResponse, UpdateTodoPayload, UpdateTodoResponse,
+    AuditLogQuery, AuditLogResponse, BatchItemResult, BatchTodosPayload, BatchTodosResponse,
+    CalendarFeedResponse, CalendarFeedsResponse, CompleteTodoResponse, CreateReminderPayload,
+    CreateTodoPayload, CreateTodoResponse, CreateWebhookPayload, DeleteCalendarFeedResponse,
+    DeleteReminderResponse, DeleteTodoPayload, DeleteTodoResponse, DeleteWebhookResponse,
+    ExportQuery, ExportResponse, ImportQuery, ImportResponse, ReminderResponse, RemindersResponse,
+    RevisionDiffQuery, RevisionDiffResponse, RevisionsResponse, TimezoneQuery, Todo,
+    TodoEventsResponse, TodoResponse, TodosResponse, TrashResponse, UpdateTodoPayload,
+    UpdateTodoResponse, WebhookDeliveriesResponse, WebhookResponse, WebhooksResponse,
 };
 
 pub async fn get_todos<TU: TodoUseCase>(Extension(tu): Extension<TU>) -> impl IntoResponse {
@@ -66,6 +97,7 @@ pub async fn get_todo<TU: TodoUseCase>(
                 entity_type: _,
             } => (
                 StatusCode::NOT_FOUND,
+                HeaderMap::new(),
                 Json(TodoResponse {
                     todo: None,
                     error: Some(PresentationalError::NotFound),
@@ -73,6 +105,7 @@ pub async fn get_todo<TU: TodoUseCase>(
             ),
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
+                HeaderMap::new(),
                 Json(TodoResponse {
                     todo: None,
                     error: Some(PresentationalError::InternalServerError),
@@ -86,6 +119,7 @@ pub async fn get_todo<TU: TodoUseCase>(
             let todo: Todo = todo.into();
             return (
                 StatusCode::OK,
+                etag(todo.version),
                 Json(TodoResponse {
                     todo: Some(todo),
                     error: None,
@@ -94,6 +128,7 @@ pub async fn get_todo<TU: TodoUseCase>(
         } else {
             return (
                 StatusCode::OK,
+                HeaderMap::new(),
                 Json(TodoResponse {
                     todo: None,
                     error: Some(PresentationalError::NotFound),
@@ -103,6 +138,7 @@ pub async fn get_todo<TU: TodoUseCase>(
     }
     (
         StatusCode::INTERNAL_SERVER_ERROR,
+        HeaderMap::new(),
         Json(TodoResponse {
             todo: None,
             error: Some(PresentationalError::InternalServerError),
@@ -112,15 +148,19 @@ pub async fn get_todo<TU: TodoUseCase>(
 
 pub async fn create_todo<TU: TodoUseCase>(
     Extension(tu): Extension<TU>,
+    headers: HeaderMap,
     Json(payload): Json<CreateTodoPayload>,
 ) -> impl IntoResponse {
-    let todo = tu.create(payload.into()).await;
-    if todo.is_err() {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    let idempotency_key = idempotency_key_from_headers(&headers);
+    let todo = tu.create(ctx, payload.into(), idempotency_key).await;
+    if let Err(err) = todo {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            status_code(&err),
+            HeaderMap::new(),
             Json(CreateTodoResponse {
                 todo: None,
-                error: Some(PresentationalError::InternalServerError),
+                error: Some(err.into()),
             }),
         );
     }
@@ -128,6 +168,7 @@ pub async fn create_todo<TU: TodoUseCase>(
         let todo: Todo = todo.into();
         return (
             StatusCode::OK,
+            etag(todo.version),
             Json(CreateTodoResponse {
                 todo: Some(todo),
                 error: None,
@@ -136,6 +177,7 @@ pub async fn create_todo<TU: TodoUseCase>(
     }
     (
         StatusCode::INTERNAL_SERVER_ERROR,
+        HeaderMap::new(),
         Json(CreateTodoResponse {
             todo: None,
             error: Some(PresentationalError::InternalServerError),
@@ -145,15 +187,31 @@ pub async fn create_todo<TU: TodoUseCase>(
 
 pub async fn update_todo<TU: TodoUseCase>(
     Extension(tu): Extension<TU>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateTodoPayload>,
 ) -> impl IntoResponse {
-    let todo = tu.update(payload.into()).await;
-    if todo.is_err() {
+    let expected_version = match expected_version(&headers) {
+        Ok(expected_version) => expected_version,
+        Err(err) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                HeaderMap::new(),
+                Json(UpdateTodoResponse {
+                    todo: None,
+                    error: Some(err),
+                }),
+            )
+        }
+    };
+    let ctx = from_headers(&headers, Protocol::Rest);
+    let todo = tu.update(ctx, payload.into(), expected_version).await;
+    if let Err(err) = todo {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            status_code(&err),
+            HeaderMap::new(),
             Json(UpdateTodoResponse {
                 todo: None,
-                error: Some(PresentationalError::InternalServerError),
+                error: Some(err.into()),
             }),
         );
     }
@@ -161,6 +219,7 @@ pub async fn update_todo<TU: TodoUseCase>(
         let todo: Todo = todo.into();
         return (
             StatusCode::OK,
+            etag(todo.version),
             Json(UpdateTodoResponse {
                 todo: Some(todo),
                 error: None,
@@ -169,6 +228,7 @@ pub async fn update_todo<TU: TodoUseCase>(
     }
     (
         StatusCode::INTERNAL_SERVER_ERROR,
+        HeaderMap::new(),
         Json(UpdateTodoResponse {
             todo: None,
             error: Some(PresentationalError::InternalServerError),
@@ -178,16 +238,14 @@ pub async fn update_todo<TU: TodoUseCase>(
 
 pub async fn delete_todo<TU: TodoUseCase>(
     Extension(tu): Extension<TU>,
+    headers: HeaderMap,
     Json(payload): Json<DeleteTodoPayload>,
 ) -> impl IntoResponse {
     let todo_id = payload.id;
 
     // check if todo exists
     let find_todo_result = tu.find_by_id(todo_id).await;
-    let todo = match find_todo_result {
-        Ok(todo) => todo,
-        Err(_) => None,
-    };
+    let todo = find_todo_result.ok().flatten();
 
     if todo.is_none() {
         return (
@@ -200,7 +258,8 @@ pub async fn delete_todo<TU: TodoUseCase>(
     }
     let todo = todo.unwrap();
 
-    let delete_todo_result = tu.delete(payload.id).await;
+    let ctx = from_headers(&headers, Protocol::Rest);
+    let delete_todo_result = tu.delete(ctx, payload.id, None).await;
     if delete_todo_result.is_err() {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -228,3 +287,974 @@ pub async fn delete_todo<TU: TodoUseCase>(
         }),
     )
 }
+
+/// `POST /todos:batch`. The router cannot match a literal `:batch`, so the route captures
+/// every `/todos…` path without a slash here and the others are answered with 404.
+pub async fn batch_todos<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    uri: Uri,
+    headers: HeaderMap,
+    payload: Result<Json<BatchTodosPayload>, JsonRejection>,
+) -> impl IntoResponse {
+    let rejection = |status, error| {
+        (
+            status,
+            Json(BatchTodosResponse {
+                committed: false,
+                results: vec![],
+                error: Some(error),
+            }),
+        )
+    };
+    if !matches!(
+        uri.path(),
+        "/todos:batch" | "/todos%3Abatch" | "/todos%3abatch"
+    ) {
+        return rejection(StatusCode::NOT_FOUND, PresentationalError::NotFound);
+    }
+    let Ok(Json(payload)) = payload else {
+        return rejection(StatusCode::BAD_REQUEST, PresentationalError::BadRequest);
+    };
+    let ctx = from_headers(&headers, Protocol::Rest);
+    let operations = payload
+        .operations
+        .into_iter()
+        .map(|operation| operation.into())
+        .collect();
+    match tu.batch(ctx, operations, payload.mode.into()).await {
+        Ok(result) => {
+            // a rolled back batch answers with the status of the operation that stopped it
+            let status = match result.items.last() {
+                Some(item) if !result.committed => match &item.result {
+                    Err(err) => status_code(err),
+                    Ok(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                },
+                _ => StatusCode::OK,
+            };
+            (
+                status,
+                Json(BatchTodosResponse {
+                    committed: result.committed,
+                    results: result
+                        .items
+                        .into_iter()
+                        .map(BatchItemResult::from)
+                        .collect(),
+                    error: None,
+                }),
+            )
+        }
+        Err(err) => rejection(status_code(&err), err.into()),
+    }
+}
+
+pub async fn get_overdue_todos<TU: TodoUseCase>(Extension(tu): Extension<TU>) -> impl IntoResponse {
+    todos_response(tu.find_overdue().await)
+}
+
+pub async fn get_todos_due_today<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Query(query): Query<TimezoneQuery>,
+) -> impl IntoResponse {
+    todos_response(tu.find_due_today(query.timezone).await)
+}
+
+pub async fn get_todos_due_this_week<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Query(query): Query<TimezoneQuery>,
+) -> impl IntoResponse {
+    todos_response(tu.find_due_this_week(query.timezone).await)
+}
+
+pub async fn get_high_priority_todos<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+) -> impl IntoResponse {
+    todos_response(tu.find_high_priority().await)
+}
+
+pub async fn complete_todo<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match tu.complete(ctx, id).await {
+        Ok(completed) => (
+            StatusCode::OK,
+            etag(completed.todo.version),
+            Json(CompleteTodoResponse {
+                todo: Some(completed.todo.into()),
+                next: completed.next.map(|next| next.into()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            HeaderMap::new(),
+            Json(CompleteTodoResponse {
+                todo: None,
+                next: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn create_reminder<RU: ReminderUseCase>(
+    Extension(ru): Extension<RU>,
+    Path(todo_id): Path<i64>,
+    Json(payload): Json<CreateReminderPayload>,
+) -> impl IntoResponse {
+    match ru.create(payload.into_dto(todo_id)).await {
+        Ok(reminder) => (
+            StatusCode::OK,
+            Json(ReminderResponse {
+                reminder: Some(reminder.into()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(ReminderResponse {
+                reminder: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_reminders<RU: ReminderUseCase>(
+    Extension(ru): Extension<RU>,
+    Path(todo_id): Path<i64>,
+) -> impl IntoResponse {
+    match ru.find_by_todo_id(todo_id).await {
+        Ok(reminders) => (
+            StatusCode::OK,
+            Json(RemindersResponse {
+                reminders: Some(
+                    reminders
+                        .into_iter()
+                        .map(|reminder| reminder.into())
+                        .collect(),
+                ),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(RemindersResponse {
+                reminders: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn delete_reminder<RU: ReminderUseCase>(
+    Extension(ru): Extension<RU>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match ru.delete(id).await {
+        Ok(id) => (
+            StatusCode::OK,
+            Json(DeleteReminderResponse {
+                id: Some(id),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(DeleteReminderResponse {
+                id: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn create_webhook<WU: WebhookUseCase>(
+    Extension(wu): Extension<WU>,
+    Json(payload): Json<CreateWebhookPayload>,
+) -> impl IntoResponse {
+    match wu.create_subscription(payload.into()).await {
+        Ok(webhook) => (
+            StatusCode::OK,
+            Json(WebhookResponse {
+                webhook: Some(webhook.into()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(WebhookResponse {
+                webhook: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_webhooks<WU: WebhookUseCase>(Extension(wu): Extension<WU>) -> impl IntoResponse {
+    match wu.find_subscriptions().await {
+        Ok(webhooks) => (
+            StatusCode::OK,
+            Json(WebhooksResponse {
+                webhooks: Some(webhooks.into_iter().map(|webhook| webhook.into()).collect()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(WebhooksResponse {
+                webhooks: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn delete_webhook<WU: WebhookUseCase>(
+    Extension(wu): Extension<WU>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match wu.delete_subscription(id).await {
+        Ok(id) => (
+            StatusCode::OK,
+            Json(DeleteWebhookResponse {
+                id: Some(id),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(DeleteWebhookResponse {
+                id: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_webhook_deliveries<WU: WebhookUseCase>(
+    Extension(wu): Extension<WU>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match wu.find_deliveries(id).await {
+        Ok(deliveries) => (
+            StatusCode::OK,
+            Json(WebhookDeliveriesResponse {
+                deliveries: Some(
+                    deliveries
+                        .into_iter()
+                        .map(|delivery| delivery.into())
+                        .collect(),
+                ),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(WebhookDeliveriesResponse {
+                deliveries: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_audit_log<AU: AuditUseCase>(
+    Extension(au): Extension<AU>,
+    Query(query): Query<AuditLogQuery>,
+) -> impl IntoResponse {
+    match au.find_audit_log(query.into()).await {
+        Ok(entries) => (
+            StatusCode::OK,
+            Json(AuditLogResponse {
+                entries: Some(entries.into_iter().map(|entry| entry.into()).collect()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(AuditLogResponse {
+                entries: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_revisions<RVU: RevisionUseCase>(
+    Extension(rvu): Extension<RVU>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match rvu.find_revisions(id).await {
+        Ok(revisions) => (
+            StatusCode::OK,
+            Json(RevisionsResponse {
+                revisions: Some(
+                    revisions
+                        .into_iter()
+                        .map(|revision| revision.into())
+                        .collect(),
+                ),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(RevisionsResponse {
+                revisions: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_revision_diff<RVU: RevisionUseCase>(
+    Extension(rvu): Extension<RVU>,
+    Path(id): Path<i64>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> impl IntoResponse {
+    match rvu.diff_revisions(id, query.from, query.to).await {
+        Ok(diff) => (
+            StatusCode::OK,
+            Json(RevisionDiffResponse {
+                diff: Some(diff.into()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(RevisionDiffResponse {
+                diff: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn restore_revision<RVU: RevisionUseCase>(
+    Extension(rvu): Extension<RVU>,
+    headers: HeaderMap,
+    Path((id, revision)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match rvu.restore_revision(ctx, id, revision).await {
+        Ok(todo) => (
+            StatusCode::OK,
+            etag(todo.version),
+            Json(UpdateTodoResponse {
+                todo: Some(todo.into()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            HeaderMap::new(),
+            Json(UpdateTodoResponse {
+                todo: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_trash<TSU: TrashUseCase>(Extension(tsu): Extension<TSU>) -> impl IntoResponse {
+    match tsu.find_trash().await {
+        Ok(todos) => (
+            StatusCode::OK,
+            Json(TrashResponse {
+                todos: Some(todos.into_iter().map(|todo| todo.into()).collect()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(TrashResponse {
+                todos: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn restore_from_trash<TSU: TrashUseCase>(
+    Extension(tsu): Extension<TSU>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match tsu.restore(ctx, id).await {
+        Ok(todo) => (
+            StatusCode::OK,
+            etag(todo.version),
+            Json(UpdateTodoResponse {
+                todo: Some(todo.into()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            HeaderMap::new(),
+            Json(UpdateTodoResponse {
+                todo: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+/// `GET /todos/export?format=`. The file is streamed as the todos are read; a failure
+/// midway cuts the response short.
+pub async fn export_todos<EU: ImportExportUseCase>(
+    Extension(eu): Extension<EU>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let format = match parse_format(query.format.as_deref()) {
+        Ok(format) => format,
+        Err(err) => {
+            return (
+                status_code(&err),
+                Json(ExportResponse {
+                    error: Some(err.into()),
+                }),
+            )
+                .into_response()
+        }
+    };
+    let chunks = eu.export(format).map(|chunk| {
+        chunk.map_err(|err| std::io::Error::other(PresentationalError::from(err).to_string()))
+    });
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", format.file_name()),
+            ),
+        ],
+        StreamBody::new(chunks),
+    )
+        .into_response()
+}
+
+/// `POST /todos/import?format=&dry_run=` with the file as the body. A file with problems
+/// is rejected as a whole, with the report listing them.
+pub async fn import_todos<EU: ImportExportUseCase>(
+    Extension(eu): Extension<EU>,
+    headers: HeaderMap,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> impl IntoResponse {
+    let format = match parse_format(query.format.as_deref()) {
+        Ok(format) => format,
+        Err(err) => {
+            return (
+                status_code(&err),
+                Json(ImportResponse {
+                    report: None,
+                    error: Some(err.into()),
+                }),
+            )
+        }
+    };
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match eu.import(ctx, format, body, query.dry_run).await {
+        Ok(report) if report.problems.is_empty() => (
+            StatusCode::OK,
+            Json(ImportResponse {
+                report: Some(report.into()),
+                error: None,
+            }),
+        ),
+        Ok(report) => (
+            StatusCode::BAD_REQUEST,
+            Json(ImportResponse {
+                report: Some(report.into()),
+                error: Some(PresentationalError::BadRequest),
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(ImportResponse {
+                report: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+/// How long a subscription waits before reading the log again after a failed read.
+const EVENT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+/// How many events a subscription reads from the log at a time.
+const EVENT_PAGE_SIZE: i64 = 100;
+/// How long a quiet subscription waits before sending a comment, so that proxies keep the
+/// connection open and clients notice when it drops.
+const EVENT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// `GET /todos/events`: todo changes as server-sent events, named after the event, with
+/// its id and its JSON payload. A `Last-Event-ID` resumes after that event; when the events
+/// after it are no longer in the log, an event named `reset` comes first, telling the
+/// subscriber to reload the todos, and the stream goes on from the newest event.
+///
+/// A caught-up stream reads the log again only once the event bus tells it events were
+/// published, so an idle stream costs no queries.
+pub async fn get_todo_events<LU: EventLogUseCase + Clone>(
+    Extension(lu): Extension<LU>,
+    headers: HeaderMap,
+) -> Response {
+    // listening before the log is read, so that no event published meanwhile is missed
+    let listener = lu.listen();
+    let last_event_id = headers.get("last-event-id").map(|value| {
+        value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().parse::<i64>().ok())
+            // not an id of this log, so the subscriber has to start over
+            .unwrap_or(-1)
+    });
+    let cursor = match lu.resume(last_event_id).await {
+        Ok(cursor) => cursor,
+        Err(err) => {
+            return (
+                status_code(&err),
+                Json(TodoEventsResponse {
+                    error: Some(err.into()),
+                }),
+            )
+                .into_response()
+        }
+    };
+    let subscription = Subscription {
+        lu,
+        last_event_id: cursor.last_event_id,
+        reset: cursor.reset,
+        pending: VecDeque::new(),
+        listener,
+        caught_up: false,
+    };
+    let events = stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next().await;
+        Some((Ok::<_, Infallible>(event), subscription))
+    });
+    Sse::new(events)
+        .keep_alive(
+            KeepAlive::new()
+                .interval(EVENT_HEARTBEAT_INTERVAL)
+                .text("heartbeat"),
+        )
+        .into_response()
+}
+
+/// A subscriber's position in the event log.
+struct Subscription<LU> {
+    lu: LU,
+    last_event_id: i64,
+    reset: bool,
+    pending: VecDeque<LoggedEventDto>,
+    listener: EventListener,
+    /// The last read found no more events than those pending.
+    caught_up: bool,
+}
+
+impl<LU: EventLogUseCase> Subscription<LU> {
+    /// Waits for the next event to send.
+    async fn next(&mut self) -> Event {
+        loop {
+            if self.reset {
+                self.reset = false;
+                return Event::default()
+                    .event("reset")
+                    .id(self.last_event_id.to_string())
+                    .data("{}");
+            }
+            if let Some(event) = self.pending.pop_front() {
+                self.last_event_id = event.id;
+                return Event::default()
+                    .event(&event.event)
+                    .id(event.id.to_string())
+                    .data(&event.payload);
+            }
+            if self.caught_up {
+                self.listener.published().await;
+            }
+            match self
+                .lu
+                .find_after(self.last_event_id, EVENT_PAGE_SIZE)
+                .await
+            {
+                Ok(events) => {
+                    self.caught_up = (events.len() as i64) < EVENT_PAGE_SIZE;
+                    // ids have no gaps: SQLite commits one writer at a time and a rolled-back
+                    // insert gives its AUTOINCREMENT id back, so a gap means the log was
+                    // pruned past this position
+                    if events
+                        .first()
+                        .is_some_and(|event| event.id > self.last_event_id + 1)
+                    {
+                        self.last_event_id = events[0].id - 1;
+                        self.reset = true;
+                    }
+                    self.pending.extend(events);
+                }
+                Err(_) => {
+                    self.caught_up = false;
+                    tokio::time::sleep(EVENT_RETRY_INTERVAL).await;
+                }
+            }
+        }
+    }
+}
+
+/// `POST /calendar/feeds`: issues a feed URL to the actor of the request.
+pub async fn create_calendar_feed<CFU: CalendarFeedUseCase>(
+    Extension(cfu): Extension<CFU>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match cfu.create_feed(ctx).await {
+        Ok(feed) => (
+            StatusCode::OK,
+            Json(CalendarFeedResponse {
+                feed: Some(feed.into()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(CalendarFeedResponse {
+                feed: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn get_calendar_feeds<CFU: CalendarFeedUseCase>(
+    Extension(cfu): Extension<CFU>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match cfu.find_feeds(ctx).await {
+        Ok(feeds) => (
+            StatusCode::OK,
+            Json(CalendarFeedsResponse {
+                feeds: Some(feeds.into_iter().map(|feed| feed.into()).collect()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(CalendarFeedsResponse {
+                feeds: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+pub async fn delete_calendar_feed<CFU: CalendarFeedUseCase>(
+    Extension(cfu): Extension<CFU>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match cfu.revoke_feed(ctx, id).await {
+        Ok(id) => (
+            StatusCode::OK,
+            Json(DeleteCalendarFeedResponse {
+                id: Some(id),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(DeleteCalendarFeedResponse {
+                id: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+/// `GET /calendar/:token/todos.ics`: the iCalendar file calendar apps subscribe to. The
+/// token is the only credential, so unknown and revoked tokens look alike.
+pub async fn get_calendar<CFU: CalendarFeedUseCase>(
+    Extension(cfu): Extension<CFU>,
+    Path(token): Path<String>,
+) -> Response {
+    let chunks = match cfu.feed(&token).await {
+        Ok(Some(chunks)) => chunks,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(err) => return status_code(&err).into_response(),
+    };
+    let chunks = chunks.map(|chunk| {
+        chunk.map_err(|err| std::io::Error::other(PresentationalError::from(err).to_string()))
+    });
+    (
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                TodoFormat::ICalendar.content_type().to_string(),
+            ),
+            (header::CACHE_CONTROL, "private, no-cache".to_string()),
+        ],
+        StreamBody::new(chunks),
+    )
+        .into_response()
+}
+
+fn parse_format(format: Option<&str>) -> Result<TodoFormat, UseCaseError> {
+    match format {
+        Some(format) => format.parse(),
+        None => Ok(TodoFormat::default()),
+    }
+}
+
+pub(crate) fn status_code(err: &UseCaseError) -> StatusCode {
+    match err {
+        UseCaseError::Validation(_) => StatusCode::BAD_REQUEST,
+        UseCaseError::NotFound {
+            entity_id: _,
+            entity_type: _,
+        } => StatusCode::NOT_FOUND,
+        UseCaseError::Conflict {
+            entity_id: _,
+            entity_type: _,
+        } => StatusCode::PRECONDITION_FAILED,
+        UseCaseError::IdempotencyKeyReused(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// `ETag` header carrying a todo's version, which `If-Match` is compared against.
+pub(crate) fn etag(version: i64) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&format!("\"{}\"", version)) {
+        headers.insert(header::ETAG, value);
+    }
+    headers
+}
+
+/// Reads the version an `If-Match` header expects; a missing header or `*` matches any.
+pub(crate) fn expected_version(headers: &HeaderMap) -> Result<Option<i64>, PresentationalError> {
+    let value = match headers.get(header::IF_MATCH) {
+        Some(value) => value
+            .to_str()
+            .map_err(|_| PresentationalError::BadRequest)?,
+        None => return Ok(None),
+    };
+    let value = value.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    match value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .and_then(|version| version.parse::<i64>().ok())
+    {
+        Some(version) => Ok(Some(version)),
+        None => Err(PresentationalError::BadRequest),
+    }
+}
+
+fn todos_response(todos: Result<Vec<TodoDto>, UseCaseError>) -> (StatusCode, Json<TodosResponse>) {
+    match todos {
+        Ok(todos) => (
+            StatusCode::OK,
+            Json(TodosResponse {
+                todos: Some(todos.into_iter().map(|todo| todo.into()).collect()),
+                error: None,
+            }),
+        ),
+        Err(err) => (
+            status_code(&err),
+            Json(TodosResponse {
+                todos: None,
+                error: Some(err.into()),
+            }),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{async_trait, body::HttpBody, http::HeaderValue};
+    use chrono::Utc;
+    use domain::event::TodoEvent;
+    use std::sync::{Arc, Mutex};
+    use use_case::{dto::event_log::EventCursorDto, event_bus::EventBus};
+
+    /// Keeps the events of the log in memory; replacing them stands in for pruning.
+    #[derive(Clone, Default)]
+    struct MockEventLogUseCase {
+        events: Arc<Mutex<Vec<LoggedEventDto>>>,
+        event_bus: EventBus,
+    }
+
+    impl MockEventLogUseCase {
+        /// Replaces the log without publishing, as pruning does.
+        fn replace(&self, ids: std::ops::RangeInclusive<i64>) {
+            let events = ids.map(|id| LoggedEventDto {
+                id,
+                event: "todo.updated".to_string(),
+                todo_id: 1,
+                payload: format!("{{\"version\":{}}}", id),
+                occurred_at: Utc::now(),
+            });
+            *self.events.lock().unwrap() = events.collect();
+        }
+
+        /// Replaces the log and publishes, as a committed change does.
+        async fn log(&self, ids: std::ops::RangeInclusive<i64>) {
+            self.replace(ids);
+            self.event_bus
+                .publish(&[TodoEvent::TodoCreated {
+                    todo: Default::default(),
+                }])
+                .await;
+        }
+    }
+
+    #[async_trait]
+    impl EventLogUseCase for MockEventLogUseCase {
+        async fn resume(&self, last_event_id: Option<i64>) -> Result<EventCursorDto, UseCaseError> {
+            let events = self.events.lock().unwrap();
+            let oldest = events.first().map_or(1, |event| event.id);
+            let newest = events.last().map_or(0, |event| event.id);
+            Ok(match last_event_id {
+                Some(id) if oldest - 1 <= id && id <= newest => EventCursorDto {
+                    last_event_id: id,
+                    reset: false,
+                },
+                _ => EventCursorDto {
+                    last_event_id: newest,
+                    reset: last_event_id.is_some(),
+                },
+            })
+        }
+
+        async fn find_after(
+            &self,
+            last_event_id: i64,
+            limit: i64,
+        ) -> Result<Vec<LoggedEventDto>, UseCaseError> {
+            let events = self.events.lock().unwrap();
+            Ok(events
+                .iter()
+                .filter(|event| event.id > last_event_id)
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        fn listen(&self) -> EventListener {
+            self.event_bus.listen()
+        }
+    }
+
+    /// The `event` and `id` fields of the next `count` events of an SSE body.
+    async fn read_events(body: &mut axum::body::BoxBody, count: usize) -> Vec<(String, String)> {
+        let mut events = Vec::new();
+        let mut text = String::new();
+        while events.len() < count {
+            let chunk = tokio::time::timeout(Duration::from_secs(5), body.data())
+                .await
+                .expect("no event within 5 seconds")
+                .unwrap()
+                .unwrap();
+            text.push_str(std::str::from_utf8(&chunk).unwrap());
+            while let Some(end) = text.find("\n\n") {
+                let block = text[..end].to_string();
+                text.drain(..end + 2);
+                let field = |name: &str| {
+                    block
+                        .lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .unwrap_or_default()
+                        .trim_start()
+                        .to_string()
+                };
+                events.push((field("event:"), field("id:")));
+            }
+        }
+        events
+    }
+
+    async fn subscribe(lu: &MockEventLogUseCase, last_event_id: &str) -> axum::body::BoxBody {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "last-event-id",
+            HeaderValue::from_str(last_event_id).unwrap(),
+        );
+        let response = get_todo_events(Extension(lu.clone()), headers).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        response.into_body()
+    }
+
+    fn event(name: &str, id: i64) -> (String, String) {
+        (name.to_string(), id.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_todo_events_resume_after_the_last_event_id() {
+        let lu = MockEventLogUseCase::default();
+        lu.log(1..=3).await;
+        let mut body = subscribe(&lu, "1").await;
+        assert_eq!(
+            read_events(&mut body, 2).await,
+            vec![event("todo.updated", 2), event("todo.updated", 3)]
+        );
+
+        // events logged while the stream is caught up arrive once they are published
+        lu.log(1..=4).await;
+        assert_eq!(
+            read_events(&mut body, 1).await,
+            vec![event("todo.updated", 4)]
+        );
+
+        // a caught-up stream does not read the log until something is published
+        lu.replace(1..=5);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), body.data())
+                .await
+                .is_err()
+        );
+        lu.log(1..=5).await;
+        assert_eq!(
+            read_events(&mut body, 1).await,
+            vec![event("todo.updated", 5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_todo_events_reset_when_the_log_was_pruned() {
+        let lu = MockEventLogUseCase::default();
+        lu.log(5..=6).await;
+        // events 3 and 4 are gone, so the stream starts over from the newest event
+        let mut body = subscribe(&lu, "2").await;
+        assert_eq!(read_events(&mut body, 1).await, vec![event("reset", 6)]);
+        let mut body = subscribe(&lu, "not an id").await;
+        assert_eq!(read_events(&mut body, 1).await, vec![event("reset", 6)]);
+
+        // pruned past the position of a stream while it was open
+        let mut body = subscribe(&lu, "5").await;
+        assert_eq!(
+            read_events(&mut body, 1).await,
+            vec![event("todo.updated", 6)]
+        );
+        lu.log(9..=10).await;
+        assert_eq!(
+            read_events(&mut body, 3).await,
+            vec![
+                event("reset", 8),
+                event("todo.updated", 9),
+                event("todo.updated", 10)
+            ]
+        );
+    }
+}
@@ -1,2 +1,5 @@
 pub mod handler;
 pub mod object;
+pub mod openapi;
+pub mod v2;
+pub mod version;
@@ -1,12 +1,57 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use use_case::dto::todo::{CreateTodoDto, TodoDto};
+use use_case::dto::{
+    audit::{AuditEntryDto, AuditLogFilterDto, FieldChangeDto},
+    batch::{BatchItemResultDto, BatchMode, BatchOperationDto, BatchOutcomeDto},
+    calendar_feed::CalendarFeedDto,
+    import_export::{ImportProblemDto, ImportReportDto},
+    reminder::{CreateReminderDto, ReminderDto},
+    revision::{RevisionDiffDto, TodoRevisionDto},
+    todo::{CreateTodoDto, TodoDto, UpdateTodoDto},
+    trash::TrashedTodoDto,
+    webhook::{CreateWebhookSubscriptionDto, WebhookDeliveryDto, WebhookSubscriptionDto},
+};
 
-use crate::error::PresentationalError;
+use crate::{adapter::present, error::PresentationalError};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl From<domain::entity::todo::Priority> for Priority {
+    fn from(priority: domain::entity::todo::Priority) -> Self {
+        match priority {
+            domain::entity::todo::Priority::Low => Self::Low,
+            domain::entity::todo::Priority::Medium => Self::Medium,
+            domain::entity::todo::Priority::High => Self::High,
+        }
+    }
+}
+
+impl From<Priority> for domain::entity::todo::Priority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => Self::Low,
+            Priority::Medium => Self::Medium,
+            Priority::High => Self::High,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Todo {
     pub id: i64,
     pub title: Option<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+    pub recurrence: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub version: i64,
 }
 
 impl From<TodoDto> for Todo {
@@ -14,6 +59,11 @@ impl From<TodoDto> for Todo {
         Self {
             id: todo_dto.id,
             title: todo_dto.title,
+            due_at: todo_dto.due_at,
+            priority: todo_dto.priority.into(),
+            recurrence: todo_dto.recurrence,
+            completed_at: todo_dto.completed_at,
+            version: todo_dto.version,
         }
     }
 }
@@ -33,27 +83,54 @@ pub struct TodoResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTodoPayload {
     pub title: String,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub recurrence: Option<String>,
 }
 
 impl From<CreateTodoPayload> for CreateTodoDto {
     fn from(create_todo_payload: CreateTodoPayload) -> Self {
         Self {
             title: create_todo_payload.title,
+            due_at: create_todo_payload.due_at,
+            priority: create_todo_payload.priority.into(),
+            recurrence: create_todo_payload.recurrence,
         }
     }
 }
 
+/// Members left out keep their stored value; `null` clears `due_at` and `recurrence`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateTodoPayload {
     pub id: i64,
     pub title: String,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub recurrence: Option<Option<String>>,
 }
 
-impl From<UpdateTodoPayload> for TodoDto {
+impl From<UpdateTodoPayload> for UpdateTodoDto {
     fn from(update_todo_payload: UpdateTodoPayload) -> Self {
         Self {
             id: update_todo_payload.id,
             title: Some(update_todo_payload.title),
+            due_at: update_todo_payload.due_at,
+            priority: update_todo_payload.priority.map(|priority| priority.into()),
+            recurrence: update_todo_payload.recurrence,
         }
     }
 }
@@ -63,6 +140,115 @@ pub struct DeleteTodoPayload {
     pub id: i64,
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchModePayload {
+    #[default]
+    Atomic,
+    PerItem,
+}
+
+impl From<BatchModePayload> for BatchMode {
+    fn from(mode: BatchModePayload) -> Self {
+        match mode {
+            BatchModePayload::Atomic => Self::Atomic,
+            BatchModePayload::PerItem => Self::PerItem,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchUpdatePayload {
+    #[serde(flatten)]
+    pub todo: UpdateTodoPayload,
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
+
+/// One operation of a batch, tagged by `op`, e.g. `{"op": "complete", "id": 1}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BatchOperationPayload {
+    Create(CreateTodoPayload),
+    Update(BatchUpdatePayload),
+    Delete(DeleteTodoPayload),
+    Complete(DeleteTodoPayload),
+}
+
+impl From<BatchOperationPayload> for BatchOperationDto {
+    fn from(operation: BatchOperationPayload) -> Self {
+        match operation {
+            BatchOperationPayload::Create(payload) => Self::Create(payload.into()),
+            BatchOperationPayload::Update(payload) => Self::Update {
+                todo: payload.todo.into(),
+                expected_version: payload.expected_version,
+            },
+            BatchOperationPayload::Delete(payload) => Self::Delete(payload.id),
+            BatchOperationPayload::Complete(payload) => Self::Complete(payload.id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTodosPayload {
+    #[serde(default)]
+    pub mode: BatchModePayload,
+    pub operations: Vec<BatchOperationPayload>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    /// The todo the operation affected; `None` when it was rejected.
+    pub id: Option<i64>,
+    pub todo: Option<Todo>,
+    pub next: Option<Todo>,
+    pub error: Option<PresentationalError>,
+}
+
+impl From<BatchItemResultDto> for BatchItemResult {
+    fn from(item: BatchItemResultDto) -> Self {
+        let mut result = Self {
+            index: item.index,
+            ..Default::default()
+        };
+        match item.result {
+            Ok(BatchOutcomeDto::Created(todo)) | Ok(BatchOutcomeDto::Updated(todo)) => {
+                result.id = Some(todo.id);
+                result.todo = Some(todo.into());
+            }
+            Ok(BatchOutcomeDto::Deleted(todo_id)) => result.id = Some(todo_id),
+            Ok(BatchOutcomeDto::Completed(completed)) => {
+                result.id = Some(completed.todo.id);
+                result.todo = Some(completed.todo.into());
+                result.next = completed.next.map(|next| next.into());
+            }
+            Err(err) => result.error = Some(err.into()),
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTodosResponse {
+    /// `false` when an atomic batch was rolled back; `results` then ends with the rejected
+    /// operation.
+    pub committed: bool,
+    pub results: Vec<BatchItemResult>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimezoneQuery {
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsOfQuery {
+    /// The time to read the todo as it was at, in RFC 3339.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTodoResponse {
     pub todo: Option<Todo>,
@@ -80,3 +266,434 @@ pub struct DeleteTodoResponse {
     pub todo: Option<Todo>,
     pub error: Option<PresentationalError>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteTodoResponse {
+    pub todo: Option<Todo>,
+    pub next: Option<Todo>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: i64,
+    pub todo_id: i64,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub offset_seconds: Option<i64>,
+    pub fire_at: Option<DateTime<Utc>>,
+    pub fired_at: Option<DateTime<Utc>>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+}
+
+impl From<ReminderDto> for Reminder {
+    fn from(reminder_dto: ReminderDto) -> Self {
+        Self {
+            id: reminder_dto.id,
+            todo_id: reminder_dto.todo_id,
+            remind_at: reminder_dto.remind_at,
+            offset_seconds: reminder_dto.offset_seconds,
+            fire_at: reminder_dto.fire_at,
+            fired_at: reminder_dto.fired_at,
+            failed_at: reminder_dto.failed_at,
+            attempts: reminder_dto.attempts,
+            last_error: reminder_dto.last_error,
+        }
+    }
+}
+
+/// Either `remind_at` or `offset_seconds` (before the todo's due date).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateReminderPayload {
+    #[serde(default)]
+    pub remind_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub offset_seconds: Option<i64>,
+}
+
+impl CreateReminderPayload {
+    pub fn into_dto(self, todo_id: i64) -> CreateReminderDto {
+        CreateReminderDto {
+            todo_id,
+            remind_at: self.remind_at,
+            offset_seconds: self.offset_seconds,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderResponse {
+    pub reminder: Option<Reminder>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemindersResponse {
+    pub reminders: Option<Vec<Reminder>>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteReminderResponse {
+    pub id: Option<i64>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<WebhookSubscriptionDto> for WebhookSubscription {
+    fn from(subscription_dto: WebhookSubscriptionDto) -> Self {
+        Self {
+            id: subscription_dto.id,
+            url: subscription_dto.url,
+            events: subscription_dto.events,
+            created_at: subscription_dto.created_at,
+        }
+    }
+}
+
+/// `events` lists event names such as `todo.created`; omit it to receive every event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookPayload {
+    pub url: String,
+    pub secret: String,
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl From<CreateWebhookPayload> for CreateWebhookSubscriptionDto {
+    fn from(create_webhook_payload: CreateWebhookPayload) -> Self {
+        Self {
+            url: create_webhook_payload.url,
+            secret: create_webhook_payload.secret,
+            events: create_webhook_payload.events,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub event: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i64,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<WebhookDeliveryDto> for WebhookDelivery {
+    fn from(delivery_dto: WebhookDeliveryDto) -> Self {
+        Self {
+            id: delivery_dto.id,
+            event: delivery_dto.event,
+            payload: delivery_dto.payload,
+            status: delivery_dto.status,
+            attempts: delivery_dto.attempts,
+            next_attempt_at: delivery_dto.next_attempt_at,
+            delivered_at: delivery_dto.delivered_at,
+            response_status: delivery_dto.response_status,
+            last_error: delivery_dto.last_error,
+            created_at: delivery_dto.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookResponse {
+    pub webhook: Option<WebhookSubscription>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhooksResponse {
+    pub webhooks: Option<Vec<WebhookSubscription>>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteWebhookResponse {
+    pub id: Option<i64>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDeliveriesResponse {
+    pub deliveries: Option<Vec<WebhookDelivery>>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogQuery {
+    pub todo_id: Option<i64>,
+    pub actor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl From<AuditLogQuery> for AuditLogFilterDto {
+    fn from(query: AuditLogQuery) -> Self {
+        Self {
+            todo_id: query.todo_id,
+            actor: query.actor,
+            limit: query.limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl From<FieldChangeDto> for FieldChange {
+    fn from(change_dto: FieldChangeDto) -> Self {
+        Self {
+            field: change_dto.field,
+            before: change_dto.before,
+            after: change_dto.after,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub todo_id: i64,
+    pub action: String,
+    pub actor: String,
+    pub protocol: String,
+    pub request_id: String,
+    pub before: Option<Todo>,
+    pub after: Option<Todo>,
+    pub changes: Vec<FieldChange>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<AuditEntryDto> for AuditEntry {
+    fn from(entry_dto: AuditEntryDto) -> Self {
+        Self {
+            id: entry_dto.id,
+            todo_id: entry_dto.todo_id,
+            action: entry_dto.action,
+            actor: entry_dto.actor,
+            protocol: entry_dto.protocol,
+            request_id: entry_dto.request_id,
+            before: entry_dto.before.map(|todo| todo.into()),
+            after: entry_dto.after.map(|todo| todo.into()),
+            changes: entry_dto
+                .changes
+                .into_iter()
+                .map(|change| change.into())
+                .collect(),
+            occurred_at: entry_dto.occurred_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogResponse {
+    pub entries: Option<Vec<AuditEntry>>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoRevision {
+    pub revision: i64,
+    pub todo: Todo,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<TodoRevisionDto> for TodoRevision {
+    fn from(revision_dto: TodoRevisionDto) -> Self {
+        Self {
+            revision: revision_dto.revision,
+            todo: revision_dto.todo.into(),
+            created_at: revision_dto.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDiff {
+    pub todo_id: i64,
+    pub from: i64,
+    pub to: i64,
+    pub changes: Vec<FieldChange>,
+}
+
+impl From<RevisionDiffDto> for RevisionDiff {
+    fn from(diff_dto: RevisionDiffDto) -> Self {
+        Self {
+            todo_id: diff_dto.todo_id,
+            from: diff_dto.from,
+            to: diff_dto.to,
+            changes: diff_dto
+                .changes
+                .into_iter()
+                .map(|change| change.into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i64,
+    pub to: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionsResponse {
+    pub revisions: Option<Vec<TodoRevision>>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionDiffResponse {
+    pub diff: Option<RevisionDiff>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedTodo {
+    pub todo: Todo,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_at: DateTime<Utc>,
+}
+
+impl From<TrashedTodoDto> for TrashedTodo {
+    fn from(trashed_dto: TrashedTodoDto) -> Self {
+        Self {
+            todo: trashed_dto.todo.into(),
+            deleted_at: trashed_dto.deleted_at,
+            purge_at: trashed_dto.purge_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashResponse {
+    pub todos: Option<Vec<TrashedTodo>>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportQuery {
+    /// `csv` (the default), `jsonl`, `todotxt` or `ics`.
+    pub format: Option<String>,
+}
+
+/// Body of a failed export; a successful one answers with the file itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportResponse {
+    pub error: Option<PresentationalError>,
+}
+
+/// Body of a failed subscription to the todo events; a successful one answers with the
+/// event stream itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoEventsResponse {
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportQuery {
+    /// `csv` (the default), `jsonl`, `todotxt` or `ics`.
+    pub format: Option<String>,
+    /// Only validates the file and reports its problems.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProblem {
+    pub line: usize,
+    pub message: String,
+}
+
+impl From<ImportProblemDto> for ImportProblem {
+    fn from(problem: ImportProblemDto) -> Self {
+        Self {
+            line: problem.line,
+            message: problem.message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub valid: usize,
+    pub imported: usize,
+    pub problems: Vec<ImportProblem>,
+}
+
+impl From<ImportReportDto> for ImportReport {
+    fn from(report: ImportReportDto) -> Self {
+        Self {
+            dry_run: report.dry_run,
+            valid: report.valid,
+            imported: report.imported,
+            problems: report
+                .problems
+                .into_iter()
+                .map(|problem| problem.into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResponse {
+    pub report: Option<ImportReport>,
+    pub error: Option<PresentationalError>,
+}
+
+/// A private calendar feed. Calendar apps subscribe to `url`, a path on this server that
+/// anyone who knows it can read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeed {
+    pub id: i64,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<CalendarFeedDto> for CalendarFeed {
+    fn from(feed_dto: CalendarFeedDto) -> Self {
+        Self {
+            id: feed_dto.id,
+            url: format!("/calendar/{}/todos.ics", feed_dto.token),
+            created_at: feed_dto.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeedResponse {
+    pub feed: Option<CalendarFeed>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeedsResponse {
+    pub feeds: Option<Vec<CalendarFeed>>,
+    pub error: Option<PresentationalError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteCalendarFeedResponse {
+    pub id: Option<i64>,
+    pub error: Option<PresentationalError>,
+}
@@ -0,0 +1,1341 @@
+//! The OpenAPI 3.1 document of the REST API. Schemas come from the types of
+//! [`super::object`] through [`ApiSchema`], whose implementations destructure every type so
+//! that adding, removing or retyping a field fails to compile until its schema follows.
+//! Operations mirror the routes the server mounts; CalDAV, GraphQL, gRPC and JSON-RPC are
+//! left out.
+
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use use_case::dto::import_export::TodoFormat;
+
+use crate::{
+    context::{ACTOR_HEADER, IDEMPOTENCY_KEY_HEADER, REQUEST_ID_HEADER},
+    error::PresentationalError,
+};
+
+use super::object::{
+    AsOfQuery, AuditEntry, AuditLogQuery, AuditLogResponse, BatchItemResult, BatchModePayload,
+    BatchOperationPayload, BatchTodosPayload, BatchTodosResponse, BatchUpdatePayload, CalendarFeed,
+    CalendarFeedResponse, CalendarFeedsResponse, CompleteTodoResponse, CreateReminderPayload,
+    CreateTodoPayload, CreateTodoResponse, CreateWebhookPayload, DeleteCalendarFeedResponse,
+    DeleteReminderResponse, DeleteTodoPayload, DeleteTodoResponse, DeleteWebhookResponse,
+    ExportQuery, ExportResponse, FieldChange, ImportProblem, ImportQuery, ImportReport,
+    ImportResponse, Priority, Reminder, ReminderResponse, RemindersResponse, RevisionDiff,
+    RevisionDiffQuery, RevisionDiffResponse, RevisionsResponse, TimezoneQuery, Todo,
+    TodoEventsResponse, TodoResponse, TodoRevision, TodosResponse, TrashResponse, TrashedTodo,
+    UpdateTodoPayload, UpdateTodoResponse, WebhookDeliveriesResponse, WebhookDelivery,
+    WebhookResponse, WebhookSubscription, WebhooksResponse,
+};
+use super::v2::{
+    handler::location,
+    object::{CompletedTodo, Problem, TodoMergePatch},
+};
+
+/// Named schemas, keyed by type name, that end up under `components.schemas`.
+pub(crate) type Schemas = BTreeMap<String, Value>;
+
+pub trait ApiSchema {
+    /// Whether the value may be `null` or left out of a request body.
+    const OPTIONAL: bool = false;
+
+    /// The JSON Schema of the type. Named types are added to `schemas` and referenced.
+    fn schema(schemas: &mut Schemas) -> Value;
+}
+
+impl ApiSchema for i64 {
+    fn schema(_: &mut Schemas) -> Value {
+        json!({ "type": "integer", "format": "int64" })
+    }
+}
+
+impl ApiSchema for u16 {
+    fn schema(_: &mut Schemas) -> Value {
+        json!({ "type": "integer", "minimum": 0, "maximum": 65535 })
+    }
+}
+
+impl ApiSchema for usize {
+    fn schema(_: &mut Schemas) -> Value {
+        json!({ "type": "integer", "minimum": 0 })
+    }
+}
+
+impl ApiSchema for bool {
+    fn schema(_: &mut Schemas) -> Value {
+        json!({ "type": "boolean" })
+    }
+}
+
+impl ApiSchema for String {
+    fn schema(_: &mut Schemas) -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl ApiSchema for DateTime<Utc> {
+    fn schema(_: &mut Schemas) -> Value {
+        json!({ "type": "string", "format": "date-time" })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn schema(schemas: &mut Schemas) -> Value {
+        let mut schema = T::schema(schemas);
+        match schema.get("type").cloned() {
+            Some(Value::String(name)) => {
+                schema["type"] = json!([name, "null"]);
+                schema
+            }
+            // already nullable, as in `Option<Option<T>>`
+            Some(Value::Array(_)) => schema,
+            _ => json!({ "anyOf": [schema, { "type": "null" }] }),
+        }
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Vec<T> {
+    fn schema(schemas: &mut Schemas) -> Value {
+        json!({ "type": "array", "items": T::schema(schemas) })
+    }
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+/// Adds the schema `build` returns under `name`, unless it is already there.
+pub(crate) fn define(
+    schemas: &mut Schemas,
+    name: &str,
+    build: impl FnOnce(&mut Schemas) -> Value,
+) -> Value {
+    if !schemas.contains_key(name) {
+        let schema = build(schemas);
+        schemas.insert(name.to_string(), schema);
+    }
+    reference(name)
+}
+
+pub(crate) fn required<T: ApiSchema>(defaulted: bool) -> bool {
+    !T::OPTIONAL && !defaulted
+}
+
+/// Whether a field is marked `#[default]`, i.e. `#[serde(default)]` in [`super::object`].
+macro_rules! defaulted {
+    () => {
+        false
+    };
+    (default) => {
+        true
+    };
+}
+
+pub(crate) use defaulted;
+
+/// An object schema for each struct, listing its fields as `name: Type`.
+macro_rules! object_schema {
+    ($($name:ident { $($(#[$default:ident])? $field:ident: $ty:ty),* $(,)? })*) => {$(
+        impl ApiSchema for $name {
+            fn schema(schemas: &mut Schemas) -> Value {
+                define(schemas, stringify!($name), |schemas| {
+                    let mut properties = Map::new();
+                    let mut required_fields: Vec<&str> = Vec::new();
+                    $(
+                        properties.insert(
+                            stringify!($field).to_string(),
+                            <$ty as ApiSchema>::schema(schemas),
+                        );
+                        if required::<$ty>(defaulted!($($default)?)) {
+                            required_fields.push(stringify!($field));
+                        }
+                    )*
+                    json!({
+                        "type": "object",
+                        "properties": properties,
+                        "required": required_fields,
+                    })
+                })
+            }
+        }
+
+        const _: fn(&$name) = |value| {
+            let $name { $($field),* } = value;
+            $(let _: &$ty = $field;)*
+        };
+    )*};
+}
+
+/// The values an enum serializes to, checked against serde by the tests.
+#[cfg(test)]
+trait StringEnum: Sized + 'static {
+    const VALUES: &'static [(Self, &'static str)];
+}
+
+/// A string schema for each unit-only enum, mapping its variants to their serialized names.
+macro_rules! string_enum_schema {
+    ($($name:ident { $($variant:ident => $value:literal),* $(,)? })*) => {$(
+        impl ApiSchema for $name {
+            fn schema(schemas: &mut Schemas) -> Value {
+                define(schemas, stringify!($name), |_| {
+                    json!({ "type": "string", "enum": [$($value),*] })
+                })
+            }
+        }
+
+        #[cfg(test)]
+        impl StringEnum for $name {
+            const VALUES: &'static [(Self, &'static str)] = &[$(($name::$variant, $value)),*];
+        }
+
+        const _: fn(&$name) -> &str = |value| match value {
+            $($name::$variant => $value),*
+        };
+    )*};
+}
+
+string_enum_schema! {
+    Priority { Low => "low", Medium => "medium", High => "high" }
+    BatchModePayload { Atomic => "atomic", PerItem => "per_item" }
+    PresentationalError {
+        BadRequest => "BadRequest",
+        NotFound => "NotFound",
+        Conflict => "Conflict",
+        IdempotencyKeyReused => "IdempotencyKeyReused",
+        InternalServerError => "InternalServerError",
+    }
+}
+
+object_schema! {
+    Todo {
+        id: i64,
+        title: Option<String>,
+        due_at: Option<DateTime<Utc>>,
+        priority: Priority,
+        recurrence: Option<String>,
+        completed_at: Option<DateTime<Utc>>,
+        version: i64,
+    }
+    TodosResponse { todos: Option<Vec<Todo>>, error: Option<PresentationalError> }
+    TodoResponse { todo: Option<Todo>, error: Option<PresentationalError> }
+    CreateTodoPayload {
+        title: String,
+        #[default] due_at: Option<DateTime<Utc>>,
+        #[default] priority: Priority,
+        #[default] recurrence: Option<String>,
+    }
+    UpdateTodoPayload {
+        id: i64,
+        title: String,
+        #[default] due_at: Option<Option<DateTime<Utc>>>,
+        #[default] priority: Option<Priority>,
+        #[default] recurrence: Option<Option<String>>,
+    }
+    DeleteTodoPayload { id: i64 }
+    BatchTodosPayload {
+        #[default] mode: BatchModePayload,
+        operations: Vec<BatchOperationPayload>,
+    }
+    BatchItemResult {
+        index: usize,
+        id: Option<i64>,
+        todo: Option<Todo>,
+        next: Option<Todo>,
+        error: Option<PresentationalError>,
+    }
+    BatchTodosResponse {
+        committed: bool,
+        results: Vec<BatchItemResult>,
+        error: Option<PresentationalError>,
+    }
+    TimezoneQuery { timezone: Option<String> }
+    AsOfQuery { as_of: Option<DateTime<Utc>> }
+    CreateTodoResponse { todo: Option<Todo>, error: Option<PresentationalError> }
+    UpdateTodoResponse { todo: Option<Todo>, error: Option<PresentationalError> }
+    DeleteTodoResponse { todo: Option<Todo>, error: Option<PresentationalError> }
+    CompleteTodoResponse {
+        todo: Option<Todo>,
+        next: Option<Todo>,
+        error: Option<PresentationalError>,
+    }
+    Reminder {
+        id: i64,
+        todo_id: i64,
+        remind_at: Option<DateTime<Utc>>,
+        offset_seconds: Option<i64>,
+        fire_at: Option<DateTime<Utc>>,
+        fired_at: Option<DateTime<Utc>>,
+        failed_at: Option<DateTime<Utc>>,
+        attempts: i64,
+        last_error: Option<String>,
+    }
+    CreateReminderPayload {
+        #[default] remind_at: Option<DateTime<Utc>>,
+        #[default] offset_seconds: Option<i64>,
+    }
+    ReminderResponse { reminder: Option<Reminder>, error: Option<PresentationalError> }
+    RemindersResponse { reminders: Option<Vec<Reminder>>, error: Option<PresentationalError> }
+    DeleteReminderResponse { id: Option<i64>, error: Option<PresentationalError> }
+    WebhookSubscription {
+        id: i64,
+        url: String,
+        events: Vec<String>,
+        created_at: DateTime<Utc>,
+    }
+    CreateWebhookPayload { url: String, secret: String, #[default] events: Vec<String> }
+    WebhookDelivery {
+        id: i64,
+        event: String,
+        payload: String,
+        status: String,
+        attempts: i64,
+        next_attempt_at: Option<DateTime<Utc>>,
+        delivered_at: Option<DateTime<Utc>>,
+        response_status: Option<i64>,
+        last_error: Option<String>,
+        created_at: DateTime<Utc>,
+    }
+    WebhookResponse { webhook: Option<WebhookSubscription>, error: Option<PresentationalError> }
+    WebhooksResponse {
+        webhooks: Option<Vec<WebhookSubscription>>,
+        error: Option<PresentationalError>,
+    }
+    DeleteWebhookResponse { id: Option<i64>, error: Option<PresentationalError> }
+    WebhookDeliveriesResponse {
+        deliveries: Option<Vec<WebhookDelivery>>,
+        error: Option<PresentationalError>,
+    }
+    AuditLogQuery { todo_id: Option<i64>, actor: Option<String>, limit: Option<i64> }
+    FieldChange { field: String, before: Option<String>, after: Option<String> }
+    AuditEntry {
+        id: i64,
+        todo_id: i64,
+        action: String,
+        actor: String,
+        protocol: String,
+        request_id: String,
+        before: Option<Todo>,
+        after: Option<Todo>,
+        changes: Vec<FieldChange>,
+        occurred_at: DateTime<Utc>,
+    }
+    AuditLogResponse { entries: Option<Vec<AuditEntry>>, error: Option<PresentationalError> }
+    TodoRevision { revision: i64, todo: Todo, created_at: DateTime<Utc> }
+    RevisionDiff { todo_id: i64, from: i64, to: i64, changes: Vec<FieldChange> }
+    RevisionDiffQuery { from: i64, to: i64 }
+    RevisionsResponse {
+        revisions: Option<Vec<TodoRevision>>,
+        error: Option<PresentationalError>,
+    }
+    RevisionDiffResponse { diff: Option<RevisionDiff>, error: Option<PresentationalError> }
+    TrashedTodo { todo: Todo, deleted_at: DateTime<Utc>, purge_at: DateTime<Utc> }
+    TrashResponse { todos: Option<Vec<TrashedTodo>>, error: Option<PresentationalError> }
+    ExportQuery { format: Option<String> }
+    ExportResponse { error: Option<PresentationalError> }
+    TodoEventsResponse { error: Option<PresentationalError> }
+    ImportQuery { format: Option<String>, #[default] dry_run: bool }
+    ImportProblem { line: usize, message: String }
+    ImportReport { dry_run: bool, valid: usize, imported: usize, problems: Vec<ImportProblem> }
+    ImportResponse { report: Option<ImportReport>, error: Option<PresentationalError> }
+    CalendarFeed { id: i64, url: String, created_at: DateTime<Utc> }
+    CalendarFeedResponse { feed: Option<CalendarFeed>, error: Option<PresentationalError> }
+    CalendarFeedsResponse { feeds: Option<Vec<CalendarFeed>>, error: Option<PresentationalError> }
+    DeleteCalendarFeedResponse { id: Option<i64>, error: Option<PresentationalError> }
+    Problem { title: String, status: u16, error: PresentationalError, detail: Option<String> }
+    CompletedTodo { todo: Todo, next: Option<Todo> }
+}
+
+/// Every member optional, and `null` only where it clears one.
+impl ApiSchema for TodoMergePatch {
+    fn schema(schemas: &mut Schemas) -> Value {
+        define(schemas, "TodoMergePatch", |schemas| {
+            json!({
+                "type": "object",
+                "properties": {
+                    "title": String::schema(schemas),
+                    "due_at": Option::<DateTime<Utc>>::schema(schemas),
+                    "priority": Option::<Priority>::schema(schemas),
+                    "recurrence": Option::<String>::schema(schemas),
+                },
+                "additionalProperties": false,
+            })
+        })
+    }
+}
+
+const _: fn(&TodoMergePatch) = |value| {
+    let TodoMergePatch {
+        title,
+        due_at,
+        priority,
+        recurrence,
+    } = value;
+    let _: &Option<Option<String>> = title;
+    let _: &Option<Option<DateTime<Utc>>> = due_at;
+    let _: &Option<Option<Priority>> = priority;
+    let _: &Option<Option<String>> = recurrence;
+};
+
+/// The fields of an [`UpdateTodoPayload`] next to `expected_version`, which is flattened.
+impl ApiSchema for BatchUpdatePayload {
+    fn schema(schemas: &mut Schemas) -> Value {
+        define(schemas, "BatchUpdatePayload", |schemas| {
+            json!({
+                "allOf": [
+                    UpdateTodoPayload::schema(schemas),
+                    {
+                        "type": "object",
+                        "properties": { "expected_version": Option::<i64>::schema(schemas) },
+                    },
+                ],
+            })
+        })
+    }
+}
+
+const _: fn(&BatchUpdatePayload) = |value| {
+    let BatchUpdatePayload {
+        todo,
+        expected_version,
+    } = value;
+    let _: &UpdateTodoPayload = todo;
+    let _: &Option<i64> = expected_version;
+};
+
+/// One schema per operation, each the fields of its payload next to the `op` tag.
+impl ApiSchema for BatchOperationPayload {
+    fn schema(schemas: &mut Schemas) -> Value {
+        define(schemas, "BatchOperationPayload", |schemas| {
+            let tagged = |op: &str, payload: Value| {
+                json!({
+                    "allOf": [
+                        {
+                            "type": "object",
+                            "properties": { "op": { "const": op } },
+                            "required": ["op"],
+                        },
+                        payload,
+                    ],
+                })
+            };
+            json!({
+                "oneOf": [
+                    tagged("create", CreateTodoPayload::schema(schemas)),
+                    tagged("update", BatchUpdatePayload::schema(schemas)),
+                    tagged("delete", DeleteTodoPayload::schema(schemas)),
+                    tagged("complete", DeleteTodoPayload::schema(schemas)),
+                ],
+            })
+        })
+    }
+}
+
+const _: fn(&BatchOperationPayload) -> Value = |value| match value {
+    BatchOperationPayload::Create(payload) => json!(["create", payload.title]),
+    BatchOperationPayload::Update(payload) => json!(["update", payload.todo.id]),
+    BatchOperationPayload::Delete(payload) => json!(["delete", payload.id]),
+    BatchOperationPayload::Complete(payload) => json!(["complete", payload.id]),
+};
+
+fn file_content(content_types: &[&str]) -> Value {
+    let content: Map<String, Value> = content_types
+        .iter()
+        .map(|content_type| {
+            (
+                content_type.to_string(),
+                json!({ "schema": { "type": "string" } }),
+            )
+        })
+        .collect();
+    Value::Object(content)
+}
+
+#[derive(Default)]
+struct Document {
+    paths: Map<String, Value>,
+    schemas: Schemas,
+}
+
+impl Document {
+    fn operation(
+        &mut self,
+        method: &'static str,
+        path: &'static str,
+        id: &str,
+        tag: &str,
+        summary: &str,
+    ) -> Operation<'_> {
+        let mut value = Map::new();
+        value.insert("operationId".to_string(), json!(id));
+        value.insert("tags".to_string(), json!([tag]));
+        value.insert("summary".to_string(), json!(summary));
+        Operation {
+            document: self,
+            method,
+            path,
+            value,
+            parameters: vec![],
+            responses: Map::new(),
+        }
+    }
+}
+
+struct Operation<'a> {
+    document: &'a mut Document,
+    method: &'static str,
+    path: &'static str,
+    value: Map<String, Value>,
+    parameters: Vec<Value>,
+    responses: Map<String, Value>,
+}
+
+impl Operation<'_> {
+    fn path_parameter(mut self, name: &str, schema: Value) -> Self {
+        self.parameters.push(json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": schema,
+        }));
+        self
+    }
+
+    fn id(self, name: &str) -> Self {
+        self.path_parameter(name, i64::schema(&mut Schemas::new()))
+    }
+
+    fn header(mut self, name: &str, description: &str) -> Self {
+        self.parameters.push(json!({
+            "name": name,
+            "in": "header",
+            "description": description,
+            "schema": { "type": "string" },
+        }));
+        self
+    }
+
+    /// The headers every request that changes todos is recorded in the audit log with.
+    fn actor(self) -> Self {
+        self.header(
+            ACTOR_HEADER,
+            "Who makes the change; `anonymous` when absent.",
+        )
+        .header(
+            REQUEST_ID_HEADER,
+            "Correlates the change in the audit log; generated when absent.",
+        )
+    }
+
+    /// One query parameter per field of `T`.
+    fn query<T: ApiSchema>(mut self) -> Self {
+        let mut schemas = Schemas::new();
+        let reference = T::schema(&mut schemas);
+        let name = reference["$ref"]
+            .as_str()
+            .and_then(|reference| reference.rsplit('/').next())
+            .unwrap_or_default();
+        let definition = &schemas[name];
+        let required = definition["required"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        if let Some(properties) = definition["properties"].as_object() {
+            for (field, schema) in properties {
+                let mut schema = schema.clone();
+                if let Some(Value::Array(types)) = schema.get("type").cloned() {
+                    schema["type"] = types[0].clone();
+                }
+                self.parameters.push(json!({
+                    "name": field,
+                    "in": "query",
+                    "required": required.contains(&json!(field)),
+                    "schema": schema,
+                }));
+            }
+        }
+        self
+    }
+
+    fn body<T: ApiSchema>(self) -> Self {
+        self.body_as::<T>("application/json")
+    }
+
+    fn body_as<T: ApiSchema>(mut self, content_type: &str) -> Self {
+        let schema = T::schema(&mut self.document.schemas);
+        self.value.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": { content_type: { "schema": schema } },
+            }),
+        );
+        self
+    }
+
+    fn file_body(mut self, content_types: &[&str]) -> Self {
+        self.value.insert(
+            "requestBody".to_string(),
+            json!({ "required": true, "content": file_content(content_types) }),
+        );
+        self
+    }
+
+    fn response(mut self, status: StatusCode, response: Value) -> Self {
+        let mut response = response;
+        response["description"] = json!(status.canonical_reason().unwrap_or_default());
+        self.responses.insert(status.as_u16().to_string(), response);
+        self
+    }
+
+    /// The JSON envelope `T` for each of `statuses`; errors share the success envelope.
+    fn json<T: ApiSchema>(self, statuses: &[StatusCode]) -> Self {
+        self.json_as::<T>("application/json", statuses)
+    }
+
+    fn json_as<T: ApiSchema>(mut self, content_type: &str, statuses: &[StatusCode]) -> Self {
+        let schema = T::schema(&mut self.document.schemas);
+        for status in statuses {
+            self = self.response(
+                *status,
+                json!({ "content": { content_type: { "schema": schema } } }),
+            );
+        }
+        self
+    }
+
+    /// The problem details a v2 operation answers each of `statuses` with.
+    fn problem(self, statuses: &[StatusCode]) -> Self {
+        self.json_as::<Problem>("application/problem+json", statuses)
+    }
+
+    fn file(self, status: StatusCode, content_types: &[&str]) -> Self {
+        self.response(status, json!({ "content": file_content(content_types) }))
+    }
+
+    /// `T` as JSON or as any of `content_types`, whichever `Accept` prefers.
+    fn negotiated<T: ApiSchema>(self, status: StatusCode, content_types: &[&str]) -> Self {
+        let mut content = file_content(content_types);
+        content["application/json"] = json!({ "schema": T::schema(&mut self.document.schemas) });
+        self.response(status, json!({ "content": content }))
+    }
+
+    /// Answers without a body.
+    fn empty(self, status: StatusCode) -> Self {
+        self.response(status, json!({}))
+    }
+
+    fn response_header(mut self, status: StatusCode, name: &str, description: &str) -> Self {
+        if let Some(response) = self.responses.get_mut(status.as_str()) {
+            response["headers"][name] = json!({
+                "description": description,
+                "schema": { "type": "string" },
+            });
+        }
+        self
+    }
+
+    /// The `ETag` the response to `status` carries: the version of the todo, quoted.
+    fn etag(self, status: StatusCode) -> Self {
+        self.response_header(
+            status,
+            "ETag",
+            "The version of the todo, for `If-Match` on updates.",
+        )
+    }
+
+    /// Replaced by a v2 route; the responses carry `Deprecation` and `Link` headers.
+    fn deprecated(mut self) -> Self {
+        self.value.insert("deprecated".to_string(), json!(true));
+        self
+    }
+
+    fn add(self) {
+        let mut value = self.value;
+        if !self.parameters.is_empty() {
+            value.insert("parameters".to_string(), Value::Array(self.parameters));
+        }
+        value.insert("responses".to_string(), Value::Object(self.responses));
+        let path = self
+            .document
+            .paths
+            .entry(self.path)
+            .or_insert_with(|| json!({}));
+        path[self.method] = Value::Object(value);
+    }
+}
+
+/// The OpenAPI document of the REST API, as served at `/openapi.json`.
+pub fn openapi() -> Value {
+    use StatusCode as S;
+
+    let mut document = Document::default();
+    let formats: Vec<&str> = TodoFormat::ALL
+        .iter()
+        .map(|format| format.content_type())
+        .collect();
+    let calendar = TodoFormat::ICalendar.content_type();
+
+    document
+        .operation("get", "/todos", "get_todos", "todos", "List todos")
+        .deprecated()
+        .json::<TodosResponse>(&[S::OK, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation("post", "/todos", "create_todo", "todos", "Create a todo")
+        .deprecated()
+        .actor()
+        .header(
+            IDEMPOTENCY_KEY_HEADER,
+            "Retries with the same key return the todo the first request created.",
+        )
+        .body::<CreateTodoPayload>()
+        .json::<CreateTodoResponse>(&[
+            S::OK,
+            S::BAD_REQUEST,
+            S::UNPROCESSABLE_ENTITY,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .etag(S::OK)
+        .add();
+    document
+        .operation("put", "/todos", "update_todo", "todos", "Update a todo")
+        .deprecated()
+        .actor()
+        .header("If-Match", "The `ETag` the todo is expected to still have.")
+        .body::<UpdateTodoPayload>()
+        .json::<UpdateTodoResponse>(&[
+            S::OK,
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::PRECONDITION_FAILED,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .etag(S::OK)
+        .add();
+    document
+        .operation(
+            "delete",
+            "/todos",
+            "delete_todo",
+            "todos",
+            "Move a todo to the trash",
+        )
+        .deprecated()
+        .actor()
+        .body::<DeleteTodoPayload>()
+        .json::<DeleteTodoResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "post",
+            "/todos:batch",
+            "batch_todos",
+            "todos",
+            "Create, update, delete and complete todos in one request",
+        )
+        .actor()
+        .body::<BatchTodosPayload>()
+        .json::<BatchTodosResponse>(&[
+            S::OK,
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::PRECONDITION_FAILED,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/export",
+            "export_todos",
+            "import-export",
+            "Download every todo as a file",
+        )
+        .query::<ExportQuery>()
+        .file(S::OK, &formats)
+        .json::<ExportResponse>(&[S::BAD_REQUEST, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/events",
+            "get_todo_events",
+            "todos",
+            "Follow todo changes as server-sent events",
+        )
+        .header(
+            "Last-Event-ID",
+            "The id of the last event seen, to resume after it; new subscribers start from \
+             the next change. An event named `reset` comes first when the events after it \
+             are no longer kept.",
+        )
+        .file(S::OK, &["text/event-stream"])
+        .json::<TodoEventsResponse>(&[S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "post",
+            "/todos/import",
+            "import_todos",
+            "import-export",
+            "Create todos from a file; nothing is imported when it has problems",
+        )
+        .actor()
+        .query::<ImportQuery>()
+        .file_body(&formats)
+        .json::<ImportResponse>(&[S::OK, S::BAD_REQUEST, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/overdue",
+            "get_overdue_todos",
+            "todos",
+            "List incomplete todos that are past due",
+        )
+        .json::<TodosResponse>(&[S::OK, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/due-today",
+            "get_todos_due_today",
+            "todos",
+            "List incomplete todos due today",
+        )
+        .query::<TimezoneQuery>()
+        .json::<TodosResponse>(&[S::OK, S::BAD_REQUEST, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/due-this-week",
+            "get_todos_due_this_week",
+            "todos",
+            "List incomplete todos due this week",
+        )
+        .query::<TimezoneQuery>()
+        .json::<TodosResponse>(&[S::OK, S::BAD_REQUEST, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/high-priority",
+            "get_high_priority_todos",
+            "todos",
+            "List incomplete high-priority todos",
+        )
+        .json::<TodosResponse>(&[S::OK, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation("get", "/todos/{id}", "get_todo", "todos", "Get a todo")
+        .deprecated()
+        .id("id")
+        .json::<TodoResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .etag(S::OK)
+        .add();
+    document
+        .operation(
+            "post",
+            "/todos/{id}/complete",
+            "complete_todo",
+            "todos",
+            "Complete a todo; recurring todos get their next occurrence",
+        )
+        .deprecated()
+        .id("id")
+        .actor()
+        .json::<CompleteTodoResponse>(&[
+            S::OK,
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .etag(S::OK)
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/{id}/reminders",
+            "get_reminders",
+            "reminders",
+            "List the reminders of a todo",
+        )
+        .id("id")
+        .json::<RemindersResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "post",
+            "/todos/{id}/reminders",
+            "create_reminder",
+            "reminders",
+            "Remind of a todo at a time or at an offset from its due date",
+        )
+        .id("id")
+        .body::<CreateReminderPayload>()
+        .json::<ReminderResponse>(&[
+            S::OK,
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/{id}/revisions",
+            "get_revisions",
+            "revisions",
+            "List the revisions of a todo",
+        )
+        .id("id")
+        .json::<RevisionsResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/todos/{id}/revisions/diff",
+            "get_revision_diff",
+            "revisions",
+            "Compare two revisions of a todo",
+        )
+        .id("id")
+        .query::<RevisionDiffQuery>()
+        .json::<RevisionDiffResponse>(&[
+            S::OK,
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .add();
+    document
+        .operation(
+            "post",
+            "/todos/{id}/revisions/{revision}/restore",
+            "restore_revision",
+            "revisions",
+            "Restore a todo to one of its revisions",
+        )
+        .id("id")
+        .id("revision")
+        .actor()
+        .json::<UpdateTodoResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .etag(S::OK)
+        .add();
+    let representations: Vec<&str> = TodoFormat::ALL
+        .iter()
+        .map(|format| format.content_type())
+        .collect();
+    document
+        .operation("get", "/v2/todos", "v2_get_todos", "v2", "List todos")
+        .negotiated::<Vec<Todo>>(S::OK, &representations)
+        .problem(&[S::NOT_ACCEPTABLE, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation("post", "/v2/todos", "v2_create_todo", "v2", "Create a todo")
+        .actor()
+        .header(
+            IDEMPOTENCY_KEY_HEADER,
+            "Retries with the same key return the todo the first request created.",
+        )
+        .body::<CreateTodoPayload>()
+        .json::<Todo>(&[S::CREATED])
+        .etag(S::CREATED)
+        .response_header(
+            S::CREATED,
+            "Location",
+            &format!("The URL of the todo, e.g. `{}`.", location(1)),
+        )
+        .problem(&[
+            S::BAD_REQUEST,
+            S::UNSUPPORTED_MEDIA_TYPE,
+            S::UNPROCESSABLE_ENTITY,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .add();
+    document
+        .operation("get", "/v2/todos/{id}", "v2_get_todo", "v2", "Get a todo")
+        .id("id")
+        .query::<AsOfQuery>()
+        .header(
+            "If-None-Match",
+            "Answers 304 while the todo still has this `ETag`.",
+        )
+        .negotiated::<Todo>(S::OK, &representations)
+        .etag(S::OK)
+        .empty(S::NOT_MODIFIED)
+        .problem(&[
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::NOT_ACCEPTABLE,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .add();
+    document
+        .operation(
+            "put",
+            "/v2/todos/{id}",
+            "v2_replace_todo",
+            "v2",
+            "Replace the fields of a todo",
+        )
+        .id("id")
+        .actor()
+        .header("If-Match", "The `ETag` the todo is expected to still have.")
+        .body::<CreateTodoPayload>()
+        .json::<Todo>(&[S::OK])
+        .etag(S::OK)
+        .problem(&[
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::PRECONDITION_FAILED,
+            S::UNSUPPORTED_MEDIA_TYPE,
+            S::UNPROCESSABLE_ENTITY,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .add();
+    document
+        .operation(
+            "patch",
+            "/v2/todos/{id}",
+            "v2_patch_todo",
+            "v2",
+            "Change some fields of a todo with a JSON Merge Patch",
+        )
+        .id("id")
+        .actor()
+        .header("If-Match", "The `ETag` the todo is expected to still have.")
+        .body_as::<TodoMergePatch>("application/merge-patch+json")
+        .json::<Todo>(&[S::OK])
+        .etag(S::OK)
+        .problem(&[
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::PRECONDITION_FAILED,
+            S::UNSUPPORTED_MEDIA_TYPE,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .response_header(
+            S::UNSUPPORTED_MEDIA_TYPE,
+            "Accept-Patch",
+            "The media type a patch must have.",
+        )
+        .add();
+    document
+        .operation(
+            "delete",
+            "/v2/todos/{id}",
+            "v2_delete_todo",
+            "v2",
+            "Move a todo to the trash",
+        )
+        .id("id")
+        .actor()
+        .header("If-Match", "The `ETag` the todo is expected to still have.")
+        .empty(S::NO_CONTENT)
+        .problem(&[
+            S::BAD_REQUEST,
+            S::NOT_FOUND,
+            S::PRECONDITION_FAILED,
+            S::INTERNAL_SERVER_ERROR,
+        ])
+        .add();
+    document
+        .operation(
+            "post",
+            "/v2/todos/{id}/complete",
+            "v2_complete_todo",
+            "v2",
+            "Complete a todo; recurring todos get their next occurrence",
+        )
+        .id("id")
+        .actor()
+        .json::<CompletedTodo>(&[S::OK])
+        .etag(S::OK)
+        .problem(&[S::BAD_REQUEST, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation("get", "/trash", "get_trash", "trash", "List deleted todos")
+        .json::<TrashResponse>(&[S::OK, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "post",
+            "/trash/{id}/restore",
+            "restore_from_trash",
+            "trash",
+            "Restore a deleted todo",
+        )
+        .id("id")
+        .actor()
+        .json::<UpdateTodoResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .etag(S::OK)
+        .add();
+    document
+        .operation(
+            "delete",
+            "/reminders/{id}",
+            "delete_reminder",
+            "reminders",
+            "Delete a reminder",
+        )
+        .id("id")
+        .json::<DeleteReminderResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/webhooks",
+            "get_webhooks",
+            "webhooks",
+            "List webhook subscriptions",
+        )
+        .json::<WebhooksResponse>(&[S::OK, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "post",
+            "/webhooks",
+            "create_webhook",
+            "webhooks",
+            "Subscribe a URL to todo events",
+        )
+        .body::<CreateWebhookPayload>()
+        .json::<WebhookResponse>(&[S::OK, S::BAD_REQUEST, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "delete",
+            "/webhooks/{id}",
+            "delete_webhook",
+            "webhooks",
+            "Delete a webhook subscription",
+        )
+        .id("id")
+        .json::<DeleteWebhookResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/webhooks/{id}/deliveries",
+            "get_webhook_deliveries",
+            "webhooks",
+            "List the deliveries of a webhook subscription",
+        )
+        .id("id")
+        .json::<WebhookDeliveriesResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/calendar/feeds",
+            "get_calendar_feeds",
+            "calendar",
+            "List the calendar feeds of the actor",
+        )
+        .actor()
+        .json::<CalendarFeedsResponse>(&[S::OK, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "post",
+            "/calendar/feeds",
+            "create_calendar_feed",
+            "calendar",
+            "Create a calendar feed at a new secret URL",
+        )
+        .actor()
+        .json::<CalendarFeedResponse>(&[S::OK, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "delete",
+            "/calendar/feeds/{id}",
+            "delete_calendar_feed",
+            "calendar",
+            "Revoke a calendar feed",
+        )
+        .id("id")
+        .actor()
+        .json::<DeleteCalendarFeedResponse>(&[S::OK, S::NOT_FOUND, S::INTERNAL_SERVER_ERROR])
+        .add();
+    document
+        .operation(
+            "get",
+            "/calendar/{token}/todos.ics",
+            "get_calendar",
+            "calendar",
+            "Subscribe to every todo as an iCalendar feed",
+        )
+        .path_parameter("token", String::schema(&mut Schemas::new()))
+        .file(S::OK, &[calendar])
+        .empty(S::NOT_FOUND)
+        .add();
+    document
+        .operation(
+            "get",
+            "/admin/audit-log",
+            "get_audit_log",
+            "audit",
+            "List changes to todos, newest first",
+        )
+        .query::<AuditLogQuery>()
+        .json::<AuditLogResponse>(&[S::OK, S::BAD_REQUEST, S::INTERNAL_SERVER_ERROR])
+        .add();
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "clean-architecture-playground",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Every v1 route is also served under `/v1`. The deprecated \
+                v1 todo routes answer with the v2 ones instead when the request accepts \
+                `application/vnd.todo.v2+json`.",
+        },
+        "paths": document.paths,
+        "components": { "schemas": document.schemas },
+    })
+}
+
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(openapi())
+}
+
+/// Swagger UI 5.17.14, vendored under `assets/swagger-ui` with its license so that the
+/// page runs no script from another origin.
+const SWAGGER_UI_BUNDLE: &str = include_str!("../../assets/swagger-ui/swagger-ui-bundle.js");
+const SWAGGER_UI_CSS: &str = include_str!("../../assets/swagger-ui/swagger-ui.css");
+
+/// Swagger UI rendering `/openapi.json`.
+pub async fn docs() -> impl IntoResponse {
+    Html(
+        r##"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>clean-architecture-playground API</title>
+    <link rel="stylesheet" href="/docs/swagger-ui.css">
+  </head>
+  <body>
+    <div id="swagger-ui"></div>
+    <script src="/docs/swagger-ui-bundle.js"></script>
+    <script>
+      window.onload = () => {
+        window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+      };
+    </script>
+  </body>
+</html>
+"##,
+    )
+}
+
+/// The vendored Swagger UI files `docs` loads.
+pub async fn docs_asset(Path(asset): Path<String>) -> Response {
+    let (content_type, body) = match asset.as_str() {
+        "swagger-ui-bundle.js" => ("text/javascript; charset=utf-8", SWAGGER_UI_BUNDLE),
+        "swagger-ui.css" => ("text/css; charset=utf-8", SWAGGER_UI_CSS),
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    /// The committed document; run the tests with `UPDATE_OPENAPI=1` to rewrite it.
+    const SNAPSHOT: &str = include_str!("../../openapi.json");
+
+    #[test]
+    fn test_openapi_matches_snapshot() {
+        let document = serde_json::to_string_pretty(&openapi()).unwrap() + "\n";
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+            std::fs::write(path, &document).unwrap();
+            return;
+        }
+        assert!(
+            document == SNAPSHOT,
+            "the OpenAPI document drifted from presentation/openapi.json; \
+             review the change and rerun with UPDATE_OPENAPI=1 to accept it"
+        );
+    }
+
+    fn assert_serialized_names<T: StringEnum + Serialize>() {
+        for (value, name) in T::VALUES {
+            assert_eq!(serde_json::to_value(value).unwrap(), json!(name));
+        }
+    }
+
+    #[test]
+    fn test_enum_schemas_match_serde() {
+        assert_serialized_names::<Priority>();
+        assert_serialized_names::<BatchModePayload>();
+        assert_serialized_names::<PresentationalError>();
+    }
+
+    #[test]
+    fn test_every_reference_is_defined() {
+        fn references(value: &Value, found: &mut Vec<String>) {
+            match value {
+                Value::Object(object) => {
+                    if let Some(Value::String(reference)) = object.get("$ref") {
+                        found.push(reference.clone());
+                    }
+                    object.values().for_each(|value| references(value, found));
+                }
+                Value::Array(values) => values.iter().for_each(|value| references(value, found)),
+                _ => {}
+            }
+        }
+        let document = openapi();
+        let mut found = vec![];
+        references(&document, &mut found);
+        for reference in found {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            assert!(
+                document["components"]["schemas"].get(name).is_some(),
+                "{} is not defined",
+                reference
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_docs_load_only_vendored_assets() {
+        let page = docs().await.into_response();
+        let page = hyper::body::to_bytes(page.into_body()).await.unwrap();
+        let page = String::from_utf8(page.to_vec()).unwrap();
+        assert!(!page.contains("://"));
+
+        for asset in ["swagger-ui-bundle.js", "swagger-ui.css"] {
+            assert!(page.contains(&format!("/docs/{}", asset)));
+            let response = docs_asset(Path(asset.to_string())).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert!(body.len() > 100_000, "{} is truncated", asset);
+        }
+        let response = docs_asset(Path("index.html".to_string())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
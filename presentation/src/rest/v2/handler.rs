@@ -0,0 +1,408 @@
+use axum::{
+    body::Bytes,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Path, Query,
+    },
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use domain::entity::audit::Protocol;
+use use_case::{
+    dto::{import_export::TodoFormat, todo::TodoDto},
+    error::UseCaseError,
+    todo_format,
+    traits::todo::TodoUseCase,
+};
+
+use crate::{
+    adapter,
+    context::{from_headers, idempotency_key_from_headers},
+    error::PresentationalError,
+    rest::{
+        handler::{etag, expected_version, status_code},
+        object::{AsOfQuery, CreateTodoPayload, Todo},
+        version::V2_MEDIA_TYPE,
+    },
+};
+
+use super::object::{replacement, CompletedTodo, Problem, TodoMergePatch};
+
+const JSON: &str = "application/json";
+const PROBLEM_JSON: &str = "application/problem+json";
+const MERGE_PATCH_JSON: &str = "application/merge-patch+json";
+
+pub async fn get_todos<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(media_type) = negotiate(&headers) else {
+        return not_acceptable();
+    };
+    match tu.find_all().await {
+        Ok(todos) => represent(media_type, todos, HeaderMap::new()),
+        Err(err) => use_case_problem(err),
+    }
+}
+
+pub async fn create_todo<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    headers: HeaderMap,
+    payload: Result<Json<CreateTodoPayload>, JsonRejection>,
+) -> Response {
+    let payload = match payload {
+        Ok(Json(payload)) => payload,
+        Err(rejection) => return json_problem(rejection),
+    };
+    let ctx = from_headers(&headers, Protocol::Rest);
+    let idempotency_key = idempotency_key_from_headers(&headers);
+    match tu.create(ctx, payload.into(), idempotency_key).await {
+        Ok(todo) => {
+            let mut headers = etag(todo.version);
+            if let Ok(location) = HeaderValue::from_str(&location(todo.id)) {
+                headers.insert(header::LOCATION, location);
+            }
+            (StatusCode::CREATED, headers, Json(Todo::from(todo))).into_response()
+        }
+        Err(err) => use_case_problem(err),
+    }
+}
+
+/// Answers `304 Not Modified` when `If-None-Match` names the todo's current `ETag`. With
+/// `as_of`, answers the todo as it was then.
+pub async fn get_todo<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Path(id): Path<i64>,
+    query: Result<Query<AsOfQuery>, QueryRejection>,
+    headers: HeaderMap,
+) -> Response {
+    let as_of = match query {
+        Ok(Query(query)) => query.as_of,
+        Err(rejection) => return query_problem(rejection),
+    };
+    let Some(media_type) = negotiate(&headers) else {
+        return not_acceptable();
+    };
+    let todo = match as_of {
+        Some(at) => adapter::find_todo_at(&tu, id, at).await,
+        None => adapter::find_todo(&tu, id).await,
+    };
+    let todo = match todo {
+        Ok(todo) => todo,
+        Err(err) => return use_case_problem(err),
+    };
+    let mut response_headers = etag(todo.version);
+    response_headers.insert(header::VARY, HeaderValue::from_static("accept"));
+    if none_match(&headers, todo.version) {
+        return (StatusCode::NOT_MODIFIED, response_headers).into_response();
+    }
+    if media_type == JSON {
+        return (StatusCode::OK, response_headers, Json(Todo::from(todo))).into_response();
+    }
+    represent(media_type, vec![todo], response_headers)
+}
+
+/// Replaces every field of the todo but `completed_at`, which only `complete` changes.
+pub async fn replace_todo<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+    payload: Result<Json<CreateTodoPayload>, JsonRejection>,
+) -> Response {
+    let expected_version = match expected_version(&headers) {
+        Ok(expected_version) => expected_version,
+        Err(err) => return if_match_problem(err),
+    };
+    let payload = match payload {
+        Ok(Json(payload)) => payload,
+        Err(rejection) => return json_problem(rejection),
+    };
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match tu
+        .update(ctx, replacement(id, payload).into(), expected_version)
+        .await
+    {
+        Ok(todo) => (StatusCode::OK, etag(todo.version), Json(Todo::from(todo))).into_response(),
+        Err(err) => use_case_problem(err),
+    }
+}
+
+/// Applies an `application/merge-patch+json` body to the todo, failing with 412 rather
+/// than overwriting a concurrent update.
+pub async fn patch_todo<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !has_content_type(&headers, MERGE_PATCH_JSON) {
+        let mut response = problem(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            PresentationalError::BadRequest,
+            Some(format!("the body must be {}", MERGE_PATCH_JSON)),
+        );
+        response.headers_mut().insert(
+            HeaderName::from_static("accept-patch"),
+            HeaderValue::from_static(MERGE_PATCH_JSON),
+        );
+        return response;
+    }
+    let expected_version = match expected_version(&headers) {
+        Ok(expected_version) => expected_version,
+        Err(err) => return if_match_problem(err),
+    };
+    let patch: TodoMergePatch = match serde_json::from_slice(&body) {
+        Ok(patch) => patch,
+        Err(e) => {
+            return problem(
+                StatusCode::BAD_REQUEST,
+                PresentationalError::BadRequest,
+                Some(e.to_string()),
+            )
+        }
+    };
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match adapter::patch_todo(&tu, ctx, id, expected_version, |todo| patch.apply(todo)).await {
+        Ok(todo) => (StatusCode::OK, etag(todo.version), Json(Todo::from(todo))).into_response(),
+        Err(err) => use_case_problem(err),
+    }
+}
+
+/// Moves the todo to the trash. With `If-Match`, only if it is still at that version.
+pub async fn delete_todo<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+) -> Response {
+    let expected_version = match expected_version(&headers) {
+        Ok(expected_version) => expected_version,
+        Err(err) => return if_match_problem(err),
+    };
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match adapter::delete_todo(&tu, ctx, id, expected_version).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => use_case_problem(err),
+    }
+}
+
+pub async fn complete_todo<TU: TodoUseCase>(
+    Extension(tu): Extension<TU>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+) -> Response {
+    let ctx = from_headers(&headers, Protocol::Rest);
+    match tu.complete(ctx, id).await {
+        Ok(completed) => (
+            StatusCode::OK,
+            etag(completed.todo.version),
+            Json(CompletedTodo::from(completed)),
+        )
+            .into_response(),
+        Err(err) => use_case_problem(err),
+    }
+}
+
+pub(crate) fn location(id: i64) -> String {
+    format!("/v2/todos/{}", id)
+}
+
+/// JSON, then every export format, each by its media type without parameters.
+fn offered_media_types() -> Vec<&'static str> {
+    std::iter::once(JSON)
+        .chain(
+            TodoFormat::ALL
+                .iter()
+                .map(|format| essence(format.content_type())),
+        )
+        .collect()
+}
+
+fn essence(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or(media_type).trim()
+}
+
+/// The offered media type the `Accept` header prefers, by quality and then in the order
+/// they are offered; JSON without an `Accept` header.
+fn negotiate(headers: &HeaderMap) -> Option<&'static str> {
+    let offered = offered_media_types();
+    let accept = match headers.get(header::ACCEPT).map(|accept| accept.to_str()) {
+        Some(Ok(accept)) if !accept.trim().is_empty() => accept,
+        Some(Err(_)) => return None,
+        _ => return Some(JSON),
+    };
+    let ranges: Vec<(&str, f32)> = accept
+        .split(',')
+        .map(|range| {
+            let mut parts = range.split(';');
+            let media_range = parts.next().unwrap_or_default().trim();
+            let quality = parts
+                .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                .find_map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (media_range, quality)
+        })
+        .collect();
+    let mut preferred: Option<(&'static str, f32)> = None;
+    for media_type in offered {
+        // the most specific range that matches decides the quality
+        let quality = ranges
+            .iter()
+            .filter_map(|(range, quality)| {
+                specificity(range, media_type).map(|specificity| (specificity, *quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, quality)| quality);
+        match (quality, preferred) {
+            (Some(quality), Some((_, best))) if quality > best => {
+                preferred = Some((media_type, quality))
+            }
+            (Some(quality), None) if quality > 0.0 => preferred = Some((media_type, quality)),
+            _ => {}
+        }
+    }
+    preferred.map(|(media_type, _)| media_type)
+}
+
+/// How specifically `range` matches `media_type`: 2 for the type itself, 1 for `type/*`
+/// and 0 for `*/*`. The versioned [`V2_MEDIA_TYPE`] is JSON.
+fn specificity(range: &str, media_type: &str) -> Option<u8> {
+    if range.eq_ignore_ascii_case(media_type)
+        || (media_type == JSON && range.eq_ignore_ascii_case(V2_MEDIA_TYPE))
+    {
+        return Some(2);
+    }
+    if range == "*/*" {
+        return Some(0);
+    }
+    match (range.strip_suffix("/*"), media_type.split('/').next()) {
+        (Some(range_type), Some(media_type)) if range_type.eq_ignore_ascii_case(media_type) => {
+            Some(1)
+        }
+        _ => None,
+    }
+}
+
+/// `todos` as `media_type`, one of [`offered_media_types`].
+fn represent(media_type: &str, todos: Vec<TodoDto>, mut headers: HeaderMap) -> Response {
+    headers.insert(header::VARY, HeaderValue::from_static("accept"));
+    let Some(format) = TodoFormat::ALL
+        .into_iter()
+        .find(|format| essence(format.content_type()) == media_type)
+    else {
+        let todos: Vec<Todo> = todos.into_iter().map(|todo| todo.into()).collect();
+        return (StatusCode::OK, headers, Json(todos)).into_response();
+    };
+    let mut body = todo_format::header(format).unwrap_or_default().to_string();
+    for todo in todos {
+        let encoded = domain::entity::todo::Todo::try_from(todo)
+            .and_then(|todo| todo_format::encode(format, &todo));
+        match encoded {
+            Ok(record) => body.push_str(&record),
+            Err(err) => return use_case_problem(err),
+        }
+    }
+    body.push_str(todo_format::footer(format).unwrap_or_default());
+    if let Ok(content_type) = HeaderValue::from_str(format.content_type()) {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    (StatusCode::OK, headers, body).into_response()
+}
+
+fn has_content_type(headers: &HeaderMap, media_type: &str) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| essence(content_type).eq_ignore_ascii_case(media_type))
+}
+
+/// Whether `If-None-Match` names the `ETag` of `version`, or is `*`.
+fn none_match(headers: &HeaderMap, version: i64) -> bool {
+    let current = format!("\"{}\"", version);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == current)
+}
+
+pub(crate) fn problem(
+    status: StatusCode,
+    error: PresentationalError,
+    detail: Option<String>,
+) -> Response {
+    let problem = Problem {
+        title: status.canonical_reason().unwrap_or_default().to_string(),
+        status: status.as_u16(),
+        error,
+        detail,
+    };
+    (
+        status,
+        [(header::CONTENT_TYPE, PROBLEM_JSON)],
+        Json(problem),
+    )
+        .into_response()
+}
+
+/// The problem an error of a use case answers with. Only errors the client can act on
+/// are detailed.
+pub(crate) fn use_case_problem(err: UseCaseError) -> Response {
+    let detail = match &err {
+        UseCaseError::Validation(message) => Some(message.clone()),
+        UseCaseError::NotFound {
+            entity_type,
+            entity_id,
+        } => Some(format!("{} {} not found", entity_type, entity_id)),
+        UseCaseError::Conflict {
+            entity_type,
+            entity_id,
+        } => Some(format!(
+            "{} {} is not at the version in If-Match",
+            entity_type, entity_id
+        )),
+        UseCaseError::IdempotencyKeyReused(key) => Some(format!(
+            "idempotency key {} was used for a different todo",
+            key
+        )),
+        UseCaseError::Other(_) | UseCaseError::Unexpected(_) => None,
+    };
+    problem(status_code(&err), err.into(), detail)
+}
+
+fn not_acceptable() -> Response {
+    problem(
+        StatusCode::NOT_ACCEPTABLE,
+        PresentationalError::BadRequest,
+        Some(format!(
+            "available media types: {}",
+            offered_media_types().join(", ")
+        )),
+    )
+}
+
+fn if_match_problem(err: PresentationalError) -> Response {
+    problem(
+        StatusCode::BAD_REQUEST,
+        err,
+        Some("If-Match must be an ETag, e.g. \"3\"".to_string()),
+    )
+}
+
+fn json_problem(rejection: JsonRejection) -> Response {
+    problem(
+        rejection.status(),
+        PresentationalError::BadRequest,
+        Some(rejection.body_text()),
+    )
+}
+
+fn query_problem(rejection: QueryRejection) -> Response {
+    problem(
+        rejection.status(),
+        PresentationalError::BadRequest,
+        Some(rejection.body_text()),
+    )
+}
@@ -0,0 +1,6 @@
+//! The second version of the REST API: todos as resources at `/v2/todos/:id`, answered
+//! with bare representations, the status codes HTTP defines for each outcome and
+//! `application/problem+json` errors. The v1 routes stay as they are for existing clients.
+
+pub mod handler;
+pub mod object;
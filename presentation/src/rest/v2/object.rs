@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use use_case::dto::todo::{CompletedTodoDto, TodoDto};
+
+use crate::{
+    adapter::present,
+    error::PresentationalError,
+    rest::object::{CreateTodoPayload, Priority, Todo},
+};
+
+/// The body of every v2 error, an RFC 9457 problem detail served as
+/// `application/problem+json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Problem {
+    pub title: String,
+    pub status: u16,
+    pub error: PresentationalError,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedTodo {
+    pub todo: Todo,
+    /// The next occurrence spawned by a recurring todo.
+    pub next: Option<Todo>,
+}
+
+impl From<CompletedTodoDto> for CompletedTodo {
+    fn from(completed: CompletedTodoDto) -> Self {
+        Self {
+            todo: completed.todo.into(),
+            next: completed.next.map(|next| next.into()),
+        }
+    }
+}
+
+/// The todo `PUT /v2/todos/:id` replaces the one at `id` with; the body is the one of a
+/// create.
+pub fn replacement(id: i64, payload: CreateTodoPayload) -> TodoDto {
+    TodoDto {
+        id,
+        title: Some(payload.title),
+        due_at: payload.due_at,
+        priority: payload.priority.into(),
+        recurrence: payload.recurrence,
+        completed_at: None,
+        // the expected version travels in the `If-Match` header
+        version: 0,
+    }
+}
+
+/// An RFC 7396 JSON Merge Patch of a todo. Members left out are kept, and `null` clears
+/// `due_at` and `recurrence` and resets `priority`; `title` cannot be cleared.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TodoMergePatch {
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub title: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub priority: Option<Option<Priority>>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub recurrence: Option<Option<String>>,
+}
+
+impl TodoMergePatch {
+    /// `todo` with the patch applied, or why it cannot be.
+    pub fn apply(self, mut todo: TodoDto) -> Result<TodoDto, String> {
+        match self.title {
+            Some(Some(title)) => todo.title = Some(title),
+            Some(None) => return Err("title cannot be null".to_string()),
+            None => {}
+        }
+        if let Some(due_at) = self.due_at {
+            todo.due_at = due_at;
+        }
+        if let Some(priority) = self.priority {
+            todo.priority = priority.unwrap_or_default().into();
+        }
+        if let Some(recurrence) = self.recurrence {
+            todo.recurrence = recurrence;
+        }
+        Ok(todo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo() -> TodoDto {
+        TodoDto {
+            id: 1,
+            title: Some("water the plants".to_string()),
+            due_at: Some(Utc::now()),
+            priority: domain::entity::todo::Priority::High,
+            recurrence: Some("FREQ=WEEKLY".to_string()),
+            completed_at: None,
+            version: 3,
+        }
+    }
+
+    #[test]
+    fn test_merge_patch_keeps_missing_members_and_clears_null_ones() {
+        let patch: TodoMergePatch = serde_json::from_str(
+            r#"{"title": "water the cactus", "due_at": null, "priority": null}"#,
+        )
+        .unwrap();
+        let patched = patch.apply(todo()).unwrap();
+        assert_eq!(patched.title.as_deref(), Some("water the cactus"));
+        assert_eq!(patched.due_at, None);
+        assert_eq!(patched.priority, domain::entity::todo::Priority::Medium);
+        assert_eq!(patched.recurrence.as_deref(), Some("FREQ=WEEKLY"));
+        assert_eq!(patched.version, 3);
+    }
+
+    #[test]
+    fn test_merge_patch_rejects_null_title_and_unknown_members() {
+        let patch: TodoMergePatch = serde_json::from_str(r#"{"title": null}"#).unwrap();
+        assert!(patch.apply(todo()).is_err());
+        assert!(serde_json::from_str::<TodoMergePatch>(r#"{"version": 4}"#).is_err());
+    }
+}
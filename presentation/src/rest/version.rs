@@ -0,0 +1,97 @@
+//! Picks the version of the todo routes a request is served by. Every route is served
+//! under its version prefix, `/v1/...` and `/v2/...`; the unprefixed todo routes are the
+//! deprecated v1 ones, unless the request accepts [`V2_MEDIA_TYPE`].
+
+use axum::http::{header, uri::PathAndQuery, Method, Request, Uri};
+
+/// The media type that asks for the v2 representation of an unprefixed todo route.
+pub const V2_MEDIA_TYPE: &str = "application/vnd.todo.v2+json";
+
+/// Rewrites an unprefixed `/todos`, `/todos/:id` or `/todos/:id/complete` to its `/v2`
+/// route when the request accepts [`V2_MEDIA_TYPE`] and v2 serves its method. Routing
+/// happens on the rewritten path, so this must wrap the router rather than be one of its
+/// layers.
+pub async fn route_by_accept<B>(mut request: Request<B>) -> Request<B> {
+    if accepts_v2(&request) && has_v2_route(request.method(), request.uri().path()) {
+        if let Some(uri) = versioned(request.uri()) {
+            *request.uri_mut() = uri;
+        }
+    }
+    request
+}
+
+fn accepts_v2<B>(request: &Request<B>) -> bool {
+    request
+        .headers()
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|accept| accept.to_str().ok())
+        .flat_map(|accept| accept.split(','))
+        .any(|range| {
+            let media_range = range.split(';').next().unwrap_or_default().trim();
+            media_range.eq_ignore_ascii_case(V2_MEDIA_TYPE)
+        })
+}
+
+fn has_v2_route(method: &Method, path: &str) -> bool {
+    let Some(rest) = path.strip_prefix("/todos") else {
+        return false;
+    };
+    let segments: Vec<&str> = rest.split('/').skip(1).collect();
+    match segments.as_slice() {
+        [] => rest.is_empty() && [Method::GET, Method::POST].contains(method),
+        [id] => id.parse::<i64>().is_ok() && method != Method::POST,
+        [id, "complete"] => id.parse::<i64>().is_ok() && method == Method::POST,
+        _ => false,
+    }
+}
+
+fn versioned(uri: &Uri) -> Option<Uri> {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("/v2{}?{}", uri.path(), query),
+        None => format!("/v2{}", uri.path()),
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(PathAndQuery::try_from(path_and_query).ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn route(method: Method, path: &str, accept: &str) -> String {
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::ACCEPT, accept)
+            .body(())
+            .unwrap();
+        route_by_accept(request).await.uri().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_route_by_accept_rewrites_todo_routes_accepting_v2() {
+        let v2 = "application/vnd.todo.v2+json; q=0.9, */*";
+        let json = "application/json";
+        assert_eq!(
+            route(Method::GET, "/todos?sort=due", v2).await,
+            "/v2/todos?sort=due"
+        );
+        assert_eq!(route(Method::PATCH, "/todos/7", v2).await, "/v2/todos/7");
+        assert_eq!(
+            route(Method::POST, "/todos/7/complete", v2).await,
+            "/v2/todos/7/complete"
+        );
+        assert_eq!(route(Method::PUT, "/todos", v2).await, "/todos");
+        assert_eq!(
+            route(Method::GET, "/todos/overdue", v2).await,
+            "/todos/overdue"
+        );
+        assert_eq!(
+            route(Method::POST, "/todos:batch", v2).await,
+            "/todos:batch"
+        );
+        assert_eq!(route(Method::GET, "/todos/7", json).await, "/todos/7");
+    }
+}
@@ -0,0 +1,309 @@
+//! Use cases kept in memory, for driving the handlers in tests.
+
+use std::sync::{Arc, Mutex};
+
+use axum::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use domain::entity::todo::Priority;
+use use_case::{
+    dto::{
+        audit::RequestContext,
+        batch::{
+            BatchItemResultDto, BatchMode, BatchOperationDto, BatchOutcomeDto, BatchResultDto,
+        },
+        todo::{CompletedTodoDto, CreateTodoDto, TodoDto, UpdateTodoDto},
+    },
+    error::UseCaseError,
+    traits::todo::{QueryUseCase, TodoUseCase},
+};
+
+/// Keeps todos in memory with the versioning of the real interactor: every change bumps
+/// the version and an update expecting another version fails with `Conflict`.
+#[derive(Clone, Default)]
+pub(crate) struct MockTodoUseCase {
+    todos: Arc<Mutex<Vec<TodoDto>>>,
+}
+
+impl MockTodoUseCase {
+    pub(crate) fn with_titles(titles: &[&str]) -> Self {
+        let todos = titles
+            .iter()
+            .enumerate()
+            .map(|(index, title)| TodoDto {
+                id: index as i64 + 1,
+                title: Some(title.to_string()),
+                due_at: None,
+                priority: Priority::default(),
+                recurrence: None,
+                completed_at: None,
+                version: 1,
+            })
+            .collect();
+        Self {
+            todos: Arc::new(Mutex::new(todos)),
+        }
+    }
+
+    pub(crate) fn todos(&self) -> Vec<TodoDto> {
+        self.todos.lock().unwrap().clone()
+    }
+
+    fn not_found(todo_id: i64) -> UseCaseError {
+        UseCaseError::NotFound {
+            entity_type: "todo".to_string(),
+            entity_id: todo_id,
+        }
+    }
+
+    fn run(&self, operation: BatchOperationDto) -> Result<BatchOutcomeDto, UseCaseError> {
+        let mut todos = self.todos.lock().unwrap();
+        match operation {
+            BatchOperationDto::Create(todo_data) => {
+                let todo = TodoDto {
+                    id: todos.iter().map(|todo| todo.id).max().unwrap_or(0) + 1,
+                    title: Some(todo_data.title),
+                    due_at: todo_data.due_at,
+                    priority: todo_data.priority,
+                    recurrence: todo_data.recurrence,
+                    completed_at: None,
+                    version: 1,
+                };
+                todos.push(todo.clone());
+                Ok(BatchOutcomeDto::Created(todo))
+            }
+            BatchOperationDto::Update {
+                todo: todo_data,
+                expected_version,
+            } => {
+                let todo = match todos.iter_mut().find(|todo| todo.id == todo_data.id) {
+                    Some(todo) => todo,
+                    None => return Err(Self::not_found(todo_data.id)),
+                };
+                if expected_version.is_some_and(|version| version != todo.version) {
+                    return Err(UseCaseError::Conflict {
+                        entity_type: "todo".to_string(),
+                        entity_id: todo.id,
+                    });
+                }
+                let UpdateTodoDto {
+                    title,
+                    due_at,
+                    priority,
+                    recurrence,
+                    ..
+                } = todo_data;
+                todo.title = title.or(todo.title.take());
+                todo.due_at = due_at.unwrap_or(todo.due_at);
+                todo.priority = priority.unwrap_or(todo.priority);
+                todo.recurrence = recurrence.unwrap_or(todo.recurrence.take());
+                todo.version += 1;
+                Ok(BatchOutcomeDto::Updated(todo.clone()))
+            }
+            // deleting a missing todo is a no-op, as in the interactor
+            BatchOperationDto::Delete(todo_id) => {
+                todos.retain(|todo| todo.id != todo_id);
+                Ok(BatchOutcomeDto::Deleted(todo_id))
+            }
+            BatchOperationDto::Complete(todo_id) => {
+                let todo = match todos.iter_mut().find(|todo| todo.id == todo_id) {
+                    Some(todo) => todo,
+                    None => return Err(Self::not_found(todo_id)),
+                };
+                todo.completed_at = Some(Utc::now());
+                todo.version += 1;
+                Ok(BatchOutcomeDto::Completed(CompletedTodoDto {
+                    todo: todo.clone(),
+                    next: None,
+                }))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TodoUseCase for MockTodoUseCase {
+    async fn create(
+        &self,
+        _ctx: RequestContext,
+        todo_data: CreateTodoDto,
+        _idempotency_key: Option<String>,
+    ) -> Result<TodoDto, UseCaseError> {
+        match self.run(BatchOperationDto::Create(todo_data))? {
+            BatchOutcomeDto::Created(todo) => Ok(todo),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn update(
+        &self,
+        _ctx: RequestContext,
+        todo_data: UpdateTodoDto,
+        expected_version: Option<i64>,
+    ) -> Result<TodoDto, UseCaseError> {
+        match self.run(BatchOperationDto::Update {
+            todo: todo_data,
+            expected_version,
+        })? {
+            BatchOutcomeDto::Updated(todo) => Ok(todo),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn delete(
+        &self,
+        _ctx: RequestContext,
+        todo_id: i64,
+        expected_version: Option<i64>,
+    ) -> Result<i64, UseCaseError> {
+        let todo = self.todos().into_iter().find(|todo| todo.id == todo_id);
+        if let (Some(todo), Some(expected_version)) = (todo, expected_version) {
+            if todo.version != expected_version {
+                return Err(UseCaseError::Conflict {
+                    entity_type: "todo".to_string(),
+                    entity_id: todo_id,
+                });
+            }
+        }
+        match self.run(BatchOperationDto::Delete(todo_id))? {
+            BatchOutcomeDto::Deleted(todo_id) => Ok(todo_id),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn complete(
+        &self,
+        _ctx: RequestContext,
+        todo_id: i64,
+    ) -> Result<CompletedTodoDto, UseCaseError> {
+        match self.run(BatchOperationDto::Complete(todo_id))? {
+            BatchOutcomeDto::Completed(completed) => Ok(completed),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Runs every operation in order; an atomic batch stops at the first rejected one but
+    /// keeps the operations before it.
+    async fn batch(
+        &self,
+        _ctx: RequestContext,
+        operations: Vec<BatchOperationDto>,
+        mode: BatchMode,
+    ) -> Result<BatchResultDto, UseCaseError> {
+        let mut items = Vec::new();
+        for (index, operation) in operations.into_iter().enumerate() {
+            let result = self.run(operation);
+            let rejected = result.is_err();
+            items.push(BatchItemResultDto { index, result });
+            if rejected && mode == BatchMode::Atomic {
+                return Ok(BatchResultDto {
+                    committed: false,
+                    items,
+                });
+            }
+        }
+        Ok(BatchResultDto {
+            committed: true,
+            items,
+        })
+    }
+
+    async fn find_all(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        Ok(self.todos())
+    }
+
+    async fn find_by_id(&self, todo_id: i64) -> Result<Option<TodoDto>, UseCaseError> {
+        Ok(self.todos().into_iter().find(|todo| todo.id == todo_id))
+    }
+
+    /// Keeps no history: a todo is as it is now at any time.
+    async fn find_by_id_at(
+        &self,
+        todo_id: i64,
+        _at: DateTime<Utc>,
+    ) -> Result<Option<TodoDto>, UseCaseError> {
+        TodoUseCase::find_by_id(self, todo_id).await
+    }
+
+    async fn find_overdue(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        let now = Utc::now();
+        Ok(self
+            .todos()
+            .into_iter()
+            .filter(|todo| todo.completed_at.is_none() && todo.due_at.is_some_and(|at| at < now))
+            .collect())
+    }
+
+    async fn find_due_today(
+        &self,
+        _timezone: Option<String>,
+    ) -> Result<Vec<TodoDto>, UseCaseError> {
+        let today = Utc::now().date_naive();
+        Ok(self
+            .todos()
+            .into_iter()
+            .filter(|todo| todo.due_at.is_some_and(|at| at.date_naive() == today))
+            .collect())
+    }
+
+    async fn find_due_this_week(
+        &self,
+        _timezone: Option<String>,
+    ) -> Result<Vec<TodoDto>, UseCaseError> {
+        let week = Utc::now().date_naive().iso_week();
+        Ok(self
+            .todos()
+            .into_iter()
+            .filter(|todo| {
+                todo.due_at
+                    .is_some_and(|at| at.date_naive().iso_week() == week)
+            })
+            .collect())
+    }
+
+    async fn find_high_priority(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        Ok(self
+            .todos()
+            .into_iter()
+            .filter(|todo| todo.priority == Priority::High)
+            .collect())
+    }
+}
+
+/// The queries of [`TodoUseCase`], for the GraphQL schema.
+#[async_trait]
+impl QueryUseCase for MockTodoUseCase {
+    async fn find_all(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        TodoUseCase::find_all(self).await
+    }
+
+    async fn find_by_id(&self, todo_id: i64) -> Result<Option<TodoDto>, UseCaseError> {
+        TodoUseCase::find_by_id(self, todo_id).await
+    }
+
+    async fn find_by_ids(&self, todo_ids: Vec<i64>) -> Result<Vec<TodoDto>, UseCaseError> {
+        Ok(self
+            .todos()
+            .into_iter()
+            .filter(|todo| todo_ids.contains(&todo.id))
+            .collect())
+    }
+
+    async fn find_overdue(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        TodoUseCase::find_overdue(self).await
+    }
+
+    async fn find_due_today(&self, timezone: Option<String>) -> Result<Vec<TodoDto>, UseCaseError> {
+        TodoUseCase::find_due_today(self, timezone).await
+    }
+
+    async fn find_due_this_week(
+        &self,
+        timezone: Option<String>,
+    ) -> Result<Vec<TodoDto>, UseCaseError> {
+        TodoUseCase::find_due_this_week(self, timezone).await
+    }
+
+    async fn find_high_priority(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        TodoUseCase::find_high_priority(self).await
+    }
+}
@@ -1,22 +1,175 @@
 use axum::{
-    routing::{get, post},
-    Extension, Router,
+    body::Body,
+    http::{HeaderValue, Request},
+    middleware::map_request,
+    ServiceExt,
+};
+use domain::notifier::Notifier;
+use infrastructure::{
+    notifier::{HttpWebhookSender, LogNotifier, WebhookNotifier},
+    unit_of_work::TodoPersistence,
 };
 use presentation::{
-    graphql::handler::{graphql_handler, graphql_playground_handler},
-    grpc::proto_impl::{todo, TodoServiceImpl, TodoServiceServer},
-    rest::handler::{create_todo, delete_todo, get_todo, get_todos, update_todo},
+    deprecation::{DeprecationLayer, V1_DEPRECATED_AT},
+    graphql::limits::SchemaLimits,
+    grpc::{
+        proto_impl::{
+            todo, AuditServiceImpl, AuditServiceServer, RevisionServiceImpl, RevisionServiceServer,
+            TodoServiceImpl, TodoServiceServer, TrashServiceImpl, TrashServiceServer,
+        },
+        transcoding::TranscodingLayer,
+        v2 as grpc_v2,
+        web::{cors, GrpcWebLayer},
+    },
+    rest::version::route_by_accept,
+};
+use server::{
+    cli,
+    dependency_injection::{dependency_injection, Settings, AI, TI, UI, VI},
+    logger,
+    router::app,
+    scheduler::{
+        spawn_event_log_pruner, spawn_reminder_scheduler, spawn_trash_purger, spawn_webhook_worker,
+    },
 };
-use server::dependency_injection::{dependency_injection, MI, QI, UI};
 use sqlx::{Pool, Sqlite};
-use std::{env, net::SocketAddr};
-use tower::ServiceBuilder;
+use std::{env, net::SocketAddr, sync::Arc, time::Duration};
+use tower::Layer;
+use use_case::time_window::parse_timezone;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
+    logger::init()?;
+    // `main export ...` and `main import ...` run once against the database instead of serving
+    let args: Vec<String> = env::args().collect();
     let database_url = env::var("DATABASE_URL")?;
-    let server_port = env::var("SERVER_PORT")?;
-    let server_port = server_port.parse::<u16>()?;
+    // IANA timezone used by the due-date views when a request does not specify one
+    let timezone = env::var("TODO_TIMEZONE").unwrap_or("UTC".to_string());
+    let timezone = parse_timezone(&timezone)
+        .map_err(|_| anyhow::anyhow!("invalid TODO_TIMEZONE: {}", timezone))?;
+
+    // reminders are POSTed here when set, otherwise they are only logged
+    let notifier: Arc<dyn Notifier> = match env::var("REMINDER_WEBHOOK_URL") {
+        Ok(url) => Arc::new(
+            WebhookNotifier::new(url, Duration::from_secs(10))
+                .map_err(|e| anyhow::anyhow!("invalid REMINDER_WEBHOOK_URL: {}", e))?,
+        ),
+        Err(_) => Arc::new(LogNotifier::default()),
+    };
+    let poll_interval = env::var("REMINDER_POLL_INTERVAL_SECS").unwrap_or("30".to_string());
+    let poll_interval = Duration::from_secs(poll_interval.parse::<u64>()?);
+    let webhook_sender = HttpWebhookSender::new(Duration::from_secs(10))
+        .map_err(|e| anyhow::anyhow!("failed to build webhook client: {}", e))?;
+    let webhook_interval = env::var("WEBHOOK_POLL_INTERVAL_SECS").unwrap_or("5".to_string());
+    let webhook_interval = Duration::from_secs(webhook_interval.parse::<u64>()?);
+    // "event-sourced" keeps todo_events as the source of truth and todos as its projection;
+    // pick one per database, as the state store does not write the event streams
+    let persistence = match env::var("TODO_PERSISTENCE")
+        .unwrap_or("state".to_string())
+        .as_str()
+    {
+        "state" => TodoPersistence::State,
+        "event-sourced" => {
+            let snapshot_every = env::var("TODO_SNAPSHOT_EVERY").unwrap_or("50".to_string());
+            let snapshot_every = snapshot_every.parse::<i64>()?;
+            if snapshot_every < 1 {
+                return Err(anyhow::anyhow!("TODO_SNAPSHOT_EVERY must be at least 1"));
+            }
+            TodoPersistence::EventSourced { snapshot_every }
+        }
+        other => return Err(anyhow::anyhow!("invalid TODO_PERSISTENCE: {}", other)),
+    };
+
+    // deleted todos stay restorable from the trash this long before they are purged
+    let trash_retention_days = env::var("TRASH_RETENTION_DAYS").unwrap_or("30".to_string());
+    let trash_retention_days = trash_retention_days.parse::<i64>()?;
+    if trash_retention_days < 0 {
+        return Err(anyhow::anyhow!("TRASH_RETENTION_DAYS must not be negative"));
+    }
+    let trash_retention = chrono::Duration::days(trash_retention_days);
+    let purge_interval = env::var("TRASH_PURGE_INTERVAL_SECS").unwrap_or("3600".to_string());
+    let purge_interval = Duration::from_secs(purge_interval.parse::<u64>()?);
+
+    // a retried create with the same idempotency key is replayed for this long
+    let idempotency_ttl_hours = env::var("IDEMPOTENCY_KEY_TTL_HOURS").unwrap_or("24".to_string());
+    let idempotency_ttl_hours = idempotency_ttl_hours.parse::<i64>()?;
+    if idempotency_ttl_hours < 1 {
+        return Err(anyhow::anyhow!(
+            "IDEMPOTENCY_KEY_TTL_HOURS must be at least 1"
+        ));
+    }
+    let idempotency_ttl = chrono::Duration::hours(idempotency_ttl_hours);
+
+    // subscribers to /todos/events can resume from this many of the newest events
+    let event_log_capacity = env::var("EVENT_LOG_CAPACITY").unwrap_or("10000".to_string());
+    let event_log_capacity = event_log_capacity.parse::<i64>()?;
+    if event_log_capacity < 1 {
+        return Err(anyhow::anyhow!("EVENT_LOG_CAPACITY must be at least 1"));
+    }
+    let event_log_prune_interval =
+        env::var("EVENT_LOG_PRUNE_INTERVAL_SECS").unwrap_or("300".to_string());
+    let event_log_prune_interval = Duration::from_secs(event_log_prune_interval.parse::<u64>()?);
+
+    // GraphQL operations nesting deeper, selecting more fields or running longer than this
+    // are rejected; production deployments may also turn introspection off
+    let graphql_max_depth = match env::var("GRAPHQL_MAX_DEPTH") {
+        Ok(max_depth) => max_depth.parse::<usize>()?,
+        Err(_) => SchemaLimits::DEFAULT_MAX_DEPTH,
+    };
+    let graphql_max_complexity = match env::var("GRAPHQL_MAX_COMPLEXITY") {
+        Ok(max_complexity) => max_complexity.parse::<usize>()?,
+        Err(_) => SchemaLimits::DEFAULT_MAX_COMPLEXITY,
+    };
+    let graphql_timeout = match env::var("GRAPHQL_TIMEOUT_SECS") {
+        Ok(timeout) => Duration::from_secs(timeout.parse::<u64>()?),
+        Err(_) => SchemaLimits::DEFAULT_TIMEOUT,
+    };
+    let graphql_introspection = env::var("GRAPHQL_INTROSPECTION").unwrap_or("true".to_string());
+    let graphql_introspection = graphql_introspection.parse::<bool>()?;
+    let graphql_limits = SchemaLimits::new()
+        .with_max_depth(graphql_max_depth)
+        .with_max_complexity(graphql_max_complexity)
+        .with_timeout(graphql_timeout)
+        .with_introspection(graphql_introspection);
+
+    // automatic persisted queries are kept as clients send them; in strict mode only the
+    // operations registered with `main operations register` may run
+    let graphql_strict = match env::var("GRAPHQL_PERSISTED_QUERIES")
+        .unwrap_or("automatic".to_string())
+        .as_str()
+    {
+        "automatic" => false,
+        "strict" => true,
+        other => {
+            return Err(anyhow::anyhow!(
+                "invalid GRAPHQL_PERSISTED_QUERIES: {}",
+                other
+            ))
+        }
+    };
+    let persisted_query_capacity =
+        env::var("GRAPHQL_PERSISTED_QUERY_CAPACITY").unwrap_or("1000".to_string());
+    let persisted_query_capacity = persisted_query_capacity.parse::<i64>()?;
+    if persisted_query_capacity < 0 {
+        return Err(anyhow::anyhow!(
+            "GRAPHQL_PERSISTED_QUERY_CAPACITY must not be negative"
+        ));
+    }
+
+    // browser origins allowed to call the gRPC server with credentials, comma-separated
+    let grpc_web_origins = match env::var("GRPC_WEB_ALLOWED_ORIGINS") {
+        Ok(origins) => origins
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(|origin| {
+                HeaderValue::from_str(origin).map_err(|_| {
+                    anyhow::anyhow!("invalid origin in GRPC_WEB_ALLOWED_ORIGINS: {}", origin)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Err(_) => Vec::new(),
+    };
 
     let pool: Pool<Sqlite> = Pool::connect(&database_url).await?;
 
@@ -25,49 +178,100 @@ async fn main() -> Result<(), anyhow::Error> {
     //     .await
     //     .expect("Migration failed.");
 
-    let (query_use_case, schema, use_case) = dependency_injection(pool);
-
-    let app = Router::new()
-        .route("/graphiql", get(graphql_playground_handler))
-        .route("/graphql", post(graphql_handler::<QI, MI>))
-        .route(
-            "/todos",
-            get(get_todos::<UI>)
-                .post(create_todo::<UI>)
-                .put(update_todo::<UI>)
-                .delete(delete_todo::<UI>),
-        )
-        .route("/todos/:id", get(get_todo::<UI>))
-        .layer(
-            ServiceBuilder::new()
-                .layer(Extension(query_use_case))
-                .layer(Extension(schema))
-                .layer(Extension(use_case.clone())),
-        );
+    let interactors = dependency_injection(
+        pool,
+        notifier,
+        webhook_sender,
+        Settings {
+            timezone,
+            persistence,
+            trash_retention,
+            idempotency_ttl,
+            event_log_capacity,
+            graphql_limits,
+            graphql_strict,
+            persisted_query_capacity,
+        },
+    );
+
+    match args.get(1).map(String::as_str) {
+        Some("export") => return cli::export(&interactors.import_export, &args[2..]).await,
+        Some("import") => return cli::import(&interactors.import_export, &args[2..]).await,
+        Some("operations") => {
+            return cli::operations(&interactors.persisted_query, &args[2..]).await
+        }
+        Some(_) => {
+            return Err(anyhow::anyhow!(
+                "usage: main | {} | {} | {}",
+                cli::EXPORT_USAGE,
+                cli::IMPORT_USAGE,
+                cli::OPERATIONS_USAGE
+            ))
+        }
+        None => {}
+    }
+
+    let server_port = env::var("SERVER_PORT")?;
+    let server_port = server_port.parse::<u16>()?;
+
+    let scheduler_handle = spawn_reminder_scheduler(interactors.reminder.clone(), poll_interval);
+    let webhook_handle = spawn_webhook_worker(interactors.webhook.clone(), webhook_interval);
+    let purge_handle = spawn_trash_purger(interactors.trash.clone(), purge_interval);
+    let prune_handle =
+        spawn_event_log_pruner(interactors.event_log.clone(), event_log_prune_interval);
+
+    // `Accept: application/vnd.todo.v2+json` picks the v2 todo routes before routing
+    let app = map_request(route_by_accept).layer(app(interactors.clone()));
 
     let reflection_service = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(todo::FILE_DESCRIPTOR_SET)
         .build()
         .unwrap();
+    let transcoding = TranscodingLayer::new(todo::FILE_DESCRIPTOR_SET)
+        .map_err(|e| anyhow::anyhow!("invalid google.api.http option: {}", e))?;
 
     let addr = SocketAddr::from(([0, 0, 0, 0], server_port));
     let grpc_addr = SocketAddr::from(([0, 0, 0, 0], server_port + 1));
     let handle = tokio::spawn(async move {
-        println!("Listening on http://{}", addr);
+        log::info!("Listening on http://{}", addr);
         axum::Server::bind(&addr)
-            .serve(app.into_make_service())
+            .serve(ServiceExt::<Request<Body>>::into_make_service(app))
             .await
             .expect("Server failed to start.");
     });
     let grpc_handle = tokio::spawn(async move {
-        println!("Listening on http://{}", grpc_addr);
+        log::info!("Listening on http://{}", grpc_addr);
+        // HTTP/1.1 for gRPC-Web and the JSON methods transcoded from the google.api.http
+        // options of the services
         tonic::transport::Server::builder()
+            .accept_http1(true)
+            .layer(cors(grpc_web_origins))
+            .layer(transcoding)
+            .layer(GrpcWebLayer::new())
             .add_service(reflection_service)
-            .add_service(TodoServiceServer::<TodoServiceImpl<UI>>::new(
-                TodoServiceImpl::<UI> {
-                    tu: use_case.clone(),
-                },
-            ))
+            .add_service(
+                DeprecationLayer::new(V1_DEPRECATED_AT).layer(TodoServiceServer::<
+                    TodoServiceImpl<UI>,
+                >::new(
+                    TodoServiceImpl::<UI> {
+                        tu: interactors.todo.clone(),
+                    },
+                )),
+            )
+            .add_service(grpc_v2::TodoServiceServer::new(grpc_v2::TodoServiceImpl::<
+                UI,
+            > {
+                tu: interactors.todo.clone(),
+            }))
+            .add_service(AuditServiceServer::new(AuditServiceImpl::<AI> {
+                au: interactors.audit,
+            }))
+            .add_service(RevisionServiceServer::new(RevisionServiceImpl::<VI> {
+                rvu: interactors.revision,
+            }))
+            .add_service(TrashServiceServer::new(TrashServiceImpl::<TI> {
+                tsu: interactors.trash,
+            }))
             .serve(grpc_addr)
             .await
             .expect("gRPC Server failed to start.");
@@ -75,6 +279,10 @@ async fn main() -> Result<(), anyhow::Error> {
 
     handle.await?;
     grpc_handle.await?;
+    scheduler_handle.await?;
+    webhook_handle.await?;
+    purge_handle.await?;
+    prune_handle.await?;
 
     Ok(())
 }
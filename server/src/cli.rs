@@ -0,0 +1,188 @@
+//! The `export` and `import` subcommands of the server binary, which move todos in and out
+//! of the database without going through the HTTP API, and the `operations` subcommand,
+//! which manages the GraphQL operations clients may persist.
+
+use std::{env, path::Path};
+
+use domain::entity::audit::Protocol;
+use futures_util::StreamExt;
+use presentation::{context::request_context, graphql::persisted::operation_names};
+use tokio::io::{self, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+use use_case::{
+    dto::import_export::TodoFormat,
+    error::UseCaseError,
+    traits::{import_export::ImportExportUseCase, persisted_query::PersistedQueryUseCase},
+};
+
+pub const EXPORT_USAGE: &str = "main export [--format csv|jsonl|todotxt|ics] [--output <file>]";
+pub const IMPORT_USAGE: &str = "main import [--format csv|jsonl|todotxt|ics] [--dry-run] <file|->";
+pub const OPERATIONS_USAGE: &str = "main operations (register <manifest.graphql> | list)";
+
+/// Writes every todo to `--output`, or to stdout. The format defaults to the one the
+/// output file is named after, then to CSV.
+pub async fn export<EU: ImportExportUseCase>(
+    use_case: &EU,
+    args: &[String],
+) -> Result<(), anyhow::Error> {
+    let mut format = None;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = Some(parse_format(args.next())?),
+            "--output" => match args.next() {
+                Some(path) => output = Some(path.clone()),
+                None => return Err(anyhow::anyhow!("usage: {}", EXPORT_USAGE)),
+            },
+            _ => return Err(anyhow::anyhow!("usage: {}", EXPORT_USAGE)),
+        }
+    }
+    let format = format
+        .or_else(|| output.as_deref().and_then(format_of))
+        .unwrap_or_default();
+    let writer: Box<dyn AsyncWrite + Unpin + Send> = match &output {
+        Some(path) => Box::new(tokio::fs::File::create(path).await?),
+        None => Box::new(io::stdout()),
+    };
+    let mut writer = BufWriter::new(writer);
+    let mut chunks = use_case.export(format);
+    while let Some(chunk) = chunks.next().await {
+        writer
+            .write_all(chunk.map_err(describe)?.as_bytes())
+            .await?;
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Imports the todos of a file, or of stdin for `-`, and prints the report. The format
+/// defaults to the one the file is named after, then to CSV. Fails when the file has
+/// problems, in which case nothing is imported.
+pub async fn import<EU: ImportExportUseCase>(
+    use_case: &EU,
+    args: &[String],
+) -> Result<(), anyhow::Error> {
+    let mut format = None;
+    let mut dry_run = false;
+    let mut input = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = Some(parse_format(args.next())?),
+            "--dry-run" => dry_run = true,
+            path if input.is_none() && (path == "-" || !path.starts_with('-')) => {
+                input = Some(path.to_string())
+            }
+            _ => return Err(anyhow::anyhow!("usage: {}", IMPORT_USAGE)),
+        }
+    }
+    let input = match input {
+        Some(input) => input,
+        None => return Err(anyhow::anyhow!("usage: {}", IMPORT_USAGE)),
+    };
+    let data = if input == "-" {
+        let mut data = String::new();
+        io::stdin().read_to_string(&mut data).await?;
+        data
+    } else {
+        tokio::fs::read_to_string(&input).await?
+    };
+    let format = format.or_else(|| format_of(&input)).unwrap_or_default();
+    let actor = env::var("USER").ok();
+    let ctx = request_context(Protocol::System, actor.as_deref(), None);
+    let report = use_case
+        .import(ctx, format, data, dry_run)
+        .await
+        .map_err(describe)?;
+    for problem in &report.problems {
+        eprintln!("{}:{}: {}", input, problem.line, problem.message);
+    }
+    if !report.problems.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} problems found; nothing was imported",
+            report.problems.len()
+        ));
+    }
+    if report.dry_run {
+        println!(
+            "{} todos are valid; nothing was imported (dry run)",
+            report.valid
+        );
+    } else {
+        println!("{} todos imported", report.imported);
+    }
+    Ok(())
+}
+
+/// Registers the operations of a client manifest such as `client/src/graphql/queries.graphql`,
+/// which clients send whole, so that they may run when only registered operations are
+/// allowed. `list` prints every persisted document with its source and operations.
+pub async fn operations<PU: PersistedQueryUseCase>(
+    use_case: &PU,
+    args: &[String],
+) -> Result<(), anyhow::Error> {
+    match args {
+        [command, path] if command == "register" => {
+            let document = tokio::fs::read_to_string(path).await?;
+            let names = operation_names(&document)
+                .map_err(|error| anyhow::anyhow!("{}: {}", path, error.message))?;
+            let query = use_case.register(document, names).await.map_err(describe)?;
+            println!(
+                "registered {} ({})",
+                query.hash,
+                query.operation_names.join(", ")
+            );
+            Ok(())
+        }
+        [command] if command == "list" => {
+            let queries = use_case.find_all().await.map_err(describe)?;
+            for query in queries {
+                let names = match query.operation_names.is_empty() {
+                    true => "-".to_string(),
+                    false => query.operation_names.join(","),
+                };
+                println!("{}\t{}\t{}", query.hash, query.source, names);
+            }
+            Ok(())
+        }
+        _ => Err(anyhow::anyhow!("usage: {}", OPERATIONS_USAGE)),
+    }
+}
+
+fn parse_format(format: Option<&String>) -> Result<TodoFormat, anyhow::Error> {
+    match format {
+        Some(format) => format.parse().map_err(describe),
+        None => Err(anyhow::anyhow!("--format needs a value")),
+    }
+}
+
+/// The format a file is named after, e.g. `todo.txt` or `backup.jsonl`.
+fn format_of(path: &str) -> Option<TodoFormat> {
+    let extension = Path::new(path).extension()?.to_str()?;
+    match extension.to_ascii_lowercase().as_str() {
+        "csv" => Some(TodoFormat::Csv),
+        "jsonl" | "ndjson" => Some(TodoFormat::JsonLines),
+        "txt" => Some(TodoFormat::TodoTxt),
+        "ics" => Some(TodoFormat::ICalendar),
+        _ => None,
+    }
+}
+
+fn describe(error: UseCaseError) -> anyhow::Error {
+    match error {
+        UseCaseError::Validation(message) => anyhow::anyhow!(message),
+        UseCaseError::NotFound {
+            entity_type,
+            entity_id,
+        } => anyhow::anyhow!("{} {} not found", entity_type, entity_id),
+        UseCaseError::Conflict {
+            entity_type,
+            entity_id,
+        } => anyhow::anyhow!("{} {} was modified concurrently", entity_type, entity_id),
+        UseCaseError::IdempotencyKeyReused(key) => {
+            anyhow::anyhow!("idempotency key {} was already used", key)
+        }
+        UseCaseError::Other(error) => error,
+        UseCaseError::Unexpected(message) => anyhow::anyhow!("unexpected error: {}", message),
+    }
+}
@@ -1,27 +1,211 @@
-use async_graphql::{EmptySubscription, Schema};
-use infrastructure::todo_repository::SqliteTodoRepository;
-use presentation::graphql::schema::{build_schema, Mutation, Query};
+use std::sync::Arc;
+
+use chrono::Duration;
+use chrono_tz::Tz;
+use domain::{notifier::Notifier, repository::todo_repository::TodoRepository};
+use infrastructure::{
+    audit_repository::SqliteAuditRepository,
+    caldav_resource_repository::SqliteCalDavResourceRepository,
+    calendar_feed_repository::SqliteCalendarFeedRepository,
+    event_log_repository::SqliteEventLogRepository,
+    event_store::EventSourcedTodoRepository,
+    event_subscriber::LogEventSubscriber,
+    notifier::HttpWebhookSender,
+    persisted_query_repository::SqlitePersistedQueryRepository,
+    reminder_repository::SqliteReminderRepository,
+    revision_repository::SqliteRevisionRepository,
+    todo_repository::SqliteTodoRepository,
+    trash_repository::SqliteTrashRepository,
+    unit_of_work::{SqliteUnitOfWork, TodoPersistence},
+    webhook_repository::SqliteWebhookRepository,
+};
+use presentation::graphql::{
+    limits::SchemaLimits,
+    persisted::PersistedQueries,
+    schema::{build_schema, Mutation, Query, TodoSchema},
+};
 use sqlx::{Pool, Sqlite};
-use use_case::interactor::todo::{MutationInteractor, QueryInteractor, TodoInteractor};
+use use_case::{
+    event_bus::EventBus,
+    interactor::{
+        audit::AuditInteractor,
+        caldav_resource::CalDavResourceInteractor,
+        calendar_feed::CalendarFeedInteractor,
+        event_log::EventLogInteractor,
+        import_export::ImportExportInteractor,
+        persisted_query::PersistedQueryInteractor,
+        reminder::ReminderInteractor,
+        revision::RevisionInteractor,
+        todo::{MutationInteractor, QueryInteractor, TodoInteractor},
+        trash::TrashInteractor,
+        webhook::WebhookInteractor,
+    },
+};
+
+pub type TR = Arc<dyn TodoRepository>;
+pub type QI = QueryInteractor<TR>;
+pub type MI = MutationInteractor<SqliteUnitOfWork>;
+pub type UI = TodoInteractor<TR, SqliteUnitOfWork>;
+pub type AI = AuditInteractor<SqliteAuditRepository>;
+pub type VI = RevisionInteractor<SqliteRevisionRepository, SqliteUnitOfWork>;
+pub type TI = TrashInteractor<SqliteTrashRepository, SqliteUnitOfWork>;
+pub type GraphQLSchema = TodoSchema<QI, AI, VI, MI, TI>;
+pub type RI = ReminderInteractor<TR, SqliteReminderRepository, Arc<dyn Notifier>>;
+pub type WI = WebhookInteractor<SqliteWebhookRepository, HttpWebhookSender>;
+pub type EI = ImportExportInteractor<TR, SqliteUnitOfWork>;
+pub type CI = CalendarFeedInteractor<SqliteCalendarFeedRepository, TR>;
+pub type LI = EventLogInteractor<SqliteEventLogRepository>;
+pub type PI = PersistedQueryInteractor<SqlitePersistedQueryRepository>;
+pub type DI = CalDavResourceInteractor<SqliteCalDavResourceRepository, SqliteUnitOfWork>;
 
-pub type QI = QueryInteractor<SqliteTodoRepository>;
-pub type MI = MutationInteractor<SqliteTodoRepository>;
-pub type UI = TodoInteractor<SqliteTodoRepository>;
+/// How the interactors behave, read from the environment by `main`.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// The timezone due-date views and recurrence rules use when a request names none.
+    pub timezone: Tz,
+    pub persistence: TodoPersistence,
+    pub trash_retention: Duration,
+    pub idempotency_ttl: Duration,
+    pub event_log_capacity: i64,
+    pub graphql_limits: SchemaLimits,
+    /// Only registered persisted queries may run.
+    pub graphql_strict: bool,
+    pub persisted_query_capacity: i64,
+}
+
+/// The interactors behind every API, sharing one unit of work and event bus.
+#[derive(Clone)]
+pub struct Interactors {
+    pub query: QI,
+    pub schema: GraphQLSchema,
+    pub todo: UI,
+    pub reminder: RI,
+    pub webhook: WI,
+    pub audit: AI,
+    pub revision: VI,
+    pub trash: TI,
+    pub import_export: EI,
+    pub calendar_feed: CI,
+    pub event_log: LI,
+    pub persisted_query: PI,
+    pub caldav_resource: DI,
+}
 
 pub fn dependency_injection(
     pool: Pool<Sqlite>,
-) -> (QI, Schema<Query<QI>, Mutation<MI>, EmptySubscription>, UI) {
-    let sqlite_todo_repository = SqliteTodoRepository::new(pool);
+    notifier: Arc<dyn Notifier>,
+    webhook_sender: HttpWebhookSender,
+    settings: Settings,
+) -> Interactors {
+    let Settings {
+        timezone,
+        persistence,
+        trash_retention,
+        idempotency_ttl,
+        event_log_capacity,
+        graphql_limits,
+        graphql_strict,
+        persisted_query_capacity,
+    } = settings;
+    let sqlite_todo_repository: TR = match persistence {
+        TodoPersistence::State => Arc::new(SqliteTodoRepository::new(pool.clone())),
+        TodoPersistence::EventSourced { snapshot_every } => Arc::new(
+            EventSourcedTodoRepository::new(pool.clone()).with_snapshot_every(snapshot_every),
+        ),
+    };
+    let sqlite_reminder_repository = SqliteReminderRepository::new(pool.clone());
+    let sqlite_webhook_repository = SqliteWebhookRepository::new(pool.clone());
+    let sqlite_audit_repository = SqliteAuditRepository::new(pool.clone());
+    let sqlite_revision_repository = SqliteRevisionRepository::new(pool.clone());
+    let sqlite_trash_repository = SqliteTrashRepository::new(pool.clone());
+    let sqlite_calendar_feed_repository = SqliteCalendarFeedRepository::new(pool.clone());
+    let sqlite_event_log_repository = SqliteEventLogRepository::new(pool.clone());
+    let sqlite_persisted_query_repository = SqlitePersistedQueryRepository::new(pool.clone());
+    let sqlite_caldav_resource_repository = SqliteCalDavResourceRepository::new(pool.clone());
+    let sqlite_unit_of_work = SqliteUnitOfWork::new(pool).with_persistence(persistence);
+
+    let event_bus = EventBus::new().subscribe(Arc::new(LogEventSubscriber::default()));
+
+    let query_use_case =
+        QueryInteractor::new(sqlite_todo_repository.clone()).with_timezone(timezone);
+    let mutation_use_case = MutationInteractor::new(sqlite_unit_of_work.clone())
+        .with_timezone(timezone)
+        .with_event_bus(event_bus.clone())
+        .with_idempotency_ttl(idempotency_ttl);
+
+    let audit_use_case = AuditInteractor::new(sqlite_audit_repository);
+    let revision_use_case =
+        RevisionInteractor::new(sqlite_revision_repository, sqlite_unit_of_work.clone())
+            .with_event_bus(event_bus.clone());
+    let trash_use_case = TrashInteractor::new(sqlite_trash_repository, sqlite_unit_of_work.clone())
+        .with_event_bus(event_bus.clone())
+        .with_retention(trash_retention);
+
+    let query = Query::new(
+        query_use_case.clone(),
+        audit_use_case.clone(),
+        revision_use_case.clone(),
+        trash_use_case.clone(),
+    );
+    let mutation = Mutation::new(
+        mutation_use_case,
+        revision_use_case.clone(),
+        trash_use_case.clone(),
+    );
+
+    let reminder_use_case = ReminderInteractor::new(
+        sqlite_todo_repository.clone(),
+        sqlite_reminder_repository,
+        notifier,
+    );
+
+    let webhook_use_case = WebhookInteractor::new(sqlite_webhook_repository, webhook_sender);
+
+    let import_export_use_case =
+        ImportExportInteractor::new(sqlite_todo_repository.clone(), sqlite_unit_of_work.clone())
+            .with_event_bus(event_bus.clone());
+
+    let calendar_feed_use_case = CalendarFeedInteractor::new(
+        sqlite_calendar_feed_repository,
+        sqlite_todo_repository.clone(),
+    );
+
+    let event_log_use_case = EventLogInteractor::new(sqlite_event_log_repository)
+        .with_capacity(event_log_capacity)
+        .with_event_bus(event_bus.clone());
 
-    let query_use_case = QueryInteractor::new(sqlite_todo_repository.clone());
-    let mutation_use_case = MutationInteractor::new(sqlite_todo_repository.clone());
+    let caldav_resource_use_case = CalDavResourceInteractor::new(
+        sqlite_caldav_resource_repository,
+        sqlite_unit_of_work.clone(),
+    )
+    .with_timezone(timezone)
+    .with_event_bus(event_bus.clone());
 
-    let query = Query::new(query_use_case.clone());
-    let mutation = Mutation::new(mutation_use_case);
+    let use_case = TodoInteractor::new(sqlite_todo_repository, sqlite_unit_of_work)
+        .with_timezone(timezone)
+        .with_event_bus(event_bus)
+        .with_idempotency_ttl(idempotency_ttl);
 
-    let use_case = TodoInteractor::new(sqlite_todo_repository);
+    let persisted_query_use_case = PersistedQueryInteractor::new(sqlite_persisted_query_repository)
+        .with_capacity(persisted_query_capacity);
+    let persisted_queries = PersistedQueries::new(Arc::new(persisted_query_use_case.clone()))
+        .with_strict(graphql_strict);
 
-    let schema = build_schema(query, mutation);
+    let schema = build_schema(query, mutation, graphql_limits, persisted_queries);
 
-    (query_use_case, schema, use_case)
+    Interactors {
+        query: query_use_case,
+        schema,
+        todo: use_case,
+        reminder: reminder_use_case,
+        webhook: webhook_use_case,
+        audit: audit_use_case,
+        revision: revision_use_case,
+        trash: trash_use_case,
+        import_export: import_export_use_case,
+        calendar_feed: calendar_feed_use_case,
+        event_log: event_log_use_case,
+        persisted_query: persisted_query_use_case,
+        caldav_resource: caldav_resource_use_case,
+    }
 }
@@ -1 +1,5 @@
+pub mod cli;
 pub mod dependency_injection;
+pub mod logger;
+pub mod router;
+pub mod scheduler;
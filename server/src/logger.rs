@@ -0,0 +1,41 @@
+use std::env;
+
+use chrono::{SecondsFormat, Utc};
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Writes every record to stderr as one line, with its time, level and module.
+struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{} {:<5} {}: {}",
+                Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logger of the server at the level `RUST_LOG` names, from `error` to
+/// `trace` or `off`, and at `info` when it is unset or names none.
+pub fn init() -> Result<(), SetLoggerError> {
+    let level = env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse::<LevelFilter>().ok())
+        .unwrap_or(LevelFilter::Info);
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(level);
+    Ok(())
+}
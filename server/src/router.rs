@@ -0,0 +1,339 @@
+//! The HTTP routes of every API but gRPC, which tonic serves on its own port.
+
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{any, delete, get, post},
+    Extension, Router,
+};
+use presentation::{
+    caldav::handler::{caldav, well_known_caldav},
+    connect::handler::connect,
+    deprecation::{DeprecationLayer, V1_DEPRECATED_AT},
+    graphql::handler::{graphql_handler, graphql_playground_handler},
+    jsonrpc::{handler::rpc, openrpc::openrpc_json},
+    rest::{
+        handler::{
+            batch_todos, complete_todo, create_calendar_feed, create_reminder, create_todo,
+            create_webhook, delete_calendar_feed, delete_reminder, delete_todo, delete_webhook,
+            export_todos, get_audit_log, get_calendar, get_calendar_feeds, get_high_priority_todos,
+            get_overdue_todos, get_reminders, get_revision_diff, get_revisions, get_todo,
+            get_todo_events, get_todos, get_todos_due_this_week, get_todos_due_today, get_trash,
+            get_webhook_deliveries, get_webhooks, import_todos, restore_from_trash,
+            restore_revision, update_todo,
+        },
+        openapi::{docs, docs_asset, openapi_json},
+        v2::handler as v2,
+    },
+};
+use tower::ServiceBuilder;
+
+use crate::dependency_injection::{Interactors, AI, CI, DI, EI, LI, MI, QI, RI, TI, UI, VI, WI};
+
+const IMPORT_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Routes every HTTP API to the `interactors`. `Accept`-based versioning is applied around
+/// the result by `main`, since it has to run before routing.
+pub fn app(interactors: Interactors) -> Router {
+    // the v1 REST API, served under /v1 and, for the clients that predate the prefix, at
+    // the root; the todo routes v2 replaces are deprecated
+    let v1 = Router::new()
+        .route(
+            "/todos",
+            get(get_todos::<UI>)
+                .post(create_todo::<UI>)
+                .put(update_todo::<UI>)
+                .delete(delete_todo::<UI>),
+        )
+        .route("/todos/:id", get(get_todo::<UI>))
+        .route("/todos/:id/complete", post(complete_todo::<UI>))
+        .route_layer(DeprecationLayer::new(V1_DEPRECATED_AT).with_successor("/v2/todos"))
+        .route("/todos:batch", post(batch_todos::<UI>))
+        .route("/todos/events", get(get_todo_events::<LI>))
+        .route("/todos/export", get(export_todos::<EI>))
+        // imports are read whole, so they get more room than other bodies
+        .route(
+            "/todos/import",
+            post(import_todos::<EI>).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+        )
+        .route("/todos/overdue", get(get_overdue_todos::<UI>))
+        .route("/todos/due-today", get(get_todos_due_today::<UI>))
+        .route("/todos/due-this-week", get(get_todos_due_this_week::<UI>))
+        .route("/todos/high-priority", get(get_high_priority_todos::<UI>))
+        .route(
+            "/todos/:id/reminders",
+            get(get_reminders::<RI>).post(create_reminder::<RI>),
+        )
+        .route("/todos/:id/revisions", get(get_revisions::<VI>))
+        .route("/todos/:id/revisions/diff", get(get_revision_diff::<VI>))
+        .route(
+            "/todos/:id/revisions/:revision/restore",
+            post(restore_revision::<VI>),
+        )
+        .route("/trash", get(get_trash::<TI>))
+        .route("/trash/:id/restore", post(restore_from_trash::<TI>))
+        .route("/reminders/:id", delete(delete_reminder::<RI>))
+        .route(
+            "/webhooks",
+            get(get_webhooks::<WI>).post(create_webhook::<WI>),
+        )
+        .route("/webhooks/:id", delete(delete_webhook::<WI>))
+        .route(
+            "/webhooks/:id/deliveries",
+            get(get_webhook_deliveries::<WI>),
+        )
+        .route(
+            "/calendar/feeds",
+            get(get_calendar_feeds::<CI>).post(create_calendar_feed::<CI>),
+        )
+        .route("/calendar/feeds/:id", delete(delete_calendar_feed::<CI>))
+        .route("/admin/audit-log", get(get_audit_log::<AI>));
+
+    Router::new()
+        .route("/graphiql", get(graphql_playground_handler))
+        .route("/graphql", post(graphql_handler::<QI, AI, VI, MI, TI>))
+        .merge(v1.clone())
+        .nest("/v1", v1)
+        .route(
+            "/v2/todos",
+            get(v2::get_todos::<UI>).post(v2::create_todo::<UI>),
+        )
+        .route(
+            "/v2/todos/:id",
+            get(v2::get_todo::<UI>)
+                .put(v2::replace_todo::<UI>)
+                .patch(v2::patch_todo::<UI>)
+                .delete(v2::delete_todo::<UI>),
+        )
+        .route("/v2/todos/:id/complete", post(v2::complete_todo::<UI>))
+        // subscribed to by calendar apps, so it keeps its URL across versions
+        .route("/calendar/:token/todos.ics", get(get_calendar::<CI>))
+        .route("/.well-known/caldav", any(well_known_caldav))
+        .route("/caldav", any(caldav::<UI, DI>))
+        .route("/caldav/", any(caldav::<UI, DI>))
+        .route("/caldav/*path", any(caldav::<UI, DI>))
+        // Connect clients call the tonic service's own paths on the HTTP port
+        .route("/todo.TodoService/:method", post(connect::<UI>))
+        .route("/rpc", post(rpc::<UI>))
+        .route("/openrpc.json", get(openrpc_json))
+        .route("/openapi.json", get(openapi_json))
+        .route("/docs", get(docs))
+        .route("/docs/:asset", get(docs_asset))
+        .layer(
+            ServiceBuilder::new()
+                .layer(Extension(interactors.query))
+                .layer(Extension(interactors.schema))
+                .layer(Extension(interactors.todo))
+                .layer(Extension(interactors.reminder))
+                .layer(Extension(interactors.webhook))
+                .layer(Extension(interactors.audit))
+                .layer(Extension(interactors.revision))
+                .layer(Extension(interactors.trash))
+                .layer(Extension(interactors.import_export))
+                .layer(Extension(interactors.calendar_feed))
+                .layer(Extension(interactors.event_log))
+                .layer(Extension(interactors.caldav_resource)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use chrono_tz::Tz;
+    use infrastructure::{
+        notifier::{HttpWebhookSender, LogNotifier},
+        unit_of_work::TodoPersistence,
+    };
+    use presentation::{graphql::limits::SchemaLimits, rest::openapi::openapi};
+    use serde_json::Value;
+    use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::dependency_injection::{dependency_injection, Settings};
+
+    /// A path of every route outside the REST API the OpenAPI document describes.
+    const UNDOCUMENTED: &[&str] = &[
+        "/graphiql",
+        "/graphql",
+        "/.well-known/caldav",
+        "/caldav",
+        "/caldav/",
+        "/caldav/todos/",
+        "/todo.TodoService/ListTodos",
+        "/rpc",
+        "/openrpc.json",
+        "/openapi.json",
+        "/docs",
+        "/docs/swagger-ui.css",
+    ];
+
+    /// Answers the requests no route matches, which axum would answer 404 like a handler
+    /// not finding a todo.
+    const UNROUTED: StatusCode = StatusCode::IM_A_TEAPOT;
+
+    /// Interactors over an empty database: the routes are matched and the handlers fail.
+    async fn interactors() -> Interactors {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        interactors_over(pool, TodoPersistence::State)
+    }
+
+    /// Interactors over one connection to a migrated database.
+    async fn migrated_interactors(persistence: TodoPersistence) -> Interactors {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+        interactors_over(pool, persistence)
+    }
+
+    fn interactors_over(pool: Pool<Sqlite>, persistence: TodoPersistence) -> Interactors {
+        dependency_injection(
+            pool,
+            Arc::new(LogNotifier::default()),
+            HttpWebhookSender::new(Duration::from_secs(1)).ok().unwrap(),
+            Settings {
+                timezone: Tz::UTC,
+                persistence,
+                trash_retention: chrono::Duration::days(30),
+                idempotency_ttl: chrono::Duration::hours(24),
+                event_log_capacity: 100,
+                graphql_limits: SchemaLimits::new(),
+                graphql_strict: false,
+                persisted_query_capacity: 100,
+            },
+        )
+    }
+
+    async fn send(app: &Router, method: Method, path: &str, body: &str) -> (StatusCode, Value) {
+        let content_type = match method {
+            Method::PATCH => "application/merge-patch+json",
+            _ => "application/json",
+        };
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header("content-type", content_type)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    async fn status(app: &Router, method: &Method, path: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    /// axum cannot list the routes of a router, so every documented path is requested with
+    /// every method: the documented methods must reach a handler and the others must be
+    /// refused with 405. The routes left out of the document are listed in `UNDOCUMENTED`.
+    #[tokio::test]
+    async fn test_openapi_documents_every_route() {
+        let app = app(interactors().await).fallback(|| async { UNROUTED });
+        let document = openapi();
+        let paths = document["paths"].as_object().unwrap();
+        assert!(paths.len() > 20);
+        for (path, item) in paths {
+            let item = item.as_object().unwrap();
+            // every path parameter is an id or a token, which "1" stands in for
+            let uri = path
+                .split('/')
+                .map(|segment| match segment.starts_with('{') {
+                    true => "1",
+                    false => segment,
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            for method in [
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ] {
+                let status = status(&app, &method, &uri).await;
+                assert_ne!(status, UNROUTED, "{} is not routed", path);
+                let documented = item.contains_key(&method.as_str().to_lowercase());
+                assert_eq!(
+                    status != StatusCode::METHOD_NOT_ALLOWED,
+                    documented,
+                    "{} {} is {}",
+                    method,
+                    path,
+                    match documented {
+                        true => "documented but not routed",
+                        false => "routed but not documented",
+                    }
+                );
+            }
+        }
+        for path in UNDOCUMENTED {
+            assert_ne!(status(&app, &Method::GET, path).await, UNROUTED, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_todo_as_of_replays_its_events() {
+        let app =
+            app(migrated_interactors(TodoPersistence::EventSourced { snapshot_every: 10 }).await);
+        let before = Utc::now() - chrono::Duration::days(1);
+        let (status, todo) = send(&app, Method::POST, "/v2/todos", r#"{"title":"draft"}"#).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, _) = send(
+            &app,
+            Method::PATCH,
+            &format!("/v2/todos/{}", todo["id"]),
+            r#"{"title":"final"}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let at = |at: DateTime<Utc>| {
+            format!(
+                "/v2/todos/{}?as_of={}",
+                todo["id"],
+                at.to_rfc3339_opts(SecondsFormat::Secs, true)
+            )
+        };
+        let (status, _) = send(&app, Method::GET, &at(before), "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, found) = send(&app, Method::GET, &at(Utc::now()), "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(found["title"], "final");
+        let (status, problem) = send(&app, Method::GET, "/v2/todos/1?as_of=yesterday", "").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(problem["error"], "BadRequest");
+    }
+
+    #[tokio::test]
+    async fn test_get_todo_as_of_needs_event_sourcing() {
+        let app = app(migrated_interactors(TodoPersistence::State).await);
+        let (_, todo) = send(&app, Method::POST, "/v2/todos", r#"{"title":"draft"}"#).await;
+        let (status, problem) = send(
+            &app,
+            Method::GET,
+            &format!("/v2/todos/{}?as_of=2024-01-01T00:00:00Z", todo["id"]),
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(problem["error"], "BadRequest");
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use chrono::{SubsecRound, Utc};
+use tokio::task::JoinHandle;
+use use_case::traits::{
+    event_log::EventLogPruneUseCase, reminder::ReminderDispatchUseCase, trash::TrashPurgeUseCase,
+    webhook::WebhookDispatchUseCase,
+};
+
+/// Runs the reminder dispatcher every `interval` for the lifetime of the process.
+///
+/// Pending reminders live in the database and are leased before delivery, so nothing is
+/// lost across restarts and several server instances can poll the same database.
+pub fn spawn_reminder_scheduler<RD: ReminderDispatchUseCase>(
+    dispatcher: RD,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match dispatcher.dispatch_due(Utc::now().trunc_subsecs(0)).await {
+                Ok(0) => {}
+                Ok(fired) => log::info!("fired {} reminder(s)", fired),
+                Err(e) => log::error!("reminder dispatch failed: {}", e),
+            }
+        }
+    })
+}
+
+/// Runs the webhook delivery worker every `interval` for the lifetime of the process.
+///
+/// Events reach the outbox in the same transaction as the todo change, and deliveries are
+/// leased while in flight, so each event is delivered at least once per subscription.
+pub fn spawn_webhook_worker<WD: WebhookDispatchUseCase>(
+    dispatcher: WD,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match dispatcher
+                .dispatch_pending(Utc::now().trunc_subsecs(0))
+                .await
+            {
+                Ok(0) => {}
+                Ok(delivered) => log::info!("delivered {} webhook(s)", delivered),
+                Err(e) => log::error!("webhook dispatch failed: {}", e),
+            }
+        }
+    })
+}
+
+/// Purges the todos whose trash retention ran out every `interval` for the lifetime of the
+/// process. Purging is a single idempotent statement, so instances may overlap.
+pub fn spawn_trash_purger<TP: TrashPurgeUseCase>(purger: TP, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match purger.purge_expired(Utc::now().trunc_subsecs(0)).await {
+                Ok(0) => {}
+                Ok(purged) => log::info!("purged {} todo(s) from the trash", purged),
+                Err(e) => log::error!("trash purge failed: {}", e),
+            }
+        }
+    })
+}
+
+/// Trims the event log to its capacity every `interval` for the lifetime of the process.
+/// Subscribers that fall behind the trimmed log are told to reload when they resume.
+pub fn spawn_event_log_pruner<EP: EventLogPruneUseCase>(
+    pruner: EP,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match pruner.prune().await {
+                Ok(0) => {}
+                Ok(pruned) => log::info!("pruned {} event(s) from the event log", pruned),
+                Err(e) => log::error!("event log prune failed: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use domain::entity::audit::{AuditEntry, FieldChange, Protocol};
+
+use crate::dto::todo::TodoDto;
+
+/// Who is making a request and through which API; recorded with every change it causes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    pub actor: String,
+    pub protocol: Protocol,
+    pub request_id: String,
+}
+
+impl RequestContext {
+    pub fn new(
+        actor: impl Into<String>,
+        protocol: Protocol,
+        request_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            actor: actor.into(),
+            protocol,
+            request_id: request_id.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilterDto {
+    pub todo_id: Option<i64>,
+    pub actor: Option<String>,
+    /// At most this many entries are returned; defaults to 100.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChangeDto {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEntryDto {
+    pub id: i64,
+    pub todo_id: i64,
+    /// `create`, `update`, `complete` or `delete`.
+    pub action: String,
+    pub actor: String,
+    /// `rest`, `graphql`, `grpc` or `system`.
+    pub protocol: String,
+    pub request_id: String,
+    pub before: Option<TodoDto>,
+    pub after: Option<TodoDto>,
+    pub changes: Vec<FieldChangeDto>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<FieldChange> for FieldChangeDto {
+    fn from(change: FieldChange) -> Self {
+        Self {
+            field: change.field.to_string(),
+            before: change.before,
+            after: change.after,
+        }
+    }
+}
+
+impl From<AuditEntry> for AuditEntryDto {
+    fn from(entry: AuditEntry) -> Self {
+        let changes = entry
+            .changes()
+            .into_iter()
+            .map(|change| change.into())
+            .collect();
+        Self {
+            id: entry.id,
+            todo_id: entry.todo_id,
+            action: entry.action.to_string(),
+            actor: entry.actor,
+            protocol: entry.protocol.to_string(),
+            request_id: entry.request_id,
+            before: entry.before.map(|todo| todo.into()),
+            after: entry.after.map(|todo| todo.into()),
+            changes,
+            occurred_at: entry.occurred_at,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    dto::todo::{CompletedTodoDto, CreateTodoDto, TodoDto, UpdateTodoDto},
+    error::UseCaseError,
+};
+
+/// Most operations a single batch may hold.
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// One operation of a batch, with the semantics of the matching single-todo call.
+#[derive(Debug, Clone)]
+pub enum BatchOperationDto {
+    Create(CreateTodoDto),
+    Update {
+        todo: UpdateTodoDto,
+        expected_version: Option<i64>,
+    },
+    Delete(i64),
+    Complete(i64),
+}
+
+/// What a rejected operation does to the rest of its batch. Operations are rejected for
+/// validation errors, missing todos and version conflicts; any other error aborts the
+/// batch in either mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchMode {
+    /// The batch stops at the first rejected operation and nothing is committed.
+    #[default]
+    Atomic,
+    /// Rejected operations are reported and skipped; the others are committed.
+    PerItem,
+}
+
+#[derive(Debug, Clone)]
+pub enum BatchOutcomeDto {
+    Created(TodoDto),
+    Updated(TodoDto),
+    Deleted(i64),
+    Completed(CompletedTodoDto),
+}
+
+pub struct BatchItemResultDto {
+    /// Position of the operation in the batch.
+    pub index: usize,
+    pub result: Result<BatchOutcomeDto, UseCaseError>,
+}
+
+pub struct BatchResultDto {
+    /// `false` when an atomic batch was rolled back; `items` then ends with the rejected
+    /// operation.
+    pub committed: bool,
+    pub items: Vec<BatchItemResultDto>,
+}
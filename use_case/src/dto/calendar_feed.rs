@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use domain::entity::calendar_feed::CalendarFeed;
+
+#[derive(Debug, Clone)]
+pub struct CalendarFeedDto {
+    pub id: i64,
+    /// The secret path segment of the feed URL.
+    pub token: String,
+    pub actor: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<CalendarFeed> for CalendarFeedDto {
+    fn from(feed: CalendarFeed) -> Self {
+        Self {
+            id: feed.id,
+            token: feed.token,
+            actor: feed.actor,
+            created_at: feed.created_at,
+        }
+    }
+}
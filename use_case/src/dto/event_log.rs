@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use domain::entity::event_log::LoggedEvent;
+
+#[derive(Debug, Clone)]
+pub struct LoggedEventDto {
+    pub id: i64,
+    pub event: String,
+    pub todo_id: i64,
+    /// The event as JSON, with the todo it is about.
+    pub payload: String,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<LoggedEvent> for LoggedEventDto {
+    fn from(event: LoggedEvent) -> Self {
+        Self {
+            id: event.id,
+            event: event.event,
+            todo_id: event.todo_id,
+            payload: event.payload,
+            occurred_at: event.occurred_at,
+        }
+    }
+}
+
+/// Where a subscriber picks the event log up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventCursorDto {
+    /// The id of the last event it has seen; it reads on from the event after.
+    pub last_event_id: i64,
+    /// Whether events it asked for are no longer in the log, so it has to reload the todos
+    /// before following the log again from `last_event_id`.
+    pub reset: bool,
+}
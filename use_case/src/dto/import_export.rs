@@ -0,0 +1,86 @@
+use std::str::FromStr;
+
+use crate::error::UseCaseError;
+
+/// A file format todos are exported to and imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TodoFormat {
+    /// One row per todo under an `id,title,due_at,priority,recurrence,completed_at,version`
+    /// header.
+    #[default]
+    Csv,
+    /// One JSON object per line, with the fields of the CSV header.
+    JsonLines,
+    /// One todo.txt task per line; see [`crate::todo_format`] for how fields are mapped.
+    TodoTxt,
+    /// An RFC 5545 calendar of VTODO components; see [`crate::icalendar`].
+    ICalendar,
+}
+
+impl TodoFormat {
+    /// Every format. A new one breaks the match below, as a reminder to list it here.
+    pub const ALL: [TodoFormat; 4] = [
+        TodoFormat::Csv,
+        TodoFormat::JsonLines,
+        TodoFormat::TodoTxt,
+        TodoFormat::ICalendar,
+    ];
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TodoFormat::Csv => "text/csv; charset=utf-8",
+            TodoFormat::JsonLines => "application/x-ndjson",
+            TodoFormat::TodoTxt => "text/plain; charset=utf-8",
+            TodoFormat::ICalendar => "text/calendar; charset=utf-8",
+        }
+    }
+
+    /// The usual name of a file in this format.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            TodoFormat::Csv => "todos.csv",
+            TodoFormat::JsonLines => "todos.jsonl",
+            TodoFormat::TodoTxt => "todo.txt",
+            TodoFormat::ICalendar => "todos.ics",
+        }
+    }
+}
+
+const _: fn(&TodoFormat) = |format| match format {
+    TodoFormat::Csv | TodoFormat::JsonLines | TodoFormat::TodoTxt | TodoFormat::ICalendar => {}
+};
+
+impl FromStr for TodoFormat {
+    type Err = UseCaseError;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_ascii_lowercase().as_str() {
+            "csv" => Ok(TodoFormat::Csv),
+            "jsonl" | "ndjson" => Ok(TodoFormat::JsonLines),
+            "todotxt" | "todo.txt" => Ok(TodoFormat::TodoTxt),
+            "ics" | "ical" | "icalendar" => Ok(TodoFormat::ICalendar),
+            _ => Err(UseCaseError::Validation(format!(
+                "unknown format {}; expected csv, jsonl, todotxt or ics",
+                format
+            ))),
+        }
+    }
+}
+
+/// A record of an import that could not be turned into a todo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportProblemDto {
+    /// 1-based line of the file the record starts on.
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportReportDto {
+    pub dry_run: bool,
+    /// Records that passed validation.
+    pub valid: usize,
+    /// Todos created; 0 on a dry run or when any record has a problem.
+    pub imported: usize,
+    pub problems: Vec<ImportProblemDto>,
+}
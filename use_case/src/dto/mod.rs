@@ -1 +1,11 @@
+pub mod audit;
+pub mod batch;
+pub mod calendar_feed;
+pub mod event_log;
+pub mod import_export;
+pub mod persisted_query;
+pub mod reminder;
+pub mod revision;
 pub mod todo;
+pub mod trash;
+pub mod webhook;
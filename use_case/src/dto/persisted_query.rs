@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use domain::entity::persisted_query::{PersistedQuery, QuerySource};
+
+#[derive(Debug, Clone)]
+pub struct PersistedQueryDto {
+    pub hash: String,
+    pub document: String,
+    pub operation_names: Vec<String>,
+    /// `manifest` or `automatic`.
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PersistedQueryDto {
+    /// Whether the query was registered from a client manifest, which strict mode requires.
+    pub fn is_registered(&self) -> bool {
+        self.source == QuerySource::Manifest.as_str()
+    }
+}
+
+impl From<PersistedQuery> for PersistedQueryDto {
+    fn from(query: PersistedQuery) -> Self {
+        Self {
+            hash: query.hash,
+            document: query.document,
+            operation_names: query.operation_names,
+            source: query.source.to_string(),
+            created_at: query.created_at,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Duration, Utc};
+use domain::entity::{
+    reminder::{Reminder, ReminderTrigger},
+    todo::Todo,
+};
+
+use crate::error::UseCaseError;
+
+#[derive(Debug, Clone)]
+pub struct ReminderDto {
+    pub id: i64,
+    pub todo_id: i64,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub offset_seconds: Option<i64>,
+    /// Resolved firing time; `None` when the todo lost its due date.
+    pub fire_at: Option<DateTime<Utc>>,
+    pub fired_at: Option<DateTime<Utc>>,
+    /// Set when delivery was abandoned after too many failures.
+    pub failed_at: Option<DateTime<Utc>>,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+}
+
+/// Exactly one of `remind_at` or `offset_seconds` (before the todo's due date) must be set.
+#[derive(Debug, Clone)]
+pub struct CreateReminderDto {
+    pub todo_id: i64,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub offset_seconds: Option<i64>,
+}
+
+impl ReminderDto {
+    pub fn new(reminder: Reminder, todo: &Todo) -> Self {
+        let (remind_at, offset_seconds) = match reminder.trigger {
+            ReminderTrigger::At(at) => (Some(at), None),
+            ReminderTrigger::BeforeDue(offset) => (None, Some(offset.num_seconds())),
+        };
+        Self {
+            id: reminder.id,
+            todo_id: reminder.todo_id,
+            remind_at,
+            offset_seconds,
+            fire_at: reminder.fire_at(todo),
+            fired_at: reminder.fired_at,
+            failed_at: reminder.failed_at,
+            attempts: reminder.attempts,
+            last_error: reminder.last_error,
+        }
+    }
+}
+
+impl TryFrom<&CreateReminderDto> for ReminderTrigger {
+    type Error = UseCaseError;
+
+    fn try_from(reminder_data: &CreateReminderDto) -> Result<Self, Self::Error> {
+        match (reminder_data.remind_at, reminder_data.offset_seconds) {
+            (Some(at), None) => Ok(ReminderTrigger::At(at)),
+            (None, Some(seconds)) => Ok(ReminderTrigger::BeforeDue(Duration::seconds(seconds))),
+            _ => Err(UseCaseError::Validation(
+                "exactly one of remind_at or offset_seconds is required".to_string(),
+            )),
+        }
+    }
+}
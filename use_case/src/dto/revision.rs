@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use domain::entity::revision::TodoRevision;
+
+use crate::dto::{audit::FieldChangeDto, todo::TodoDto};
+
+#[derive(Debug, Clone)]
+pub struct TodoRevisionDto {
+    pub todo_id: i64,
+    pub revision: i64,
+    pub todo: TodoDto,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a todo changed from revision `from` to revision `to`.
+#[derive(Debug, Clone)]
+pub struct RevisionDiffDto {
+    pub todo_id: i64,
+    pub from: i64,
+    pub to: i64,
+    pub changes: Vec<FieldChangeDto>,
+}
+
+impl From<TodoRevision> for TodoRevisionDto {
+    fn from(revision: TodoRevision) -> Self {
+        Self {
+            todo_id: revision.todo_id,
+            revision: revision.revision,
+            todo: revision.todo.into(),
+            created_at: revision.created_at,
+        }
+    }
+}
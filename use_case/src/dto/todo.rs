@@ -1,4 +1,10 @@
-use domain::entity::todo::Todo;
+use chrono::{DateTime, Utc};
+use domain::entity::{
+    idempotency::IdempotencyRecord,
+    recurrence::Recurrence,
+    todo::{Priority, Todo},
+};
+use serde_json::json;
 
 use crate::error::UseCaseError;
 
@@ -6,11 +12,94 @@ use crate::error::UseCaseError;
 pub struct TodoDto {
     pub id: i64,
     pub title: Option<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+    /// RFC 5545 RRULE, e.g. `FREQ=WEEKLY;BYDAY=MO`.
+    pub recurrence: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Bumped by every update; used for optimistic concurrency control.
+    pub version: i64,
+}
+
+/// The changes an update makes. A field left `None` keeps its stored value, so a client
+/// that only renames a todo keeps its due date, priority and recurrence; `due_at` and
+/// `recurrence` are cleared with `Some(None)`.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateTodoDto {
+    pub id: i64,
+    pub title: Option<String>,
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub priority: Option<Priority>,
+    pub recurrence: Option<Option<String>>,
+}
+
+impl UpdateTodoDto {
+    /// `todo` with the changes applied.
+    pub(crate) fn apply(self, mut todo: Todo) -> Result<Todo, UseCaseError> {
+        if let Some(title) = self.title {
+            todo.title = Some(title);
+        }
+        if let Some(due_at) = self.due_at {
+            todo.due_at = due_at;
+        }
+        if let Some(priority) = self.priority {
+            todo.priority = priority;
+        }
+        if let Some(recurrence) = self.recurrence {
+            todo.recurrence = parse_recurrence(recurrence)?;
+        }
+        Ok(todo)
+    }
+}
+
+/// Replaces every field of the todo but `completed_at`.
+impl From<TodoDto> for UpdateTodoDto {
+    fn from(todo: TodoDto) -> Self {
+        Self {
+            id: todo.id,
+            title: todo.title,
+            due_at: Some(todo.due_at),
+            priority: Some(todo.priority),
+            recurrence: Some(todo.recurrence),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct CreateTodoDto {
     pub title: String,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+    pub recurrence: Option<String>,
+}
+
+impl CreateTodoDto {
+    /// Identifies the request body, so a replayed idempotency key can be checked against it.
+    /// The body is hashed as a JSON array of its fields in a fixed order, with the due date
+    /// in unix seconds as it is stored.
+    pub(crate) fn fingerprint(&self) -> String {
+        let canonical = json!([
+            self.title,
+            self.due_at.map(|due_at| due_at.timestamp()),
+            self.priority.as_i64(),
+            self.recurrence,
+        ]);
+        IdempotencyRecord::fingerprint_of(&canonical.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompletedTodoDto {
+    pub todo: TodoDto,
+    /// The next occurrence spawned by a recurring todo.
+    pub next: Option<TodoDto>,
+}
+
+fn parse_recurrence(recurrence: Option<String>) -> Result<Option<Recurrence>, UseCaseError> {
+    match recurrence {
+        Some(rule) if !rule.trim().is_empty() => Ok(Some(rule.parse::<Recurrence>()?)),
+        _ => Ok(None),
+    }
 }
 
 impl From<Todo> for TodoDto {
@@ -18,6 +107,11 @@ impl From<Todo> for TodoDto {
         Self {
             id: todo.id,
             title: todo.title,
+            due_at: todo.due_at,
+            priority: todo.priority,
+            recurrence: todo.recurrence.map(|recurrence| recurrence.to_string()),
+            completed_at: todo.completed_at,
+            version: todo.version,
         }
     }
 }
@@ -29,6 +123,11 @@ impl TryFrom<TodoDto> for Todo {
         Ok(Self {
             id: todo_data.id,
             title: todo_data.title,
+            due_at: todo_data.due_at,
+            priority: todo_data.priority,
+            recurrence: parse_recurrence(todo_data.recurrence)?,
+            completed_at: todo_data.completed_at,
+            version: todo_data.version,
         })
     }
 }
@@ -40,6 +139,11 @@ impl TryFrom<CreateTodoDto> for Todo {
         Ok(Self {
             id: 0,
             title: Some(todo_data.title),
+            due_at: todo_data.due_at,
+            priority: todo_data.priority,
+            recurrence: parse_recurrence(todo_data.recurrence)?,
+            completed_at: None,
+            version: Todo::FIRST_VERSION,
         })
     }
 }
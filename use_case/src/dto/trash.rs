@@ -0,0 +1,22 @@
+use chrono::{DateTime, Duration, Utc};
+use domain::entity::trash::TrashedTodo;
+
+use crate::dto::todo::TodoDto;
+
+#[derive(Debug, Clone)]
+pub struct TrashedTodoDto {
+    pub todo: TodoDto,
+    pub deleted_at: DateTime<Utc>,
+    /// When the todo will be purged unless it is restored first.
+    pub purge_at: DateTime<Utc>,
+}
+
+impl TrashedTodoDto {
+    pub fn new(trashed: TrashedTodo, retention: Duration) -> Self {
+        Self {
+            purge_at: trashed.purge_at(retention),
+            deleted_at: trashed.deleted_at,
+            todo: trashed.todo.into(),
+        }
+    }
+}
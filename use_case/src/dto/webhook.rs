@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use domain::entity::webhook::{WebhookDelivery, WebhookEvent, WebhookSubscription};
+
+use crate::error::UseCaseError;
+
+/// A subscription as shown to API clients; the secret is never echoed back.
+#[derive(Debug, Clone)]
+pub struct WebhookSubscriptionDto {
+    pub id: i64,
+    pub url: String,
+    /// Event names such as `todo.created`; empty means every event.
+    pub events: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateWebhookSubscriptionDto {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookDeliveryDto {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event: String,
+    pub payload: String,
+    /// `pending`, `delivered` or `failed`.
+    pub status: String,
+    pub attempts: i64,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<WebhookSubscription> for WebhookSubscriptionDto {
+    fn from(subscription: WebhookSubscription) -> Self {
+        Self {
+            id: subscription.id,
+            url: subscription.url,
+            events: subscription
+                .events
+                .iter()
+                .map(|event| event.to_string())
+                .collect(),
+            created_at: subscription.created_at,
+        }
+    }
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryDto {
+    fn from(delivery: WebhookDelivery) -> Self {
+        let (status, next_attempt_at) = match (delivery.delivered_at, delivery.failed_at) {
+            (Some(_), _) => ("delivered", None),
+            (None, Some(_)) => ("failed", None),
+            (None, None) => ("pending", Some(delivery.next_attempt_at)),
+        };
+        Self {
+            id: delivery.id,
+            subscription_id: delivery.subscription_id,
+            event: delivery.event.to_string(),
+            payload: delivery.payload,
+            status: status.to_string(),
+            attempts: delivery.attempts,
+            next_attempt_at,
+            delivered_at: delivery.delivered_at,
+            response_status: delivery.response_status,
+            last_error: delivery.last_error,
+            created_at: delivery.created_at,
+        }
+    }
+}
+
+pub(crate) fn parse_events(events: &[String]) -> Result<Vec<WebhookEvent>, UseCaseError> {
+    let mut parsed = Vec::new();
+    for event in events {
+        let event = event.parse::<WebhookEvent>()?;
+        if !parsed.contains(&event) {
+            parsed.push(event);
+        }
+    }
+    Ok(parsed)
+}
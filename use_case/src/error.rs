@@ -1,8 +1,20 @@
+use std::fmt::Display;
+
 use domain::error::DomainError;
 
 pub enum UseCaseError {
     Validation(String),
-    NotFound { entity_type: String, entity_id: i64 },
+    NotFound {
+        entity_type: String,
+        entity_id: i64,
+    },
+    /// The entity changed since the version the caller expected.
+    Conflict {
+        entity_type: String,
+        entity_id: i64,
+    },
+    /// The idempotency key was already used for a request with a different body.
+    IdempotencyKeyReused(String),
     Other(anyhow::Error),
     Unexpected(String),
 }
@@ -18,8 +30,40 @@ impl From<DomainError> for UseCaseError {
                 entity_type,
                 entity_id,
             },
+            DomainError::Conflict {
+                entity_type,
+                entity_id,
+            } => Self::Conflict {
+                entity_type,
+                entity_id,
+            },
             DomainError::Infrastructure(error) => Self::Other(error),
             DomainError::Unexpected(message) => Self::Unexpected(message),
         }
     }
 }
+
+impl Display for UseCaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UseCaseError::Validation(message) => write!(f, "validation error: {}", message),
+            UseCaseError::NotFound {
+                entity_type,
+                entity_id,
+            } => write!(f, "{} {} not found", entity_type, entity_id),
+            UseCaseError::Conflict {
+                entity_type,
+                entity_id,
+            } => write!(f, "{} {} was modified concurrently", entity_type, entity_id),
+            UseCaseError::IdempotencyKeyReused(key) => {
+                write!(
+                    f,
+                    "idempotency key {} was reused with a different request",
+                    key
+                )
+            }
+            UseCaseError::Other(error) => write!(f, "{}", error),
+            UseCaseError::Unexpected(message) => write!(f, "unexpected error: {}", message),
+        }
+    }
+}
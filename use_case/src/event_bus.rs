@@ -0,0 +1,159 @@
+use std::{fmt::Debug, sync::Arc};
+
+use domain::event::{EventSubscriber, TodoEvent};
+use tokio::sync::watch;
+
+/// Dispatches committed events to in-process subscribers, in subscription order.
+/// Subscriber failures are logged and do not affect the change that raised the event,
+/// which is already committed along with its entry in the event log.
+#[derive(Clone)]
+pub struct EventBus {
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+    /// Counts the publishes, so that listeners learn the event log has grown.
+    published: Arc<watch::Sender<u64>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            subscribers: Vec::new(),
+            published: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(mut self, subscriber: Arc<dyn EventSubscriber>) -> Self {
+        self.subscribers.push(subscriber);
+        self
+    }
+
+    /// A listener woken by every publish from now on.
+    pub fn listen(&self) -> EventListener {
+        EventListener {
+            published: self.published.subscribe(),
+        }
+    }
+
+    pub async fn publish(&self, events: &[TodoEvent]) {
+        if !events.is_empty() {
+            self.published.send_modify(|count| *count += 1);
+        }
+        for event in events {
+            for subscriber in self.subscribers.iter() {
+                if let Err(e) = subscriber.handle(event).await {
+                    log::warn!("event subscriber failed on {}: {}", event.name(), e);
+                }
+            }
+        }
+    }
+}
+
+impl Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+/// Waits for events published on an `EventBus`.
+#[derive(Debug)]
+pub struct EventListener {
+    published: watch::Receiver<u64>,
+}
+
+impl EventListener {
+    /// Waits until events were published since the listener was made or last woken.
+    pub async fn published(&mut self) {
+        if self.published.changed().await.is_err() {
+            // the bus is gone, so nothing will be published any more
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use domain::{entity::todo::Todo, error::DomainError};
+    use std::{sync::Mutex, time::Duration};
+
+    /// Remembers the name and todo id of every event it handles.
+    #[derive(Debug, Default)]
+    pub struct RecordingSubscriber {
+        pub handled: Mutex<Vec<(&'static str, i64)>>,
+    }
+
+    #[async_trait]
+    impl EventSubscriber for RecordingSubscriber {
+        async fn handle(&self, event: &TodoEvent) -> Result<(), DomainError> {
+            self.handled
+                .lock()
+                .unwrap()
+                .push((event.name(), event.todo().id));
+            Ok(())
+        }
+    }
+
+    struct FailingSubscriber;
+
+    #[async_trait]
+    impl EventSubscriber for FailingSubscriber {
+        async fn handle(&self, _event: &TodoEvent) -> Result<(), DomainError> {
+            Err(DomainError::Unexpected("boom".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_continues_past_failures() {
+        let recorder = Arc::new(RecordingSubscriber::default());
+        let bus = EventBus::new()
+            .subscribe(Arc::new(FailingSubscriber))
+            .subscribe(recorder.clone());
+        let todo = Todo {
+            id: 7,
+            ..Default::default()
+        };
+        bus.publish(&[
+            TodoEvent::TodoCreated { todo: todo.clone() },
+            TodoEvent::TodoDeleted { todo },
+        ])
+        .await;
+        assert_eq!(
+            *recorder.handled.lock().unwrap(),
+            vec![("TodoCreated", 7), ("TodoDeleted", 7)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_publish_wakes_listeners() {
+        let bus = EventBus::new();
+        let mut listener = bus.clone().listen();
+        bus.publish(&[]).await;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), listener.published())
+                .await
+                .is_err()
+        );
+
+        // a publish while the listener is not waiting still wakes it
+        bus.publish(&[TodoEvent::TodoCreated {
+            todo: Todo::default(),
+        }])
+        .await;
+        tokio::time::timeout(Duration::from_secs(1), listener.published())
+            .await
+            .expect("not woken by the publish");
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), listener.published())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! Todos as RFC 5545 VTODO components.
+//!
+//! A todo maps to a VTODO with its title as `SUMMARY`, its due date as `DUE`, its
+//! recurrence as `RRULE` and its priority as `PRIORITY` 1 (high), 5 (medium) or 9 (low).
+//! `STATUS` is `COMPLETED`, with the completion time in `COMPLETED`, or `NEEDS-ACTION`.
+//!
+//! On import, `PRIORITY` 1 to 4 is high, 6 to 9 low and anything else medium. `DUE` may
+//! be a UTC, floating (read as UTC) or `TZID` date-time, or a date. A VTODO is completed
+//! when it has `STATUS:COMPLETED` or a `COMPLETED` time; every other status, cancelled
+//! included, is imported as an open todo. Components other than VTODO are ignored.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use domain::entity::todo::{Priority, Todo};
+
+use crate::todo_format::parse_recurrence;
+
+pub const HEADER: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+                          PRODID:-//clean-architecture-playground//todo//EN\r\n\
+                          CALSCALE:GREGORIAN\r\nX-WR-CALNAME:Todos\r\n";
+pub const FOOTER: &str = "END:VCALENDAR\r\n";
+
+/// Longest content line, in octets, before it is folded.
+const LINE_LIMIT: usize = 75;
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// `todo` as a VTODO component stamped at `stamp`, each content line folded and ended
+/// with CRLF.
+pub fn encode_vtodo(todo: &Todo, stamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VTODO".to_string(),
+        format!("UID:todo-{}@clean-architecture-playground", todo.id),
+        format!("DTSTAMP:{}", stamp.format(DATE_TIME_FORMAT)),
+        format!("SEQUENCE:{}", todo.version - Todo::FIRST_VERSION),
+        format!(
+            "SUMMARY:{}",
+            escape(todo.title.as_deref().unwrap_or_default())
+        ),
+        format!("PRIORITY:{}", priority_level(todo.priority)),
+    ];
+    if let Some(due_at) = todo.due_at {
+        lines.push(format!("DUE:{}", due_at.format(DATE_TIME_FORMAT)));
+    }
+    if let Some(recurrence) = &todo.recurrence {
+        lines.push(format!("RRULE:{}", recurrence));
+    }
+    match todo.completed_at {
+        Some(completed_at) => {
+            lines.push("STATUS:COMPLETED".to_string());
+            lines.push(format!(
+                "COMPLETED:{}",
+                completed_at.format(DATE_TIME_FORMAT)
+            ));
+        }
+        None => lines.push("STATUS:NEEDS-ACTION".to_string()),
+    }
+    lines.push("END:VTODO".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// The VTODO components of `data`, each with the line its `BEGIN:VTODO` is on.
+pub fn decode_vtodos(data: &str) -> Vec<(usize, Result<Todo, String>)> {
+    let mut todos = Vec::new();
+    let mut vtodo: Option<(usize, Vec<Property>)> = None;
+    // components nested in a VTODO, such as VALARM, are skipped
+    let mut nested = 0;
+    for (line, text) in unfold(data) {
+        let property = match parse_property(&text) {
+            Some(property) => property,
+            None => {
+                if let Some((start, _)) = vtodo.take() {
+                    todos.push((start, Err(format!("line {}: malformed line", line))));
+                    nested = 0;
+                }
+                continue;
+            }
+        };
+        match (
+            property.name.as_str(),
+            property.value.to_ascii_uppercase().as_str(),
+        ) {
+            ("BEGIN", "VTODO") if vtodo.is_none() => vtodo = Some((line, Vec::new())),
+            ("END", "VTODO") if nested == 0 => {
+                if let Some((start, properties)) = vtodo.take() {
+                    todos.push((start, decode_properties(&properties)));
+                }
+            }
+            ("BEGIN", _) if vtodo.is_some() => nested += 1,
+            ("END", _) if vtodo.is_some() && nested > 0 => nested -= 1,
+            _ if nested == 0 => {
+                if let Some((_, properties)) = &mut vtodo {
+                    properties.push(property);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((start, _)) = vtodo {
+        todos.push((start, Err("VTODO is missing its END:VTODO".to_string())));
+    }
+    todos
+}
+
+struct Property {
+    name: String,
+    parameters: HashMap<String, String>,
+    value: String,
+}
+
+/// The content lines of `data` with their folds undone, each with the line it starts on.
+fn unfold(data: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, text) in data.lines().enumerate() {
+        match (text.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some((_, last))) => last.push_str(continuation),
+            _ if text.trim().is_empty() => {}
+            _ => lines.push((index + 1, text.to_string())),
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // the value starts at the first colon outside a quoted parameter value
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let parameters = parts
+        .filter_map(|parameter| parameter.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Some(Property {
+        name,
+        parameters,
+        value: value.to_string(),
+    })
+}
+
+fn decode_properties(properties: &[Property]) -> Result<Todo, String> {
+    let mut todo = Todo::default();
+    let mut status_completed = false;
+    let mut stamp = None;
+    for property in properties {
+        match property.name.as_str() {
+            "SUMMARY" => todo.title = Some(unescape(&property.value)),
+            "DUE" => todo.due_at = Some(parse_date_time(property)?),
+            "PRIORITY" => match property.value.trim().parse::<u8>() {
+                Ok(level) => todo.priority = parse_priority_level(level),
+                Err(_) => return Err(format!("invalid PRIORITY {}", property.value)),
+            },
+            "RRULE" => todo.recurrence = parse_recurrence(Some(&property.value))?,
+            "STATUS" => status_completed = property.value.eq_ignore_ascii_case("COMPLETED"),
+            "COMPLETED" => todo.completed_at = Some(parse_date_time(property)?),
+            "DTSTAMP" => stamp = Some(parse_date_time(property)?),
+            _ => {}
+        }
+    }
+    if status_completed && todo.completed_at.is_none() {
+        match stamp {
+            Some(stamp) => todo.completed_at = Some(stamp),
+            None => return Err("a completed VTODO needs a COMPLETED or DTSTAMP".to_string()),
+        }
+    }
+    if todo.title.is_none() {
+        return Err("VTODO has no SUMMARY".to_string());
+    }
+    Ok(todo)
+}
+
+fn parse_date_time(property: &Property) -> Result<DateTime<Utc>, String> {
+    let invalid = || format!("invalid {} {}", property.name, property.value);
+    let value = property.value.trim();
+    let is_date = property
+        .parameters
+        .get("VALUE")
+        .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"))
+        || value.len() == 8;
+    if is_date {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date_time| date_time.and_utc())
+            .ok_or_else(invalid);
+    }
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .map(|date_time| date_time.and_utc())
+            .map_err(|_| invalid());
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    match property.parameters.get("TZID") {
+        Some(tzid) => {
+            let tz = tzid
+                .parse::<Tz>()
+                .map_err(|_| format!("unknown TZID {}", tzid))?;
+            local
+                .and_local_timezone(tz)
+                .earliest()
+                .map(|date_time| date_time.with_timezone(&Utc))
+                .ok_or_else(invalid)
+        }
+        None => Ok(local.and_utc()),
+    }
+}
+
+fn priority_level(priority: Priority) -> u8 {
+    match priority {
+        Priority::High => 1,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }
+}
+
+fn parse_priority_level(level: u8) -> Priority {
+    match level {
+        1..=4 => Priority::High,
+        6..=9 => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// `line` split into lines of at most [`LINE_LIMIT`] octets, continuations starting with
+/// a space, each ended with CRLF.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_vtodo() {
+        let todo = Todo {
+            id: 7,
+            title: Some(format!("Call Mom; bring cake, {}", "x".repeat(60))),
+            due_at: DateTime::from_timestamp(1_704_448_800, 0),
+            priority: Priority::High,
+            recurrence: Some("FREQ=WEEKLY;BYDAY=MO".parse().ok().unwrap()),
+            completed_at: None,
+            version: 3,
+        };
+        let stamp = DateTime::from_timestamp(1_704_067_200, 0).unwrap();
+        let encoded = encode_vtodo(&todo, stamp);
+        assert!(encoded.lines().all(|line| line.len() <= LINE_LIMIT));
+        assert_eq!(
+            encoded,
+            format!(
+                "BEGIN:VTODO\r\nUID:todo-7@clean-architecture-playground\r\n\
+                 DTSTAMP:20240101T000000Z\r\nSEQUENCE:2\r\n\
+                 SUMMARY:Call Mom\\; bring cake\\, {}\r\n {}\r\n\
+                 PRIORITY:1\r\nDUE:20240105T100000Z\r\nRRULE:FREQ=WEEKLY;BYDAY=MO\r\n\
+                 STATUS:NEEDS-ACTION\r\nEND:VTODO\r\n",
+                "x".repeat(43),
+                "x".repeat(17)
+            )
+        );
+    }
+
+    #[test]
+    fn test_decode_vtodos() {
+        let data = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nSUMMARY:Party\r\n\
+                    END:VEVENT\r\nBEGIN:VTODO\r\nSUMMARY:Pay bills\\, rent\r\n  and tax\r\n\
+                    DUE;TZID=Europe/Berlin:20240201T100000\r\nPRIORITY:7\r\nBEGIN:VALARM\r\n\
+                    TRIGGER:-PT15M\r\nEND:VALARM\r\nEND:VTODO\r\nBEGIN:VTODO\r\n\
+                    SUMMARY:Read\r\nDUE;VALUE=DATE:20240103\r\nSTATUS:COMPLETED\r\n\
+                    DTSTAMP:20240104T080000Z\r\nEND:VTODO\r\nBEGIN:VTODO\r\n\
+                    SUMMARY:Plan\r\nDUE:soon\r\nEND:VTODO\r\nBEGIN:VTODO\r\nSUMMARY:Open\r\n\
+                    END:VCALENDAR\r\n";
+        let decoded = decode_vtodos(data);
+        assert_eq!(decoded.len(), 4);
+        match &decoded[0] {
+            (6, Ok(todo)) => {
+                assert_eq!(todo.title, Some("Pay bills, rent and tax".to_string()));
+                assert_eq!(todo.priority, Priority::Low);
+                assert_eq!(todo.due_at, DateTime::from_timestamp(1_706_778_000, 0));
+                assert_eq!(todo.completed_at, None);
+            }
+            _ => panic!("failed to decode a VTODO"),
+        }
+        match &decoded[1] {
+            (15, Ok(todo)) => {
+                assert_eq!(todo.priority, Priority::Medium);
+                assert_eq!(todo.due_at, DateTime::from_timestamp(1_704_240_000, 0));
+                assert_eq!(
+                    todo.completed_at,
+                    DateTime::from_timestamp(1_704_355_200, 0)
+                );
+            }
+            _ => panic!("failed to decode a completed VTODO"),
+        }
+        assert!(matches!(decoded[2], (21, Err(_))));
+        assert!(matches!(decoded[3], (25, Err(_))));
+    }
+}
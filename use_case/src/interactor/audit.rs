@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use domain::repository::audit_repository::AuditRepository;
+
+use crate::{
+    dto::audit::{AuditEntryDto, AuditLogFilterDto},
+    error::UseCaseError,
+    traits::audit::AuditUseCase,
+};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct AuditInteractor<AR> {
+    audit_repository: AR,
+}
+
+impl<AR> AuditInteractor<AR> {
+    pub fn new(audit_repository: AR) -> Self {
+        Self { audit_repository }
+    }
+}
+
+#[async_trait]
+impl<AR> AuditUseCase for AuditInteractor<AR>
+where
+    AR: AuditRepository,
+{
+    async fn find_audit_log(
+        &self,
+        filter: AuditLogFilterDto,
+    ) -> Result<Vec<AuditEntryDto>, UseCaseError> {
+        let limit = match filter.limit {
+            Some(limit) if !(1..=MAX_LIMIT).contains(&limit) => {
+                return Err(UseCaseError::Validation(format!(
+                    "limit must be between 1 and {}",
+                    MAX_LIMIT
+                )))
+            }
+            Some(limit) => limit,
+            None => DEFAULT_LIMIT,
+        };
+        let entries = self
+            .audit_repository
+            .find(filter.todo_id, filter.actor, limit)
+            .await?;
+        Ok(entries.into_iter().map(|entry| entry.into()).collect())
+    }
+}
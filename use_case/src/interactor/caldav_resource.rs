@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use domain::{
+    entity::caldav_resource::CalDavResource,
+    repository::caldav_resource_repository::CalDavResourceRepository, unit_of_work::UnitOfWork,
+};
+
+use crate::{
+    dto::{
+        audit::RequestContext,
+        todo::{CreateTodoDto, TodoDto, UpdateTodoDto},
+    },
+    error::UseCaseError,
+    event_bus::EventBus,
+    interactor::todo::{commit, complete_in, create_in, update_in},
+    traits::caldav_resource::CalDavResourceUseCase,
+};
+
+#[derive(Debug, Clone)]
+pub struct CalDavResourceInteractor<CR, UW> {
+    caldav_resource_repository: CR,
+    unit_of_work: UW,
+    timezone: Tz,
+    event_bus: EventBus,
+}
+
+impl<CR, UW> CalDavResourceInteractor<CR, UW> {
+    pub fn new(caldav_resource_repository: CR, unit_of_work: UW) -> Self {
+        Self {
+            caldav_resource_repository,
+            unit_of_work,
+            timezone: Tz::UTC,
+            event_bus: EventBus::default(),
+        }
+    }
+
+    /// Sets the bus committed todo events are dispatched to.
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+
+    /// Sets the timezone recurrence rules are evaluated in.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+}
+
+#[async_trait]
+impl<CR, UW> CalDavResourceUseCase for CalDavResourceInteractor<CR, UW>
+where
+    CR: CalDavResourceRepository,
+    UW: UnitOfWork,
+{
+    async fn find_todo_id(&self, name: String) -> Result<Option<i64>, UseCaseError> {
+        match self.caldav_resource_repository.find_by_name(&name).await? {
+            Some(resource) => Ok(Some(resource.todo_id)),
+            None => Ok(CalDavResource::default_name_id(&name)),
+        }
+    }
+
+    async fn find_names(&self) -> Result<HashMap<i64, String>, UseCaseError> {
+        let resources = self.caldav_resource_repository.find_all().await?;
+        Ok(resources
+            .into_iter()
+            .map(|resource| (resource.todo_id, resource.name))
+            .collect())
+    }
+
+    fn validate_name(&self, name: &str) -> Result<(), UseCaseError> {
+        Ok(CalDavResource::validate_name(name)?)
+    }
+
+    async fn create_todo(
+        &self,
+        ctx: RequestContext,
+        name: String,
+        todo_data: CreateTodoDto,
+        completed: bool,
+    ) -> Result<TodoDto, UseCaseError> {
+        CalDavResource::validate_name(&name)?;
+        let mut tx = self.unit_of_work.begin().await?;
+        let todo = create_in(tx.as_mut(), &ctx, todo_data, None)
+            .await?
+            .expect("a todo created without a key is new");
+        let resource = CalDavResource::new(name, todo.id)?;
+        // dropping `tx` rolls back the todo when a concurrent request took the name
+        if !tx.create_caldav_resource(&resource).await? {
+            return Err(UseCaseError::Conflict {
+                entity_type: "caldav resource".to_string(),
+                entity_id: todo.id,
+            });
+        }
+        let todo = if completed {
+            complete_in(tx.as_mut(), &ctx, todo.id, self.timezone)
+                .await?
+                .todo
+        } else {
+            todo
+        };
+        commit(tx, &self.event_bus).await?;
+        Ok(todo)
+    }
+
+    async fn replace_todo(
+        &self,
+        ctx: RequestContext,
+        todo_data: UpdateTodoDto,
+        expected_version: Option<i64>,
+        completed: bool,
+    ) -> Result<TodoDto, UseCaseError> {
+        let mut tx = self.unit_of_work.begin().await?;
+        let todo = update_in(tx.as_mut(), &ctx, todo_data, expected_version).await?;
+        let todo = if completed && todo.completed_at.is_none() {
+            complete_in(tx.as_mut(), &ctx, todo.id, self.timezone)
+                .await?
+                .todo
+        } else {
+            todo
+        };
+        commit(tx, &self.event_bus).await?;
+        Ok(todo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interactor::todo::{
+            tests::{ctx, MockTodoRepository},
+            QueryInteractor,
+        },
+        traits::todo::QueryUseCase,
+    };
+    use domain::entity::todo::Priority;
+
+    fn todo_data(title: &str) -> CreateTodoDto {
+        CreateTodoDto {
+            title: title.to_string(),
+            due_at: None,
+            priority: Priority::default(),
+            recurrence: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_todo_and_find() {
+        let todo_repository = MockTodoRepository::new();
+        let interactor = CalDavResourceInteractor::new(todo_repository.clone(), todo_repository);
+
+        assert!(matches!(
+            interactor
+                .create_todo(ctx(), "7.ics".to_string(), todo_data("a"), false)
+                .await,
+            Err(UseCaseError::Validation(_))
+        ));
+        let todo = match interactor
+            .create_todo(ctx(), "0f8fad5b.ics".to_string(), todo_data("a"), true)
+            .await
+        {
+            Ok(todo) => todo,
+            Err(_) => panic!("failed to create the todo"),
+        };
+        assert!(todo.completed_at.is_some());
+
+        for (name, todo_id) in [
+            ("0f8fad5b.ics", Some(todo.id)),
+            ("7.ics", Some(7)),
+            ("x.ics", None),
+        ] {
+            match interactor.find_todo_id(name.to_string()).await {
+                Ok(found) => assert_eq!(found, todo_id, "{}", name),
+                Err(_) => panic!(),
+            }
+        }
+        match interactor.find_names().await {
+            Ok(names) => assert_eq!(
+                names,
+                HashMap::from([(todo.id, "0f8fad5b.ics".to_string())])
+            ),
+            Err(_) => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_todo_under_a_taken_name_rolls_back() {
+        let todo_repository = MockTodoRepository::new();
+        let interactor =
+            CalDavResourceInteractor::new(todo_repository.clone(), todo_repository.clone());
+        let name = "0f8fad5b.ics".to_string();
+
+        let first = match interactor
+            .create_todo(ctx(), name.clone(), todo_data("a"), false)
+            .await
+        {
+            Ok(todo) => todo,
+            Err(_) => panic!("failed to create the todo"),
+        };
+        assert!(matches!(
+            interactor
+                .create_todo(ctx(), name.clone(), todo_data("b"), false)
+                .await,
+            Err(UseCaseError::Conflict { .. })
+        ));
+        assert!(matches!(
+            interactor.find_todo_id(name).await,
+            Ok(Some(todo_id)) if todo_id == first.id
+        ));
+        // the todo created before the name was refused went with the transaction
+        match QueryInteractor::new(todo_repository).find_all().await {
+            Ok(todos) => assert_eq!(todos.len(), 2),
+            Err(_) => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_todo_completes_it() {
+        let todo_repository = MockTodoRepository::new();
+        let interactor = CalDavResourceInteractor::new(todo_repository.clone(), todo_repository);
+        let todo_data = UpdateTodoDto {
+            id: 1,
+            title: Some("renamed".to_string()),
+            ..Default::default()
+        };
+
+        match interactor.replace_todo(ctx(), todo_data, None, true).await {
+            Ok(todo) => {
+                assert_eq!(todo.title, Some("renamed".to_string()));
+                assert!(todo.completed_at.is_some());
+            }
+            Err(_) => panic!("failed to replace the todo"),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use chrono::{SubsecRound, Utc};
+use domain::{
+    entity::calendar_feed::CalendarFeed,
+    repository::{
+        calendar_feed_repository::CalendarFeedRepository, todo_repository::TodoRepository,
+    },
+};
+
+use crate::{
+    dto::{audit::RequestContext, calendar_feed::CalendarFeedDto, import_export::TodoFormat},
+    error::UseCaseError,
+    interactor::import_export::export_todos,
+    traits::{calendar_feed::CalendarFeedUseCase, import_export::ExportStream},
+};
+
+#[derive(Debug, Clone)]
+pub struct CalendarFeedInteractor<CR, TR> {
+    calendar_feed_repository: CR,
+    todo_repository: TR,
+}
+
+impl<CR, TR> CalendarFeedInteractor<CR, TR> {
+    pub fn new(calendar_feed_repository: CR, todo_repository: TR) -> Self {
+        Self {
+            calendar_feed_repository,
+            todo_repository,
+        }
+    }
+}
+
+/// A fresh feed token: two random v4 UUIDs, 244 random bits in 64 hex digits.
+fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+#[async_trait]
+impl<CR, TR> CalendarFeedUseCase for CalendarFeedInteractor<CR, TR>
+where
+    CR: CalendarFeedRepository,
+    TR: TodoRepository,
+{
+    async fn create_feed(&self, ctx: RequestContext) -> Result<CalendarFeedDto, UseCaseError> {
+        let mut feed = CalendarFeed::new(generate_token(), ctx.actor, Utc::now().trunc_subsecs(0))?;
+        feed.id = self.calendar_feed_repository.create_feed(&feed).await?;
+        Ok(feed.into())
+    }
+
+    async fn find_feeds(&self, ctx: RequestContext) -> Result<Vec<CalendarFeedDto>, UseCaseError> {
+        let feeds = self
+            .calendar_feed_repository
+            .find_feeds_by_actor(&ctx.actor)
+            .await?;
+        Ok(feeds.into_iter().map(|feed| feed.into()).collect())
+    }
+
+    async fn revoke_feed(&self, ctx: RequestContext, feed_id: i64) -> Result<i64, UseCaseError> {
+        match self
+            .calendar_feed_repository
+            .find_feed_by_id(feed_id)
+            .await?
+        {
+            Some(feed) if feed.is_owned_by(&ctx.actor) => {
+                self.calendar_feed_repository.delete_feed(feed_id).await?;
+                Ok(feed_id)
+            }
+            _ => Err(UseCaseError::NotFound {
+                entity_type: "calendar feed".to_string(),
+                entity_id: feed_id,
+            }),
+        }
+    }
+
+    async fn feed(&self, token: &str) -> Result<Option<ExportStream>, UseCaseError> {
+        let feed = self
+            .calendar_feed_repository
+            .find_feed_by_token(token)
+            .await?;
+        Ok(feed.map(|_| export_todos(&self.todo_repository, TodoFormat::ICalendar)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interactor::todo::tests::{ctx, MockTodoRepository};
+    use domain::{entity::audit::Protocol, error::DomainError};
+    use futures_util::StreamExt;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, Default)]
+    struct MockCalendarFeedRepository {
+        feeds: Arc<Mutex<Vec<CalendarFeed>>>,
+    }
+
+    #[async_trait]
+    impl CalendarFeedRepository for MockCalendarFeedRepository {
+        async fn create_feed(&self, feed: &CalendarFeed) -> Result<i64, DomainError> {
+            let mut feeds = self.feeds.lock().unwrap();
+            let new_id = feeds.len() as i64 + 1;
+            feeds.push(CalendarFeed {
+                id: new_id,
+                ..feed.clone()
+            });
+            Ok(new_id)
+        }
+
+        async fn find_feeds_by_actor(&self, actor: &str) -> Result<Vec<CalendarFeed>, DomainError> {
+            let feeds = self.feeds.lock().unwrap();
+            Ok(feeds
+                .iter()
+                .filter(|feed| feed.actor == actor)
+                .cloned()
+                .collect())
+        }
+
+        async fn find_feed_by_id(&self, id: i64) -> Result<Option<CalendarFeed>, DomainError> {
+            let feeds = self.feeds.lock().unwrap();
+            Ok(feeds.iter().find(|feed| feed.id == id).cloned())
+        }
+
+        async fn find_feed_by_token(
+            &self,
+            token: &str,
+        ) -> Result<Option<CalendarFeed>, DomainError> {
+            let feeds = self.feeds.lock().unwrap();
+            Ok(feeds.iter().find(|feed| feed.token == token).cloned())
+        }
+
+        async fn delete_feed(&self, id: i64) -> Result<(), DomainError> {
+            self.feeds.lock().unwrap().retain(|feed| feed.id != id);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calendar_feeds() {
+        let interactor = CalendarFeedInteractor::new(
+            MockCalendarFeedRepository::default(),
+            MockTodoRepository::new(),
+        );
+        let bob = RequestContext::new("bob", Protocol::Rest, "req-2");
+
+        let feed = interactor.create_feed(ctx()).await.ok().unwrap();
+        assert_eq!(feed.actor, "alice");
+        assert_eq!(feed.token.len(), 64);
+        let other = interactor.create_feed(bob.clone()).await.ok().unwrap();
+        assert_ne!(feed.token, other.token);
+
+        match interactor.find_feeds(ctx()).await {
+            Ok(feeds) => assert_eq!(feeds.len(), 1),
+            Err(_) => panic!("failed to find feeds"),
+        }
+
+        let calendar = match interactor.feed(&feed.token).await {
+            Ok(Some(chunks)) => chunks
+                .map(|chunk| chunk.ok().unwrap())
+                .collect::<Vec<_>>()
+                .await
+                .concat(),
+            _ => panic!("failed to read the feed"),
+        };
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.contains("SUMMARY:task1\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+
+        // only the actor a feed was issued to can revoke it
+        assert!(matches!(
+            interactor.revoke_feed(bob, feed.id).await,
+            Err(UseCaseError::NotFound { .. })
+        ));
+        assert!(matches!(
+            interactor.revoke_feed(ctx(), feed.id).await,
+            Ok(id) if id == feed.id
+        ));
+        assert!(matches!(interactor.feed(&feed.token).await, Ok(None)));
+    }
+}
@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use domain::repository::event_log_repository::EventLogRepository;
+
+use crate::{
+    dto::event_log::{EventCursorDto, LoggedEventDto},
+    error::UseCaseError,
+    event_bus::{EventBus, EventListener},
+    traits::event_log::{EventLogPruneUseCase, EventLogUseCase},
+};
+
+const DEFAULT_CAPACITY: i64 = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct EventLogInteractor<ER> {
+    event_log_repository: ER,
+    capacity: i64,
+    event_bus: EventBus,
+}
+
+impl<ER> EventLogInteractor<ER> {
+    pub fn new(event_log_repository: ER) -> Self {
+        Self {
+            event_log_repository,
+            capacity: DEFAULT_CAPACITY,
+            event_bus: EventBus::new(),
+        }
+    }
+
+    /// Sets the bus the changes are published on, which tells listeners the log has grown.
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+
+    /// Sets how many of the newest events the log keeps for subscribers to resume from.
+    pub fn with_capacity(mut self, capacity: i64) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+}
+
+#[async_trait]
+impl<ER> EventLogUseCase for EventLogInteractor<ER>
+where
+    ER: EventLogRepository,
+{
+    async fn resume(&self, last_event_id: Option<i64>) -> Result<EventCursorDto, UseCaseError> {
+        let bounds = self.event_log_repository.find_bounds().await?;
+        let newest = bounds.map_or(0, |(_, newest)| newest);
+        let resumable = match (last_event_id, bounds) {
+            (None, _) => false,
+            (Some(last_event_id), None) => last_event_id == 0,
+            // an id past the newest event was not handed out by this log
+            (Some(last_event_id), Some((oldest, newest))) => {
+                oldest - 1 <= last_event_id && last_event_id <= newest
+            }
+        };
+        Ok(match last_event_id {
+            Some(last_event_id) if resumable => EventCursorDto {
+                last_event_id,
+                reset: false,
+            },
+            _ => EventCursorDto {
+                last_event_id: newest,
+                reset: last_event_id.is_some(),
+            },
+        })
+    }
+
+    async fn find_after(
+        &self,
+        last_event_id: i64,
+        limit: i64,
+    ) -> Result<Vec<LoggedEventDto>, UseCaseError> {
+        let events = self
+            .event_log_repository
+            .find_after(last_event_id, limit)
+            .await?;
+        Ok(events.into_iter().map(LoggedEventDto::from).collect())
+    }
+
+    fn listen(&self) -> EventListener {
+        self.event_bus.listen()
+    }
+}
+
+#[async_trait]
+impl<ER> EventLogPruneUseCase for EventLogInteractor<ER>
+where
+    ER: EventLogRepository,
+{
+    async fn prune(&self) -> Result<u64, UseCaseError> {
+        let pruned = self.event_log_repository.prune(self.capacity).await?;
+        Ok(pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use domain::{entity::event_log::LoggedEvent, error::DomainError};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, Default)]
+    struct MockEventLogRepository {
+        events: Arc<Mutex<Vec<LoggedEvent>>>,
+    }
+
+    impl MockEventLogRepository {
+        fn append(&self, count: i64) {
+            let mut events = self.events.lock().unwrap();
+            let last_id = events.last().map_or(0, |event| event.id);
+            for id in last_id + 1..=last_id + count {
+                events.push(LoggedEvent {
+                    id,
+                    event: "TodoCreated".to_string(),
+                    todo_id: id,
+                    payload: "{}".to_string(),
+                    occurred_at: Utc::now(),
+                });
+            }
+        }
+    }
+
+    #[async_trait]
+    impl EventLogRepository for MockEventLogRepository {
+        async fn find_after(
+            &self,
+            after_id: i64,
+            limit: i64,
+        ) -> Result<Vec<LoggedEvent>, DomainError> {
+            let events = self.events.lock().unwrap();
+            Ok(events
+                .iter()
+                .filter(|event| event.id > after_id)
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn find_bounds(&self) -> Result<Option<(i64, i64)>, DomainError> {
+            let events = self.events.lock().unwrap();
+            Ok(events
+                .first()
+                .zip(events.last())
+                .map(|(oldest, newest)| (oldest.id, newest.id)))
+        }
+
+        async fn prune(&self, keep: i64) -> Result<u64, DomainError> {
+            let mut events = self.events.lock().unwrap();
+            let pruned = events.len().saturating_sub(keep as usize);
+            events.drain(..pruned);
+            Ok(pruned as u64)
+        }
+    }
+
+    fn cursor(last_event_id: i64, reset: bool) -> EventCursorDto {
+        EventCursorDto {
+            last_event_id,
+            reset,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_within_and_beyond_the_log() {
+        let repository = MockEventLogRepository::default();
+        let interactor = EventLogInteractor::new(repository.clone()).with_capacity(3);
+
+        assert_eq!(interactor.resume(None).await.ok(), Some(cursor(0, false)));
+        assert_eq!(
+            interactor.resume(Some(0)).await.ok(),
+            Some(cursor(0, false))
+        );
+        assert_eq!(interactor.resume(Some(4)).await.ok(), Some(cursor(0, true)));
+
+        repository.append(5);
+        match interactor.prune().await {
+            Ok(pruned) => assert_eq!(pruned, 2),
+            Err(_) => panic!(),
+        }
+        assert_eq!(interactor.resume(None).await.ok(), Some(cursor(5, false)));
+        assert_eq!(
+            interactor.resume(Some(2)).await.ok(),
+            Some(cursor(2, false))
+        );
+        assert_eq!(interactor.resume(Some(1)).await.ok(), Some(cursor(5, true)));
+        assert_eq!(interactor.resume(Some(9)).await.ok(), Some(cursor(5, true)));
+
+        match interactor.find_after(2, 2).await {
+            Ok(events) => assert_eq!(
+                events.iter().map(|event| event.id).collect::<Vec<_>>(),
+                vec![3, 4]
+            ),
+            Err(_) => panic!(),
+        }
+    }
+}
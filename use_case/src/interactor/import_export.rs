@@ -0,0 +1,206 @@
+use async_trait::async_trait;
+use domain::{
+    entity::audit::AuditAction, event::TodoEvent, repository::todo_repository::TodoRepository,
+    unit_of_work::UnitOfWork,
+};
+use futures_util::{stream, StreamExt};
+
+use crate::{
+    dto::{
+        audit::RequestContext,
+        import_export::{ImportProblemDto, ImportReportDto, TodoFormat},
+    },
+    error::UseCaseError,
+    event_bus::EventBus,
+    interactor::todo::{audit, commit},
+    todo_format::{decode, encode, footer, header},
+    traits::import_export::{ExportStream, ImportExportUseCase},
+};
+
+/// Every todo of `todo_repository` in `format`, read as the stream is polled.
+pub(crate) fn export_todos<TR: TodoRepository>(
+    todo_repository: &TR,
+    format: TodoFormat,
+) -> ExportStream {
+    let header = header(format).map(|header| Ok(header.to_string()));
+    let todos = todo_repository.stream_all().map(move |todo| match todo {
+        Ok(todo) => encode(format, &todo),
+        Err(e) => Err(e.into()),
+    });
+    let footer = footer(format).map(|footer| Ok(footer.to_string()));
+    Box::pin(
+        stream::iter(header)
+            .chain(todos)
+            .chain(stream::iter(footer)),
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportExportInteractor<TR, UW> {
+    todo_repository: TR,
+    unit_of_work: UW,
+    event_bus: EventBus,
+}
+
+impl<TR, UW> ImportExportInteractor<TR, UW> {
+    pub fn new(todo_repository: TR, unit_of_work: UW) -> Self {
+        Self {
+            todo_repository,
+            unit_of_work,
+            event_bus: EventBus::default(),
+        }
+    }
+
+    /// Sets the bus the events of imported todos are dispatched to.
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+}
+
+#[async_trait]
+impl<TR, UW> ImportExportUseCase for ImportExportInteractor<TR, UW>
+where
+    TR: TodoRepository,
+    UW: UnitOfWork,
+{
+    fn export(&self, format: TodoFormat) -> ExportStream {
+        export_todos(&self.todo_repository, format)
+    }
+
+    async fn import(
+        &self,
+        ctx: RequestContext,
+        format: TodoFormat,
+        data: String,
+        dry_run: bool,
+    ) -> Result<ImportReportDto, UseCaseError> {
+        let mut todos = Vec::new();
+        let mut problems = Vec::new();
+        for (line, todo) in decode(format, &data) {
+            match todo {
+                Ok(todo) => todos.push(todo),
+                Err(message) => problems.push(ImportProblemDto { line, message }),
+            }
+        }
+        let mut report = ImportReportDto {
+            dry_run,
+            valid: todos.len(),
+            imported: 0,
+            problems,
+        };
+        if dry_run || !report.problems.is_empty() {
+            return Ok(report);
+        }
+        let mut tx = self.unit_of_work.begin().await?;
+        for mut todo in todos {
+            todo.id = tx.create_todo(&todo).await?;
+            tx.append_revision(&todo).await?;
+            audit(tx.as_mut(), &ctx, AuditAction::Create, None, Some(&todo)).await?;
+            tx.record(TodoEvent::TodoCreated { todo });
+            report.imported += 1;
+        }
+        commit(tx, &self.event_bus).await?;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interactor::todo::{
+            tests::{ctx, MockTodoRepository},
+            QueryInteractor,
+        },
+        traits::todo::QueryUseCase,
+    };
+
+    async fn export(interactor: &impl ImportExportUseCase, format: TodoFormat) -> String {
+        let mut chunks = interactor.export(format);
+        let mut data = String::new();
+        while let Some(chunk) = chunks.next().await {
+            match chunk {
+                Ok(chunk) => data.push_str(&chunk),
+                Err(_) => panic!("failed to export todos"),
+            }
+        }
+        data
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let todo_repository = MockTodoRepository::new();
+        let interactor = ImportExportInteractor::new(todo_repository.clone(), todo_repository);
+
+        assert_eq!(
+            export(&interactor, TodoFormat::Csv).await,
+            "id,title,due_at,priority,recurrence,completed_at,version\n1,task1,,medium,,,1\n"
+        );
+        assert_eq!(
+            export(&interactor, TodoFormat::TodoTxt).await,
+            "(B) task1\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import() {
+        let todo_repository = MockTodoRepository::new();
+        let interactor =
+            ImportExportInteractor::new(todo_repository.clone(), todo_repository.clone());
+        let query_interactor = QueryInteractor::new(todo_repository.clone());
+        let data = "{\"title\":\"task2\",\"priority\":\"high\"}\n\n{\"title\":\"task3\"}\n";
+
+        // a dry run only validates
+        match interactor
+            .import(ctx(), TodoFormat::JsonLines, data.to_string(), true)
+            .await
+        {
+            Ok(report) => {
+                assert_eq!((report.valid, report.imported), (2, 0));
+                assert!(report.problems.is_empty());
+            }
+            Err(_) => panic!("failed to validate import"),
+        }
+        assert!(matches!(query_interactor.find_all().await, Ok(todos) if todos.len() == 1));
+
+        // a single problem rejects the whole file
+        let invalid = format!("{}{{\"title\":\"task4\",\"recurrence\":\"daily\"}}\n", data);
+        match interactor
+            .import(ctx(), TodoFormat::JsonLines, invalid, false)
+            .await
+        {
+            Ok(report) => {
+                assert_eq!((report.valid, report.imported), (2, 0));
+                assert_eq!(report.problems.len(), 1);
+                assert_eq!(report.problems[0].line, 4);
+            }
+            Err(_) => panic!("failed to validate import"),
+        }
+        assert!(matches!(query_interactor.find_all().await, Ok(todos) if todos.len() == 1));
+
+        match interactor
+            .import(ctx(), TodoFormat::JsonLines, data.to_string(), false)
+            .await
+        {
+            Ok(report) => assert_eq!(report.imported, 2),
+            Err(_) => panic!("failed to import todos"),
+        }
+        match query_interactor.find_all().await {
+            Ok(todos) => {
+                let titles: Vec<Option<String>> =
+                    todos.into_iter().map(|todo| todo.title).collect();
+                assert_eq!(
+                    titles,
+                    vec![
+                        Some("task1".to_string()),
+                        Some("task2".to_string()),
+                        Some("task3".to_string())
+                    ]
+                );
+            }
+            Err(_) => panic!("failed to fetch todos"),
+        }
+        assert_eq!(todo_repository.audit_log.lock().unwrap().len(), 2);
+    }
+}
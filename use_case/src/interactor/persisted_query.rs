@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use chrono::{SubsecRound, Utc};
+use domain::{
+    entity::persisted_query::{PersistedQuery, QuerySource},
+    repository::persisted_query_repository::PersistedQueryRepository,
+};
+
+use crate::{
+    dto::persisted_query::PersistedQueryDto, error::UseCaseError,
+    traits::persisted_query::PersistedQueryUseCase,
+};
+
+const DEFAULT_CAPACITY: i64 = 1_000;
+
+#[derive(Debug, Clone)]
+pub struct PersistedQueryInteractor<PR> {
+    persisted_query_repository: PR,
+    capacity: i64,
+}
+
+impl<PR> PersistedQueryInteractor<PR> {
+    pub fn new(persisted_query_repository: PR) -> Self {
+        Self {
+            persisted_query_repository,
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Sets how many automatic persisted queries are kept; registered ones do not count.
+    pub fn with_capacity(mut self, capacity: i64) -> Self {
+        self.capacity = capacity.max(0);
+        self
+    }
+}
+
+#[async_trait]
+impl<PR> PersistedQueryUseCase for PersistedQueryInteractor<PR>
+where
+    PR: PersistedQueryRepository,
+{
+    async fn find(&self, hash: String) -> Result<Option<PersistedQueryDto>, UseCaseError> {
+        let query = self.persisted_query_repository.find_by_hash(&hash).await?;
+        Ok(query.map(|query| query.into()))
+    }
+
+    async fn find_all(&self) -> Result<Vec<PersistedQueryDto>, UseCaseError> {
+        let queries = self.persisted_query_repository.find_all().await?;
+        Ok(queries.into_iter().map(|query| query.into()).collect())
+    }
+
+    async fn register(
+        &self,
+        document: String,
+        operation_names: Vec<String>,
+    ) -> Result<PersistedQueryDto, UseCaseError> {
+        if operation_names.is_empty() {
+            return Err(UseCaseError::Validation(
+                "a manifest must hold at least one named operation".to_string(),
+            ));
+        }
+        let query = PersistedQuery::new(
+            document,
+            operation_names,
+            QuerySource::Manifest,
+            Utc::now().trunc_subsecs(0),
+        );
+        self.persisted_query_repository.save(&query).await?;
+        Ok(query.into())
+    }
+
+    async fn save_automatic(
+        &self,
+        document: String,
+        operation_names: Vec<String>,
+    ) -> Result<PersistedQueryDto, UseCaseError> {
+        let query = PersistedQuery::new(
+            document,
+            operation_names,
+            QuerySource::Automatic,
+            Utc::now().trunc_subsecs(0),
+        );
+        self.persisted_query_repository.save(&query).await?;
+        self.persisted_query_repository
+            .prune_automatic(self.capacity)
+            .await?;
+        Ok(query.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::error::DomainError;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, Default)]
+    struct MockPersistedQueryRepository {
+        queries: Arc<Mutex<Vec<PersistedQuery>>>,
+    }
+
+    #[async_trait]
+    impl PersistedQueryRepository for MockPersistedQueryRepository {
+        async fn find_by_hash(&self, hash: &str) -> Result<Option<PersistedQuery>, DomainError> {
+            let queries = self.queries.lock().unwrap();
+            Ok(queries.iter().find(|query| query.hash == hash).cloned())
+        }
+
+        async fn find_all(&self) -> Result<Vec<PersistedQuery>, DomainError> {
+            let mut queries = self.queries.lock().unwrap().clone();
+            queries.sort_by_key(|query| query.source == QuerySource::Automatic);
+            Ok(queries)
+        }
+
+        async fn save(&self, query: &PersistedQuery) -> Result<(), DomainError> {
+            let mut queries = self.queries.lock().unwrap();
+            match queries.iter_mut().find(|saved| saved.hash == query.hash) {
+                Some(saved) if query.source == QuerySource::Manifest => *saved = query.clone(),
+                Some(_) => {}
+                None => queries.push(query.clone()),
+            }
+            Ok(())
+        }
+
+        async fn prune_automatic(&self, keep: i64) -> Result<u64, DomainError> {
+            let mut queries = self.queries.lock().unwrap();
+            let automatic = queries
+                .iter()
+                .filter(|query| query.source == QuerySource::Automatic)
+                .count();
+            let mut excess = automatic.saturating_sub(keep as usize);
+            let pruned = excess as u64;
+            queries.retain(|query| {
+                if query.source == QuerySource::Automatic && excess > 0 {
+                    excess -= 1;
+                    return false;
+                }
+                true
+            });
+            Ok(pruned)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_and_save_automatic() {
+        let interactor =
+            PersistedQueryInteractor::new(MockPersistedQueryRepository::default()).with_capacity(1);
+
+        assert!(matches!(
+            interactor
+                .register("{ todos { id } }".to_string(), vec![])
+                .await,
+            Err(UseCaseError::Validation(_))
+        ));
+        let manifest = match interactor
+            .register(
+                "query getTodos { todos { id } }".to_string(),
+                vec!["getTodos".to_string()],
+            )
+            .await
+        {
+            Ok(query) => query,
+            Err(_) => panic!(),
+        };
+        assert!(manifest.is_registered());
+        assert_eq!(
+            manifest.hash,
+            PersistedQuery::hash_of("query getTodos { todos { id } }")
+        );
+
+        for document in ["{ todos { id } }", "{ todos { title } }"] {
+            match interactor
+                .save_automatic(document.to_string(), vec![])
+                .await
+            {
+                Ok(query) => assert!(!query.is_registered()),
+                Err(_) => panic!(),
+            }
+        }
+        match interactor.find_all().await {
+            Ok(queries) => assert_eq!(
+                queries
+                    .iter()
+                    .map(|query| query.document.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["query getTodos { todos { id } }", "{ todos { title } }"]
+            ),
+            Err(_) => panic!(),
+        }
+        match interactor.find(manifest.hash.clone()).await {
+            Ok(found) => assert_eq!(found.map(|query| query.hash), Some(manifest.hash)),
+            Err(_) => panic!(),
+        }
+    }
+}
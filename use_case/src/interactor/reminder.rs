@@ -0,0 +1,400 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use domain::{
+    entity::reminder::{Reminder, ReminderTrigger},
+    notifier::Notifier,
+    repository::{reminder_repository::ReminderRepository, todo_repository::TodoRepository},
+};
+
+use crate::{
+    dto::reminder::{CreateReminderDto, ReminderDto},
+    error::UseCaseError,
+    interactor::todo::find_existing,
+    traits::reminder::{ReminderDispatchUseCase, ReminderUseCase},
+};
+
+const DEFAULT_LEASE_SECONDS: i64 = 300;
+const DEFAULT_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct ReminderInteractor<TR, RR, N> {
+    todo_repository: TR,
+    reminder_repository: RR,
+    notifier: N,
+    lease: Duration,
+    batch_size: i64,
+}
+
+impl<TR, RR, N> ReminderInteractor<TR, RR, N> {
+    pub fn new(todo_repository: TR, reminder_repository: RR, notifier: N) -> Self {
+        Self {
+            todo_repository,
+            reminder_repository,
+            notifier,
+            lease: Duration::seconds(DEFAULT_LEASE_SECONDS),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets how long a claimed reminder stays invisible to other dispatchers. It should
+    /// comfortably exceed the notifier's timeout.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+}
+
+#[async_trait]
+impl<TR, RR, N> ReminderUseCase for ReminderInteractor<TR, RR, N>
+where
+    TR: TodoRepository,
+    RR: ReminderRepository,
+    N: Notifier,
+{
+    async fn create(&self, reminder_data: CreateReminderDto) -> Result<ReminderDto, UseCaseError> {
+        let todo = find_existing(&self.todo_repository, reminder_data.todo_id).await?;
+        let trigger = ReminderTrigger::try_from(&reminder_data)?;
+        let mut reminder = Reminder::new(&todo, trigger)?;
+        reminder.id = self.reminder_repository.create(&reminder).await?;
+        Ok(ReminderDto::new(reminder, &todo))
+    }
+
+    async fn find_by_todo_id(&self, todo_id: i64) -> Result<Vec<ReminderDto>, UseCaseError> {
+        let todo = find_existing(&self.todo_repository, todo_id).await?;
+        let reminders = self.reminder_repository.find_by_todo_id(todo_id).await?;
+        Ok(reminders
+            .into_iter()
+            .map(|reminder| ReminderDto::new(reminder, &todo))
+            .collect())
+    }
+
+    async fn delete(&self, reminder_id: i64) -> Result<i64, UseCaseError> {
+        if self
+            .reminder_repository
+            .find_by_id(reminder_id)
+            .await?
+            .is_none()
+        {
+            return Err(UseCaseError::NotFound {
+                entity_type: "reminder".to_string(),
+                entity_id: reminder_id,
+            });
+        }
+        self.reminder_repository.delete(reminder_id).await?;
+        Ok(reminder_id)
+    }
+}
+
+#[async_trait]
+impl<TR, RR, N> ReminderDispatchUseCase for ReminderInteractor<TR, RR, N>
+where
+    TR: TodoRepository,
+    RR: ReminderRepository,
+    N: Notifier,
+{
+    async fn dispatch_due(&self, now: DateTime<Utc>) -> Result<usize, UseCaseError> {
+        let reminders = self
+            .reminder_repository
+            .claim_due(now, now + self.lease, self.batch_size)
+            .await?;
+
+        let mut fired = 0;
+        for reminder in reminders {
+            // The todo can disappear between the claim and this lookup; its reminders go with it.
+            let todo = match self.todo_repository.find_by_id(reminder.todo_id).await? {
+                Some(todo) => todo,
+                None => continue,
+            };
+            match self.notifier.notify(&reminder, &todo).await {
+                Ok(()) => {
+                    self.reminder_repository
+                        .mark_fired(reminder.id, now)
+                        .await?;
+                    fired += 1;
+                }
+                Err(error) => {
+                    let retry_at = Reminder::next_retry(reminder.attempts + 1, now);
+                    self.reminder_repository
+                        .mark_failed(reminder.id, &error.to_string(), retry_at, now)
+                        .await?;
+                }
+            }
+        }
+        Ok(fired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interactor::todo::tests::MockTodoRepository;
+    use domain::{entity::todo::Todo, error::DomainError};
+    use std::sync::{Arc, Mutex};
+
+    /// A reminder with the instant its lease expires.
+    type LeasedReminder = (Reminder, Option<DateTime<Utc>>);
+
+    #[derive(Debug, Clone, Default)]
+    struct MockReminderRepository {
+        reminders: Arc<Mutex<Vec<LeasedReminder>>>,
+    }
+
+    #[async_trait]
+    impl ReminderRepository for MockReminderRepository {
+        async fn create(&self, reminder: &Reminder) -> Result<i64, DomainError> {
+            let mut reminders = self.reminders.lock().unwrap();
+            let new_id = reminders.len() as i64 + 1;
+            reminders.push((
+                Reminder {
+                    id: new_id,
+                    ..reminder.clone()
+                },
+                None,
+            ));
+            Ok(new_id)
+        }
+
+        async fn find_by_id(&self, id: i64) -> Result<Option<Reminder>, DomainError> {
+            let reminders = self.reminders.lock().unwrap();
+            Ok(reminders
+                .iter()
+                .find(|(reminder, _)| reminder.id == id)
+                .map(|(reminder, _)| reminder.clone()))
+        }
+
+        async fn find_by_todo_id(&self, todo_id: i64) -> Result<Vec<Reminder>, DomainError> {
+            let reminders = self.reminders.lock().unwrap();
+            Ok(reminders
+                .iter()
+                .filter(|(reminder, _)| reminder.todo_id == todo_id)
+                .map(|(reminder, _)| reminder.clone())
+                .collect())
+        }
+
+        async fn delete(&self, id: i64) -> Result<(), DomainError> {
+            let mut reminders = self.reminders.lock().unwrap();
+            reminders.retain(|(reminder, _)| reminder.id != id);
+            Ok(())
+        }
+
+        async fn claim_due(
+            &self,
+            now: DateTime<Utc>,
+            lease_until: DateTime<Utc>,
+            limit: i64,
+        ) -> Result<Vec<Reminder>, DomainError> {
+            // The tests only use absolute reminders, so the todo's due date is irrelevant here.
+            let todo = Todo::default();
+            let mut reminders = self.reminders.lock().unwrap();
+            let mut claimed = Vec::new();
+            for (reminder, claimed_until) in reminders.iter_mut() {
+                let is_due = matches!(reminder.fire_at(&todo), Some(at) if at <= now);
+                let is_free = !matches!(claimed_until, Some(until) if *until > now);
+                if reminder.fired_at.is_none()
+                    && reminder.failed_at.is_none()
+                    && is_due
+                    && is_free
+                    && claimed.len() < limit as usize
+                {
+                    *claimed_until = Some(lease_until);
+                    claimed.push(reminder.clone());
+                }
+            }
+            Ok(claimed)
+        }
+
+        async fn mark_fired(&self, id: i64, fired_at: DateTime<Utc>) -> Result<(), DomainError> {
+            let mut reminders = self.reminders.lock().unwrap();
+            for (reminder, _) in reminders.iter_mut().filter(|(r, _)| r.id == id) {
+                reminder.fired_at = Some(fired_at);
+            }
+            Ok(())
+        }
+
+        async fn mark_failed(
+            &self,
+            id: i64,
+            error: &str,
+            retry_at: Option<DateTime<Utc>>,
+            now: DateTime<Utc>,
+        ) -> Result<(), DomainError> {
+            let mut reminders = self.reminders.lock().unwrap();
+            for (reminder, claimed_until) in reminders.iter_mut().filter(|(r, _)| r.id == id) {
+                reminder.attempts += 1;
+                reminder.last_error = Some(error.to_string());
+                match retry_at {
+                    Some(retry_at) => *claimed_until = Some(retry_at),
+                    None => reminder.failed_at = Some(now),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct MockNotifier {
+        delivered: Arc<Mutex<Vec<i64>>>,
+        failing: Arc<Mutex<bool>>,
+    }
+
+    #[async_trait]
+    impl Notifier for MockNotifier {
+        async fn notify(&self, reminder: &Reminder, _todo: &Todo) -> Result<(), DomainError> {
+            if *self.failing.lock().unwrap() {
+                return Err(DomainError::Unexpected("endpoint unavailable".to_string()));
+            }
+            self.delivered.lock().unwrap().push(reminder.id);
+            Ok(())
+        }
+    }
+
+    fn instant(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_validates_trigger() {
+        let interactor = ReminderInteractor::new(
+            MockTodoRepository::new(),
+            MockReminderRepository::default(),
+            MockNotifier::default(),
+        );
+
+        // Todo 1 has no due date, so a relative reminder is rejected.
+        let relative = CreateReminderDto {
+            todo_id: 1,
+            remind_at: None,
+            offset_seconds: Some(60),
+        };
+        match interactor.create(relative).await {
+            Err(UseCaseError::Validation(_)) => {}
+            _ => panic!("expected a validation error"),
+        }
+
+        let both = CreateReminderDto {
+            todo_id: 1,
+            remind_at: Some(instant(1_000)),
+            offset_seconds: Some(60),
+        };
+        match interactor.create(both).await {
+            Err(UseCaseError::Validation(_)) => {}
+            _ => panic!("expected a validation error"),
+        }
+
+        let missing_todo = CreateReminderDto {
+            todo_id: 42,
+            remind_at: Some(instant(1_000)),
+            offset_seconds: None,
+        };
+        match interactor.create(missing_todo).await {
+            Err(UseCaseError::NotFound { entity_id, .. }) => assert_eq!(entity_id, 42),
+            _ => panic!("expected not found"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_fires_once_and_retries_failures() {
+        let reminder_repository = MockReminderRepository::default();
+        let notifier = MockNotifier::default();
+        let interactor = ReminderInteractor::new(
+            MockTodoRepository::new(),
+            reminder_repository.clone(),
+            notifier.clone(),
+        );
+
+        let created = interactor
+            .create(CreateReminderDto {
+                todo_id: 1,
+                remind_at: Some(instant(1_000)),
+                offset_seconds: None,
+            })
+            .await;
+        let reminder_id = match created {
+            Ok(reminder) => {
+                assert_eq!(reminder.fire_at, Some(instant(1_000)));
+                reminder.id
+            }
+            Err(_) => panic!("failed to create reminder"),
+        };
+
+        // Not due yet.
+        assert!(matches!(interactor.dispatch_due(instant(999)).await, Ok(0)));
+
+        // A failed delivery is retried only after the backoff.
+        *notifier.failing.lock().unwrap() = true;
+        assert!(matches!(
+            interactor.dispatch_due(instant(1_000)).await,
+            Ok(0)
+        ));
+        *notifier.failing.lock().unwrap() = false;
+        assert!(matches!(
+            interactor.dispatch_due(instant(1_010)).await,
+            Ok(0)
+        ));
+        assert!(matches!(
+            interactor.dispatch_due(instant(1_030)).await,
+            Ok(1)
+        ));
+
+        // Once fired it is never delivered again.
+        assert!(matches!(
+            interactor.dispatch_due(instant(5_000)).await,
+            Ok(0)
+        ));
+        assert_eq!(*notifier.delivered.lock().unwrap(), vec![reminder_id]);
+
+        match reminder_repository.find_by_id(reminder_id).await {
+            Ok(Some(reminder)) => {
+                assert_eq!(reminder.attempts, 1);
+                assert_eq!(reminder.fired_at, Some(instant(1_030)));
+            }
+            _ => panic!("reminder disappeared"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_gives_up_after_max_attempts() {
+        let reminder_repository = MockReminderRepository::default();
+        let notifier = MockNotifier::default();
+        let interactor = ReminderInteractor::new(
+            MockTodoRepository::new(),
+            reminder_repository.clone(),
+            notifier.clone(),
+        );
+        let reminder_id = match interactor
+            .create(CreateReminderDto {
+                todo_id: 1,
+                remind_at: Some(instant(1_000)),
+                offset_seconds: None,
+            })
+            .await
+        {
+            Ok(reminder) => reminder.id,
+            Err(_) => panic!("failed to create reminder"),
+        };
+
+        *notifier.failing.lock().unwrap() = true;
+        let mut now = instant(1_000);
+        for _ in 0..Reminder::MAX_ATTEMPTS + 2 {
+            assert!(matches!(interactor.dispatch_due(now).await, Ok(0)));
+            now += Duration::hours(1);
+        }
+        // Once abandoned it is not delivered, even after the endpoint recovers.
+        *notifier.failing.lock().unwrap() = false;
+        assert!(matches!(interactor.dispatch_due(now).await, Ok(0)));
+        assert!(notifier.delivered.lock().unwrap().is_empty());
+
+        match interactor.find_by_todo_id(1).await {
+            Ok(reminders) => {
+                assert_eq!(reminders[0].id, reminder_id);
+                assert_eq!(reminders[0].attempts, Reminder::MAX_ATTEMPTS);
+                assert_eq!(
+                    reminders[0].failed_at,
+                    Some(instant(1_000) + Duration::hours(Reminder::MAX_ATTEMPTS - 1))
+                );
+                assert_eq!(reminders[0].fired_at, None);
+            }
+            Err(_) => panic!("failed to fetch reminders"),
+        }
+    }
+}
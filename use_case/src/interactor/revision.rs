@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use domain::{
+    entity::revision::TodoRevision, repository::revision_repository::RevisionRepository,
+    unit_of_work::UnitOfWork,
+};
+
+use crate::{
+    dto::{
+        audit::RequestContext,
+        revision::{RevisionDiffDto, TodoRevisionDto},
+        todo::TodoDto,
+    },
+    error::UseCaseError,
+    event_bus::EventBus,
+    interactor::todo::update_todo,
+    traits::revision::RevisionUseCase,
+};
+
+#[derive(Debug, Clone)]
+pub struct RevisionInteractor<RR, UW> {
+    revision_repository: RR,
+    unit_of_work: UW,
+    event_bus: EventBus,
+}
+
+impl<RR, UW> RevisionInteractor<RR, UW> {
+    pub fn new(revision_repository: RR, unit_of_work: UW) -> Self {
+        Self {
+            revision_repository,
+            unit_of_work,
+            event_bus: EventBus::default(),
+        }
+    }
+
+    /// Sets the bus the events of restores are dispatched to.
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+}
+
+#[async_trait]
+impl<RR, UW> RevisionUseCase for RevisionInteractor<RR, UW>
+where
+    RR: RevisionRepository,
+    UW: UnitOfWork,
+{
+    async fn find_revisions(&self, todo_id: i64) -> Result<Vec<TodoRevisionDto>, UseCaseError> {
+        let revisions = self.revision_repository.find_by_todo_id(todo_id).await?;
+        if revisions.is_empty() {
+            return Err(UseCaseError::NotFound {
+                entity_type: "todo".to_string(),
+                entity_id: todo_id,
+            });
+        }
+        Ok(revisions
+            .into_iter()
+            .map(|revision| revision.into())
+            .collect())
+    }
+
+    async fn diff_revisions(
+        &self,
+        todo_id: i64,
+        from: i64,
+        to: i64,
+    ) -> Result<RevisionDiffDto, UseCaseError> {
+        let before = find_existing(&self.revision_repository, todo_id, from).await?;
+        let after = find_existing(&self.revision_repository, todo_id, to).await?;
+        Ok(RevisionDiffDto {
+            todo_id,
+            from,
+            to,
+            changes: before
+                .diff(&after)
+                .into_iter()
+                .map(|change| change.into())
+                .collect(),
+        })
+    }
+
+    async fn restore_revision(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+        revision: i64,
+    ) -> Result<TodoDto, UseCaseError> {
+        let revision = find_existing(&self.revision_repository, todo_id, revision).await?;
+        update_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            TodoDto::from(revision.todo).into(),
+            None,
+        )
+        .await
+    }
+}
+
+async fn find_existing<RR: RevisionRepository>(
+    revision_repository: &RR,
+    todo_id: i64,
+    revision: i64,
+) -> Result<TodoRevision, UseCaseError> {
+    match revision_repository.find(todo_id, revision).await? {
+        Some(revision) => Ok(revision),
+        None => Err(UseCaseError::NotFound {
+            entity_type: "revision".to_string(),
+            entity_id: revision,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dto::todo::CreateTodoDto,
+        interactor::todo::{
+            tests::{ctx, MockTodoRepository},
+            MutationInteractor,
+        },
+        traits::todo::MutationUseCase,
+    };
+    use domain::{entity::todo::Priority, repository::todo_repository::TodoRepository};
+
+    #[tokio::test]
+    async fn test_restore_revision() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let revision_interactor =
+            RevisionInteractor::new(todo_repository.clone(), todo_repository.clone());
+        let todo_data = CreateTodoDto {
+            title: "buy milk".to_string(),
+            due_at: None,
+            priority: Priority::Medium,
+            recurrence: None,
+        };
+        let mut todo = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        todo.title = Some("oops".to_string());
+        todo.priority = Priority::High;
+        assert!(mutation_interactor
+            .update(ctx(), todo.clone().into(), None)
+            .await
+            .is_ok());
+
+        match revision_interactor.diff_revisions(todo.id, 1, 2).await {
+            Ok(diff) => assert_eq!(
+                diff.changes
+                    .iter()
+                    .map(|change| change.field.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["title", "priority"]
+            ),
+            Err(_) => panic!(),
+        }
+        match revision_interactor
+            .restore_revision(ctx(), todo.id, 1)
+            .await
+        {
+            Ok(restored) => {
+                assert_eq!(restored.title, Some("buy milk".to_string()));
+                assert_eq!(restored.priority, Priority::Medium);
+            }
+            Err(_) => panic!(),
+        }
+        let restored = todo_repository.find_by_id(todo.id).await.ok().flatten();
+        assert_eq!(
+            restored.and_then(|todo| todo.title),
+            Some("buy milk".to_string())
+        );
+        match revision_interactor.find_revisions(todo.id).await {
+            Ok(revisions) => assert_eq!(
+                revisions
+                    .iter()
+                    .map(|revision| revision.revision)
+                    .collect::<Vec<_>>(),
+                vec![1, 2, 3]
+            ),
+            Err(_) => panic!(),
+        }
+        assert!(matches!(
+            revision_interactor
+                .restore_revision(ctx(), todo.id, 9)
+                .await,
+            Err(UseCaseError::NotFound { .. })
+        ));
+        assert!(matches!(
+            revision_interactor.find_revisions(42).await,
+            Err(UseCaseError::NotFound { .. })
+        ));
+    }
+}
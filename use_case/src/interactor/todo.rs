@@ -1,54 +1,177 @@
 use async_trait::async_trait;
-use domain::{entity::todo::Todo, repository::todo_repository::TodoRepository};
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use chrono_tz::Tz;
+use domain::{
+    entity::{
+        audit::{AuditAction, AuditEntry},
+        idempotency::IdempotencyRecord,
+        reminder::{Reminder, ReminderTrigger},
+        todo::{Priority, Todo},
+    },
+    event::TodoEvent,
+    repository::todo_repository::TodoRepository,
+    unit_of_work::{Transaction, UnitOfWork},
+};
 
 use crate::{
-    dto::todo::{CreateTodoDto, TodoDto},
+    dto::{
+        audit::RequestContext,
+        batch::{
+            BatchItemResultDto, BatchMode, BatchOperationDto, BatchOutcomeDto, BatchResultDto,
+            MAX_BATCH_SIZE,
+        },
+        todo::{CompletedTodoDto, CreateTodoDto, TodoDto, UpdateTodoDto},
+    },
     error::UseCaseError,
+    event_bus::EventBus,
+    time_window::{day_window, parse_timezone, week_window},
     traits::todo::{MutationUseCase, QueryUseCase, TodoUseCase},
 };
 
+const DEFAULT_IDEMPOTENCY_TTL_HOURS: i64 = 24;
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
 #[derive(Debug, Clone)]
-pub struct MutationInteractor<TR> {
-    todo_repository: TR,
+pub struct MutationInteractor<UW> {
+    unit_of_work: UW,
+    timezone: Tz,
+    event_bus: EventBus,
+    idempotency_ttl: Duration,
 }
 
-impl<TR> MutationInteractor<TR> {
-    pub fn new(todo_repository: TR) -> Self {
-        Self { todo_repository }
+impl<UW> MutationInteractor<UW> {
+    pub fn new(unit_of_work: UW) -> Self {
+        Self {
+            unit_of_work,
+            timezone: Tz::UTC,
+            event_bus: EventBus::default(),
+            idempotency_ttl: Duration::hours(DEFAULT_IDEMPOTENCY_TTL_HOURS),
+        }
+    }
+
+    /// Sets how long an idempotency key replays the todo created under it.
+    pub fn with_idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    /// Sets the bus committed todo events are dispatched to.
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+
+    /// Sets the timezone recurrence rules are evaluated in.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
     }
 }
 
 #[async_trait]
-impl<TR> MutationUseCase for MutationInteractor<TR>
+impl<UW> MutationUseCase for MutationInteractor<UW>
 where
-    TR: TodoRepository,
+    UW: UnitOfWork,
 {
-    async fn create(&self, todo_data: CreateTodoDto) -> Result<TodoDto, UseCaseError> {
-        let todo = Todo::try_from(todo_data)?;
-        self.todo_repository.create(&todo).await?;
-        Ok(todo.into())
+    async fn create(
+        &self,
+        ctx: RequestContext,
+        todo_data: CreateTodoDto,
+        idempotency_key: Option<String>,
+    ) -> Result<TodoDto, UseCaseError> {
+        create_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_data,
+            idempotency_key.map(|key| (key, self.idempotency_ttl)),
+        )
+        .await
+    }
+
+    async fn update(
+        &self,
+        ctx: RequestContext,
+        todo_data: UpdateTodoDto,
+        expected_version: Option<i64>,
+    ) -> Result<TodoDto, UseCaseError> {
+        update_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_data,
+            expected_version,
+        )
+        .await
+    }
+
+    async fn delete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+        expected_version: Option<i64>,
+    ) -> Result<i64, UseCaseError> {
+        delete_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_id,
+            expected_version,
+        )
+        .await
     }
 
-    async fn update(&self, todo_data: TodoDto) -> Result<TodoDto, UseCaseError> {
-        let todo = Todo::try_from(todo_data)?;
-        self.todo_repository.update(&todo).await?;
-        Ok(todo.into())
+    async fn complete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+    ) -> Result<CompletedTodoDto, UseCaseError> {
+        complete_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_id,
+            self.timezone,
+        )
+        .await
     }
 
-    async fn delete(&self, todo_id: i64) -> Result<i64, UseCaseError> {
-        self.todo_repository.delete(todo_id).await?;
-        Ok(todo_id)
+    async fn batch(
+        &self,
+        ctx: RequestContext,
+        operations: Vec<BatchOperationDto>,
+        mode: BatchMode,
+    ) -> Result<BatchResultDto, UseCaseError> {
+        run_batch(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            operations,
+            mode,
+            self.timezone,
+        )
+        .await
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct QueryInteractor<TR> {
     todo_repository: TR,
+    timezone: Tz,
 }
 
 impl<TR> QueryInteractor<TR> {
     pub fn new(todo_repository: TR) -> Self {
-        Self { todo_repository }
+        Self {
+            todo_repository,
+            timezone: Tz::UTC,
+        }
+    }
+
+    /// Sets the timezone used by the smart views when the caller does not pass one.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
     }
 }
 
@@ -78,39 +201,156 @@ where
             Err(e) => Err(UseCaseError::from(e)),
         }
     }
+
+    async fn find_by_ids(&self, todo_ids: Vec<i64>) -> Result<Vec<TodoDto>, UseCaseError> {
+        let todos = self.todo_repository.find_by_ids(&todo_ids).await?;
+        Ok(todos.into_iter().map(|todo| todo.into()).collect())
+    }
+
+    async fn find_overdue(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_overdue(&self.todo_repository).await
+    }
+
+    async fn find_due_today(&self, timezone: Option<String>) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_due_today(&self.todo_repository, timezone, self.timezone).await
+    }
+
+    async fn find_due_this_week(
+        &self,
+        timezone: Option<String>,
+    ) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_due_this_week(&self.todo_repository, timezone, self.timezone).await
+    }
+
+    async fn find_high_priority(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_high_priority(&self.todo_repository).await
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct TodoInteractor<TR> {
+pub struct TodoInteractor<TR, UW> {
     todo_repository: TR,
+    unit_of_work: UW,
+    timezone: Tz,
+    event_bus: EventBus,
+    idempotency_ttl: Duration,
 }
 
-impl<TR> TodoInteractor<TR> {
-    pub fn new(todo_repository: TR) -> Self {
-        Self { todo_repository }
+impl<TR, UW> TodoInteractor<TR, UW> {
+    pub fn new(todo_repository: TR, unit_of_work: UW) -> Self {
+        Self {
+            todo_repository,
+            unit_of_work,
+            timezone: Tz::UTC,
+            event_bus: EventBus::default(),
+            idempotency_ttl: Duration::hours(DEFAULT_IDEMPOTENCY_TTL_HOURS),
+        }
+    }
+
+    /// Sets how long an idempotency key replays the todo created under it.
+    pub fn with_idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    /// Sets the bus committed todo events are dispatched to.
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+
+    /// Sets the timezone used by the smart views when the caller does not pass one,
+    /// and that recurrence rules are evaluated in.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
     }
 }
 
 #[async_trait]
-impl<TR> TodoUseCase for TodoInteractor<TR>
+impl<TR, UW> TodoUseCase for TodoInteractor<TR, UW>
 where
     TR: TodoRepository,
+    UW: UnitOfWork,
 {
-    async fn create(&self, todo_data: CreateTodoDto) -> Result<TodoDto, UseCaseError> {
-        let todo = Todo::try_from(todo_data)?;
-        self.todo_repository.create(&todo).await?;
-        Ok(todo.into())
+    async fn create(
+        &self,
+        ctx: RequestContext,
+        todo_data: CreateTodoDto,
+        idempotency_key: Option<String>,
+    ) -> Result<TodoDto, UseCaseError> {
+        create_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_data,
+            idempotency_key.map(|key| (key, self.idempotency_ttl)),
+        )
+        .await
+    }
+
+    async fn update(
+        &self,
+        ctx: RequestContext,
+        todo_data: UpdateTodoDto,
+        expected_version: Option<i64>,
+    ) -> Result<TodoDto, UseCaseError> {
+        update_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_data,
+            expected_version,
+        )
+        .await
     }
 
-    async fn update(&self, todo_data: TodoDto) -> Result<TodoDto, UseCaseError> {
-        let todo = Todo::try_from(todo_data)?;
-        self.todo_repository.update(&todo).await?;
-        Ok(todo.into())
+    async fn delete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+        expected_version: Option<i64>,
+    ) -> Result<i64, UseCaseError> {
+        delete_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_id,
+            expected_version,
+        )
+        .await
     }
 
-    async fn delete(&self, todo_id: i64) -> Result<i64, UseCaseError> {
-        self.todo_repository.delete(todo_id).await?;
-        Ok(todo_id)
+    async fn complete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+    ) -> Result<CompletedTodoDto, UseCaseError> {
+        complete_todo(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            todo_id,
+            self.timezone,
+        )
+        .await
+    }
+
+    async fn batch(
+        &self,
+        ctx: RequestContext,
+        operations: Vec<BatchOperationDto>,
+        mode: BatchMode,
+    ) -> Result<BatchResultDto, UseCaseError> {
+        run_batch(
+            &self.unit_of_work,
+            &self.event_bus,
+            &ctx,
+            operations,
+            mode,
+            self.timezone,
+        )
+        .await
     }
 
     async fn find_all(&self) -> Result<Vec<TodoDto>, UseCaseError> {
@@ -134,17 +374,464 @@ where
             Err(e) => Err(UseCaseError::from(e)),
         }
     }
+
+    async fn find_by_id_at(
+        &self,
+        todo_id: i64,
+        at: DateTime<Utc>,
+    ) -> Result<Option<TodoDto>, UseCaseError> {
+        let todo = self.todo_repository.find_by_id_at(todo_id, at).await?;
+        Ok(todo.map(TodoDto::from))
+    }
+
+    async fn find_overdue(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_overdue(&self.todo_repository).await
+    }
+
+    async fn find_due_today(&self, timezone: Option<String>) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_due_today(&self.todo_repository, timezone, self.timezone).await
+    }
+
+    async fn find_due_this_week(
+        &self,
+        timezone: Option<String>,
+    ) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_due_this_week(&self.todo_repository, timezone, self.timezone).await
+    }
+
+    async fn find_high_priority(&self) -> Result<Vec<TodoDto>, UseCaseError> {
+        find_high_priority(&self.todo_repository).await
+    }
+}
+
+async fn create_todo<UW: UnitOfWork>(
+    unit_of_work: &UW,
+    event_bus: &EventBus,
+    ctx: &RequestContext,
+    todo_data: CreateTodoDto,
+    idempotency: Option<(String, Duration)>,
+) -> Result<TodoDto, UseCaseError> {
+    let mut tx = unit_of_work.begin().await?;
+    match create_in(tx.as_mut(), ctx, todo_data.clone(), idempotency.clone()).await? {
+        Some(todo) => {
+            commit(tx, event_bus).await?;
+            Ok(todo)
+        }
+        None => {
+            // a concurrent request took the key first: dropping `tx` rolls back the todo
+            // created here, and the retry replays that request's todo or rejects the body
+            drop(tx);
+            let mut tx = unit_of_work.begin().await?;
+            match create_in(tx.as_mut(), ctx, todo_data, idempotency).await? {
+                Some(todo) => {
+                    commit(tx, event_bus).await?;
+                    Ok(todo)
+                }
+                None => Err(UseCaseError::Unexpected(
+                    "the idempotency key was taken twice".to_string(),
+                )),
+            }
+        }
+    }
+}
+
+pub(crate) async fn update_todo<UW: UnitOfWork>(
+    unit_of_work: &UW,
+    event_bus: &EventBus,
+    ctx: &RequestContext,
+    todo_data: UpdateTodoDto,
+    expected_version: Option<i64>,
+) -> Result<TodoDto, UseCaseError> {
+    let mut tx = unit_of_work.begin().await?;
+    let todo = update_in(tx.as_mut(), ctx, todo_data, expected_version).await?;
+    commit(tx, event_bus).await?;
+    Ok(todo)
+}
+
+async fn delete_todo<UW: UnitOfWork>(
+    unit_of_work: &UW,
+    event_bus: &EventBus,
+    ctx: &RequestContext,
+    todo_id: i64,
+    expected_version: Option<i64>,
+) -> Result<i64, UseCaseError> {
+    let mut tx = unit_of_work.begin().await?;
+    let todo_id = delete_in(tx.as_mut(), ctx, todo_id, expected_version).await?;
+    commit(tx, event_bus).await?;
+    Ok(todo_id)
+}
+
+async fn complete_todo<UW: UnitOfWork>(
+    unit_of_work: &UW,
+    event_bus: &EventBus,
+    ctx: &RequestContext,
+    todo_id: i64,
+    timezone: Tz,
+) -> Result<CompletedTodoDto, UseCaseError> {
+    let mut tx = unit_of_work.begin().await?;
+    let completed = complete_in(tx.as_mut(), ctx, todo_id, timezone).await?;
+    commit(tx, event_bus).await?;
+    Ok(completed)
+}
+
+/// Runs `operations` in order in one transaction; see [`BatchMode`] for what a failing
+/// operation does to the others.
+async fn run_batch<UW: UnitOfWork>(
+    unit_of_work: &UW,
+    event_bus: &EventBus,
+    ctx: &RequestContext,
+    operations: Vec<BatchOperationDto>,
+    mode: BatchMode,
+    timezone: Tz,
+) -> Result<BatchResultDto, UseCaseError> {
+    if operations.len() > MAX_BATCH_SIZE {
+        return Err(UseCaseError::Validation(format!(
+            "a batch holds at most {} operations",
+            MAX_BATCH_SIZE
+        )));
+    }
+    let mut tx = unit_of_work.begin().await?;
+    let mut items = Vec::with_capacity(operations.len());
+    for (index, operation) in operations.into_iter().enumerate() {
+        tx.savepoint().await?;
+        let result = match operation {
+            BatchOperationDto::Create(todo_data) => create_in(tx.as_mut(), ctx, todo_data, None)
+                .await
+                .map(|todo| {
+                    BatchOutcomeDto::Created(todo.expect("a todo created without a key is new"))
+                }),
+            BatchOperationDto::Update {
+                todo,
+                expected_version,
+            } => update_in(tx.as_mut(), ctx, todo, expected_version)
+                .await
+                .map(BatchOutcomeDto::Updated),
+            BatchOperationDto::Delete(todo_id) => delete_in(tx.as_mut(), ctx, todo_id, None)
+                .await
+                .map(BatchOutcomeDto::Deleted),
+            BatchOperationDto::Complete(todo_id) => {
+                complete_in(tx.as_mut(), ctx, todo_id, timezone)
+                    .await
+                    .map(BatchOutcomeDto::Completed)
+            }
+        };
+        match result {
+            Err(error) if !is_item_error(&error) => return Err(error),
+            Err(error) if mode == BatchMode::Atomic => {
+                items.push(BatchItemResultDto {
+                    index,
+                    result: Err(error),
+                });
+                // dropping the transaction rolls back the operations that succeeded
+                return Ok(BatchResultDto {
+                    committed: false,
+                    items,
+                });
+            }
+            Err(error) => {
+                // the operation may have written before it was rejected
+                tx.rollback_to_savepoint().await?;
+                items.push(BatchItemResultDto {
+                    index,
+                    result: Err(error),
+                });
+            }
+            result => {
+                tx.release_savepoint().await?;
+                items.push(BatchItemResultDto { index, result });
+            }
+        }
+    }
+    commit(tx, event_bus).await?;
+    Ok(BatchResultDto {
+        committed: true,
+        items,
+    })
+}
+
+/// Whether `error` rejects a single operation of a batch. What the operation wrote before
+/// failing is rolled back to the savepoint taken before it, so the rest of the batch can go
+/// on without it; any other error aborts the batch.
+fn is_item_error(error: &UseCaseError) -> bool {
+    matches!(
+        error,
+        UseCaseError::Validation(_) | UseCaseError::NotFound { .. } | UseCaseError::Conflict { .. }
+    )
+}
+
+/// Creates the todo in `tx`; under an idempotency key seen before, returns the todo created
+/// then instead. Returns `None` when a concurrent request saved the key while this one ran;
+/// `tx` must then be dropped rather than committed.
+pub(crate) async fn create_in(
+    tx: &mut dyn Transaction,
+    ctx: &RequestContext,
+    todo_data: CreateTodoDto,
+    idempotency: Option<(String, Duration)>,
+) -> Result<Option<TodoDto>, UseCaseError> {
+    let fingerprint = todo_data.fingerprint();
+    let mut todo = Todo::try_from(todo_data)?;
+    // timestamps are persisted with second precision
+    let now = Utc::now().trunc_subsecs(0);
+    if let Some((key, _)) = &idempotency {
+        validate_idempotency_key(key)?;
+        if let Some(record) = tx.find_idempotency_record(key, now).await? {
+            if !record.is_expired(now) {
+                if record.fingerprint != fingerprint {
+                    return Err(UseCaseError::IdempotencyKeyReused(key.clone()));
+                }
+                return Ok(Some(record.todo.into()));
+            }
+        }
+    }
+    todo.id = tx.create_todo(&todo).await?;
+    tx.append_revision(&todo).await?;
+    audit(tx, ctx, AuditAction::Create, None, Some(&todo)).await?;
+    if let Some((key, ttl)) = idempotency {
+        let saved = tx
+            .save_idempotency_record(&IdempotencyRecord {
+                key,
+                fingerprint,
+                todo: todo.clone(),
+                created_at: now,
+                expires_at: now + ttl,
+            })
+            .await?;
+        if !saved {
+            return Ok(None);
+        }
+    }
+    tx.record(TodoEvent::TodoCreated { todo: todo.clone() });
+    Ok(Some(todo.into()))
+}
+
+fn validate_idempotency_key(key: &str) -> Result<(), UseCaseError> {
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(UseCaseError::Validation(format!(
+            "idempotency key must be 1 to {} bytes long",
+            MAX_IDEMPOTENCY_KEY_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) async fn update_in(
+    tx: &mut dyn Transaction,
+    ctx: &RequestContext,
+    todo_data: UpdateTodoDto,
+    expected_version: Option<i64>,
+) -> Result<TodoDto, UseCaseError> {
+    let current = find_existing_in(tx, todo_data.id).await?;
+    let mut todo = todo_data.apply(current.clone())?;
+    // the repository compares this against the stored version before writing
+    todo.version = expected_version.unwrap_or(current.version);
+    tx.update_todo(&todo).await?;
+    todo.version += 1;
+    tx.append_revision(&todo).await?;
+    audit(tx, ctx, AuditAction::Update, Some(&current), Some(&todo)).await?;
+    tx.record(TodoEvent::TodoUpdated {
+        before: current,
+        after: todo.clone(),
+    });
+    Ok(todo.into())
+}
+
+async fn delete_in(
+    tx: &mut dyn Transaction,
+    ctx: &RequestContext,
+    todo_id: i64,
+    expected_version: Option<i64>,
+) -> Result<i64, UseCaseError> {
+    // deleting a missing todo is a no-op and raises no event
+    if let Some(todo) = tx.find_todo_by_id(todo_id).await? {
+        // the store checks the version as it deletes, so a concurrent update still conflicts
+        tx.delete_todo(todo_id, expected_version).await?;
+        audit(tx, ctx, AuditAction::Delete, Some(&todo), None).await?;
+        tx.record(TodoEvent::TodoDeleted { todo });
+    }
+    Ok(todo_id)
+}
+
+/// Completes the todo and, for a recurring one, creates the next occurrence carrying over
+/// the reminders relative to its due date.
+pub(crate) async fn complete_in(
+    tx: &mut dyn Transaction,
+    ctx: &RequestContext,
+    todo_id: i64,
+    timezone: Tz,
+) -> Result<CompletedTodoDto, UseCaseError> {
+    let mut todo = find_existing_in(tx, todo_id).await?;
+    let current = todo.clone();
+    // timestamps are persisted with second precision
+    let next = todo.complete(Utc::now().trunc_subsecs(0), &timezone)?;
+    tx.update_todo(&todo).await?;
+    todo.version += 1;
+    tx.append_revision(&todo).await?;
+    audit(tx, ctx, AuditAction::Complete, Some(&current), Some(&todo)).await?;
+    let next = match next {
+        Some(mut next) => {
+            next.id = tx.create_todo(&next).await?;
+            tx.append_revision(&next).await?;
+            audit(tx, ctx, AuditAction::Create, None, Some(&next)).await?;
+            for reminder in tx.find_reminders_by_todo_id(todo.id).await? {
+                if let ReminderTrigger::BeforeDue(_) = reminder.trigger {
+                    let carried = Reminder::new(&next, reminder.trigger)?;
+                    tx.create_reminder(&carried).await?;
+                }
+            }
+            Some(next)
+        }
+        None => None,
+    };
+    tx.record(TodoEvent::TodoCompleted {
+        todo: todo.clone(),
+        next: next.clone(),
+    });
+    Ok(CompletedTodoDto {
+        todo: todo.into(),
+        next: next.map(|next| next.into()),
+    })
+}
+
+/// Appends the audit entry for a change made in `tx`; `before` is `None` for a creation
+/// and `after` is `None` for a deletion.
+pub(crate) async fn audit(
+    tx: &mut dyn Transaction,
+    ctx: &RequestContext,
+    action: AuditAction,
+    before: Option<&Todo>,
+    after: Option<&Todo>,
+) -> Result<(), UseCaseError> {
+    let todo_id = match (after, before) {
+        (Some(todo), _) | (None, Some(todo)) => todo.id,
+        (None, None) => return Ok(()),
+    };
+    let entry = AuditEntry {
+        id: 0,
+        todo_id,
+        action,
+        actor: ctx.actor.clone(),
+        protocol: ctx.protocol,
+        request_id: ctx.request_id.clone(),
+        before: before.cloned(),
+        after: after.cloned(),
+        occurred_at: Utc::now(),
+    };
+    tx.append_audit(&entry).await?;
+    Ok(())
+}
+
+/// Commits `tx` and, once its changes are durable, dispatches the events it recorded.
+pub(crate) async fn commit(
+    tx: Box<dyn Transaction>,
+    event_bus: &EventBus,
+) -> Result<(), UseCaseError> {
+    let events = tx.commit().await?;
+    event_bus.publish(&events).await;
+    Ok(())
+}
+
+async fn find_existing_in(tx: &mut dyn Transaction, todo_id: i64) -> Result<Todo, UseCaseError> {
+    match tx.find_todo_by_id(todo_id).await? {
+        Some(todo) => Ok(todo),
+        None => Err(UseCaseError::NotFound {
+            entity_type: "todo".to_string(),
+            entity_id: todo_id,
+        }),
+    }
+}
+
+pub(crate) async fn find_existing<TR: TodoRepository>(
+    todo_repository: &TR,
+    todo_id: i64,
+) -> Result<Todo, UseCaseError> {
+    match todo_repository.find_by_id(todo_id).await? {
+        Some(todo) => Ok(todo),
+        None => Err(UseCaseError::NotFound {
+            entity_type: "todo".to_string(),
+            entity_id: todo_id,
+        }),
+    }
+}
+
+fn resolve_timezone(timezone: Option<String>, default: Tz) -> Result<Tz, UseCaseError> {
+    match timezone {
+        Some(name) => parse_timezone(&name),
+        None => Ok(default),
+    }
+}
+
+fn into_dtos(todos: Vec<Todo>) -> Vec<TodoDto> {
+    todos.into_iter().map(|todo| todo.into()).collect()
+}
+
+async fn find_overdue<TR: TodoRepository>(
+    todo_repository: &TR,
+) -> Result<Vec<TodoDto>, UseCaseError> {
+    let todos = todo_repository.find_due_before(Utc::now()).await?;
+    Ok(into_dtos(todos))
+}
+
+async fn find_due_today<TR: TodoRepository>(
+    todo_repository: &TR,
+    timezone: Option<String>,
+    default: Tz,
+) -> Result<Vec<TodoDto>, UseCaseError> {
+    let timezone = resolve_timezone(timezone, default)?;
+    let (from, to) = day_window(Utc::now(), timezone);
+    let todos = todo_repository.find_due_between(from, to).await?;
+    Ok(into_dtos(todos))
+}
+
+async fn find_due_this_week<TR: TodoRepository>(
+    todo_repository: &TR,
+    timezone: Option<String>,
+    default: Tz,
+) -> Result<Vec<TodoDto>, UseCaseError> {
+    let timezone = resolve_timezone(timezone, default)?;
+    let (from, to) = week_window(Utc::now(), timezone);
+    let todos = todo_repository.find_due_between(from, to).await?;
+    Ok(into_dtos(todos))
+}
+
+async fn find_high_priority<TR: TodoRepository>(
+    todo_repository: &TR,
+) -> Result<Vec<TodoDto>, UseCaseError> {
+    let todos = todo_repository.find_by_min_priority(Priority::High).await?;
+    Ok(into_dtos(todos))
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::event_bus::tests::RecordingSubscriber;
     use async_trait::async_trait;
+    use chrono::Duration;
+    use domain::{
+        entity::{
+            audit::Protocol, caldav_resource::CalDavResource, revision::TodoRevision,
+            trash::TrashedTodo,
+        },
+        repository::{
+            caldav_resource_repository::CalDavResourceRepository,
+            revision_repository::RevisionRepository, todo_repository::TodoStream,
+            trash_repository::TrashRepository,
+        },
+    };
     use std::sync::{Arc, Mutex};
 
+    pub(crate) fn ctx() -> RequestContext {
+        RequestContext::new("alice", Protocol::Rest, "req-1")
+    }
+
     #[derive(Debug, Clone)]
     pub struct MockTodoRepository {
         todos: Arc<Mutex<Vec<Todo>>>,
+        reminders: Arc<Mutex<Vec<Reminder>>>,
+        pub(crate) audit_log: Arc<Mutex<Vec<AuditEntry>>>,
+        revisions: Arc<Mutex<Vec<TodoRevision>>>,
+        trash: Arc<Mutex<Vec<TrashedTodo>>>,
+        idempotency_records: Arc<Mutex<Vec<IdempotencyRecord>>>,
+        caldav_resources: Arc<Mutex<Vec<CalDavResource>>>,
     }
 
     impl MockTodoRepository {
@@ -152,15 +839,320 @@ mod tests {
             let todos = vec![Todo {
                 id: 1,
                 title: Some("task1".to_string()),
+                ..Default::default()
             }];
             let todos = Arc::new(Mutex::new(todos));
-            Self { todos }
+            let reminders = Arc::new(Mutex::new(Vec::new()));
+            let audit_log = Arc::new(Mutex::new(Vec::new()));
+            let revisions = Arc::new(Mutex::new(Vec::new()));
+            let trash = Arc::new(Mutex::new(Vec::new()));
+            let idempotency_records = Arc::new(Mutex::new(Vec::new()));
+            let caldav_resources = Arc::new(Mutex::new(Vec::new()));
+            Self {
+                todos,
+                reminders,
+                audit_log,
+                revisions,
+                trash,
+                idempotency_records,
+                caldav_resources,
+            }
+        }
+
+        /// A copy of the state that changes independently of it.
+        fn snapshot(&self) -> Self {
+            MockTodoRepository {
+                todos: Arc::new(Mutex::new(self.todos.lock().unwrap().clone())),
+                reminders: Arc::new(Mutex::new(self.reminders.lock().unwrap().clone())),
+                audit_log: Arc::new(Mutex::new(self.audit_log.lock().unwrap().clone())),
+                revisions: Arc::new(Mutex::new(self.revisions.lock().unwrap().clone())),
+                trash: Arc::new(Mutex::new(self.trash.lock().unwrap().clone())),
+                idempotency_records: Arc::new(Mutex::new(
+                    self.idempotency_records.lock().unwrap().clone(),
+                )),
+                caldav_resources: Arc::new(Mutex::new(
+                    self.caldav_resources.lock().unwrap().clone(),
+                )),
+            }
+        }
+    }
+
+    /// Works on a copy of the mock's state and swaps it in on commit.
+    struct MockTransaction {
+        staged: MockTodoRepository,
+        target: MockTodoRepository,
+        events: Vec<TodoEvent>,
+        /// Copies of the staged state and event count at each open savepoint.
+        savepoints: Vec<(MockTodoRepository, usize)>,
+    }
+
+    #[async_trait]
+    impl Transaction for MockTransaction {
+        async fn create_todo(&mut self, todo: &Todo) -> Result<i64, domain::error::DomainError> {
+            self.staged.create(todo).await
+        }
+
+        async fn find_todo_by_id(
+            &mut self,
+            id: i64,
+        ) -> Result<Option<Todo>, domain::error::DomainError> {
+            self.staged.find_by_id(id).await
+        }
+
+        async fn update_todo(&mut self, todo: &Todo) -> Result<(), domain::error::DomainError> {
+            self.staged.update(todo).await
+        }
+
+        async fn delete_todo(
+            &mut self,
+            todo_id: i64,
+            expected_version: Option<i64>,
+        ) -> Result<(), domain::error::DomainError> {
+            if let Some(todo) = self.staged.find_by_id(todo_id).await? {
+                if expected_version.is_some_and(|version| version != todo.version) {
+                    return Err(domain::error::DomainError::Conflict {
+                        entity_type: "todo".to_string(),
+                        entity_id: todo_id,
+                    });
+                }
+            }
+            self.staged.delete(todo_id).await
+        }
+
+        async fn restore_todo(
+            &mut self,
+            todo_id: i64,
+        ) -> Result<Option<Todo>, domain::error::DomainError> {
+            let mut trash = self.staged.trash.lock().unwrap();
+            match trash.iter().position(|trashed| trashed.todo.id == todo_id) {
+                Some(index) => {
+                    let todo = trash.remove(index).todo;
+                    self.staged.todos.lock().unwrap().push(todo.clone());
+                    Ok(Some(todo))
+                }
+                None => Ok(None),
+            }
+        }
+
+        async fn create_reminder(
+            &mut self,
+            reminder: &Reminder,
+        ) -> Result<i64, domain::error::DomainError> {
+            let mut reminders = self.staged.reminders.lock().unwrap();
+            let id = reminders.len() as i64 + 1;
+            reminders.push(Reminder {
+                id,
+                ..reminder.clone()
+            });
+            Ok(id)
+        }
+
+        async fn find_reminders_by_todo_id(
+            &mut self,
+            todo_id: i64,
+        ) -> Result<Vec<Reminder>, domain::error::DomainError> {
+            let reminders = self.staged.reminders.lock().unwrap();
+            Ok(reminders
+                .iter()
+                .filter(|reminder| reminder.todo_id == todo_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn append_revision(
+            &mut self,
+            todo: &Todo,
+        ) -> Result<i64, domain::error::DomainError> {
+            let mut revisions = self.staged.revisions.lock().unwrap();
+            let revision = revisions
+                .iter()
+                .filter(|revision| revision.todo_id == todo.id)
+                .count() as i64
+                + 1;
+            revisions.push(TodoRevision {
+                todo_id: todo.id,
+                revision,
+                todo: todo.clone(),
+                created_at: Utc::now(),
+            });
+            Ok(revision)
+        }
+
+        async fn append_audit(
+            &mut self,
+            entry: &AuditEntry,
+        ) -> Result<i64, domain::error::DomainError> {
+            let mut audit_log = self.staged.audit_log.lock().unwrap();
+            let id = audit_log.len() as i64 + 1;
+            audit_log.push(AuditEntry {
+                id,
+                ..entry.clone()
+            });
+            Ok(id)
+        }
+
+        async fn find_idempotency_record(
+            &mut self,
+            key: &str,
+            now: DateTime<Utc>,
+        ) -> Result<Option<IdempotencyRecord>, domain::error::DomainError> {
+            let mut records = self.staged.idempotency_records.lock().unwrap();
+            records.retain(|stored| !stored.is_expired(now));
+            Ok(records.iter().find(|record| record.key == key).cloned())
+        }
+
+        async fn save_idempotency_record(
+            &mut self,
+            record: &IdempotencyRecord,
+        ) -> Result<bool, domain::error::DomainError> {
+            // a record committed by another transaction holds the key like the primary key
+            let committed = self.target.idempotency_records.lock().unwrap().clone();
+            let mut records = self.staged.idempotency_records.lock().unwrap();
+            let taken = records
+                .iter()
+                .chain(committed.iter())
+                .any(|stored| stored.key == record.key && !stored.is_expired(record.created_at));
+            if taken {
+                return Ok(false);
+            }
+            records.push(record.clone());
+            Ok(true)
+        }
+
+        async fn create_caldav_resource(
+            &mut self,
+            resource: &CalDavResource,
+        ) -> Result<bool, domain::error::DomainError> {
+            let mut resources = self.staged.caldav_resources.lock().unwrap();
+            if resources.iter().any(|saved| saved.name == resource.name) {
+                return Ok(false);
+            }
+            resources.push(resource.clone());
+            Ok(true)
+        }
+
+        async fn savepoint(&mut self) -> Result<(), domain::error::DomainError> {
+            self.savepoints
+                .push((self.staged.snapshot(), self.events.len()));
+            Ok(())
+        }
+
+        async fn release_savepoint(&mut self) -> Result<(), domain::error::DomainError> {
+            self.savepoints.pop();
+            Ok(())
+        }
+
+        async fn rollback_to_savepoint(&mut self) -> Result<(), domain::error::DomainError> {
+            if let Some((staged, recorded)) = self.savepoints.pop() {
+                self.staged = staged;
+                self.events.truncate(recorded);
+            }
+            Ok(())
+        }
+
+        fn record(&mut self, event: TodoEvent) {
+            self.events.push(event);
+        }
+
+        async fn commit(self: Box<Self>) -> Result<Vec<TodoEvent>, domain::error::DomainError> {
+            let todos = self.staged.todos.lock().unwrap().clone();
+            *self.target.todos.lock().unwrap() = todos;
+            let reminders = self.staged.reminders.lock().unwrap().clone();
+            *self.target.reminders.lock().unwrap() = reminders;
+            let audit_log = self.staged.audit_log.lock().unwrap().clone();
+            *self.target.audit_log.lock().unwrap() = audit_log;
+            let revisions = self.staged.revisions.lock().unwrap().clone();
+            *self.target.revisions.lock().unwrap() = revisions;
+            let trash = self.staged.trash.lock().unwrap().clone();
+            *self.target.trash.lock().unwrap() = trash;
+            let idempotency_records = self.staged.idempotency_records.lock().unwrap().clone();
+            *self.target.idempotency_records.lock().unwrap() = idempotency_records;
+            let caldav_resources = self.staged.caldav_resources.lock().unwrap().clone();
+            *self.target.caldav_resources.lock().unwrap() = caldav_resources;
+            Ok(self.events)
+        }
+    }
+
+    #[async_trait]
+    impl UnitOfWork for MockTodoRepository {
+        async fn begin(&self) -> Result<Box<dyn Transaction>, domain::error::DomainError> {
+            let staged = self.snapshot();
+            Ok(Box::new(MockTransaction {
+                staged,
+                target: self.clone(),
+                events: Vec::new(),
+                savepoints: Vec::new(),
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl RevisionRepository for MockTodoRepository {
+        async fn find_by_todo_id(
+            &self,
+            todo_id: i64,
+        ) -> Result<Vec<TodoRevision>, domain::error::DomainError> {
+            let revisions = self.revisions.lock().unwrap();
+            Ok(revisions
+                .iter()
+                .filter(|revision| revision.todo_id == todo_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn find(
+            &self,
+            todo_id: i64,
+            revision: i64,
+        ) -> Result<Option<TodoRevision>, domain::error::DomainError> {
+            let revisions = self.revisions.lock().unwrap();
+            Ok(revisions
+                .iter()
+                .find(|found| found.todo_id == todo_id && found.revision == revision)
+                .cloned())
+        }
+    }
+
+    #[async_trait]
+    impl CalDavResourceRepository for MockTodoRepository {
+        async fn find_by_name(
+            &self,
+            name: &str,
+        ) -> Result<Option<CalDavResource>, domain::error::DomainError> {
+            let resources = self.caldav_resources.lock().unwrap();
+            Ok(resources
+                .iter()
+                .find(|resource| resource.name == name)
+                .cloned())
+        }
+
+        async fn find_all(&self) -> Result<Vec<CalDavResource>, domain::error::DomainError> {
+            Ok(self.caldav_resources.lock().unwrap().clone())
+        }
+    }
+
+    #[async_trait]
+    impl TrashRepository for MockTodoRepository {
+        async fn find_all(&self) -> Result<Vec<TrashedTodo>, domain::error::DomainError> {
+            let mut trash = self.trash.lock().unwrap().clone();
+            trash.reverse();
+            Ok(trash)
+        }
+
+        async fn purge_deleted_before(
+            &self,
+            before: DateTime<Utc>,
+        ) -> Result<u64, domain::error::DomainError> {
+            let mut trash = self.trash.lock().unwrap();
+            let count = trash.len();
+            trash.retain(|trashed| trashed.deleted_at >= before);
+            Ok((count - trash.len()) as u64)
         }
     }
 
     #[async_trait]
     impl TodoRepository for MockTodoRepository {
-        async fn create(&self, new_todo: &Todo) -> Result<(), domain::error::DomainError> {
+        async fn create(&self, new_todo: &Todo) -> Result<i64, domain::error::DomainError> {
             let original_todos = self.todos.clone();
             let mut todos = original_todos.lock().unwrap();
             let length = todos.len();
@@ -171,10 +1163,10 @@ mod tests {
             }
             new_todos.push(Todo {
                 id: new_id,
-                title: new_todo.title.clone(),
+                ..new_todo.clone()
             });
             *todos = new_todos;
-            Ok(())
+            Ok(new_id)
         }
 
         async fn find_all(&self) -> Result<Vec<Todo>, domain::error::DomainError> {
@@ -183,6 +1175,11 @@ mod tests {
             Ok(todos.clone())
         }
 
+        fn stream_all(&self) -> TodoStream {
+            let todos = self.todos.lock().unwrap().clone();
+            Box::pin(futures_util::stream::iter(todos.into_iter().map(Ok)))
+        }
+
         async fn find_by_id(
             &self,
             todo_id: i64,
@@ -197,16 +1194,45 @@ mod tests {
             Ok(None)
         }
 
+        /// Keeps no history: a todo is as it is now at any time.
+        async fn find_by_id_at(
+            &self,
+            todo_id: i64,
+            _at: DateTime<Utc>,
+        ) -> Result<Option<Todo>, domain::error::DomainError> {
+            self.find_by_id(todo_id).await
+        }
+
+        async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<Todo>, domain::error::DomainError> {
+            let todos = self.todos.lock().unwrap();
+            let mut found: Vec<Todo> = todos
+                .iter()
+                .filter(|todo| ids.contains(&todo.id))
+                .cloned()
+                .collect();
+            found.sort_by_key(|todo| todo.id);
+            Ok(found)
+        }
+
         async fn update(&self, new_todo: &Todo) -> Result<(), domain::error::DomainError> {
             let original_todos = self.todos.clone();
             let mut todos = original_todos.lock().unwrap();
             let mut new_todos = Vec::new();
             for todo in todos.iter() {
                 if todo.id == new_todo.id {
+                    if todo.version != new_todo.version {
+                        return Err(domain::error::DomainError::Conflict {
+                            entity_type: "todo".to_string(),
+                            entity_id: todo.id,
+                        });
+                    }
                     new_todos.push(Todo {
                         id: todo.id,
-                        title: new_todo.title.clone(),
+                        version: todo.version + 1,
+                        ..new_todo.clone()
                     });
+                } else {
+                    new_todos.push(todo.clone());
                 }
             }
             *todos = new_todos;
@@ -220,11 +1246,55 @@ mod tests {
             for todo in todos.iter() {
                 if todo.id != todo_id {
                     new_todos.push(todo.clone());
+                } else {
+                    self.trash.lock().unwrap().push(TrashedTodo {
+                        todo: todo.clone(),
+                        deleted_at: Utc::now(),
+                    });
                 }
             }
             *todos = new_todos;
             Ok(())
         }
+
+        async fn find_due_before(
+            &self,
+            before: DateTime<Utc>,
+        ) -> Result<Vec<Todo>, domain::error::DomainError> {
+            let todos = self.todos.lock().unwrap();
+            Ok(todos
+                .iter()
+                .filter(|todo| !todo.is_completed())
+                .filter(|todo| matches!(todo.due_at, Some(due_at) if due_at < before))
+                .cloned()
+                .collect())
+        }
+
+        async fn find_due_between(
+            &self,
+            from: DateTime<Utc>,
+            to: DateTime<Utc>,
+        ) -> Result<Vec<Todo>, domain::error::DomainError> {
+            let todos = self.todos.lock().unwrap();
+            Ok(todos
+                .iter()
+                .filter(|todo| !todo.is_completed())
+                .filter(|todo| matches!(todo.due_at, Some(due_at) if from <= due_at && due_at < to))
+                .cloned()
+                .collect())
+        }
+
+        async fn find_by_min_priority(
+            &self,
+            priority: Priority,
+        ) -> Result<Vec<Todo>, domain::error::DomainError> {
+            let todos = self.todos.lock().unwrap();
+            Ok(todos
+                .iter()
+                .filter(|todo| !todo.is_completed() && todo.priority >= priority)
+                .cloned()
+                .collect())
+        }
     }
 
     #[tokio::test]
@@ -233,8 +1303,11 @@ mod tests {
         let mutation_interactor = MutationInteractor::new(todo_repository.clone());
         let todo_data = CreateTodoDto {
             title: "task2".to_string(),
+            due_at: None,
+            priority: Priority::Medium,
+            recurrence: None,
         };
-        let result = mutation_interactor.create(todo_data).await;
+        let result = mutation_interactor.create(ctx(), todo_data, None).await;
         assert!(result.is_ok());
 
         let query_interactor = QueryInteractor::new(todo_repository);
@@ -253,11 +1326,28 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_delete_expecting_a_version() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let version = todo_repository.todos.lock().unwrap()[0].version;
+
+        let result = mutation_interactor
+            .delete(ctx(), 1, Some(version + 1))
+            .await;
+        assert!(matches!(result, Err(UseCaseError::Conflict { .. })));
+        assert_eq!(todo_repository.todos.lock().unwrap().len(), 1);
+
+        let result = mutation_interactor.delete(ctx(), 1, Some(version)).await;
+        assert!(result.is_ok());
+        assert!(todo_repository.todos.lock().unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_delete() {
         let todo_repository = MockTodoRepository::new();
         let mutation_interactor = MutationInteractor::new(todo_repository.clone());
-        let result = mutation_interactor.delete(1).await;
+        let result = mutation_interactor.delete(ctx(), 1, None).await;
         assert!(result.is_ok());
 
         let query_interactor = QueryInteractor::new(todo_repository);
@@ -285,5 +1375,619 @@ mod tests {
             }
             Err(_) => panic!(),
         }
+        match query_interactor.find_by_ids(vec![2, 1]).await {
+            Ok(todos) => assert_eq!(
+                todos.iter().map(|todo| todo.id).collect::<Vec<_>>(),
+                vec![1]
+            ),
+            Err(_) => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_smart_views() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let now = Utc::now();
+        for (title, due_at, priority) in [
+            ("overdue", Some(now - Duration::days(10)), Priority::Low),
+            ("next month", Some(now + Duration::days(40)), Priority::High),
+            ("someday", None, Priority::Medium),
+        ] {
+            let todo_data = CreateTodoDto {
+                title: title.to_string(),
+                due_at,
+                priority,
+                recurrence: None,
+            };
+            assert!(mutation_interactor
+                .create(ctx(), todo_data, None)
+                .await
+                .is_ok());
+        }
+
+        let query_interactor = QueryInteractor::new(todo_repository);
+        match query_interactor.find_overdue().await {
+            Ok(todos) => {
+                assert_eq!(todos.len(), 1);
+                assert_eq!(todos[0].title, Some("overdue".to_string()));
+            }
+            Err(_) => panic!(),
+        }
+        match query_interactor.find_high_priority().await {
+            Ok(todos) => {
+                assert_eq!(todos.len(), 1);
+                assert_eq!(todos[0].title, Some("next month".to_string()));
+            }
+            Err(_) => panic!(),
+        }
+        match query_interactor
+            .find_due_this_week(Some("Asia/Tokyo".to_string()))
+            .await
+        {
+            Ok(todos) => assert!(todos.is_empty()),
+            Err(_) => panic!(),
+        }
+        let result = query_interactor
+            .find_due_today(Some("Not/AZone".to_string()))
+            .await;
+        assert!(matches!(result, Err(UseCaseError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_complete_recurring() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor =
+            MutationInteractor::new(todo_repository.clone()).with_timezone(chrono_tz::Asia::Tokyo);
+        let due_at = Utc::now();
+        let todo_data = CreateTodoDto {
+            title: "water the plants".to_string(),
+            due_at: Some(due_at),
+            priority: Priority::Medium,
+            recurrence: Some("FREQ=DAILY;INTERVAL=2".to_string()),
+        };
+        let created = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        assert_eq!(created.id, 2);
+
+        match mutation_interactor.complete(ctx(), created.id).await {
+            Ok(completed) => {
+                assert!(completed.todo.completed_at.is_some());
+                let next = completed.next.unwrap();
+                assert_eq!(next.id, 3);
+                assert_eq!(next.due_at, Some(due_at + Duration::days(2)));
+                assert_eq!(next.recurrence, Some("FREQ=DAILY;INTERVAL=2".to_string()));
+                assert!(next.completed_at.is_none());
+            }
+            Err(_) => panic!(),
+        }
+        assert!(matches!(
+            mutation_interactor.complete(ctx(), created.id).await,
+            Err(UseCaseError::Validation(_))
+        ));
+        assert!(matches!(
+            mutation_interactor.complete(ctx(), 42).await,
+            Err(UseCaseError::NotFound { .. })
+        ));
+
+        let todo_data = CreateTodoDto {
+            title: "invalid".to_string(),
+            due_at: None,
+            priority: Priority::Medium,
+            recurrence: Some("FREQ=HOURLY".to_string()),
+        };
+        assert!(matches!(
+            mutation_interactor.create(ctx(), todo_data, None).await,
+            Err(UseCaseError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mutations_dispatch_events_after_commit() {
+        let todo_repository = MockTodoRepository::new();
+        let recorder = Arc::new(RecordingSubscriber::default());
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone())
+            .with_event_bus(EventBus::new().subscribe(recorder.clone()));
+        let todo_data = CreateTodoDto {
+            title: "stretch".to_string(),
+            due_at: Some(Utc::now()),
+            priority: Priority::Medium,
+            recurrence: Some("FREQ=DAILY".to_string()),
+        };
+        let mut created = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        created.title = Some("stretch more".to_string());
+        assert!(mutation_interactor
+            .update(ctx(), created.clone().into(), None)
+            .await
+            .is_ok());
+        assert!(mutation_interactor
+            .complete(ctx(), created.id)
+            .await
+            .is_ok());
+        assert!(mutation_interactor.delete(ctx(), 1, None).await.is_ok());
+        // failed or no-op mutations raise nothing
+        assert!(mutation_interactor.delete(ctx(), 1, None).await.is_ok());
+        assert!(mutation_interactor.complete(ctx(), 42).await.is_err());
+
+        assert_eq!(
+            *recorder.handled.lock().unwrap(),
+            vec![
+                ("TodoCreated", 2),
+                ("TodoUpdated", 2),
+                ("TodoCompleted", 2),
+                ("TodoDeleted", 1)
+            ]
+        );
+        assert_eq!(
+            TodoRepository::find_all(&todo_repository)
+                .await
+                .ok()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_stale_version() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let todo_data = CreateTodoDto {
+            title: "draft".to_string(),
+            due_at: None,
+            priority: Priority::Low,
+            recurrence: None,
+        };
+        let mut created = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        assert_eq!(created.version, Todo::FIRST_VERSION);
+
+        created.title = Some("review".to_string());
+        let updated = match mutation_interactor
+            .update(ctx(), created.clone().into(), Some(created.version))
+            .await
+        {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        assert_eq!(updated.version, created.version + 1);
+
+        created.title = Some("publish".to_string());
+        match mutation_interactor
+            .update(ctx(), created.clone().into(), Some(created.version))
+            .await
+        {
+            Err(UseCaseError::Conflict { entity_id, .. }) => assert_eq!(entity_id, created.id),
+            _ => panic!("stale update was not rejected"),
+        }
+        match todo_repository.find_by_id(created.id).await {
+            Ok(Some(todo)) => {
+                assert_eq!(todo.title, Some("review".to_string()));
+                assert_eq!(todo.version, updated.version);
+            }
+            _ => panic!("failed to fetch todo"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_the_fields_it_leaves_out() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let due_at = Utc::now().trunc_subsecs(0);
+        let todo_data = CreateTodoDto {
+            title: "water the plants".to_string(),
+            due_at: Some(due_at),
+            priority: Priority::High,
+            recurrence: Some("FREQ=WEEKLY".to_string()),
+        };
+        let created = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+
+        let rename = UpdateTodoDto {
+            id: created.id,
+            title: Some("water the cactus".to_string()),
+            ..Default::default()
+        };
+        let updated = match mutation_interactor.update(ctx(), rename, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        assert_eq!(updated.title.as_deref(), Some("water the cactus"));
+        assert_eq!(updated.due_at, Some(due_at));
+        assert_eq!(updated.priority, Priority::High);
+        assert_eq!(updated.recurrence.as_deref(), Some("FREQ=WEEKLY"));
+
+        let clear = UpdateTodoDto {
+            id: created.id,
+            due_at: Some(None),
+            recurrence: Some(None),
+            ..Default::default()
+        };
+        let cleared = match mutation_interactor.update(ctx(), clear, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        assert_eq!(cleared.title.as_deref(), Some("water the cactus"));
+        assert_eq!(cleared.due_at, None);
+        assert_eq!(cleared.priority, Priority::High);
+        assert_eq!(cleared.recurrence, None);
+    }
+
+    #[tokio::test]
+    async fn test_create_replays_idempotency_key() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let todo_data = CreateTodoDto {
+            title: "pay rent".to_string(),
+            due_at: None,
+            priority: Priority::High,
+            recurrence: None,
+        };
+        let key = Some("retry-1".to_string());
+        let created = match mutation_interactor
+            .create(ctx(), todo_data.clone(), key.clone())
+            .await
+        {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        match mutation_interactor
+            .create(ctx(), todo_data.clone(), key.clone())
+            .await
+        {
+            Ok(replayed) => assert_eq!(replayed.id, created.id),
+            Err(_) => panic!("replay failed"),
+        }
+        assert_eq!(
+            TodoRepository::find_all(&todo_repository)
+                .await
+                .ok()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(todo_repository.audit_log.lock().unwrap().len(), 1);
+
+        let other = CreateTodoDto {
+            title: "pay bills".to_string(),
+            ..todo_data.clone()
+        };
+        match mutation_interactor.create(ctx(), other, key.clone()).await {
+            Err(UseCaseError::IdempotencyKeyReused(reused)) => {
+                assert_eq!(Some(reused), key)
+            }
+            _ => panic!("reused key was not rejected"),
+        }
+        assert!(matches!(
+            mutation_interactor
+                .create(ctx(), todo_data.clone(), Some(String::new()))
+                .await,
+            Err(UseCaseError::Validation(_))
+        ));
+
+        // an expired key creates anew
+        let mutation_interactor =
+            MutationInteractor::new(todo_repository.clone()).with_idempotency_ttl(Duration::zero());
+        for _ in 0..2 {
+            assert!(mutation_interactor
+                .create(ctx(), todo_data.clone(), Some("retry-2".to_string()))
+                .await
+                .is_ok());
+        }
+        assert_eq!(
+            TodoRepository::find_all(&todo_repository)
+                .await
+                .ok()
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_replays_a_key_taken_by_a_concurrent_request() {
+        let todo_repository = MockTodoRepository::new();
+        let todo_data = CreateTodoDto {
+            title: "pay rent".to_string(),
+            due_at: None,
+            priority: Priority::High,
+            recurrence: None,
+        };
+        let key = Some(("retry-1".to_string(), Duration::hours(1)));
+
+        // both requests find the key free, and the first one commits first
+        let mut first = todo_repository.begin().await.ok().unwrap();
+        let mut second = todo_repository.begin().await.ok().unwrap();
+        let created = match create_in(first.as_mut(), &ctx(), todo_data.clone(), key.clone()).await
+        {
+            Ok(Some(todo)) => todo,
+            _ => panic!(),
+        };
+        commit(first, &EventBus::new()).await.ok().unwrap();
+        match create_in(second.as_mut(), &ctx(), todo_data.clone(), key.clone()).await {
+            Ok(None) => drop(second),
+            _ => panic!("the second request took the key as well"),
+        }
+
+        // retried, the loser replays the winner's todo
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        match mutation_interactor
+            .create(ctx(), todo_data, Some("retry-1".to_string()))
+            .await
+        {
+            Ok(replayed) => assert_eq!(replayed.id, created.id),
+            Err(_) => panic!("replay failed"),
+        }
+        assert_eq!(todo_repository.todos.lock().unwrap().len(), 2);
+    }
+
+    fn batch_operations() -> Vec<BatchOperationDto> {
+        vec![
+            BatchOperationDto::Create(CreateTodoDto {
+                title: "water plants".to_string(),
+                due_at: None,
+                priority: Priority::Low,
+                recurrence: None,
+            }),
+            BatchOperationDto::Complete(1),
+            BatchOperationDto::Complete(42),
+            BatchOperationDto::Delete(1),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_atomic_batch_rolls_back_on_rejected_operation() {
+        let todo_repository = MockTodoRepository::new();
+        let recorder = Arc::new(RecordingSubscriber::default());
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone())
+            .with_event_bus(EventBus::new().subscribe(recorder.clone()));
+        let result = match mutation_interactor
+            .batch(ctx(), batch_operations(), BatchMode::Atomic)
+            .await
+        {
+            Ok(result) => result,
+            Err(_) => panic!("batch failed"),
+        };
+        assert!(!result.committed);
+        assert_eq!(result.items.len(), 3);
+        match &result.items[2].result {
+            Err(UseCaseError::NotFound { entity_id, .. }) => assert_eq!(*entity_id, 42),
+            _ => panic!("missing todo was not rejected"),
+        }
+        match TodoRepository::find_all(&todo_repository).await {
+            Ok(todos) => {
+                assert_eq!(todos.len(), 1);
+                assert!(todos[0].completed_at.is_none());
+            }
+            Err(_) => panic!("failed to fetch todos"),
+        }
+        assert!(todo_repository.audit_log.lock().unwrap().is_empty());
+        assert!(recorder.handled.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_per_item_batch_skips_rejected_operation() {
+        let todo_repository = MockTodoRepository::new();
+        let recorder = Arc::new(RecordingSubscriber::default());
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone())
+            .with_event_bus(EventBus::new().subscribe(recorder.clone()));
+        let result = match mutation_interactor
+            .batch(ctx(), batch_operations(), BatchMode::PerItem)
+            .await
+        {
+            Ok(result) => result,
+            Err(_) => panic!("batch failed"),
+        };
+        assert!(result.committed);
+        let outcomes = result
+            .items
+            .iter()
+            .map(|item| match &item.result {
+                Ok(BatchOutcomeDto::Created(todo)) => format!("created {}", todo.id),
+                Ok(BatchOutcomeDto::Completed(completed)) => {
+                    format!("completed {}", completed.todo.id)
+                }
+                Ok(BatchOutcomeDto::Deleted(todo_id)) => format!("deleted {}", todo_id),
+                Ok(BatchOutcomeDto::Updated(todo)) => format!("updated {}", todo.id),
+                Err(_) => format!("rejected {}", item.index),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec!["created 2", "completed 1", "rejected 2", "deleted 1"]
+        );
+        match TodoRepository::find_all(&todo_repository).await {
+            Ok(todos) => assert_eq!(
+                todos.iter().map(|todo| todo.id).collect::<Vec<_>>(),
+                vec![2]
+            ),
+            Err(_) => panic!("failed to fetch todos"),
+        }
+        assert_eq!(
+            *recorder.handled.lock().unwrap(),
+            vec![("TodoCreated", 2), ("TodoCompleted", 1), ("TodoDeleted", 1)]
+        );
+
+        let too_many = (0..=MAX_BATCH_SIZE)
+            .map(|_| BatchOperationDto::Delete(42))
+            .collect();
+        assert!(matches!(
+            mutation_interactor
+                .batch(ctx(), too_many, BatchMode::PerItem)
+                .await,
+            Err(UseCaseError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_per_item_batch_undoes_a_complete_rejected_partway() {
+        let todo_repository = MockTodoRepository::new();
+        let recorder = Arc::new(RecordingSubscriber::default());
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone())
+            .with_event_bus(EventBus::new().subscribe(recorder.clone()));
+        let todo_data = CreateTodoDto {
+            title: "standup".to_string(),
+            due_at: Some(Utc::now().trunc_subsecs(0)),
+            priority: Priority::Medium,
+            recurrence: Some("FREQ=DAILY".to_string()),
+        };
+        let created = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        // a stored reminder the next occurrence cannot carry over fails the complete after
+        // it has completed the todo and created the next occurrence
+        todo_repository.reminders.lock().unwrap().push(Reminder {
+            id: 1,
+            todo_id: created.id,
+            trigger: ReminderTrigger::BeforeDue(Duration::minutes(-10)),
+            fired_at: None,
+            failed_at: None,
+            attempts: 0,
+            last_error: None,
+        });
+        let audited = todo_repository.audit_log.lock().unwrap().len();
+        recorder.handled.lock().unwrap().clear();
+
+        let operations = vec![
+            BatchOperationDto::Complete(created.id),
+            BatchOperationDto::Complete(1),
+        ];
+        let result = match mutation_interactor
+            .batch(ctx(), operations, BatchMode::PerItem)
+            .await
+        {
+            Ok(result) => result,
+            Err(_) => panic!("batch failed"),
+        };
+        assert!(result.committed);
+        assert!(matches!(
+            result.items[0].result,
+            Err(UseCaseError::Validation(_))
+        ));
+        assert!(result.items[1].result.is_ok());
+        match TodoRepository::find_all(&todo_repository).await {
+            Ok(todos) => {
+                assert_eq!(
+                    todos.iter().map(|todo| todo.id).collect::<Vec<_>>(),
+                    vec![1, created.id]
+                );
+                assert!(todos[1].completed_at.is_none());
+            }
+            Err(_) => panic!("failed to fetch todos"),
+        }
+        assert_eq!(todo_repository.audit_log.lock().unwrap().len(), audited + 1);
+        assert_eq!(
+            *recorder.handled.lock().unwrap(),
+            vec![("TodoCompleted", 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_complete_carries_relative_reminders_over() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let due_at = Utc::now().trunc_subsecs(0);
+        let todo_data = CreateTodoDto {
+            title: "standup".to_string(),
+            due_at: Some(due_at),
+            priority: Priority::Medium,
+            recurrence: Some("FREQ=DAILY".to_string()),
+        };
+        let created = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        let todo = todo_repository
+            .find_by_id(created.id)
+            .await
+            .ok()
+            .unwrap()
+            .unwrap();
+        for trigger in [
+            ReminderTrigger::BeforeDue(Duration::minutes(10)),
+            ReminderTrigger::At(due_at),
+        ] {
+            let reminder = Reminder::new(&todo, trigger).ok().unwrap();
+            todo_repository.reminders.lock().unwrap().push(reminder);
+        }
+
+        let next = match mutation_interactor.complete(ctx(), created.id).await {
+            Ok(completed) => completed.next.unwrap(),
+            Err(_) => panic!(),
+        };
+        let reminders = todo_repository.reminders.lock().unwrap();
+        let carried: Vec<_> = reminders
+            .iter()
+            .filter(|reminder| reminder.todo_id == next.id)
+            .collect();
+        assert_eq!(carried.len(), 1);
+        assert_eq!(
+            carried[0].trigger,
+            ReminderTrigger::BeforeDue(Duration::minutes(10))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mutations_are_audited() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let todo_data = CreateTodoDto {
+            title: "stretch".to_string(),
+            due_at: Some(Utc::now()),
+            priority: Priority::Medium,
+            recurrence: Some("FREQ=DAILY".to_string()),
+        };
+        let mut created = match mutation_interactor.create(ctx(), todo_data, None).await {
+            Ok(todo) => todo,
+            Err(_) => panic!(),
+        };
+        created.title = Some("stretch more".to_string());
+        let bob = RequestContext::new("bob", Protocol::Grpc, "req-2");
+        assert!(mutation_interactor
+            .update(bob.clone(), created.clone().into(), None)
+            .await
+            .is_ok());
+        assert!(mutation_interactor.complete(bob, created.id).await.is_ok());
+        assert!(mutation_interactor.delete(ctx(), 1, None).await.is_ok());
+        // failed or no-op mutations leave no trace
+        assert!(mutation_interactor.delete(ctx(), 1, None).await.is_ok());
+        assert!(mutation_interactor.complete(ctx(), 42).await.is_err());
+
+        let audit_log = todo_repository.audit_log.lock().unwrap();
+        assert_eq!(
+            audit_log
+                .iter()
+                .map(|entry| (entry.action, entry.todo_id, entry.actor.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (AuditAction::Create, 2, "alice"),
+                (AuditAction::Update, 2, "bob"),
+                (AuditAction::Complete, 2, "bob"),
+                (AuditAction::Create, 3, "bob"),
+                (AuditAction::Delete, 1, "alice"),
+            ]
+        );
+        let update = &audit_log[1];
+        assert_eq!(update.protocol, Protocol::Grpc);
+        assert_eq!(update.request_id, "req-2");
+        assert_eq!(
+            update
+                .changes()
+                .iter()
+                .map(|change| change.field)
+                .collect::<Vec<_>>(),
+            vec!["title"]
+        );
+        assert!(audit_log[4].after.is_none());
     }
 }
@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use domain::{
+    entity::audit::AuditAction, event::TodoEvent, repository::trash_repository::TrashRepository,
+    unit_of_work::UnitOfWork,
+};
+
+use crate::{
+    dto::{audit::RequestContext, todo::TodoDto, trash::TrashedTodoDto},
+    error::UseCaseError,
+    event_bus::EventBus,
+    interactor::todo::{audit, commit},
+    traits::trash::{TrashPurgeUseCase, TrashUseCase},
+};
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone)]
+pub struct TrashInteractor<TR, UW> {
+    trash_repository: TR,
+    unit_of_work: UW,
+    event_bus: EventBus,
+    retention: Duration,
+}
+
+impl<TR, UW> TrashInteractor<TR, UW> {
+    pub fn new(trash_repository: TR, unit_of_work: UW) -> Self {
+        Self {
+            trash_repository,
+            unit_of_work,
+            event_bus: EventBus::default(),
+            retention: Duration::days(DEFAULT_RETENTION_DAYS),
+        }
+    }
+
+    /// Sets the bus the events of restores are dispatched to.
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = event_bus;
+        self
+    }
+
+    /// Sets how long deleted todos are kept before they are purged.
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+}
+
+#[async_trait]
+impl<TR, UW> TrashUseCase for TrashInteractor<TR, UW>
+where
+    TR: TrashRepository,
+    UW: UnitOfWork,
+{
+    async fn find_trash(&self) -> Result<Vec<TrashedTodoDto>, UseCaseError> {
+        let trash = self.trash_repository.find_all().await?;
+        Ok(trash
+            .into_iter()
+            .map(|trashed| TrashedTodoDto::new(trashed, self.retention))
+            .collect())
+    }
+
+    async fn restore(&self, ctx: RequestContext, todo_id: i64) -> Result<TodoDto, UseCaseError> {
+        let mut tx = self.unit_of_work.begin().await?;
+        let todo = match tx.restore_todo(todo_id).await? {
+            Some(todo) => todo,
+            None => {
+                return Err(UseCaseError::NotFound {
+                    entity_type: "trashed todo".to_string(),
+                    entity_id: todo_id,
+                })
+            }
+        };
+        audit(tx.as_mut(), &ctx, AuditAction::Restore, None, Some(&todo)).await?;
+        tx.record(TodoEvent::TodoRestored { todo: todo.clone() });
+        commit(tx, &self.event_bus).await?;
+        Ok(todo.into())
+    }
+}
+
+#[async_trait]
+impl<TR, UW> TrashPurgeUseCase for TrashInteractor<TR, UW>
+where
+    TR: TrashRepository,
+    UW: UnitOfWork,
+{
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64, UseCaseError> {
+        let purged = self
+            .trash_repository
+            .purge_deleted_before(now - self.retention)
+            .await?;
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interactor::todo::{
+            tests::{ctx, MockTodoRepository},
+            MutationInteractor, QueryInteractor,
+        },
+        traits::todo::{MutationUseCase, QueryUseCase},
+    };
+
+    #[tokio::test]
+    async fn test_delete_restore_and_purge() {
+        let todo_repository = MockTodoRepository::new();
+        let mutation_interactor = MutationInteractor::new(todo_repository.clone());
+        let query_interactor = QueryInteractor::new(todo_repository.clone());
+        let trash_interactor =
+            TrashInteractor::new(todo_repository.clone(), todo_repository.clone())
+                .with_retention(Duration::days(7));
+
+        assert!(mutation_interactor.delete(ctx(), 1, None).await.is_ok());
+        assert!(matches!(query_interactor.find_by_id(1).await, Ok(None)));
+        match trash_interactor.find_trash().await {
+            Ok(trash) => {
+                assert_eq!(trash.len(), 1);
+                assert_eq!(trash[0].todo.id, 1);
+                assert_eq!(trash[0].purge_at, trash[0].deleted_at + Duration::days(7));
+            }
+            Err(_) => panic!(),
+        }
+
+        match trash_interactor.restore(ctx(), 1).await {
+            Ok(todo) => assert_eq!(todo.title, Some("task1".to_string())),
+            Err(_) => panic!(),
+        }
+        assert!(matches!(query_interactor.find_by_id(1).await, Ok(Some(_))));
+        assert_eq!(
+            todo_repository
+                .audit_log
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|entry| entry.action)
+                .collect::<Vec<_>>(),
+            vec![AuditAction::Delete, AuditAction::Restore]
+        );
+        assert!(matches!(
+            trash_interactor.restore(ctx(), 1).await,
+            Err(UseCaseError::NotFound { .. })
+        ));
+
+        assert!(mutation_interactor.delete(ctx(), 1, None).await.is_ok());
+        match trash_interactor.purge_expired(Utc::now()).await {
+            Ok(purged) => assert_eq!(purged, 0),
+            Err(_) => panic!(),
+        }
+        match trash_interactor
+            .purge_expired(Utc::now() + Duration::days(8))
+            .await
+        {
+            Ok(purged) => assert_eq!(purged, 1),
+            Err(_) => panic!(),
+        }
+        match trash_interactor.find_trash().await {
+            Ok(trash) => assert!(trash.is_empty()),
+            Err(_) => panic!(),
+        }
+    }
+}
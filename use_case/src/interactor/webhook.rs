@@ -0,0 +1,495 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use domain::{
+    entity::webhook::{WebhookDelivery, WebhookSubscription},
+    notifier::WebhookSender,
+    repository::webhook_repository::WebhookRepository,
+};
+
+use crate::{
+    dto::webhook::{
+        parse_events, CreateWebhookSubscriptionDto, WebhookDeliveryDto, WebhookSubscriptionDto,
+    },
+    error::UseCaseError,
+    traits::webhook::{WebhookDispatchUseCase, WebhookUseCase},
+};
+
+const DEFAULT_LEASE_SECONDS: i64 = 60;
+const DEFAULT_BATCH_SIZE: i64 = 100;
+const DELIVERY_LOG_LIMIT: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct WebhookInteractor<WR, S> {
+    webhook_repository: WR,
+    sender: S,
+    lease: Duration,
+    batch_size: i64,
+}
+
+impl<WR, S> WebhookInteractor<WR, S> {
+    pub fn new(webhook_repository: WR, sender: S) -> Self {
+        Self {
+            webhook_repository,
+            sender,
+            lease: Duration::seconds(DEFAULT_LEASE_SECONDS),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets how long an in-flight delivery stays invisible to other workers. It should
+    /// comfortably exceed the sender's timeout.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+}
+
+#[async_trait]
+impl<WR, S> WebhookUseCase for WebhookInteractor<WR, S>
+where
+    WR: WebhookRepository,
+    S: WebhookSender,
+{
+    async fn create_subscription(
+        &self,
+        subscription_data: CreateWebhookSubscriptionDto,
+    ) -> Result<WebhookSubscriptionDto, UseCaseError> {
+        let events = parse_events(&subscription_data.events)?;
+        let mut subscription = WebhookSubscription::new(
+            subscription_data.url,
+            subscription_data.secret,
+            events,
+            Utc::now().trunc_subsecs(0),
+        )?;
+        subscription.id = self
+            .webhook_repository
+            .create_subscription(&subscription)
+            .await?;
+        Ok(subscription.into())
+    }
+
+    async fn find_subscriptions(&self) -> Result<Vec<WebhookSubscriptionDto>, UseCaseError> {
+        let subscriptions = self.webhook_repository.find_subscriptions().await?;
+        Ok(subscriptions
+            .into_iter()
+            .map(|subscription| subscription.into())
+            .collect())
+    }
+
+    async fn delete_subscription(&self, subscription_id: i64) -> Result<i64, UseCaseError> {
+        find_subscription(&self.webhook_repository, subscription_id).await?;
+        self.webhook_repository
+            .delete_subscription(subscription_id)
+            .await?;
+        Ok(subscription_id)
+    }
+
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+    ) -> Result<Vec<WebhookDeliveryDto>, UseCaseError> {
+        find_subscription(&self.webhook_repository, subscription_id).await?;
+        let deliveries = self
+            .webhook_repository
+            .find_deliveries(subscription_id, DELIVERY_LOG_LIMIT)
+            .await?;
+        Ok(deliveries
+            .into_iter()
+            .map(|delivery| delivery.into())
+            .collect())
+    }
+}
+
+#[async_trait]
+impl<WR, S> WebhookDispatchUseCase for WebhookInteractor<WR, S>
+where
+    WR: WebhookRepository,
+    S: WebhookSender,
+{
+    async fn dispatch_pending(&self, now: DateTime<Utc>) -> Result<usize, UseCaseError> {
+        self.webhook_repository.fan_out(now).await?;
+        let deliveries = self
+            .webhook_repository
+            .claim_due_deliveries(now, now + self.lease, self.batch_size)
+            .await?;
+
+        let mut subscriptions = HashMap::new();
+        let mut delivered = 0;
+        for delivery in deliveries {
+            if let Entry::Vacant(entry) = subscriptions.entry(delivery.subscription_id) {
+                let subscription = self
+                    .webhook_repository
+                    .find_subscription_by_id(delivery.subscription_id)
+                    .await?;
+                entry.insert(subscription);
+            }
+            // Deliveries of a deleted subscription are removed along with it.
+            let subscription = match &subscriptions[&delivery.subscription_id] {
+                Some(subscription) => subscription,
+                None => continue,
+            };
+            if send_delivery(
+                &self.webhook_repository,
+                &self.sender,
+                subscription,
+                &delivery,
+                now,
+            )
+            .await?
+            {
+                delivered += 1;
+            }
+        }
+        Ok(delivered)
+    }
+}
+
+async fn find_subscription<WR: WebhookRepository>(
+    webhook_repository: &WR,
+    subscription_id: i64,
+) -> Result<WebhookSubscription, UseCaseError> {
+    match webhook_repository
+        .find_subscription_by_id(subscription_id)
+        .await?
+    {
+        Some(subscription) => Ok(subscription),
+        None => Err(UseCaseError::NotFound {
+            entity_type: "webhook".to_string(),
+            entity_id: subscription_id,
+        }),
+    }
+}
+
+/// Sends one delivery and records the outcome; returns whether it succeeded.
+async fn send_delivery<WR: WebhookRepository, S: WebhookSender>(
+    webhook_repository: &WR,
+    sender: &S,
+    subscription: &WebhookSubscription,
+    delivery: &WebhookDelivery,
+    now: DateTime<Utc>,
+) -> Result<bool, UseCaseError> {
+    let (status, error) = match sender.send(subscription, delivery).await {
+        Ok(status) if (200..300).contains(&status) => {
+            webhook_repository
+                .mark_delivered(delivery.id, status as i64, now)
+                .await?;
+            return Ok(true);
+        }
+        Ok(status) => (Some(status as i64), format!("HTTP status {}", status)),
+        Err(error) => (None, error.to_string()),
+    };
+    let retry_at = WebhookDelivery::next_retry(delivery.attempts + 1, now);
+    webhook_repository
+        .mark_failed(delivery.id, status, &error, retry_at, now)
+        .await?;
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::{entity::webhook::WebhookEvent, error::DomainError};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, Default)]
+    struct MockWebhookRepository {
+        subscriptions: Arc<Mutex<Vec<WebhookSubscription>>>,
+        outbox: Arc<Mutex<Vec<(WebhookEvent, String)>>>,
+        deliveries: Arc<Mutex<Vec<WebhookDelivery>>>,
+    }
+
+    #[async_trait]
+    impl WebhookRepository for MockWebhookRepository {
+        async fn create_subscription(
+            &self,
+            subscription: &WebhookSubscription,
+        ) -> Result<i64, DomainError> {
+            let mut subscriptions = self.subscriptions.lock().unwrap();
+            let new_id = subscriptions.len() as i64 + 1;
+            subscriptions.push(WebhookSubscription {
+                id: new_id,
+                ..subscription.clone()
+            });
+            Ok(new_id)
+        }
+
+        async fn find_subscriptions(&self) -> Result<Vec<WebhookSubscription>, DomainError> {
+            Ok(self.subscriptions.lock().unwrap().clone())
+        }
+
+        async fn find_subscription_by_id(
+            &self,
+            id: i64,
+        ) -> Result<Option<WebhookSubscription>, DomainError> {
+            let subscriptions = self.subscriptions.lock().unwrap();
+            Ok(subscriptions
+                .iter()
+                .find(|subscription| subscription.id == id)
+                .cloned())
+        }
+
+        async fn delete_subscription(&self, id: i64) -> Result<(), DomainError> {
+            let mut subscriptions = self.subscriptions.lock().unwrap();
+            subscriptions.retain(|subscription| subscription.id != id);
+            Ok(())
+        }
+
+        async fn find_deliveries(
+            &self,
+            subscription_id: i64,
+            limit: i64,
+        ) -> Result<Vec<WebhookDelivery>, DomainError> {
+            let deliveries = self.deliveries.lock().unwrap();
+            Ok(deliveries
+                .iter()
+                .rev()
+                .filter(|delivery| delivery.subscription_id == subscription_id)
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn fan_out(&self, now: DateTime<Utc>) -> Result<usize, DomainError> {
+            let subscriptions = self.subscriptions.lock().unwrap();
+            let mut outbox = self.outbox.lock().unwrap();
+            let mut deliveries = self.deliveries.lock().unwrap();
+            let before = deliveries.len();
+            for (event, payload) in outbox.drain(..) {
+                for subscription in subscriptions.iter().filter(|s| s.accepts(event)) {
+                    let id = deliveries.len() as i64 + 1;
+                    deliveries.push(WebhookDelivery {
+                        id,
+                        subscription_id: subscription.id,
+                        event,
+                        payload: payload.clone(),
+                        attempts: 0,
+                        next_attempt_at: now,
+                        delivered_at: None,
+                        failed_at: None,
+                        response_status: None,
+                        last_error: None,
+                        created_at: now,
+                    });
+                }
+            }
+            Ok(deliveries.len() - before)
+        }
+
+        async fn claim_due_deliveries(
+            &self,
+            now: DateTime<Utc>,
+            lease_until: DateTime<Utc>,
+            limit: i64,
+        ) -> Result<Vec<WebhookDelivery>, DomainError> {
+            let mut deliveries = self.deliveries.lock().unwrap();
+            let mut claimed = Vec::new();
+            for delivery in deliveries.iter_mut() {
+                let pending = delivery.delivered_at.is_none() && delivery.failed_at.is_none();
+                if pending && delivery.next_attempt_at <= now && claimed.len() < limit as usize {
+                    delivery.next_attempt_at = lease_until;
+                    claimed.push(delivery.clone());
+                }
+            }
+            Ok(claimed)
+        }
+
+        async fn mark_delivered(
+            &self,
+            id: i64,
+            status: i64,
+            delivered_at: DateTime<Utc>,
+        ) -> Result<(), DomainError> {
+            let mut deliveries = self.deliveries.lock().unwrap();
+            for delivery in deliveries.iter_mut().filter(|delivery| delivery.id == id) {
+                delivery.attempts += 1;
+                delivery.delivered_at = Some(delivered_at);
+                delivery.response_status = Some(status);
+                delivery.last_error = None;
+            }
+            Ok(())
+        }
+
+        async fn mark_failed(
+            &self,
+            id: i64,
+            status: Option<i64>,
+            error: &str,
+            retry_at: Option<DateTime<Utc>>,
+            now: DateTime<Utc>,
+        ) -> Result<(), DomainError> {
+            let mut deliveries = self.deliveries.lock().unwrap();
+            for delivery in deliveries.iter_mut().filter(|delivery| delivery.id == id) {
+                delivery.attempts += 1;
+                delivery.response_status = status;
+                delivery.last_error = Some(error.to_string());
+                match retry_at {
+                    Some(retry_at) => delivery.next_attempt_at = retry_at,
+                    None => delivery.failed_at = Some(now),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Replies with the queued responses in order, then 200.
+    #[derive(Debug, Clone, Default)]
+    struct MockSender {
+        responses: Arc<Mutex<Vec<Result<u16, String>>>>,
+        sent: Arc<Mutex<Vec<(String, i64)>>>,
+    }
+
+    #[async_trait]
+    impl WebhookSender for MockSender {
+        async fn send(
+            &self,
+            subscription: &WebhookSubscription,
+            delivery: &WebhookDelivery,
+        ) -> Result<u16, DomainError> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((subscription.url.clone(), delivery.id));
+            let mut responses = self.responses.lock().unwrap();
+            if responses.is_empty() {
+                return Ok(200);
+            }
+            responses.remove(0).map_err(DomainError::Unexpected)
+        }
+    }
+
+    fn subscription_data(events: Vec<&str>) -> CreateWebhookSubscriptionDto {
+        CreateWebhookSubscriptionDto {
+            url: "https://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: events.into_iter().map(|event| event.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_subscription() {
+        let interactor =
+            WebhookInteractor::new(MockWebhookRepository::default(), MockSender::default());
+
+        match interactor
+            .create_subscription(subscription_data(vec!["todo.renamed"]))
+            .await
+        {
+            Err(UseCaseError::Validation(_)) => {}
+            _ => panic!("expected a validation error"),
+        }
+
+        match interactor
+            .create_subscription(subscription_data(vec!["todo.created", "todo.created"]))
+            .await
+        {
+            Ok(subscription) => {
+                assert_eq!(subscription.id, 1);
+                assert_eq!(subscription.events, vec!["todo.created"]);
+            }
+            Err(_) => panic!("failed to create subscription"),
+        }
+
+        match interactor.find_deliveries(2).await {
+            Err(UseCaseError::NotFound { entity_id, .. }) => assert_eq!(entity_id, 2),
+            _ => panic!("expected not found"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_retries_with_backoff() {
+        let repository = MockWebhookRepository::default();
+        let sender = MockSender::default();
+        let interactor = WebhookInteractor::new(repository.clone(), sender.clone());
+        let subscription_id = match interactor
+            .create_subscription(subscription_data(vec![]))
+            .await
+        {
+            Ok(subscription) => subscription.id,
+            Err(_) => panic!("failed to create subscription"),
+        };
+
+        repository
+            .outbox
+            .lock()
+            .unwrap()
+            .push((WebhookEvent::TodoCreated, "{}".to_string()));
+        *sender.responses.lock().unwrap() = vec![Ok(500), Err("connection refused".to_string())];
+
+        let now = Utc::now();
+        assert!(matches!(interactor.dispatch_pending(now).await, Ok(0)));
+        // Still backing off after the first failure.
+        assert!(matches!(
+            interactor
+                .dispatch_pending(now + Duration::seconds(5))
+                .await,
+            Ok(0)
+        ));
+        assert!(matches!(
+            interactor
+                .dispatch_pending(now + Duration::seconds(10))
+                .await,
+            Ok(0)
+        ));
+        assert!(matches!(
+            interactor
+                .dispatch_pending(now + Duration::seconds(30))
+                .await,
+            Ok(1)
+        ));
+        assert_eq!(sender.sent.lock().unwrap().len(), 3);
+
+        match interactor.find_deliveries(subscription_id).await {
+            Ok(deliveries) => {
+                assert_eq!(deliveries.len(), 1);
+                assert_eq!(deliveries[0].status, "delivered");
+                assert_eq!(deliveries[0].attempts, 3);
+                assert_eq!(deliveries[0].response_status, Some(200));
+            }
+            Err(_) => panic!("failed to fetch deliveries"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_gives_up_after_max_attempts() {
+        let repository = MockWebhookRepository::default();
+        let sender = MockSender::default();
+        let interactor = WebhookInteractor::new(repository.clone(), sender.clone());
+        if interactor
+            .create_subscription(subscription_data(vec!["todo.deleted"]))
+            .await
+            .is_err()
+        {
+            panic!("failed to create subscription");
+        }
+
+        repository.outbox.lock().unwrap().extend([
+            (WebhookEvent::TodoCreated, "{}".to_string()),
+            (WebhookEvent::TodoDeleted, "{}".to_string()),
+        ]);
+        *sender.responses.lock().unwrap() =
+            vec![Ok(410); WebhookDelivery::MAX_ATTEMPTS as usize + 1];
+
+        let mut now = Utc::now();
+        for _ in 0..WebhookDelivery::MAX_ATTEMPTS + 2 {
+            assert!(matches!(interactor.dispatch_pending(now).await, Ok(0)));
+            now += Duration::hours(6);
+        }
+        // The created event was filtered out; the deleted one was tried MAX_ATTEMPTS times.
+        assert_eq!(
+            sender.sent.lock().unwrap().len(),
+            WebhookDelivery::MAX_ATTEMPTS as usize
+        );
+        match interactor.find_deliveries(1).await {
+            Ok(deliveries) => {
+                assert_eq!(deliveries[0].status, "failed");
+                assert_eq!(
+                    deliveries[0].last_error,
+                    Some("HTTP status 410".to_string())
+                );
+            }
+            Err(_) => panic!("failed to fetch deliveries"),
+        }
+    }
+}
@@ -1,6 +1,10 @@
 pub mod dto;
 pub mod error;
+pub mod event_bus;
+pub mod icalendar;
 pub mod interactor;
+pub mod time_window;
+pub mod todo_format;
 pub mod traits;
 
 pub fn add(left: usize, right: usize) -> usize {
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::error::UseCaseError;
+
+pub fn parse_timezone(name: &str) -> Result<Tz, UseCaseError> {
+    name.parse::<Tz>()
+        .map_err(|_| UseCaseError::Validation(format!("unknown timezone: {}", name)))
+}
+
+/// The local calendar day containing `now` in `tz`, as a half-open UTC range.
+pub fn day_window(now: DateTime<Utc>, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let today = now.with_timezone(&tz).date_naive();
+    let tomorrow = today + Duration::days(1);
+    (start_of_day(today, tz), start_of_day(tomorrow, tz))
+}
+
+/// The local ISO week (Monday to Sunday) containing `now` in `tz`, as a half-open UTC range.
+pub fn week_window(now: DateTime<Utc>, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let today = now.with_timezone(&tz).date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let next_monday = monday + Duration::days(7);
+    (start_of_day(monday, tz), start_of_day(next_monday, tz))
+}
+
+fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    match tz.from_local_datetime(&midnight) {
+        LocalResult::Single(start) => start.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        // Midnight was skipped by a DST transition; the day starts at the first valid instant.
+        LocalResult::None => tz
+            .from_local_datetime(&(midnight + Duration::hours(1)))
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert!(parse_timezone("Asia/Tokyo").is_ok());
+        assert!(matches!(
+            parse_timezone("Mars/Olympus"),
+            Err(UseCaseError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_day_window() {
+        // 2023-09-30 20:00 UTC is already 2023-10-01 in Tokyo.
+        let now = utc("2023-09-30T20:00:00Z");
+        let (from, to) = day_window(now, chrono_tz::Asia::Tokyo);
+        assert_eq!(from, utc("2023-09-30T15:00:00Z"));
+        assert_eq!(to, utc("2023-10-01T15:00:00Z"));
+
+        let (from, to) = day_window(now, chrono_tz::UTC);
+        assert_eq!(from, utc("2023-09-30T00:00:00Z"));
+        assert_eq!(to, utc("2023-10-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_week_window() {
+        // Thursday 2023-10-05 in New York.
+        let now = utc("2023-10-05T12:00:00Z");
+        let (from, to) = week_window(now, chrono_tz::America::New_York);
+        assert_eq!(from, utc("2023-10-02T04:00:00Z"));
+        assert_eq!(to, utc("2023-10-09T04:00:00Z"));
+    }
+
+    #[test]
+    fn test_day_window_across_dst() {
+        // New York springs forward on 2023-03-12, so that day is 23 hours long.
+        let now = utc("2023-03-12T12:00:00Z");
+        let (from, to) = day_window(now, chrono_tz::America::New_York);
+        assert_eq!(from, utc("2023-03-12T05:00:00Z"));
+        assert_eq!(to, utc("2023-03-13T04:00:00Z"));
+    }
+}
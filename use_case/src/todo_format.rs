@@ -0,0 +1,409 @@
+//! Encoding of todos for export and their decoding on import.
+//!
+//! CSV and JSON Lines carry every field of a todo. In todo.txt, a task line
+//! `x 2024-01-02 Call Mom @phone +family due:2024-01-05 rrule:FREQ=WEEKLY pri:A` maps to:
+//!
+//! - completion: the leading `x` and its date; the time of day is not kept,
+//! - priority: `(A)` is high, `(B)` medium and `(C)` and below low; completed tasks keep it
+//!   in a `pri:` tag instead,
+//! - due date: the `due:` tag, a date or an RFC 3339 date-time,
+//! - recurrence: the `rrule:` tag, an RFC 5545 RRULE,
+//! - title: the rest of the line, contexts (`@phone`) and projects (`+family`) included.
+//!
+//! iCalendar files hold one VTODO per todo; see [`crate::icalendar`].
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Timelike, Utc};
+use domain::entity::{
+    recurrence::Recurrence,
+    todo::{Priority, Todo},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{dto::import_export::TodoFormat, error::UseCaseError, icalendar};
+
+const CSV_HEADER: &str = "id,title,due_at,priority,recurrence,completed_at,version\n";
+
+/// The fields of a todo in CSV and JSON Lines. `id` and `version` are exported for
+/// reference only; imported todos are always created anew.
+#[derive(Debug, Serialize, Deserialize)]
+struct TodoRecord {
+    #[serde(default)]
+    id: Option<i64>,
+    title: String,
+    #[serde(default)]
+    due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    recurrence: Option<String>,
+    #[serde(default)]
+    completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    version: Option<i64>,
+}
+
+impl From<&Todo> for TodoRecord {
+    fn from(todo: &Todo) -> Self {
+        Self {
+            id: Some(todo.id),
+            title: todo.title.clone().unwrap_or_default(),
+            due_at: todo.due_at,
+            priority: Some(priority_name(todo.priority).to_string()),
+            recurrence: todo
+                .recurrence
+                .as_ref()
+                .map(|recurrence| recurrence.to_string()),
+            completed_at: todo.completed_at,
+            version: Some(todo.version),
+        }
+    }
+}
+
+impl TryFrom<TodoRecord> for Todo {
+    type Error = String;
+
+    fn try_from(record: TodoRecord) -> Result<Self, Self::Error> {
+        let priority = match record.priority.as_deref() {
+            None | Some("") => Priority::default(),
+            Some(name) => parse_priority(name)?,
+        };
+        Ok(Todo {
+            title: Some(record.title),
+            due_at: record.due_at,
+            priority,
+            recurrence: parse_recurrence(record.recurrence.as_deref())?,
+            completed_at: record.completed_at,
+            ..Default::default()
+        })
+    }
+}
+
+fn priority_name(priority: Priority) -> &'static str {
+    match priority {
+        Priority::Low => "low",
+        Priority::Medium => "medium",
+        Priority::High => "high",
+    }
+}
+
+fn parse_priority(name: &str) -> Result<Priority, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "low" => Ok(Priority::Low),
+        "medium" => Ok(Priority::Medium),
+        "high" => Ok(Priority::High),
+        _ => Err(format!(
+            "invalid priority {}; expected low, medium or high",
+            name
+        )),
+    }
+}
+
+pub(crate) fn parse_recurrence(rule: Option<&str>) -> Result<Option<Recurrence>, String> {
+    match rule {
+        Some(rule) if !rule.trim().is_empty() => match rule.parse::<Recurrence>() {
+            Ok(recurrence) => Ok(Some(recurrence)),
+            Err(e) => Err(e.to_string()),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// What precedes the todos of an export, if anything.
+pub fn header(format: TodoFormat) -> Option<&'static str> {
+    match format {
+        TodoFormat::Csv => Some(CSV_HEADER),
+        TodoFormat::ICalendar => Some(icalendar::HEADER),
+        TodoFormat::JsonLines | TodoFormat::TodoTxt => None,
+    }
+}
+
+/// What follows the todos of an export, if anything.
+pub fn footer(format: TodoFormat) -> Option<&'static str> {
+    match format {
+        TodoFormat::ICalendar => Some(icalendar::FOOTER),
+        TodoFormat::Csv | TodoFormat::JsonLines | TodoFormat::TodoTxt => None,
+    }
+}
+
+/// `todo` as one record of `format`, line break included.
+pub fn encode(format: TodoFormat, todo: &Todo) -> Result<String, UseCaseError> {
+    match format {
+        TodoFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(vec![]);
+            if let Err(e) = writer.serialize(TodoRecord::from(todo)) {
+                return Err(UseCaseError::Other(e.into()));
+            }
+            match writer.into_inner() {
+                Ok(line) => Ok(String::from_utf8_lossy(&line).into_owned()),
+                Err(e) => Err(UseCaseError::Unexpected(e.to_string())),
+            }
+        }
+        TodoFormat::JsonLines => match serde_json::to_string(&TodoRecord::from(todo)) {
+            Ok(line) => Ok(line + "\n"),
+            Err(e) => Err(UseCaseError::Other(e.into())),
+        },
+        TodoFormat::TodoTxt => Ok(encode_todo_txt(todo) + "\n"),
+        TodoFormat::ICalendar => Ok(icalendar::encode_vtodo(todo, Utc::now())),
+    }
+}
+
+/// The todos of `data`, each with the line it starts on; records that cannot be decoded
+/// carry the reason instead.
+pub fn decode(format: TodoFormat, data: &str) -> Vec<(usize, Result<Todo, String>)> {
+    match format {
+        TodoFormat::Csv => decode_csv(data),
+        TodoFormat::JsonLines => non_blank_lines(data)
+            .map(|(line, text)| {
+                let todo = match serde_json::from_str::<TodoRecord>(text) {
+                    Ok(record) => Todo::try_from(record),
+                    Err(e) => Err(e.to_string()),
+                };
+                (line, todo)
+            })
+            .collect(),
+        TodoFormat::TodoTxt => non_blank_lines(data)
+            .map(|(line, text)| (line, decode_todo_txt(text)))
+            .collect(),
+        TodoFormat::ICalendar => icalendar::decode_vtodos(data),
+    }
+}
+
+fn non_blank_lines(data: &str) -> impl Iterator<Item = (usize, &str)> {
+    data.lines()
+        .enumerate()
+        .map(|(index, text)| (index + 1, text.trim()))
+        .filter(|(_, text)| !text.is_empty())
+}
+
+fn decode_csv(data: &str) -> Vec<(usize, Result<Todo, String>)> {
+    let mut reader = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => return vec![(1, Err(e.to_string()))],
+    };
+    reader
+        .records()
+        .map(|record| match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, |position| position.line());
+                let todo = match record.deserialize::<TodoRecord>(Some(&headers)) {
+                    Ok(record) => Todo::try_from(record),
+                    Err(e) => Err(e.to_string()),
+                };
+                (line as usize, todo)
+            }
+            Err(e) => {
+                let line = e.position().map_or(0, |position| position.line());
+                (line as usize, Err(e.to_string()))
+            }
+        })
+        .collect()
+}
+
+fn priority_letter(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+fn parse_priority_letter(letter: &str) -> Option<Priority> {
+    match letter.as_bytes() {
+        [b'A'] => Some(Priority::High),
+        [b'B'] => Some(Priority::Medium),
+        [b'C'..=b'Z'] => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+fn encode_todo_txt(todo: &Todo) -> String {
+    let mut words = Vec::new();
+    match todo.completed_at {
+        Some(completed_at) => {
+            words.push("x".to_string());
+            words.push(completed_at.format("%Y-%m-%d").to_string());
+        }
+        None => words.push(format!("({})", priority_letter(todo.priority))),
+    }
+    // a task is a single line
+    if let Some(title) = &todo.title {
+        words.extend(title.split_whitespace().map(str::to_string));
+    }
+    if let Some(due_at) = todo.due_at {
+        if due_at.num_seconds_from_midnight() == 0 {
+            words.push(format!("due:{}", due_at.format("%Y-%m-%d")));
+        } else {
+            words.push(format!(
+                "due:{}",
+                due_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+            ));
+        }
+    }
+    if let Some(recurrence) = &todo.recurrence {
+        words.push(format!("rrule:{}", recurrence));
+    }
+    if todo.is_completed() {
+        words.push(format!("pri:{}", priority_letter(todo.priority)));
+    }
+    words.join(" ")
+}
+
+fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date_time| date_time.and_utc())
+}
+
+fn decode_todo_txt(line: &str) -> Result<Todo, String> {
+    let mut words = line.split_whitespace().peekable();
+    let mut todo = Todo::default();
+    if words.peek() == Some(&"x") {
+        words.next();
+        match words.next().and_then(parse_date) {
+            Some(completed_at) => todo.completed_at = Some(completed_at),
+            None => return Err("a completed task needs a completion date".to_string()),
+        }
+    } else if let Some(priority) = words
+        .peek()
+        .and_then(|word| word.strip_prefix('(')?.strip_suffix(')'))
+        .and_then(parse_priority_letter)
+    {
+        words.next();
+        todo.priority = priority;
+    }
+    // the creation date is not kept
+    if words.peek().and_then(|word| parse_date(word)).is_some() {
+        words.next();
+    }
+    let mut title = Vec::new();
+    for word in words {
+        match word.split_once(':') {
+            Some(("due", due_at)) => {
+                let parsed = match parse_date(due_at) {
+                    Some(due_at) => Some(due_at),
+                    None => DateTime::parse_from_rfc3339(due_at)
+                        .ok()
+                        .map(|due_at| due_at.with_timezone(&Utc)),
+                };
+                match parsed {
+                    Some(due_at) => todo.due_at = Some(due_at),
+                    None => return Err(format!("invalid due date {}", due_at)),
+                }
+            }
+            Some(("rrule", rule)) => todo.recurrence = parse_recurrence(Some(rule))?,
+            Some(("pri", letter)) => match parse_priority_letter(letter) {
+                Some(priority) => todo.priority = priority,
+                None => return Err(format!("invalid priority {}", letter)),
+            },
+            _ => title.push(word),
+        }
+    }
+    todo.title = Some(title.join(" "));
+    Ok(todo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo() -> Todo {
+        Todo {
+            id: 7,
+            title: Some("Call Mom, \"soon\" @phone +family".to_string()),
+            due_at: DateTime::from_timestamp(1_704_448_800, 0),
+            priority: Priority::High,
+            recurrence: Some("FREQ=WEEKLY;BYDAY=MO".parse().ok().unwrap()),
+            completed_at: None,
+            version: 3,
+        }
+    }
+
+    fn round_trip(format: TodoFormat, todo: &Todo) -> Todo {
+        let data = format!(
+            "{}{}{}",
+            header(format).unwrap_or_default(),
+            encode(format, todo).ok().unwrap(),
+            footer(format).unwrap_or_default()
+        );
+        let mut decoded = decode(format, &data);
+        assert_eq!(decoded.len(), 1);
+        match decoded.remove(0) {
+            (_, Ok(decoded)) => decoded,
+            (line, Err(message)) => panic!("line {}: {}", line, message),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let completed = Todo {
+            completed_at: DateTime::from_timestamp(1_704_153_600, 0),
+            ..todo()
+        };
+        for format in [
+            TodoFormat::Csv,
+            TodoFormat::JsonLines,
+            TodoFormat::TodoTxt,
+            TodoFormat::ICalendar,
+        ] {
+            for todo in [todo(), completed.clone()] {
+                // imported todos are created anew
+                let expected = Todo {
+                    id: 0,
+                    version: Todo::FIRST_VERSION,
+                    ..todo.clone()
+                };
+                assert_eq!(round_trip(format, &todo), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_todo_txt() {
+        assert_eq!(
+            encode(TodoFormat::TodoTxt, &todo()).ok().unwrap(),
+            "(A) Call Mom, \"soon\" @phone +family due:2024-01-05T10:00:00Z \
+             rrule:FREQ=WEEKLY;BYDAY=MO\n"
+        );
+    }
+
+    #[test]
+    fn test_decode_todo_txt() {
+        let data = "\n(D) 2024-01-01 Pay bills +home due:2024-02-01\nx 2024-01-03 2024-01-01 \
+                    Read @train\nx Read\n(A) Plan due:soon\n";
+        let decoded = decode(TodoFormat::TodoTxt, data);
+        assert_eq!(decoded.len(), 4);
+        match &decoded[0] {
+            (2, Ok(todo)) => {
+                assert_eq!(todo.title, Some("Pay bills +home".to_string()));
+                assert_eq!(todo.priority, Priority::Low);
+                assert_eq!(todo.due_at, parse_date("2024-02-01"));
+            }
+            _ => panic!("failed to decode a task"),
+        }
+        match &decoded[1] {
+            (3, Ok(todo)) => {
+                assert_eq!(todo.title, Some("Read @train".to_string()));
+                assert_eq!(todo.priority, Priority::Medium);
+                assert_eq!(todo.completed_at, parse_date("2024-01-03"));
+            }
+            _ => panic!("failed to decode a completed task"),
+        }
+        assert!(matches!(decoded[2], (4, Err(_))));
+        assert!(matches!(decoded[3], (5, Err(_))));
+    }
+
+    #[test]
+    fn test_decode_csv_problems() {
+        let data = "title,priority,due_at\nok,high,\nbad,urgent,\nlate,,tomorrow\nshort\n";
+        let decoded = decode(TodoFormat::Csv, data);
+        let lines: Vec<(usize, bool)> = decoded
+            .iter()
+            .map(|(line, todo)| (*line, todo.is_ok()))
+            .collect();
+        assert_eq!(lines, vec![(2, true), (3, false), (4, false), (5, false)]);
+    }
+}
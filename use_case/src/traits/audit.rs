@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::{
+    dto::audit::{AuditEntryDto, AuditLogFilterDto},
+    error::UseCaseError,
+};
+
+#[async_trait]
+pub trait AuditUseCase: Send + Sync + 'static {
+    /// Audit entries matching `filter`, newest first.
+    async fn find_audit_log(
+        &self,
+        filter: AuditLogFilterDto,
+    ) -> Result<Vec<AuditEntryDto>, UseCaseError>;
+}
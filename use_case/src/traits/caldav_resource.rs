@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{
+    dto::{
+        audit::RequestContext,
+        todo::{CreateTodoDto, TodoDto, UpdateTodoDto},
+    },
+    error::UseCaseError,
+};
+
+#[async_trait]
+pub trait CalDavResourceUseCase: Send + Sync + 'static {
+    /// The id of the todo served under `name`, either a name a client gave it or its
+    /// default `<id>.ics` name.
+    async fn find_todo_id(&self, name: String) -> Result<Option<i64>, UseCaseError>;
+    /// The names clients gave todos, by todo id.
+    async fn find_names(&self) -> Result<HashMap<i64, String>, UseCaseError>;
+    /// Fails unless a client may create a resource called `name`.
+    fn validate_name(&self, name: &str) -> Result<(), UseCaseError>;
+    /// Creates a todo served under `name` from now on and completes it if `completed`, all
+    /// in one transaction. Fails with `Conflict` when another todo took the name first.
+    async fn create_todo(
+        &self,
+        ctx: RequestContext,
+        name: String,
+        todo_data: CreateTodoDto,
+        completed: bool,
+    ) -> Result<TodoDto, UseCaseError>;
+    /// Changes the todo like `update` and completes it if `completed` and still open, all
+    /// in one transaction.
+    async fn replace_todo(
+        &self,
+        ctx: RequestContext,
+        todo_data: UpdateTodoDto,
+        expected_version: Option<i64>,
+        completed: bool,
+    ) -> Result<TodoDto, UseCaseError>;
+}
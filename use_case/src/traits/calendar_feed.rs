@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::{
+    dto::{audit::RequestContext, calendar_feed::CalendarFeedDto},
+    error::UseCaseError,
+    traits::import_export::ExportStream,
+};
+
+#[async_trait]
+pub trait CalendarFeedUseCase: Send + Sync + 'static {
+    /// Issues a new feed URL to the actor of `ctx`.
+    async fn create_feed(&self, ctx: RequestContext) -> Result<CalendarFeedDto, UseCaseError>;
+    /// The feeds issued to the actor of `ctx`.
+    async fn find_feeds(&self, ctx: RequestContext) -> Result<Vec<CalendarFeedDto>, UseCaseError>;
+    /// Revokes a feed; feeds issued to other actors are not found.
+    async fn revoke_feed(&self, ctx: RequestContext, feed_id: i64) -> Result<i64, UseCaseError>;
+    /// Every todo as an iCalendar file, or `None` when no feed has `token`.
+    async fn feed(&self, token: &str) -> Result<Option<ExportStream>, UseCaseError>;
+}
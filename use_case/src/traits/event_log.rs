@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+
+use crate::{
+    dto::event_log::{EventCursorDto, LoggedEventDto},
+    error::UseCaseError,
+    event_bus::EventListener,
+};
+
+#[async_trait]
+pub trait EventLogUseCase: Send + Sync + 'static {
+    /// Where to read on from for a subscriber that last saw the event `last_event_id`, or
+    /// from the newest event for a new one.
+    async fn resume(&self, last_event_id: Option<i64>) -> Result<EventCursorDto, UseCaseError>;
+    /// Up to `limit` events logged after the event `last_event_id`, oldest first.
+    async fn find_after(
+        &self,
+        last_event_id: i64,
+        limit: i64,
+    ) -> Result<Vec<LoggedEventDto>, UseCaseError>;
+    /// A listener woken whenever events are added to the log from now on.
+    fn listen(&self) -> EventListener;
+}
+
+#[async_trait]
+pub trait EventLogPruneUseCase: Send + Sync + 'static {
+    /// Drops the events beyond the capacity of the log and returns how many were dropped.
+    async fn prune(&self) -> Result<u64, UseCaseError>;
+}
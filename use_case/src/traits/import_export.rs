@@ -0,0 +1,30 @@
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures_util::Stream;
+
+use crate::{
+    dto::{
+        audit::RequestContext,
+        import_export::{ImportReportDto, TodoFormat},
+    },
+    error::UseCaseError,
+};
+
+/// An export, as chunks of the file to be written out in order.
+pub type ExportStream = Pin<Box<dyn Stream<Item = Result<String, UseCaseError>> + Send>>;
+
+#[async_trait]
+pub trait ImportExportUseCase: Send + Sync + 'static {
+    /// Every todo in `format`, read from the store as the stream is polled.
+    fn export(&self, format: TodoFormat) -> ExportStream;
+    /// Creates the todos of `data` in one transaction. Nothing is created on a `dry_run`
+    /// or when any record has a problem; the report lists the problems either way.
+    async fn import(
+        &self,
+        ctx: RequestContext,
+        format: TodoFormat,
+        data: String,
+        dry_run: bool,
+    ) -> Result<ImportReportDto, UseCaseError>;
+}
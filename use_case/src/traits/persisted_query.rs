@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+use crate::{dto::persisted_query::PersistedQueryDto, error::UseCaseError};
+
+#[async_trait]
+pub trait PersistedQueryUseCase: Send + Sync + 'static {
+    async fn find(&self, hash: String) -> Result<Option<PersistedQueryDto>, UseCaseError>;
+    /// Every persisted query, registered ones first, oldest first within each source.
+    async fn find_all(&self) -> Result<Vec<PersistedQueryDto>, UseCaseError>;
+    /// Registers the document of a client manifest, whose operations must all be named.
+    async fn register(
+        &self,
+        document: String,
+        operation_names: Vec<String>,
+    ) -> Result<PersistedQueryDto, UseCaseError>;
+    /// Keeps a document a client sent along with its hash, dropping the oldest automatic
+    /// queries beyond the capacity.
+    async fn save_automatic(
+        &self,
+        document: String,
+        operation_names: Vec<String>,
+    ) -> Result<PersistedQueryDto, UseCaseError>;
+}
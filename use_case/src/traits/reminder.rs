@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    dto::reminder::{CreateReminderDto, ReminderDto},
+    error::UseCaseError,
+};
+
+#[async_trait]
+pub trait ReminderUseCase: Send + Sync + 'static {
+    async fn create(&self, reminder_data: CreateReminderDto) -> Result<ReminderDto, UseCaseError>;
+    async fn find_by_todo_id(&self, todo_id: i64) -> Result<Vec<ReminderDto>, UseCaseError>;
+    async fn delete(&self, reminder_id: i64) -> Result<i64, UseCaseError>;
+}
+
+#[async_trait]
+pub trait ReminderDispatchUseCase: Send + Sync + 'static {
+    /// Delivers the reminders due at `now` and returns how many were fired. Delivery is
+    /// at least once: a reminder delivered by a dispatcher that stopped before recording it
+    /// is delivered again when its lease runs out. A failed delivery is retried with backoff
+    /// until `Reminder::MAX_ATTEMPTS` failures, after which the reminder is abandoned.
+    async fn dispatch_due(&self, now: DateTime<Utc>) -> Result<usize, UseCaseError>;
+}
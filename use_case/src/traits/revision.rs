@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+
+use crate::{
+    dto::{
+        audit::RequestContext,
+        revision::{RevisionDiffDto, TodoRevisionDto},
+        todo::TodoDto,
+    },
+    error::UseCaseError,
+};
+
+#[async_trait]
+pub trait RevisionUseCase: Send + Sync + 'static {
+    /// Every revision of the todo, oldest first.
+    async fn find_revisions(&self, todo_id: i64) -> Result<Vec<TodoRevisionDto>, UseCaseError>;
+    async fn diff_revisions(
+        &self,
+        todo_id: i64,
+        from: i64,
+        to: i64,
+    ) -> Result<RevisionDiffDto, UseCaseError>;
+    /// Updates the todo back to the fields of `revision`, which adds a new revision;
+    /// `completed_at` is kept, as with any update.
+    async fn restore_revision(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+        revision: i64,
+    ) -> Result<TodoDto, UseCaseError>;
+}
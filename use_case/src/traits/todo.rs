@@ -1,28 +1,125 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
 use crate::{
-    dto::todo::{CreateTodoDto, TodoDto},
+    dto::{
+        audit::RequestContext,
+        batch::{BatchMode, BatchOperationDto, BatchResultDto},
+        todo::{CompletedTodoDto, CreateTodoDto, TodoDto, UpdateTodoDto},
+    },
     error::UseCaseError,
 };
 
+/// Every change is recorded in the audit log under `ctx`, in the same transaction.
 #[async_trait]
 pub trait MutationUseCase: Send + Sync + 'static {
-    async fn create(&self, todo_data: CreateTodoDto) -> Result<TodoDto, UseCaseError>;
-    async fn update(&self, todo_data: TodoDto) -> Result<TodoDto, UseCaseError>;
-    async fn delete(&self, todo_id: i64) -> Result<i64, UseCaseError>;
+    /// Under an `idempotency_key` seen before, returns the todo created then instead of
+    /// creating another, or fails with `IdempotencyKeyReused` if the body differs.
+    async fn create(
+        &self,
+        ctx: RequestContext,
+        todo_data: CreateTodoDto,
+        idempotency_key: Option<String>,
+    ) -> Result<TodoDto, UseCaseError>;
+    /// Changes the fields set in `todo_data` and keeps the others; `completed_at` is only
+    /// changed by `complete`. Fails with `Conflict` unless the todo is still at
+    /// `expected_version`; `None` skips the check.
+    async fn update(
+        &self,
+        ctx: RequestContext,
+        todo_data: UpdateTodoDto,
+        expected_version: Option<i64>,
+    ) -> Result<TodoDto, UseCaseError>;
+    /// Moves the todo to the trash; deleting a missing todo does nothing. Fails with
+    /// `Conflict` unless the todo is still at `expected_version`; `None` skips the check.
+    async fn delete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+        expected_version: Option<i64>,
+    ) -> Result<i64, UseCaseError>;
+    async fn complete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+    ) -> Result<CompletedTodoDto, UseCaseError>;
+    /// Runs `operations` in order in one transaction, at most `MAX_BATCH_SIZE` of them.
+    async fn batch(
+        &self,
+        ctx: RequestContext,
+        operations: Vec<BatchOperationDto>,
+        mode: BatchMode,
+    ) -> Result<BatchResultDto, UseCaseError>;
 }
 
 #[async_trait]
 pub trait QueryUseCase: Send + Sync + 'static {
     async fn find_all(&self) -> Result<Vec<TodoDto>, UseCaseError>;
     async fn find_by_id(&self, todo_id: i64) -> Result<Option<TodoDto>, UseCaseError>;
+    /// The todos among `todo_ids` in one lookup, ordered by id; missing ones are left out.
+    async fn find_by_ids(&self, todo_ids: Vec<i64>) -> Result<Vec<TodoDto>, UseCaseError>;
+    async fn find_overdue(&self) -> Result<Vec<TodoDto>, UseCaseError>;
+    async fn find_due_today(&self, timezone: Option<String>) -> Result<Vec<TodoDto>, UseCaseError>;
+    async fn find_due_this_week(
+        &self,
+        timezone: Option<String>,
+    ) -> Result<Vec<TodoDto>, UseCaseError>;
+    async fn find_high_priority(&self) -> Result<Vec<TodoDto>, UseCaseError>;
 }
 
 #[async_trait]
 pub trait TodoUseCase: Send + Sync + 'static {
-    async fn create(&self, todo_data: CreateTodoDto) -> Result<TodoDto, UseCaseError>;
-    async fn update(&self, todo_data: TodoDto) -> Result<TodoDto, UseCaseError>;
-    async fn delete(&self, todo_id: i64) -> Result<i64, UseCaseError>;
+    /// Under an `idempotency_key` seen before, returns the todo created then instead of
+    /// creating another, or fails with `IdempotencyKeyReused` if the body differs.
+    async fn create(
+        &self,
+        ctx: RequestContext,
+        todo_data: CreateTodoDto,
+        idempotency_key: Option<String>,
+    ) -> Result<TodoDto, UseCaseError>;
+    /// Changes the fields set in `todo_data` and keeps the others; `completed_at` is only
+    /// changed by `complete`. Fails with `Conflict` unless the todo is still at
+    /// `expected_version`; `None` skips the check.
+    async fn update(
+        &self,
+        ctx: RequestContext,
+        todo_data: UpdateTodoDto,
+        expected_version: Option<i64>,
+    ) -> Result<TodoDto, UseCaseError>;
+    /// Moves the todo to the trash; deleting a missing todo does nothing. Fails with
+    /// `Conflict` unless the todo is still at `expected_version`; `None` skips the check.
+    async fn delete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+        expected_version: Option<i64>,
+    ) -> Result<i64, UseCaseError>;
+    async fn complete(
+        &self,
+        ctx: RequestContext,
+        todo_id: i64,
+    ) -> Result<CompletedTodoDto, UseCaseError>;
+    /// Runs `operations` in order in one transaction, at most `MAX_BATCH_SIZE` of them.
+    async fn batch(
+        &self,
+        ctx: RequestContext,
+        operations: Vec<BatchOperationDto>,
+        mode: BatchMode,
+    ) -> Result<BatchResultDto, UseCaseError>;
     async fn find_all(&self) -> Result<Vec<TodoDto>, UseCaseError>;
     async fn find_by_id(&self, todo_id: i64) -> Result<Option<TodoDto>, UseCaseError>;
+    /// The todo as it was at `at`, or `None` if it did not exist then. Fails with
+    /// `Validation` unless the todos are persisted as events, which keeps their history.
+    async fn find_by_id_at(
+        &self,
+        todo_id: i64,
+        at: DateTime<Utc>,
+    ) -> Result<Option<TodoDto>, UseCaseError>;
+    async fn find_overdue(&self) -> Result<Vec<TodoDto>, UseCaseError>;
+    async fn find_due_today(&self, timezone: Option<String>) -> Result<Vec<TodoDto>, UseCaseError>;
+    async fn find_due_this_week(
+        &self,
+        timezone: Option<String>,
+    ) -> Result<Vec<TodoDto>, UseCaseError>;
+    async fn find_high_priority(&self) -> Result<Vec<TodoDto>, UseCaseError>;
 }
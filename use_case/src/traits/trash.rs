@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    dto::{audit::RequestContext, todo::TodoDto, trash::TrashedTodoDto},
+    error::UseCaseError,
+};
+
+#[async_trait]
+pub trait TrashUseCase: Send + Sync + 'static {
+    /// Every deleted todo still in the trash, most recently deleted first.
+    async fn find_trash(&self) -> Result<Vec<TrashedTodoDto>, UseCaseError>;
+    /// Takes the todo out of the trash, as it was when it was deleted.
+    async fn restore(&self, ctx: RequestContext, todo_id: i64) -> Result<TodoDto, UseCaseError>;
+}
+
+#[async_trait]
+pub trait TrashPurgeUseCase: Send + Sync + 'static {
+    /// Permanently removes the todos whose retention ran out by `now` and returns how many
+    /// were purged.
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64, UseCaseError>;
+}
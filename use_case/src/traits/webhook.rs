@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    dto::webhook::{CreateWebhookSubscriptionDto, WebhookDeliveryDto, WebhookSubscriptionDto},
+    error::UseCaseError,
+};
+
+#[async_trait]
+pub trait WebhookUseCase: Send + Sync + 'static {
+    async fn create_subscription(
+        &self,
+        subscription_data: CreateWebhookSubscriptionDto,
+    ) -> Result<WebhookSubscriptionDto, UseCaseError>;
+    async fn find_subscriptions(&self) -> Result<Vec<WebhookSubscriptionDto>, UseCaseError>;
+    async fn delete_subscription(&self, subscription_id: i64) -> Result<i64, UseCaseError>;
+    /// Recent delivery attempts for a subscription, newest first.
+    async fn find_deliveries(
+        &self,
+        subscription_id: i64,
+    ) -> Result<Vec<WebhookDeliveryDto>, UseCaseError>;
+}
+
+#[async_trait]
+pub trait WebhookDispatchUseCase: Send + Sync + 'static {
+    /// Queues new outbox events and sends every delivery due at `now`; returns how many
+    /// were delivered successfully.
+    async fn dispatch_pending(&self, now: DateTime<Utc>) -> Result<usize, UseCaseError>;
+}